@@ -5,11 +5,18 @@ import {IVerifier} from "./interfaces/IVerifier.sol";
 import {ReentrancyGuard} from "@openzeppelin/contracts/utils/ReentrancyGuard.sol";
 import {Pausable} from "@openzeppelin/contracts/utils/Pausable.sol";
 import {Ownable} from "@openzeppelin/contracts/access/Ownable.sol";
+import {IERC20} from "@openzeppelin/contracts/token/ERC20/IERC20.sol";
+import {IERC20Permit} from "@openzeppelin/contracts/token/ERC20/extensions/IERC20Permit.sol";
+import {SafeERC20} from "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
 
 /// @title VeilocityVault
 /// @notice Main contract for Veilocity private execution layer on Mantle
-/// @dev Handles deposits, withdrawals, and state root anchoring with ZK proof verification
+/// @dev Handles deposits, withdrawals, and state root anchoring with ZK proof verification.
+///      Native MNT and owner-approved ERC-20 tokens each form their own pool; token
+///      commitments and leaves carry the token address as their asset id.
 contract VeilocityVault is ReentrancyGuard, Pausable, Ownable {
+    using SafeERC20 for IERC20;
+
     // ============ Constants ============
 
     /// @notice Number of historical roots to keep (for withdrawal flexibility)
@@ -18,6 +25,19 @@ contract VeilocityVault is ReentrancyGuard, Pausable, Ownable {
     /// @notice Minimum deposit amount (to prevent dust attacks)
     uint256 public constant MIN_DEPOSIT = 0.001 ether;
 
+    /// @notice Number of withdrawal slots in one batched proof (matches the withdraw_batch circuit)
+    uint256 public constant WITHDRAW_BATCH_SIZE = 4;
+
+    /// @notice Number of transfer slots in one state-transition proof (matches the state_transition circuit)
+    uint256 public constant TRANSFER_BATCH_SIZE = 4;
+
+    /// @notice Number of deposit slots in one state-transition proof (matches the state_transition circuit)
+    uint256 public constant DEPOSIT_BATCH_SIZE = 4;
+
+    /// @notice BN254 scalar field modulus; commitments must be field elements to be proven
+    uint256 internal constant FIELD_MODULUS =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+
     // ============ State Variables ============
 
     /// @notice The ZK verifier contract
@@ -38,12 +58,33 @@ contract VeilocityVault is ReentrancyGuard, Pausable, Ownable {
     /// @notice Number of deposits (also serves as leaf index counter)
     uint256 public depositCount;
 
+    /// @notice Number of deposits covered by the roots anchored so far
+    uint256 public anchoredDepositCount;
+
+    /// @notice Commitment of each deposit (leaf index => commitment)
+    mapping(uint256 => bytes32) public depositCommitments;
+
     /// @notice Mapping of used nullifiers (nullifier => isUsed)
     mapping(bytes32 => bool) public nullifiers;
 
     /// @notice Total value locked in the vault
     uint256 public totalValueLocked;
 
+    /// @notice The ZK verifier for batched withdrawal proofs (unset disables withdrawBatch)
+    IVerifier public batchVerifier;
+
+    /// @notice The ZK verifier for token withdrawal proofs (unset disables withdrawToken)
+    IVerifier public tokenVerifier;
+
+    /// @notice The ZK verifier for state-transition proofs (unset disables root updates)
+    IVerifier public transitionVerifier;
+
+    /// @notice Minimum deposit per supported token (zero means the token is not supported)
+    mapping(address => uint256) public tokenMinDeposit;
+
+    /// @notice Value locked per token, in the token's own units
+    mapping(address => uint256) public tokenValueLocked;
+
     // ============ Events ============
 
     /// @notice Emitted when a deposit is made
@@ -80,6 +121,59 @@ contract VeilocityVault is ReentrancyGuard, Pausable, Ownable {
         uint256 timestamp
     );
 
+    /// @notice Emitted once per batched withdrawal, after the per-entry Withdrawal events
+    /// @param root The state root the batch was proven against
+    /// @param count Number of withdrawals settled
+    /// @param totalAmount Sum of all withdrawn amounts in wei
+    event BatchWithdrawal(
+        bytes32 indexed root,
+        uint256 count,
+        uint256 totalAmount
+    );
+
+    /// @notice Emitted when the batch verifier is changed
+    /// @param batchVerifier The new batch verifier address
+    event BatchVerifierUpdated(address indexed batchVerifier);
+
+    /// @notice Emitted when a token deposit is made
+    /// @param commitment The deposit commitment (hash of secret, amount and token)
+    /// @param token The deposited token
+    /// @param amount The deposited amount in the token's units
+    /// @param leafIndex The assigned leaf index in the Merkle tree
+    /// @param timestamp Block timestamp of the deposit
+    event TokenDeposit(
+        bytes32 indexed commitment,
+        address indexed token,
+        uint256 amount,
+        uint256 leafIndex,
+        uint256 timestamp
+    );
+
+    /// @notice Emitted when a token withdrawal is made
+    /// @param nullifier The nullifier preventing double-spend
+    /// @param token The withdrawn token
+    /// @param recipient The address receiving the tokens
+    /// @param amount The withdrawn amount in the token's units
+    event TokenWithdrawal(
+        bytes32 indexed nullifier,
+        address indexed token,
+        address indexed recipient,
+        uint256 amount
+    );
+
+    /// @notice Emitted when a token is added, removed or its minimum deposit changed
+    /// @param token The token address
+    /// @param minDeposit The new minimum deposit (zero removes support)
+    event TokenSupportUpdated(address indexed token, uint256 minDeposit);
+
+    /// @notice Emitted when the token verifier is changed
+    /// @param tokenVerifier The new token verifier address
+    event TokenVerifierUpdated(address indexed tokenVerifier);
+
+    /// @notice Emitted when the state-transition verifier is changed
+    /// @param transitionVerifier The new state-transition verifier address
+    event TransitionVerifierUpdated(address indexed transitionVerifier);
+
     // ============ Errors ============
 
     error InvalidVerifier();
@@ -92,6 +186,11 @@ contract VeilocityVault is ReentrancyGuard, Pausable, Ownable {
     error InvalidAmount();
     error TransferFailed();
     error InvalidNullifier();
+    error InvalidBatch();
+    error BatchVerifierNotSet();
+    error TokenNotSupported();
+    error TokenVerifierNotSet();
+    error TransitionVerifierNotSet();
 
     // ============ Constructor ============
 
@@ -117,15 +216,67 @@ contract VeilocityVault is ReentrancyGuard, Pausable, Ownable {
     /// @dev The commitment binds the deposit to a secret known only to the depositor
     function deposit(bytes32 commitment) external payable nonReentrant whenNotPaused {
         if (msg.value < MIN_DEPOSIT) revert DepositTooSmall();
-        if (commitment == bytes32(0)) revert InvalidCommitment();
+        if (commitment == bytes32(0) || uint256(commitment) >= FIELD_MODULUS) revert InvalidCommitment();
 
         uint256 leafIndex = depositCount;
         depositCount++;
+        depositCommitments[leafIndex] = commitment;
         totalValueLocked += msg.value;
 
         emit Deposit(commitment, msg.value, leafIndex, block.timestamp);
     }
 
+    /// @notice Deposit ERC-20 tokens into the private execution layer
+    /// @param token The token to deposit (must be supported)
+    /// @param amount Amount to deposit in the token's units
+    /// @param commitment The deposit commitment = hash(secret, amount, token)
+    /// @dev The vault must be approved for `amount` first. Tokens that take a fee on
+    ///      transfer are rejected, since the commitment binds the full amount.
+    function depositToken(
+        address token,
+        uint256 amount,
+        bytes32 commitment
+    ) public nonReentrant whenNotPaused {
+        uint256 minDeposit = tokenMinDeposit[token];
+        if (minDeposit == 0) revert TokenNotSupported();
+        if (amount < minDeposit) revert DepositTooSmall();
+        if (commitment == bytes32(0) || uint256(commitment) >= FIELD_MODULUS) revert InvalidCommitment();
+
+        uint256 balanceBefore = IERC20(token).balanceOf(address(this));
+        IERC20(token).safeTransferFrom(msg.sender, address(this), amount);
+        if (IERC20(token).balanceOf(address(this)) - balanceBefore != amount) revert InvalidAmount();
+
+        uint256 leafIndex = depositCount;
+        depositCount++;
+        depositCommitments[leafIndex] = commitment;
+        tokenValueLocked[token] += amount;
+
+        emit TokenDeposit(commitment, token, amount, leafIndex, block.timestamp);
+    }
+
+    /// @notice Deposit ERC-20 tokens, approving the vault with an EIP-2612 permit
+    /// @param token The token to deposit (must be supported and implement permit)
+    /// @param amount Amount to deposit in the token's units
+    /// @param commitment The deposit commitment = hash(secret, amount, token)
+    /// @param deadline Permit deadline
+    /// @param v Permit signature v
+    /// @param r Permit signature r
+    /// @param s Permit signature s
+    /// @dev A failing permit is ignored so a front-run permit can't block the deposit;
+    ///      the transfer then relies on the allowance already in place.
+    function depositTokenWithPermit(
+        address token,
+        uint256 amount,
+        bytes32 commitment,
+        uint256 deadline,
+        uint8 v,
+        bytes32 r,
+        bytes32 s
+    ) external {
+        try IERC20Permit(token).permit(msg.sender, address(this), amount, deadline, v, r, s) {} catch {}
+        depositToken(token, amount, commitment);
+    }
+
     /// @notice Withdraw funds from the private execution layer
     /// @param nullifier Unique identifier preventing double-withdrawal
     /// @param recipient Address to receive the funds
@@ -168,33 +319,161 @@ contract VeilocityVault is ReentrancyGuard, Pausable, Ownable {
         emit Withdrawal(nullifier, recipient, amount);
     }
 
-    /// @notice Update the state root with a validity proof
+    /// @notice Withdraw ERC-20 tokens from the private execution layer
+    /// @param token The token to withdraw
+    /// @param nullifier Unique identifier preventing double-withdrawal
+    /// @param recipient Address to receive the tokens
+    /// @param amount Amount to withdraw in the token's units
+    /// @param root The state root used in the proof
+    /// @param proof The ZK proof of valid withdrawal from a `token` account
+    function withdrawToken(
+        address token,
+        bytes32 nullifier,
+        address recipient,
+        uint256 amount,
+        bytes32 root,
+        bytes calldata proof
+    ) external nonReentrant whenNotPaused {
+        // Validate inputs
+        if (address(tokenVerifier) == address(0)) revert TokenVerifierNotSet();
+        if (token == address(0)) revert TokenNotSupported();
+        if (nullifier == bytes32(0)) revert InvalidNullifier();
+        if (recipient == address(0)) revert InvalidRecipient();
+        if (amount == 0) revert InvalidAmount();
+        if (!isValidRoot(root)) revert InvalidRoot();
+        if (nullifiers[nullifier]) revert NullifierAlreadyUsed();
+
+        // Verify the ZK proof; the asset id binds the proof to the token's pool
+        bytes32[] memory publicInputs = new bytes32[](5);
+        publicInputs[0] = root;                                 // state_root
+        publicInputs[1] = nullifier;                            // nullifier
+        publicInputs[2] = bytes32(amount);                      // amount
+        publicInputs[3] = bytes32(uint256(uint160(recipient))); // recipient as field
+        publicInputs[4] = bytes32(uint256(uint160(token)));     // asset as field
+
+        if (!tokenVerifier.verify(proof, publicInputs)) revert InvalidProof();
+
+        // Mark nullifier as used
+        nullifiers[nullifier] = true;
+
+        // Update TVL
+        tokenValueLocked[token] -= amount;
+
+        // Transfer tokens
+        IERC20(token).safeTransfer(recipient, amount);
+
+        emit TokenWithdrawal(nullifier, token, recipient, amount);
+    }
+
+    /// @notice Settle several withdrawals with a single batched proof
+    /// @param batchNullifiers Nullifiers, one per withdrawal
+    /// @param recipients Addresses receiving the funds, one per withdrawal
+    /// @param amounts Amounts to withdraw in wei, one per withdrawal
+    /// @param root The state root every withdrawal in the batch was proven against
+    /// @param proof The ZK proof of all withdrawals in the batch
+    /// @dev Batches shorter than WITHDRAW_BATCH_SIZE are zero-padded in the public inputs
+    function withdrawBatch(
+        bytes32[] calldata batchNullifiers,
+        address[] calldata recipients,
+        uint256[] calldata amounts,
+        bytes32 root,
+        bytes calldata proof
+    ) external nonReentrant whenNotPaused {
+        uint256 count = batchNullifiers.length;
+
+        // Validate inputs
+        if (address(batchVerifier) == address(0)) revert BatchVerifierNotSet();
+        if (count == 0 || count > WITHDRAW_BATCH_SIZE) revert InvalidBatch();
+        if (recipients.length != count || amounts.length != count) revert InvalidBatch();
+        if (!isValidRoot(root)) revert InvalidRoot();
+
+        // Public inputs: root, nullifiers[N], amounts[N], recipients[N]
+        bytes32[] memory publicInputs = new bytes32[](1 + 3 * WITHDRAW_BATCH_SIZE);
+        publicInputs[0] = root;
+
+        uint256 totalAmount = 0;
+        for (uint256 i = 0; i < count; i++) {
+            if (batchNullifiers[i] == bytes32(0)) revert InvalidNullifier();
+            if (recipients[i] == address(0)) revert InvalidRecipient();
+            if (amounts[i] == 0) revert InvalidAmount();
+            if (nullifiers[batchNullifiers[i]]) revert NullifierAlreadyUsed();
+
+            // Mark immediately so a nullifier repeated within the batch is rejected
+            nullifiers[batchNullifiers[i]] = true;
+            totalAmount += amounts[i];
+
+            publicInputs[1 + i] = batchNullifiers[i];
+            publicInputs[1 + WITHDRAW_BATCH_SIZE + i] = bytes32(amounts[i]);
+            publicInputs[1 + 2 * WITHDRAW_BATCH_SIZE + i] = bytes32(uint256(uint160(recipients[i])));
+        }
+
+        if (!batchVerifier.verify(proof, publicInputs)) revert InvalidProof();
+
+        // Update TVL
+        totalValueLocked -= totalAmount;
+
+        // Transfer funds
+        for (uint256 i = 0; i < count; i++) {
+            (bool success, ) = recipients[i].call{value: amounts[i]}("");
+            if (!success) revert TransferFailed();
+
+            emit Withdrawal(batchNullifiers[i], recipients[i], amounts[i]);
+        }
+
+        emit BatchWithdrawal(root, count, totalAmount);
+    }
+
+    /// @notice Update the state root with a validity proof for a batch of deposits and private transfers
     /// @param newRoot The new state root
-    /// @param proof ZK proof of valid state transition
-    /// @dev Only callable by authorized operators (owner for MVP)
+    /// @param transferNullifiers Nullifiers of the private transfers between the current root and newRoot
+    /// @param deposits Number of unanchored deposits the proof inserts, oldest first
+    /// @param proof ZK proof of the state transition
+    /// @dev Every root update needs the transition verifier. The proof must show that inserting
+    ///      the next `deposits` commitments recorded here and then applying the transfers moves
+    ///      currentRoot to newRoot (batches shorter than DEPOSIT_BATCH_SIZE or
+    ///      TRANSFER_BATCH_SIZE are zero-padded in the public inputs). The commitments and their
+    ///      leaf positions come from this contract, so the owner cannot add, drop or reorder
+    ///      deposits, and only then are the transfer nullifiers marked used, so a spent account
+    ///      cannot also withdraw.
     function updateStateRoot(
         bytes32 newRoot,
+        bytes32[] calldata transferNullifiers,
+        uint256 deposits,
         bytes calldata proof
     ) external onlyOwner whenNotPaused {
+        if (address(transitionVerifier) == address(0)) revert TransitionVerifierNotSet();
         if (newRoot == bytes32(0)) revert InvalidRoot();
 
-        // For MVP, we trust the owner to submit valid state roots
-        // In production, this would verify a state transition proof
-        // bytes32[] memory publicInputs = new bytes32[](2);
-        // publicInputs[0] = currentRoot;
-        // publicInputs[1] = newRoot;
-        // if (!verifier.verify(proof, publicInputs)) revert InvalidProof();
+        uint256 count = transferNullifiers.length;
+        if (count > TRANSFER_BATCH_SIZE || deposits > DEPOSIT_BATCH_SIZE) revert InvalidBatch();
+        if (count + deposits == 0) revert InvalidBatch();
+        if (deposits > depositCount - anchoredDepositCount) revert InvalidBatch();
 
-        bytes32 oldRoot = currentRoot;
-        currentRoot = newRoot;
+        // Public inputs: old root, new root, nullifiers[N], first deposit index, commitments[D]
+        bytes32[] memory publicInputs = new bytes32[](3 + TRANSFER_BATCH_SIZE + DEPOSIT_BATCH_SIZE);
+        publicInputs[0] = currentRoot;
+        publicInputs[1] = newRoot;
 
-        // Add to history
-        uint256 newIndex = (currentRootIndex + 1) % ROOT_HISTORY_SIZE;
-        currentRootIndex = newIndex;
-        roots[newIndex] = newRoot;
-        rootHistory[newRoot] = true;
+        for (uint256 i = 0; i < count; i++) {
+            if (transferNullifiers[i] == bytes32(0)) revert InvalidNullifier();
+            if (nullifiers[transferNullifiers[i]]) revert NullifierAlreadyUsed();
 
-        emit StateRootUpdated(oldRoot, newRoot, newIndex, block.timestamp);
+            // Mark immediately so a nullifier repeated within the batch is rejected
+            nullifiers[transferNullifiers[i]] = true;
+            publicInputs[2 + i] = transferNullifiers[i];
+        }
+
+        uint256 firstDeposit = anchoredDepositCount;
+        uint256 depositInputs = 2 + TRANSFER_BATCH_SIZE;
+        publicInputs[depositInputs] = bytes32(firstDeposit);
+        for (uint256 i = 0; i < deposits; i++) {
+            publicInputs[depositInputs + 1 + i] = depositCommitments[firstDeposit + i];
+        }
+
+        if (!transitionVerifier.verify(proof, publicInputs)) revert InvalidProof();
+
+        anchoredDepositCount = firstDeposit + deposits;
+        _pushRoot(newRoot);
     }
 
     // ============ View Functions ============
@@ -225,6 +504,13 @@ contract VeilocityVault is ReentrancyGuard, Pausable, Ownable {
         return totalValueLocked;
     }
 
+    /// @notice Check whether a token can be deposited
+    /// @param token The token to check
+    /// @return True if the token is supported
+    function isTokenSupported(address token) external view returns (bool) {
+        return tokenMinDeposit[token] != 0;
+    }
+
     // ============ Admin Functions ============
 
     /// @notice Pause the contract in case of emergency
@@ -237,6 +523,37 @@ contract VeilocityVault is ReentrancyGuard, Pausable, Ownable {
         _unpause();
     }
 
+    /// @notice Set the verifier used for batched withdrawal proofs
+    /// @param _batchVerifier Address of the withdraw_batch verifier (zero disables batching)
+    function setBatchVerifier(address _batchVerifier) external onlyOwner {
+        batchVerifier = IVerifier(_batchVerifier);
+        emit BatchVerifierUpdated(_batchVerifier);
+    }
+
+    /// @notice Add or remove a supported token
+    /// @param token The ERC-20 token address
+    /// @param minDeposit Minimum deposit in the token's units (zero removes support)
+    /// @dev Removing a token stops new deposits; existing balances can still be withdrawn
+    function setTokenSupport(address token, uint256 minDeposit) external onlyOwner {
+        if (token == address(0)) revert TokenNotSupported();
+        tokenMinDeposit[token] = minDeposit;
+        emit TokenSupportUpdated(token, minDeposit);
+    }
+
+    /// @notice Set the verifier used for token withdrawal proofs
+    /// @param _tokenVerifier Address of the withdraw_token verifier (zero disables token withdrawals)
+    function setTokenVerifier(address _tokenVerifier) external onlyOwner {
+        tokenVerifier = IVerifier(_tokenVerifier);
+        emit TokenVerifierUpdated(_tokenVerifier);
+    }
+
+    /// @notice Set the verifier used for state-transition proofs in updateStateRoot
+    /// @param _transitionVerifier Address of the state_transition verifier (zero disables root updates)
+    function setTransitionVerifier(address _transitionVerifier) external onlyOwner {
+        transitionVerifier = IVerifier(_transitionVerifier);
+        emit TransitionVerifierUpdated(_transitionVerifier);
+    }
+
     /// @notice Emergency withdrawal (only when paused)
     /// @param recipient Address to receive all funds
     /// @dev This is a last resort for recovering funds in case of critical issues
@@ -250,6 +567,35 @@ contract VeilocityVault is ReentrancyGuard, Pausable, Ownable {
         if (!success) revert TransferFailed();
     }
 
+    /// @notice Emergency withdrawal of one token's pool (only when paused)
+    /// @param token The token to recover
+    /// @param recipient Address to receive the vault's whole balance of `token`
+    function emergencyWithdrawToken(address token, address recipient) external onlyOwner whenPaused {
+        if (recipient == address(0)) revert InvalidRecipient();
+
+        uint256 balance = IERC20(token).balanceOf(address(this));
+        tokenValueLocked[token] = 0;
+
+        IERC20(token).safeTransfer(recipient, balance);
+    }
+
+    // ============ Internal Functions ============
+
+    /// @notice Make `newRoot` current and add it to the root history
+    /// @param newRoot The new state root
+    function _pushRoot(bytes32 newRoot) internal {
+        bytes32 oldRoot = currentRoot;
+        currentRoot = newRoot;
+
+        // Add to history
+        uint256 newIndex = (currentRootIndex + 1) % ROOT_HISTORY_SIZE;
+        currentRootIndex = newIndex;
+        roots[newIndex] = newRoot;
+        rootHistory[newRoot] = true;
+
+        emit StateRootUpdated(oldRoot, newRoot, newIndex, block.timestamp);
+    }
+
     // ============ Receive ============
 
     /// @notice Receive function to accept plain MNT transfers
@@ -4,10 +4,12 @@ pragma solidity ^0.8.24;
 import {Test, console} from "forge-std/Test.sol";
 import {VeilocityVault} from "../src/VeilocityVault.sol";
 import {MockVerifier} from "../src/mocks/MockVerifier.sol";
+import {MockERC20} from "../src/mocks/MockERC20.sol";
 
 contract VeilocityVaultTest is Test {
     VeilocityVault public vault;
     MockVerifier public verifier;
+    MockERC20 public usdc;
 
     address public owner = address(this);
     address public alice = address(0x1);
@@ -27,6 +29,11 @@ contract VeilocityVaultTest is Test {
         // Fund test accounts
         vm.deal(alice, 100 ether);
         vm.deal(bob, 100 ether);
+
+        // 6-decimal stablecoin, enabled with a 1 USDC minimum
+        usdc = new MockERC20("USD Coin", "USDC", 6);
+        usdc.mint(alice, 1_000e6);
+        vault.setTokenSupport(address(usdc), 1e6);
     }
 
     // ============ Constructor Tests ============
@@ -50,6 +57,7 @@ contract VeilocityVaultTest is Test {
         vault.deposit{value: 1 ether}(TEST_COMMITMENT);
 
         assertEq(vault.depositCount(), 1);
+        assertEq(vault.depositCommitments(0), TEST_COMMITMENT);
         assertEq(vault.totalValueLocked(), 1 ether);
         assertEq(address(vault).balance, 1 ether);
     }
@@ -85,6 +93,13 @@ contract VeilocityVaultTest is Test {
         vault.deposit{value: 1 ether}(bytes32(0));
     }
 
+    function test_Deposit_RevertCommitmentOutsideField() public {
+        // A commitment the circuits can't represent could never be inserted
+        vm.prank(alice);
+        vm.expectRevert(VeilocityVault.InvalidCommitment.selector);
+        vault.deposit{value: 1 ether}(bytes32(type(uint256).max));
+    }
+
     function test_Deposit_RevertWhenPaused() public {
         vault.pause();
         vm.prank(alice);
@@ -173,48 +188,530 @@ contract VeilocityVaultTest is Test {
         vault.withdraw(TEST_NULLIFIER, bob, 0, INITIAL_ROOT, proof);
     }
 
-    // ============ State Root Tests ============
+    // ============ Batch Withdrawal Tests ============
+
+    function _batch()
+        internal
+        view
+        returns (bytes32[] memory batchNullifiers, address[] memory recipients, uint256[] memory amounts)
+    {
+        batchNullifiers = new bytes32[](2);
+        batchNullifiers[0] = TEST_NULLIFIER;
+        batchNullifiers[1] = keccak256("nullifier2");
+
+        recipients = new address[](2);
+        recipients[0] = bob;
+        recipients[1] = alice;
+
+        amounts = new uint256[](2);
+        amounts[0] = 0.5 ether;
+        amounts[1] = 0.25 ether;
+    }
+
+    function test_WithdrawBatch() public {
+        vault.setBatchVerifier(address(verifier));
+
+        vm.prank(alice);
+        vault.deposit{value: 1 ether}(TEST_COMMITMENT);
+
+        (bytes32[] memory batchNullifiers, address[] memory recipients, uint256[] memory amounts) = _batch();
+        uint256 bobBalanceBefore = bob.balance;
+        uint256 aliceBalanceBefore = alice.balance;
+
+        vault.withdrawBatch(batchNullifiers, recipients, amounts, INITIAL_ROOT, hex"1234");
+
+        assertEq(bob.balance, bobBalanceBefore + 0.5 ether);
+        assertEq(alice.balance, aliceBalanceBefore + 0.25 ether);
+        assertEq(vault.totalValueLocked(), 0.25 ether);
+        assertTrue(vault.isNullifierUsed(batchNullifiers[0]));
+        assertTrue(vault.isNullifierUsed(batchNullifiers[1]));
+    }
+
+    function test_WithdrawBatch_EmitsEvents() public {
+        vault.setBatchVerifier(address(verifier));
+
+        vm.prank(alice);
+        vault.deposit{value: 1 ether}(TEST_COMMITMENT);
+
+        (bytes32[] memory batchNullifiers, address[] memory recipients, uint256[] memory amounts) = _batch();
+
+        vm.expectEmit(true, true, false, true);
+        emit VeilocityVault.Withdrawal(batchNullifiers[0], bob, 0.5 ether);
+        vm.expectEmit(true, true, false, true);
+        emit VeilocityVault.Withdrawal(batchNullifiers[1], alice, 0.25 ether);
+        vm.expectEmit(true, false, false, true);
+        emit VeilocityVault.BatchWithdrawal(INITIAL_ROOT, 2, 0.75 ether);
+        vault.withdrawBatch(batchNullifiers, recipients, amounts, INITIAL_ROOT, hex"1234");
+    }
+
+    function test_WithdrawBatch_RevertVerifierNotSet() public {
+        (bytes32[] memory batchNullifiers, address[] memory recipients, uint256[] memory amounts) = _batch();
+
+        vm.expectRevert(VeilocityVault.BatchVerifierNotSet.selector);
+        vault.withdrawBatch(batchNullifiers, recipients, amounts, INITIAL_ROOT, hex"1234");
+    }
+
+    function test_WithdrawBatch_RevertTooLarge() public {
+        vault.setBatchVerifier(address(verifier));
+
+        uint256 size = vault.WITHDRAW_BATCH_SIZE() + 1;
+        bytes32[] memory batchNullifiers = new bytes32[](size);
+        address[] memory recipients = new address[](size);
+        uint256[] memory amounts = new uint256[](size);
+
+        vm.expectRevert(VeilocityVault.InvalidBatch.selector);
+        vault.withdrawBatch(batchNullifiers, recipients, amounts, INITIAL_ROOT, hex"1234");
+    }
+
+    function test_WithdrawBatch_RevertLengthMismatch() public {
+        vault.setBatchVerifier(address(verifier));
+
+        (bytes32[] memory batchNullifiers, address[] memory recipients,) = _batch();
+        uint256[] memory amounts = new uint256[](1);
+        amounts[0] = 0.5 ether;
+
+        vm.expectRevert(VeilocityVault.InvalidBatch.selector);
+        vault.withdrawBatch(batchNullifiers, recipients, amounts, INITIAL_ROOT, hex"1234");
+    }
+
+    function test_WithdrawBatch_RevertDuplicateNullifier() public {
+        vault.setBatchVerifier(address(verifier));
+
+        vm.prank(alice);
+        vault.deposit{value: 1 ether}(TEST_COMMITMENT);
+
+        (bytes32[] memory batchNullifiers, address[] memory recipients, uint256[] memory amounts) = _batch();
+        batchNullifiers[1] = batchNullifiers[0];
+
+        vm.expectRevert(VeilocityVault.NullifierAlreadyUsed.selector);
+        vault.withdrawBatch(batchNullifiers, recipients, amounts, INITIAL_ROOT, hex"1234");
+    }
+
+    function test_WithdrawBatch_RevertInvalidProof() public {
+        vault.setBatchVerifier(address(verifier));
+
+        vm.prank(alice);
+        vault.deposit{value: 1 ether}(TEST_COMMITMENT);
+
+        verifier.setVerifyResult(false);
+
+        (bytes32[] memory batchNullifiers, address[] memory recipients, uint256[] memory amounts) = _batch();
+        vm.expectRevert(VeilocityVault.InvalidProof.selector);
+        vault.withdrawBatch(batchNullifiers, recipients, amounts, INITIAL_ROOT, hex"1234");
+    }
+
+    function test_SetBatchVerifier_RevertNotOwner() public {
+        vm.prank(alice);
+        vm.expectRevert();
+        vault.setBatchVerifier(address(verifier));
+    }
+
+    // ============ Token Tests ============
+
+    function test_DepositToken() public {
+        vm.startPrank(alice);
+        usdc.approve(address(vault), 100e6);
+        vault.depositToken(address(usdc), 100e6, TEST_COMMITMENT);
+        vm.stopPrank();
+
+        assertEq(vault.depositCount(), 1);
+        assertEq(vault.tokenValueLocked(address(usdc)), 100e6);
+        assertEq(vault.totalValueLocked(), 0);
+        assertEq(usdc.balanceOf(address(vault)), 100e6);
+    }
+
+    function test_DepositToken_EmitsEvent() public {
+        vm.startPrank(alice);
+        usdc.approve(address(vault), 100e6);
+        vm.expectEmit(true, true, false, true);
+        emit VeilocityVault.TokenDeposit(TEST_COMMITMENT, address(usdc), 100e6, 0, block.timestamp);
+        vault.depositToken(address(usdc), 100e6, TEST_COMMITMENT);
+        vm.stopPrank();
+    }
+
+    function test_DepositToken_SharesLeafIndex() public {
+        vm.prank(bob);
+        vault.deposit{value: 1 ether}(TEST_COMMITMENT);
+
+        vm.startPrank(alice);
+        usdc.approve(address(vault), 100e6);
+        vm.expectEmit(true, true, false, true);
+        emit VeilocityVault.TokenDeposit(keccak256("token"), address(usdc), 100e6, 1, block.timestamp);
+        vault.depositToken(address(usdc), 100e6, keccak256("token"));
+        vm.stopPrank();
+    }
+
+    function test_DepositToken_RevertNotSupported() public {
+        MockERC20 other = new MockERC20("Other", "OTH", 18);
+        vm.prank(alice);
+        vm.expectRevert(VeilocityVault.TokenNotSupported.selector);
+        vault.depositToken(address(other), 1 ether, TEST_COMMITMENT);
+    }
+
+    function test_DepositToken_RevertTooSmall() public {
+        vm.startPrank(alice);
+        usdc.approve(address(vault), 0.5e6);
+        vm.expectRevert(VeilocityVault.DepositTooSmall.selector);
+        vault.depositToken(address(usdc), 0.5e6, TEST_COMMITMENT);
+        vm.stopPrank();
+    }
+
+    function test_DepositToken_RevertWithoutApproval() public {
+        vm.prank(alice);
+        vm.expectRevert();
+        vault.depositToken(address(usdc), 100e6, TEST_COMMITMENT);
+    }
+
+    function test_DepositTokenWithPermit() public {
+        uint256 signerKey = 0xA11CE;
+        address signer = vm.addr(signerKey);
+        usdc.mint(signer, 100e6);
+
+        uint256 deadline = block.timestamp + 1 hours;
+        bytes32 structHash = keccak256(
+            abi.encode(
+                keccak256("Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)"),
+                signer,
+                address(vault),
+                100e6,
+                usdc.nonces(signer),
+                deadline
+            )
+        );
+        bytes32 digest = keccak256(abi.encodePacked("\x19\x01", usdc.DOMAIN_SEPARATOR(), structHash));
+        (uint8 v, bytes32 r, bytes32 s) = vm.sign(signerKey, digest);
+
+        vm.prank(signer);
+        vault.depositTokenWithPermit(address(usdc), 100e6, TEST_COMMITMENT, deadline, v, r, s);
+
+        assertEq(vault.tokenValueLocked(address(usdc)), 100e6);
+        assertEq(usdc.balanceOf(signer), 0);
+    }
+
+    function test_WithdrawToken() public {
+        vault.setTokenVerifier(address(verifier));
+
+        vm.startPrank(alice);
+        usdc.approve(address(vault), 100e6);
+        vault.depositToken(address(usdc), 100e6, TEST_COMMITMENT);
+        vm.stopPrank();
+
+        vm.expectEmit(true, true, true, true);
+        emit VeilocityVault.TokenWithdrawal(TEST_NULLIFIER, address(usdc), bob, 40e6);
+        vault.withdrawToken(address(usdc), TEST_NULLIFIER, bob, 40e6, INITIAL_ROOT, hex"1234");
+
+        assertEq(usdc.balanceOf(bob), 40e6);
+        assertEq(vault.tokenValueLocked(address(usdc)), 60e6);
+        assertTrue(vault.isNullifierUsed(TEST_NULLIFIER));
+    }
+
+    function test_WithdrawToken_RevertVerifierNotSet() public {
+        vm.expectRevert(VeilocityVault.TokenVerifierNotSet.selector);
+        vault.withdrawToken(address(usdc), TEST_NULLIFIER, bob, 40e6, INITIAL_ROOT, hex"1234");
+    }
+
+    function test_WithdrawToken_RevertNullifierReuse() public {
+        vault.setTokenVerifier(address(verifier));
+
+        vm.startPrank(alice);
+        usdc.approve(address(vault), 100e6);
+        vault.depositToken(address(usdc), 100e6, TEST_COMMITMENT);
+        vm.stopPrank();
+
+        vault.withdrawToken(address(usdc), TEST_NULLIFIER, bob, 40e6, INITIAL_ROOT, hex"1234");
+
+        vm.expectRevert(VeilocityVault.NullifierAlreadyUsed.selector);
+        vault.withdrawToken(address(usdc), TEST_NULLIFIER, bob, 40e6, INITIAL_ROOT, hex"1234");
+    }
+
+    function test_WithdrawToken_RevertInvalidProof() public {
+        vault.setTokenVerifier(address(verifier));
+        verifier.setVerifyResult(false);
+
+        vm.expectRevert(VeilocityVault.InvalidProof.selector);
+        vault.withdrawToken(address(usdc), TEST_NULLIFIER, bob, 40e6, INITIAL_ROOT, hex"1234");
+    }
+
+    function test_SetTokenSupport() public {
+        assertTrue(vault.isTokenSupported(address(usdc)));
+
+        vault.setTokenSupport(address(usdc), 0);
+        assertFalse(vault.isTokenSupported(address(usdc)));
+    }
+
+    function test_SetTokenSupport_RevertNotOwner() public {
+        vm.prank(alice);
+        vm.expectRevert();
+        vault.setTokenSupport(address(usdc), 1e6);
+    }
+
+    function test_EmergencyWithdrawToken() public {
+        vm.startPrank(alice);
+        usdc.approve(address(vault), 100e6);
+        vault.depositToken(address(usdc), 100e6, TEST_COMMITMENT);
+        vm.stopPrank();
+
+        vault.pause();
+        vault.emergencyWithdrawToken(address(usdc), bob);
+
+        assertEq(usdc.balanceOf(bob), 100e6);
+        assertEq(vault.tokenValueLocked(address(usdc)), 0);
+    }
+
+    // ============ State Transition Tests ============
+
+    function _deposit() internal {
+        vm.prank(alice);
+        vault.deposit{value: 1 ether}(TEST_COMMITMENT);
+    }
+
+    function _transfers() internal pure returns (bytes32[] memory transferNullifiers) {
+        transferNullifiers = new bytes32[](2);
+        transferNullifiers[0] = TEST_NULLIFIER;
+        transferNullifiers[1] = keccak256("nullifier2");
+    }
+
+    /// @dev Public inputs for a batch: old root, new root, nullifiers zero-padded to
+    ///      TRANSFER_BATCH_SIZE, first deposit index, commitments zero-padded to DEPOSIT_BATCH_SIZE
+    function _publicInputs(
+        bytes32 oldRoot,
+        bytes32 newRoot,
+        bytes32[] memory transferNullifiers,
+        uint256 firstDeposit,
+        bytes32[] memory commitments
+    ) internal view returns (bytes32[] memory publicInputs) {
+        uint256 transferSlots = vault.TRANSFER_BATCH_SIZE();
+        publicInputs = new bytes32[](3 + transferSlots + vault.DEPOSIT_BATCH_SIZE());
+        publicInputs[0] = oldRoot;
+        publicInputs[1] = newRoot;
+        for (uint256 i = 0; i < transferNullifiers.length; i++) {
+            publicInputs[2 + i] = transferNullifiers[i];
+        }
+        publicInputs[2 + transferSlots] = bytes32(firstDeposit);
+        for (uint256 i = 0; i < commitments.length; i++) {
+            publicInputs[3 + transferSlots + i] = commitments[i];
+        }
+    }
+
+    function test_UpdateStateRoot_WithTransitionProof() public {
+        MockVerifier transitionVerifier = new MockVerifier();
+        vault.setTransitionVerifier(address(transitionVerifier));
 
-    function test_UpdateStateRoot() public {
         bytes32 newRoot = keccak256("new root");
+        bytes32[] memory transferNullifiers = _transfers();
         bytes memory proof = hex"1234";
 
-        vault.updateStateRoot(newRoot, proof);
+        bytes32[] memory publicInputs =
+            _publicInputs(INITIAL_ROOT, newRoot, transferNullifiers, 0, new bytes32[](0));
+        vm.expectCall(
+            address(transitionVerifier),
+            abi.encodeCall(MockVerifier.verify, (proof, publicInputs))
+        );
+        vault.updateStateRoot(newRoot, transferNullifiers, 0, proof);
 
         assertEq(vault.currentRoot(), newRoot);
-        assertTrue(vault.isValidRoot(newRoot));
         assertTrue(vault.isValidRoot(INITIAL_ROOT)); // Old root still valid
+        assertTrue(vault.isNullifierUsed(transferNullifiers[0]));
+        assertTrue(vault.isNullifierUsed(transferNullifiers[1]));
     }
 
     function test_UpdateStateRoot_EmitsEvent() public {
+        vault.setTransitionVerifier(address(new MockVerifier()));
         bytes32 newRoot = keccak256("new root");
-        bytes memory proof = hex"1234";
 
         vm.expectEmit(true, true, false, true);
         emit VeilocityVault.StateRootUpdated(INITIAL_ROOT, newRoot, 1, block.timestamp);
-        vault.updateStateRoot(newRoot, proof);
+        vault.updateStateRoot(newRoot, _transfers(), 0, hex"1234");
     }
 
-    function test_UpdateStateRoot_RevertNotOwner() public {
+    function test_UpdateStateRoot_WithDeposits() public {
+        MockVerifier transitionVerifier = new MockVerifier();
+        vault.setTransitionVerifier(address(transitionVerifier));
+
+        bytes32 second = keccak256("second commitment") >> 8;
+        _deposit();
+        vm.prank(bob);
+        vault.deposit{value: 1 ether}(second);
+        assertEq(vault.depositCommitments(1), second);
+
+        // The proof is checked against the commitments the vault recorded, in deposit order
+        bytes32[] memory commitments = new bytes32[](2);
+        commitments[0] = TEST_COMMITMENT;
+        commitments[1] = second;
         bytes32 newRoot = keccak256("new root");
         bytes memory proof = hex"1234";
 
+        bytes32[] memory publicInputs =
+            _publicInputs(INITIAL_ROOT, newRoot, new bytes32[](0), 0, commitments);
+        vm.expectCall(
+            address(transitionVerifier),
+            abi.encodeCall(MockVerifier.verify, (proof, publicInputs))
+        );
+        vault.updateStateRoot(newRoot, new bytes32[](0), 2, proof);
+
+        assertEq(vault.currentRoot(), newRoot);
+        assertEq(vault.anchoredDepositCount(), 2);
+    }
+
+    function test_UpdateStateRoot_DepositsContinueFromAnchored() public {
+        MockVerifier transitionVerifier = new MockVerifier();
+        vault.setTransitionVerifier(address(transitionVerifier));
+
+        _deposit();
+        vault.updateStateRoot(keccak256("root 1"), new bytes32[](0), 1, hex"1234");
+        _deposit();
+
+        // The second batch starts at leaf 1 and carries its transfers alongside
+        bytes32[] memory commitments = new bytes32[](1);
+        commitments[0] = TEST_COMMITMENT;
+        bytes32[] memory transferNullifiers = _transfers();
+        bytes memory proof = hex"1234";
+        bytes32[] memory publicInputs = _publicInputs(
+            keccak256("root 1"), keccak256("root 2"), transferNullifiers, 1, commitments
+        );
+        vm.expectCall(
+            address(transitionVerifier),
+            abi.encodeCall(MockVerifier.verify, (proof, publicInputs))
+        );
+        vault.updateStateRoot(keccak256("root 2"), transferNullifiers, 1, proof);
+
+        assertEq(vault.anchoredDepositCount(), 2);
+    }
+
+    function test_UpdateStateRoot_RevertBadTransition() public {
+        MockVerifier transitionVerifier = new MockVerifier();
+        vault.setTransitionVerifier(address(transitionVerifier));
+        _deposit();
+
+        // The verifier rejects the claimed old root -> new root transition
+        transitionVerifier.setVerifyResult(false);
+
+        bytes32[] memory transferNullifiers = _transfers();
+        vm.expectRevert(VeilocityVault.InvalidProof.selector);
+        vault.updateStateRoot(keccak256("forged root"), transferNullifiers, 1, hex"1234");
+
+        assertEq(vault.currentRoot(), INITIAL_ROOT);
+        assertEq(vault.anchoredDepositCount(), 0);
+        assertFalse(vault.isNullifierUsed(transferNullifiers[0]));
+    }
+
+    function test_UpdateStateRoot_RevertNoTransitionVerifier() public {
+        // Without a proof the owner can neither set a root nor mark nullifiers spent
+        _deposit();
+        bytes32[] memory transferNullifiers = _transfers();
+        vm.expectRevert(VeilocityVault.TransitionVerifierNotSet.selector);
+        vault.updateStateRoot(keccak256("new root"), transferNullifiers, 1, hex"1234");
+
+        assertEq(vault.currentRoot(), INITIAL_ROOT);
+        assertFalse(vault.isNullifierUsed(transferNullifiers[0]));
+    }
+
+    function test_UpdateStateRoot_RevertEmptyBatch() public {
+        vault.setTransitionVerifier(address(new MockVerifier()));
+
+        vm.expectRevert(VeilocityVault.InvalidBatch.selector);
+        vault.updateStateRoot(keccak256("new root"), new bytes32[](0), 0, hex"1234");
+    }
+
+    function test_UpdateStateRoot_RevertTooManyTransfers() public {
+        vault.setTransitionVerifier(address(new MockVerifier()));
+        bytes32[] memory transferNullifiers = new bytes32[](vault.TRANSFER_BATCH_SIZE() + 1);
+        for (uint256 i = 0; i < transferNullifiers.length; i++) {
+            transferNullifiers[i] = keccak256(abi.encodePacked("nullifier", i));
+        }
+
+        vm.expectRevert(VeilocityVault.InvalidBatch.selector);
+        vault.updateStateRoot(keccak256("new root"), transferNullifiers, 0, hex"1234");
+    }
+
+    function test_UpdateStateRoot_RevertTooManyDeposits() public {
+        vault.setTransitionVerifier(address(new MockVerifier()));
+        uint256 deposits = vault.DEPOSIT_BATCH_SIZE() + 1;
+        for (uint256 i = 0; i < deposits; i++) {
+            _deposit();
+        }
+
+        vm.expectRevert(VeilocityVault.InvalidBatch.selector);
+        vault.updateStateRoot(keccak256("new root"), new bytes32[](0), deposits, hex"1234");
+    }
+
+    function test_UpdateStateRoot_RevertDepositsNotMade() public {
+        vault.setTransitionVerifier(address(new MockVerifier()));
+
+        // No deposit exists to insert
+        vm.expectRevert(VeilocityVault.InvalidBatch.selector);
+        vault.updateStateRoot(keccak256("new root"), new bytes32[](0), 1, hex"1234");
+
+        // Each deposit is inserted once
+        _deposit();
+        vault.updateStateRoot(keccak256("new root"), new bytes32[](0), 1, hex"1234");
+        vm.expectRevert(VeilocityVault.InvalidBatch.selector);
+        vault.updateStateRoot(keccak256("another root"), new bytes32[](0), 1, hex"1234");
+    }
+
+    function test_UpdateStateRoot_RevertDuplicateNullifier() public {
+        vault.setTransitionVerifier(address(new MockVerifier()));
+        bytes32[] memory transferNullifiers = _transfers();
+        transferNullifiers[1] = transferNullifiers[0];
+
+        vm.expectRevert(VeilocityVault.NullifierAlreadyUsed.selector);
+        vault.updateStateRoot(keccak256("new root"), transferNullifiers, 0, hex"1234");
+    }
+
+    function test_UpdateStateRoot_RevertZeroNullifier() public {
+        vault.setTransitionVerifier(address(new MockVerifier()));
+        bytes32[] memory transferNullifiers = _transfers();
+        transferNullifiers[1] = bytes32(0);
+
+        vm.expectRevert(VeilocityVault.InvalidNullifier.selector);
+        vault.updateStateRoot(keccak256("new root"), transferNullifiers, 0, hex"1234");
+    }
+
+    function test_UpdateStateRoot_RevertNotOwner() public {
+        vault.setTransitionVerifier(address(new MockVerifier()));
+
         vm.prank(alice);
         vm.expectRevert();
-        vault.updateStateRoot(newRoot, proof);
+        vault.updateStateRoot(keccak256("new root"), _transfers(), 0, hex"1234");
     }
 
     function test_UpdateStateRoot_RootHistory() public {
-        bytes memory proof = hex"1234";
+        vault.setTransitionVerifier(address(new MockVerifier()));
 
         // Update root multiple times
         for (uint256 i = 0; i < 5; i++) {
+            _deposit();
             bytes32 newRoot = keccak256(abi.encodePacked("root", i));
-            vault.updateStateRoot(newRoot, proof);
+            vault.updateStateRoot(newRoot, new bytes32[](0), 1, hex"1234");
             assertTrue(vault.isValidRoot(newRoot));
         }
     }
 
+    function test_UpdateStateRoot_SpentTransferCannotWithdraw() public {
+        vault.setTransitionVerifier(address(new MockVerifier()));
+        _deposit();
+
+        vault.updateStateRoot(keccak256("new root"), _transfers(), 1, hex"1234");
+
+        // Withdrawing the pre-transfer balance against the old root is a double spend
+        vm.expectRevert(VeilocityVault.NullifierAlreadyUsed.selector);
+        vault.withdraw(TEST_NULLIFIER, bob, 0.5 ether, INITIAL_ROOT, hex"1234");
+    }
+
+    function test_SetTransitionVerifier() public {
+        vm.expectEmit(true, false, false, false);
+        emit VeilocityVault.TransitionVerifierUpdated(address(verifier));
+        vault.setTransitionVerifier(address(verifier));
+
+        assertEq(address(vault.transitionVerifier()), address(verifier));
+    }
+
+    function test_SetTransitionVerifier_RevertNotOwner() public {
+        vm.prank(alice);
+        vm.expectRevert();
+        vault.setTransitionVerifier(address(verifier));
+    }
+
     // ============ Admin Tests ============
 
     function test_Pause() public {
@@ -0,0 +1,533 @@
+//! Admin command - deploy the vault and call its owner-only functions
+//!
+//! Deployment reads the compiled artifacts that `forge build` writes to
+//! `contracts/out`. Every other action must be sent from the vault owner.
+
+use crate::config::Config;
+use crate::signer;
+use crate::ui;
+use crate::wallet::{self, WalletManager};
+use alloy::primitives::{Address, B256, U256};
+use alloy::providers::Provider;
+use anyhow::{anyhow, Context, Result};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+use veilocity_contracts::admin::{HONK_VERIFIER_CONTRACT, MOCK_VERIFIER_CONTRACT, VAULT_CONTRACT};
+use veilocity_contracts::{
+    create_admin_client, deploy_vault_with, AdminClient, Artifact, SigningWallet, TxOutcome,
+    VerifierSource,
+};
+use veilocity_core::merkle::{EMPTY_HASHES, TREE_DEPTH};
+use veilocity_core::poseidon::field_to_bytes;
+use veilocity_core::state::StateManager;
+
+/// Options for `admin deploy`
+#[derive(Debug, Clone)]
+pub struct DeployOptions {
+    /// Foundry output directory holding the artifacts
+    pub out_dir: PathBuf,
+    /// Reuse this verifier instead of deploying one
+    pub verifier: Option<String>,
+    /// Deploy the verifier that accepts every proof (local testing only)
+    pub mock_verifier: bool,
+    /// Root to start from (default: the empty tree's root)
+    pub initial_root: Option<String>,
+    /// Write the vault address and deployment block into the config
+    pub save: bool,
+}
+
+/// Owner-only action on a deployed vault
+#[derive(Debug, Clone)]
+pub enum AdminAction {
+    /// Deploy the verifier and vault
+    Deploy(DeployOptions),
+    /// Pause deposits, withdrawals and root updates
+    Pause,
+    /// Resume a paused vault
+    Unpause,
+    /// Drain a paused vault's MNT (or one token pool) to a recipient
+    EmergencyWithdraw {
+        recipient: String,
+        token: Option<String>,
+    },
+    /// Anchor a state root proven by a state transition (default: the local state root)
+    UpdateRoot {
+        root: Option<String>,
+        deposits: u64,
+        nullifiers: Vec<String>,
+        proof: Option<PathBuf>,
+    },
+    /// Set the verifier root updates are checked against (zero address disables them)
+    SetTransitionVerifier { verifier: String },
+}
+
+/// Run the admin command
+pub async fn run(config: &Config, action: AdminAction, yes: bool) -> Result<()> {
+    match action {
+        AdminAction::Deploy(options) => deploy(config, &options, yes).await,
+        AdminAction::Pause => pause(config, true).await,
+        AdminAction::Unpause => pause(config, false).await,
+        AdminAction::EmergencyWithdraw { recipient, token } => {
+            emergency_withdraw(config, &recipient, token.as_deref(), yes).await
+        }
+        AdminAction::UpdateRoot {
+            root,
+            deposits,
+            nullifiers,
+            proof,
+        } => {
+            update_root(
+                config,
+                root.as_deref(),
+                deposits,
+                &nullifiers,
+                proof.as_deref(),
+            )
+            .await
+        }
+        AdminAction::SetTransitionVerifier { verifier } => {
+            set_transition_verifier(config, &verifier).await
+        }
+    }
+}
+
+/// Deploy the verifier (unless one is given) and the vault
+async fn deploy(config: &Config, options: &DeployOptions, yes: bool) -> Result<()> {
+    let vault_artifact = Artifact::load(&options.out_dir, VAULT_CONTRACT)?;
+    let verifier = match (&options.verifier, options.mock_verifier) {
+        (Some(_), true) => {
+            return Err(anyhow!(
+                "Use either --verifier or --mock-verifier, not both"
+            ));
+        }
+        (Some(address), false) => {
+            VerifierSource::Existing(address.parse().context("Invalid verifier address")?)
+        }
+        (None, mock) => {
+            let name = if mock {
+                MOCK_VERIFIER_CONTRACT
+            } else {
+                HONK_VERIFIER_CONTRACT
+            };
+            VerifierSource::Deploy(Artifact::load(&options.out_dir, name)?)
+        }
+    };
+    let initial_root = match &options.initial_root {
+        Some(root) => parse_bytes32(root, "root")?,
+        None => B256::from(field_to_bytes(&EMPTY_HASHES[TREE_DEPTH])),
+    };
+
+    println!();
+    println!("{}", ui::header("Deploy Vault"));
+    println!();
+    println!(
+        "  {} {}",
+        "Network:     ".truecolor(120, 120, 120),
+        format!(
+            "{} (chain {})",
+            config.network.rpc_url, config.network.chain_id
+        )
+        .dimmed()
+    );
+    println!(
+        "  {} {}",
+        "Verifier:    ".truecolor(120, 120, 120),
+        match &verifier {
+            VerifierSource::Existing(address) => format!("{} (existing)", address),
+            VerifierSource::Deploy(artifact) => format!("new {}", artifact.name),
+        }
+        .bright_white()
+    );
+    println!(
+        "  {} 0x{}",
+        "Initial root:".truecolor(120, 120, 120),
+        hex::encode(initial_root).dimmed()
+    );
+
+    if options.mock_verifier {
+        ui::print_notice(
+            "MOCK VERIFIER",
+            "Every proof will be accepted. Never use this outside local testing.",
+        );
+    }
+
+    println!();
+    if !yes && !ui::confirm("Deploy?")? {
+        println!("  {}", "Deployment cancelled.".dimmed());
+        return Ok(());
+    }
+
+    let signing = signing_wallet(config).await?;
+    println!();
+    println!(
+        "  {} Deploying contracts...",
+        "◐".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
+    );
+    let deployment = deploy_vault_with(
+        &config.network.rpc(),
+        signing,
+        &vault_artifact,
+        &verifier,
+        initial_root,
+    )
+    .await?;
+
+    println!();
+    println!(
+        "  {} {}",
+        "Verifier:    ".truecolor(120, 120, 120),
+        deployment.verifier.to_string().bright_white()
+    );
+    println!(
+        "  {} {}",
+        "Vault:       ".truecolor(120, 120, 120),
+        deployment.vault.to_string().bright_white().bold()
+    );
+    if let Some(block) = deployment.block_number {
+        println!(
+            "  {} {}",
+            "Block:       ".truecolor(120, 120, 120),
+            block.to_string().dimmed()
+        );
+    }
+
+    if options.save {
+        let mut saved = config.clone();
+        saved.network.vault_address = deployment.vault.to_string();
+        saved.sync.deployment_block = deployment.block_number;
+        saved.save()?;
+        ui::print_success("Vault deployed and saved to config!");
+    } else {
+        ui::print_success("Vault deployed!");
+        println!();
+        println!(
+            "  {} {}",
+            "Use it with:".truecolor(120, 120, 120),
+            format!("veilocity config set vault {}", deployment.vault).bright_white()
+        );
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Pause or unpause the vault
+async fn pause(config: &Config, pause: bool) -> Result<()> {
+    let admin = admin_client(config).await?;
+
+    println!();
+    println!(
+        "{}",
+        ui::header(if pause {
+            "Pause Vault"
+        } else {
+            "Unpause Vault"
+        })
+    );
+    println!();
+
+    if admin.is_paused().await? == pause {
+        println!(
+            "  {}",
+            if pause {
+                "Vault is already paused."
+            } else {
+                "Vault is not paused."
+            }
+            .dimmed()
+        );
+        println!();
+        return Ok(());
+    }
+
+    let outcome = if pause {
+        admin.pause().await?
+    } else {
+        admin.unpause().await?
+    };
+    print_outcome(&outcome);
+
+    ui::print_success(if pause {
+        "Vault paused."
+    } else {
+        "Vault unpaused."
+    });
+    println!();
+
+    Ok(())
+}
+
+/// Drain a paused vault's MNT or one token pool to a recipient
+async fn emergency_withdraw(
+    config: &Config,
+    recipient: &str,
+    token: Option<&str>,
+    yes: bool,
+) -> Result<()> {
+    let recipient: Address = recipient.parse().context("Invalid recipient address")?;
+    let token = token.map(|t| config.token(t)).transpose()?;
+    let admin = admin_client(config).await?;
+
+    if !admin.is_paused().await? {
+        return Err(anyhow!(
+            "Emergency withdrawals need a paused vault. Run 'veilocity admin pause' first."
+        ));
+    }
+
+    let vault = admin.vault();
+    let amount = match &token {
+        Some(info) => info.format(vault.token_value_locked(info.address).await?),
+        None => wallet::format_mnt(vault.get_balance(vault.address()).await?.to::<u128>()),
+    };
+
+    println!();
+    println!("{}", ui::header("Emergency Withdrawal"));
+    println!();
+    println!(
+        "  {} {}",
+        "Amount:    ".truecolor(120, 120, 120),
+        amount
+            .truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
+            .bold()
+    );
+    println!(
+        "  {} {}",
+        "Recipient: ".truecolor(120, 120, 120),
+        recipient.to_string().bright_white()
+    );
+    ui::print_notice(
+        "Irreversible",
+        "The whole pool is sent to the recipient. Private balances are no longer backed.",
+    );
+
+    println!();
+    if !yes && !ui::confirm("Withdraw everything?")? {
+        println!("  {}", "Emergency withdrawal cancelled.".dimmed());
+        return Ok(());
+    }
+
+    let outcome = match &token {
+        Some(info) => {
+            admin
+                .emergency_withdraw_token(info.address, recipient)
+                .await?
+        }
+        None => admin.emergency_withdraw(recipient).await?,
+    };
+    print_outcome(&outcome);
+
+    ui::print_success("Emergency withdrawal complete.");
+    println!();
+
+    Ok(())
+}
+
+/// Anchor a state-transition root, by default the local one
+///
+/// The state-transition proof inserts the next `deposits` unanchored deposits
+/// and covers the transfers with these `nullifiers`; the vault checks it
+/// against its transition verifier and its own deposit commitments before
+/// marking the nullifiers spent.
+async fn update_root(
+    config: &Config,
+    root: Option<&str>,
+    deposits: u64,
+    nullifiers: &[String],
+    proof: Option<&Path>,
+) -> Result<()> {
+    let new_root = resolve_root(config, root)?;
+    let proof = match proof {
+        Some(path) => std::fs::read(path)
+            .with_context(|| format!("Failed to read proof from {}", path.display()))?,
+        None => Vec::new(),
+    };
+    let nullifiers = nullifiers
+        .iter()
+        .map(|n| parse_bytes32(n, "nullifier"))
+        .collect::<Result<Vec<_>>>()?;
+
+    if (nullifiers.is_empty() && deposits == 0) || proof.is_empty() {
+        return Err(anyhow!(
+            "Pass the deposits with --deposits and the transfers with --nullifier, and their state_transition proof with --proof"
+        ));
+    }
+
+    let admin = admin_client(config).await?;
+    let current_root = admin.vault().current_root().await?;
+    if admin.vault().transition_verifier().await? == Address::ZERO {
+        return Err(anyhow!(
+            "The vault has no transition verifier; set one with 'veilocity admin set-transition-verifier'"
+        ));
+    }
+    let pending =
+        admin.vault().deposit_count().await? - admin.vault().anchored_deposit_count().await?;
+    if U256::from(deposits) > pending {
+        return Err(anyhow!(
+            "Only {} deposits are waiting to be anchored, not {}",
+            pending,
+            deposits
+        ));
+    }
+
+    println!();
+    println!("{}", ui::header("Update State Root"));
+    println!();
+    println!(
+        "  {} 0x{}",
+        "Current root:".truecolor(120, 120, 120),
+        hex::encode(current_root).dimmed()
+    );
+    println!(
+        "  {} 0x{}",
+        "New root:    ".truecolor(120, 120, 120),
+        hex::encode(new_root).bright_white()
+    );
+    println!(
+        "  {} {} of {}",
+        "Deposits:    ".truecolor(120, 120, 120),
+        deposits.to_string().bright_white(),
+        pending
+    );
+    println!(
+        "  {} {}",
+        "Transfers:   ".truecolor(120, 120, 120),
+        nullifiers.len().to_string().bright_white()
+    );
+
+    if new_root == current_root {
+        println!();
+        println!("  {}", "Root is already current.".dimmed());
+        println!();
+        return Ok(());
+    }
+
+    let tx_hash = admin
+        .update_state_root(new_root, &nullifiers, deposits, proof)
+        .await?;
+    println!(
+        "  {} 0x{}",
+        "Transaction: ".truecolor(120, 120, 120),
+        hex::encode(tx_hash).dimmed()
+    );
+
+    ui::print_success("State root anchored.");
+    println!();
+
+    Ok(())
+}
+
+/// Root given on the command line, or the local state root
+fn resolve_root(config: &Config, root: Option<&str>) -> Result<B256> {
+    match root {
+        Some(root) => parse_bytes32(root, "root"),
+        None => {
+            config.ensure_data_dir()?;
+            let state =
+                StateManager::new(&config.db_path()).context("Failed to open local state")?;
+            Ok(B256::from(field_to_bytes(&state.state_root())))
+        }
+    }
+}
+
+/// Point root updates at a state_transition verifier
+async fn set_transition_verifier(config: &Config, verifier: &str) -> Result<()> {
+    let verifier: Address = verifier.parse().context("Invalid verifier address")?;
+    let admin = admin_client(config).await?;
+    let current = admin.vault().transition_verifier().await?;
+
+    println!();
+    println!("{}", ui::header("Set Transition Verifier"));
+    println!();
+    println!(
+        "  {} {}",
+        "Current:".truecolor(120, 120, 120),
+        current.to_string().dimmed()
+    );
+    println!(
+        "  {} {}",
+        "New:    ".truecolor(120, 120, 120),
+        verifier.to_string().bright_white()
+    );
+
+    let outcome = admin.set_transition_verifier(verifier).await?;
+    print_outcome(&outcome);
+
+    ui::print_success(if verifier == Address::ZERO {
+        "Transfer root updates are disabled until a verifier is set."
+    } else {
+        "Transfer root updates now need a state-transition proof."
+    });
+    println!();
+
+    Ok(())
+}
+
+/// Admin client for the configured vault
+async fn admin_client(config: &Config) -> Result<AdminClient<impl Provider + Clone>> {
+    if config.network.vault_address.is_empty() {
+        return Err(anyhow!(
+            "Vault address not configured. Deploy with 'veilocity admin deploy --save' or run 'veilocity config set vault <addr>'."
+        ));
+    }
+    let vault_address = config
+        .network
+        .vault_address
+        .parse()
+        .context("Invalid vault address")?;
+
+    let admin = create_admin_client(
+        &config.network.rpc(),
+        vault_address,
+        signing_wallet(config).await?,
+    )
+    .await?;
+
+    // Fail early rather than on the simulated call's OwnableUnauthorizedAccount
+    let owner = admin.owner().await?;
+    if admin.vault().sender().is_some_and(|sender| sender != owner) {
+        return Err(anyhow!("Only the vault owner ({}) can do this", owner));
+    }
+
+    Ok(admin)
+}
+
+/// Configured signer; admin actions only need the EVM key, not the Veilocity secret
+async fn signing_wallet(config: &Config) -> Result<SigningWallet> {
+    signer::signing_wallet(config, || {
+        let wallet_manager = WalletManager::new(config.clone());
+        let wallet = wallet_manager.load_wallet()?;
+        let password = rpassword::prompt_password(format!(
+            "{} ",
+            "Enter wallet password:".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
+        ))
+        .context("Failed to read password")?;
+        wallet_manager.unlock(&wallet, &password)
+    })
+    .await
+}
+
+/// Print the hash and block of a mined admin transaction
+fn print_outcome(outcome: &TxOutcome) {
+    println!(
+        "  {} 0x{}",
+        "Transaction:".truecolor(120, 120, 120),
+        hex::encode(outcome.tx_hash).dimmed()
+    );
+    if let Some(block) = outcome.block_number {
+        println!(
+            "  {} {}",
+            "Block:      ".truecolor(120, 120, 120),
+            block.to_string().dimmed()
+        );
+    }
+}
+
+/// Parse a 0x-prefixed (or bare) 32-byte value such as a root or nullifier
+fn parse_bytes32(value: &str, what: &str) -> Result<B256> {
+    let bytes =
+        hex::decode(value.trim_start_matches("0x")).with_context(|| format!("Invalid {}", what))?;
+    if bytes.len() != 32 {
+        return Err(anyhow!("{} must be 32 bytes", what));
+    }
+    Ok(B256::from_slice(&bytes))
+}
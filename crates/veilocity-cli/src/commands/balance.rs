@@ -2,7 +2,7 @@
 
 use crate::config::Config;
 use crate::ui;
-use crate::wallet::{format_mnt, WalletManager};
+use crate::wallet::{format_asset, format_mnt, WalletManager};
 use anyhow::{Context, Result};
 use colored::Colorize;
 use veilocity_core::poseidon::{field_to_bytes, PoseidonHasher};
@@ -48,8 +48,8 @@ pub async fn run(config: &Config) -> Result<()> {
     let state = StateManager::new(&db_path).context("Failed to load state")?;
 
     // Get account
-    let mut hasher = PoseidonHasher::new();
-    let pubkey_field = veilocity_secret.derive_pubkey(&mut hasher);
+    let hasher = PoseidonHasher::new();
+    let pubkey_field = veilocity_secret.derive_pubkey(&hasher);
     let pubkey_bytes = field_to_bytes(&pubkey_field);
 
     let account = state.get_account(&pubkey_bytes)?;
@@ -97,6 +97,29 @@ pub async fn run(config: &Config) -> Result<()> {
         );
     }
 
+    // Token pools are separate accounts under the same key
+    let tokens: Vec<_> = state
+        .get_accounts(&pubkey_bytes)?
+        .into_iter()
+        .filter(|a| !a.asset.is_native())
+        .collect();
+    if !tokens.is_empty() {
+        println!();
+        println!("{}", ui::header("Token Balances"));
+        println!();
+        for account in tokens {
+            let token = config.asset_info(&account.asset);
+            println!(
+                "  {} {}  {}",
+                "◈".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2),
+                format_asset(account.balance, &token)
+                    .truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
+                    .bold(),
+                format!("(leaf {}, nonce {})", account.index, account.nonce).dimmed()
+            );
+        }
+    }
+
     // Show state info
     println!();
     println!("{}", ui::header("State Info"));
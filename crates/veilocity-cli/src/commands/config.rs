@@ -1,9 +1,10 @@
 //! Config command - view and update configuration
 
-use crate::config::{get_data_dir, Config};
+use crate::config::{get_data_dir, Config, SignerKind, TokenConfig};
 use crate::ui;
 use anyhow::{anyhow, Result};
 use colored::Colorize;
+use veilocity_contracts::gas::MIN_REPLACEMENT_BUMP_PERCENT;
 
 /// Config subcommands
 #[derive(Debug, Clone)]
@@ -46,6 +47,21 @@ async fn show_config(config_path: &std::path::Path) -> Result<()> {
         "RPC URL:      ".truecolor(120, 120, 120),
         config.network.rpc_url.bright_white()
     );
+    for url in &config.network.fallback_rpc_urls {
+        println!(
+            "  {} {}",
+            "Fallback RPC: ".truecolor(120, 120, 120),
+            url.bright_white()
+        );
+    }
+    if let Some(quorum) = config.network.rpc_quorum {
+        println!(
+            "  {} {} of {} endpoints",
+            "RPC Quorum:   ".truecolor(120, 120, 120),
+            quorum.to_string().bright_white(),
+            1 + config.network.fallback_rpc_urls.len()
+        );
+    }
     println!(
         "  {} {}",
         "Chain ID:     ".truecolor(120, 120, 120),
@@ -89,6 +105,88 @@ async fn show_config(config_path: &std::path::Path) -> Result<()> {
         );
     }
 
+    println!();
+    println!("{}", ui::header("Gas"));
+    println!();
+    println!(
+        "  {} {}x",
+        "Gas Multiplier:".truecolor(120, 120, 120),
+        config.gas.gas_multiplier.to_string().bright_white()
+    );
+    println!(
+        "  {} {}",
+        "Priority Fee:  ".truecolor(120, 120, 120),
+        match config.gas.priority_fee_gwei {
+            Some(gwei) => format!("{} gwei", gwei).bright_white(),
+            None => "network suggestion".dimmed(),
+        }
+    );
+    println!(
+        "  {} {}",
+        "Max Fee:       ".truecolor(120, 120, 120),
+        match config.gas.max_fee_gwei {
+            Some(gwei) => format!("{} gwei", gwei).bright_white(),
+            None => "no ceiling".dimmed(),
+        }
+    );
+    println!(
+        "  {} {}%",
+        "Replace Bump:  ".truecolor(120, 120, 120),
+        config.gas.replacement_bump_percent.to_string().bright_white()
+    );
+    println!(
+        "  {} {}s",
+        "Receipt Wait:  ".truecolor(120, 120, 120),
+        config.gas.receipt_timeout_secs.to_string().bright_white()
+    );
+
+    println!();
+    println!("{}", ui::header("Signer"));
+    println!();
+    println!(
+        "  {} {}",
+        "Signer:        ".truecolor(120, 120, 120),
+        match config.signer.kind {
+            SignerKind::Local => "local wallet key".bright_white(),
+            SignerKind::Ledger =>
+                format!("ledger (account {})", config.signer.ledger_index).bright_white(),
+            SignerKind::Remote => "remote".bright_white(),
+        }
+    );
+    if config.signer.kind == SignerKind::Remote {
+        println!(
+            "  {} {}",
+            "Signer URL:    ".truecolor(120, 120, 120),
+            match &config.signer.remote_url {
+                Some(url) => url.bright_white(),
+                None => "not set".red(),
+            }
+        );
+        println!(
+            "  {} {}",
+            "Signer Address:".truecolor(120, 120, 120),
+            match &config.signer.remote_address {
+                Some(address) => address.bright_white(),
+                None => "not set".red(),
+            }
+        );
+    }
+
+    println!();
+    println!("{}", ui::header("Tokens"));
+    println!();
+    if config.tokens.is_empty() {
+        println!("  {}", "No tokens configured (native MNT only)".dimmed());
+    }
+    for token in &config.tokens {
+        println!(
+            "  {} {} {}",
+            format!("{:<15}", token.symbol).truecolor(120, 120, 120),
+            token.address.bright_white(),
+            format!("({} decimals)", token.decimals).dimmed()
+        );
+    }
+
     println!();
     println!("{}", ui::header("Paths"));
     println!();
@@ -154,6 +252,63 @@ async fn set_config(config_path: &std::path::Path, key: &str, value: &str) -> Re
             );
             println!();
         }
+        "fallback_rpc" | "fallback-rpc" | "fallback_rpc_urls" => {
+            config.network.fallback_rpc_urls = if value.is_empty() || value == "none" {
+                Vec::new()
+            } else {
+                value
+                    .split(',')
+                    .map(|url| url.trim().to_string())
+                    .filter(|url| !url.is_empty())
+                    .collect()
+            };
+            config.save()?;
+
+            println!();
+            ui::print_success("Fallback RPC URLs updated!");
+            println!();
+            if config.network.fallback_rpc_urls.is_empty() {
+                println!("  {}", "No fallback RPC URLs".dimmed());
+            }
+            for url in &config.network.fallback_rpc_urls {
+                println!(
+                    "  {} {}",
+                    "Fallback RPC:".truecolor(120, 120, 120),
+                    url.bright_white()
+                );
+            }
+            println!();
+        }
+        "rpc_quorum" | "rpc-quorum" | "quorum" => {
+            let quorum = if value.is_empty() || value == "none" {
+                None
+            } else {
+                let quorum: usize = value.parse().map_err(|_| anyhow!("Invalid quorum"))?;
+                let endpoints = 1 + config.network.fallback_rpc_urls.len();
+                if quorum == 0 || quorum > endpoints {
+                    return Err(anyhow!(
+                        "Quorum must be between 1 and the number of RPC URLs ({})",
+                        endpoints
+                    ));
+                }
+                Some(quorum)
+            };
+            config.network.rpc_quorum = quorum;
+            config.save()?;
+
+            println!();
+            ui::print_success("RPC quorum updated!");
+            println!();
+            println!(
+                "  {} {}",
+                "RPC Quorum:".truecolor(120, 120, 120),
+                quorum
+                    .map(|q| q.to_string())
+                    .unwrap_or_else(|| "off".to_string())
+                    .bright_white()
+            );
+            println!();
+        }
         "chain_id" | "chain-id" | "chainid" => {
             let chain_id: u64 = value.parse().map_err(|_| anyhow!("Invalid chain ID"))?;
             config.network.chain_id = chain_id;
@@ -184,13 +339,271 @@ async fn set_config(config_path: &std::path::Path, key: &str, value: &str) -> Re
             );
             println!();
         }
+        "vk_hash" | "vk-hash" => {
+            crate::commands::vk::parse_vk_hash(value)?;
+            config.prover.vk_hash = Some(value.to_string());
+            config.save()?;
+
+            println!();
+            ui::print_success("Pinned vk hash updated!");
+            println!();
+            println!(
+                "  {} {}",
+                "VK Hash:".truecolor(120, 120, 120),
+                value.bright_white()
+            );
+            println!();
+        }
+        "remote_prover" | "remote-prover" | "prover_url" | "remote_url" => {
+            if value.is_empty() || value == "none" {
+                config.prover.remote_url = None;
+            } else {
+                if !value.starts_with("http://") && !value.starts_with("https://") {
+                    return Err(anyhow!("Invalid prover URL. Expected http://... or https://..."));
+                }
+                config.prover.remote_url = Some(value.to_string());
+            }
+            config.save()?;
+
+            println!();
+            ui::print_success("Remote prover updated!");
+            println!();
+            println!(
+                "  {} {}",
+                "Prover:".truecolor(120, 120, 120),
+                config
+                    .prover
+                    .remote_url
+                    .as_deref()
+                    .unwrap_or("local (nargo + bb)")
+                    .bright_white()
+            );
+            println!();
+        }
+        "gas_multiplier" | "gas-multiplier" => {
+            let multiplier: f64 = value.parse().map_err(|_| anyhow!("Invalid gas multiplier"))?;
+            if multiplier < 1.0 {
+                return Err(anyhow!("Gas multiplier must be at least 1.0"));
+            }
+            config.gas.gas_multiplier = multiplier;
+            config.save()?;
+
+            println!();
+            ui::print_success("Gas multiplier updated!");
+            println!();
+            println!(
+                "  {} {}x",
+                "Gas Multiplier:".truecolor(120, 120, 120),
+                multiplier.to_string().bright_white()
+            );
+            println!();
+        }
+        "priority_fee" | "priority-fee" | "priority_fee_gwei" | "max_fee" | "max-fee"
+        | "max_fee_gwei" => {
+            let gwei = if value.is_empty() || value == "none" {
+                None
+            } else {
+                Some(value.parse::<f64>().map_err(|_| anyhow!("Invalid fee in gwei"))?)
+            };
+            let priority = key.to_lowercase().starts_with("priority");
+            if priority {
+                config.gas.priority_fee_gwei = gwei;
+            } else {
+                config.gas.max_fee_gwei = gwei;
+            }
+            config.save()?;
+
+            println!();
+            ui::print_success(if priority {
+                "Priority fee updated!"
+            } else {
+                "Max fee ceiling updated!"
+            });
+            println!();
+            println!(
+                "  {} {}",
+                "Fee:".truecolor(120, 120, 120),
+                gwei.map(|g| format!("{} gwei", g))
+                    .unwrap_or_else(|| "unset".to_string())
+                    .bright_white()
+            );
+            println!();
+        }
+        "replacement_bump" | "replacement-bump" => {
+            let percent: u64 = value.parse().map_err(|_| anyhow!("Invalid percentage"))?;
+            if percent < MIN_REPLACEMENT_BUMP_PERCENT {
+                return Err(anyhow!(
+                    "Replacement bump must be at least {}%",
+                    MIN_REPLACEMENT_BUMP_PERCENT
+                ));
+            }
+            config.gas.replacement_bump_percent = percent;
+            config.save()?;
+
+            println!();
+            ui::print_success("Replacement bump updated!");
+            println!();
+            println!(
+                "  {} {}%",
+                "Replace Bump:".truecolor(120, 120, 120),
+                percent.to_string().bright_white()
+            );
+            println!();
+        }
+        "receipt_timeout" | "receipt-timeout" | "receipt_timeout_secs" => {
+            let secs: u64 = value.parse().map_err(|_| anyhow!("Invalid number of seconds"))?;
+            if secs == 0 {
+                return Err(anyhow!("Receipt timeout must be at least 1 second"));
+            }
+            config.gas.receipt_timeout_secs = secs;
+            config.save()?;
+
+            println!();
+            ui::print_success("Receipt timeout updated!");
+            println!();
+            println!(
+                "  {} {}s",
+                "Receipt Wait:".truecolor(120, 120, 120),
+                secs.to_string().bright_white()
+            );
+            println!();
+        }
+        "signer" | "signer_kind" => {
+            config.signer.kind = match value.to_lowercase().as_str() {
+                "local" => SignerKind::Local,
+                "ledger" => SignerKind::Ledger,
+                "remote" => SignerKind::Remote,
+                _ => return Err(anyhow!("Signer must be 'local', 'ledger' or 'remote'")),
+            };
+            config.save()?;
+
+            println!();
+            ui::print_success("Signer updated!");
+            println!();
+            println!(
+                "  {} {}",
+                "Signer:".truecolor(120, 120, 120),
+                value.to_lowercase().bright_white()
+            );
+            println!();
+        }
+        "signer_url" | "signer-url" => {
+            if !value.starts_with("http://") && !value.starts_with("https://") {
+                return Err(anyhow!("Signer URL must start with http:// or https://"));
+            }
+            config.signer.remote_url = Some(value.to_string());
+            config.save()?;
+
+            println!();
+            ui::print_success("Remote signer URL updated!");
+            println!();
+            println!(
+                "  {} {}",
+                "Signer URL:".truecolor(120, 120, 120),
+                value.bright_white()
+            );
+            println!();
+        }
+        "signer_address" | "signer-address" => {
+            if !value.starts_with("0x") || value.len() != 42 {
+                return Err(anyhow!("Invalid signer address. Expected 0x... (42 characters)"));
+            }
+            config.signer.remote_address = Some(value.to_string());
+            config.save()?;
+
+            println!();
+            ui::print_success("Remote signer address updated!");
+            println!();
+            println!(
+                "  {} {}",
+                "Signer Address:".truecolor(120, 120, 120),
+                value.bright_white()
+            );
+            println!();
+        }
+        "ledger_index" | "ledger-index" => {
+            let index: usize = value.parse().map_err(|_| anyhow!("Invalid account index"))?;
+            config.signer.ledger_index = index;
+            config.save()?;
+
+            println!();
+            ui::print_success("Ledger account updated!");
+            println!();
+            println!(
+                "  {} {}",
+                "Ledger Account:".truecolor(120, 120, 120),
+                index.to_string().bright_white()
+            );
+            println!();
+        }
+        "token" | "add_token" | "add-token" => {
+            let parts: Vec<&str> = value.split(':').map(str::trim).collect();
+            let [symbol, address, decimals] = parts[..] else {
+                return Err(anyhow!("Expected <SYMBOL>:<address>:<decimals>, e.g. USDC:0x...:6"));
+            };
+            if symbol.is_empty() || symbol.eq_ignore_ascii_case("MNT") {
+                return Err(anyhow!("Invalid token symbol"));
+            }
+            if !address.starts_with("0x") || address.len() != 42 {
+                return Err(anyhow!("Invalid token address. Expected 0x... (42 characters)"));
+            }
+            let decimals: u8 = decimals.parse().map_err(|_| anyhow!("Invalid decimals"))?;
+
+            let token = TokenConfig {
+                symbol: symbol.to_string(),
+                address: address.to_string(),
+                decimals,
+            };
+            token.info()?;
+            config.tokens.retain(|t| !t.symbol.eq_ignore_ascii_case(symbol));
+            config.tokens.push(token);
+            config.save()?;
+
+            println!();
+            ui::print_success("Token added!");
+            println!();
+            println!(
+                "  {} {} {}",
+                format!("{}:", symbol).truecolor(120, 120, 120),
+                address.bright_white(),
+                format!("({} decimals)", decimals).dimmed()
+            );
+            println!();
+        }
+        "remove_token" | "remove-token" => {
+            let before = config.tokens.len();
+            config.tokens.retain(|t| !t.symbol.eq_ignore_ascii_case(value));
+            if config.tokens.len() == before {
+                return Err(anyhow!("No token '{}' configured", value));
+            }
+            config.save()?;
+
+            println!();
+            ui::print_success(&format!("Token {} removed", value));
+            println!();
+        }
         _ => {
             return Err(anyhow!(
                 "Unknown config key: '{}'\n\nAvailable keys:\n  \
                 vault, vault_address  - VeilocityVault contract address\n  \
                 rpc, rpc_url          - Network RPC URL\n  \
+                fallback_rpc          - Comma-separated RPC URLs to fail over to ('none' to clear)\n  \
+                rpc_quorum            - RPC URLs that must agree on root/nullifier reads ('none' to disable)\n  \
                 chain_id              - Network chain ID\n  \
-                deployment_block      - Block number where vault was deployed",
+                deployment_block      - Block number where vault was deployed\n  \
+                vk_hash               - Pinned withdraw verification key hash\n  \
+                remote_prover         - Remote prover server URL ('none' for local)\n  \
+                gas_multiplier        - Multiplier on estimated gas (at least 1.0)\n  \
+                priority_fee          - Priority fee in gwei ('none' for network suggestion)\n  \
+                max_fee               - Max fee ceiling in gwei ('none' to disable)\n  \
+                replacement_bump      - Percent fee increase for tx speedup/cancel\n  \
+                receipt_timeout       - Seconds to wait for a transaction receipt\n  \
+                signer                - Transaction signer: local, ledger or remote\n  \
+                signer_url            - Remote eth_signTransaction endpoint\n  \
+                signer_address        - Account the remote signer signs for\n  \
+                ledger_index          - Ledger Live account index\n  \
+                token                 - Add an ERC-20 token as <SYMBOL>:<address>:<decimals>\n  \
+                remove_token          - Remove a configured token by symbol",
                 key
             ));
         }
@@ -1,21 +1,44 @@
 //! Deposit command - deposit funds from Mantle into Veilocity
 
-use crate::config::Config;
+use crate::config::{Config, SignerKind};
+use crate::signer;
 use crate::ui;
-use crate::wallet::{format_mnt, parse_mnt, WalletManager};
+use crate::wallet::{format_asset, parse_mnt, WalletManager};
 use alloy::primitives::{B256, U256};
 use anyhow::{anyhow, Context, Result};
 use colored::Colorize;
 use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::info;
-use veilocity_contracts::create_vault_client;
+use veilocity_contracts::token::sign_permit;
+use veilocity_contracts::{create_vault_client, TokenInfo};
 use veilocity_core::poseidon::{field_to_bytes, PoseidonHasher};
 use veilocity_core::state::StateManager;
+use veilocity_core::transaction::{Transaction, TransactionStatus, TransactionType};
+use veilocity_core::AssetId;
+
+/// How long a signed token permit stays valid
+const PERMIT_VALIDITY_SECS: u64 = 3600;
 
 /// Run the deposit command
-pub async fn run(config: &Config, amount: f64, dry_run: bool) -> Result<()> {
+///
+/// `token` is a configured token symbol or address; without it the deposit
+/// is native MNT.
+pub async fn run(
+    config: &Config,
+    amount: f64,
+    token: Option<&str>,
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
     let wallet_manager = WalletManager::new(config.clone());
 
+    let token = match token {
+        Some(token) => config.token(token)?,
+        None => TokenInfo::native(),
+    };
+    let asset = AssetId::from(token.address.0 .0);
+
     // Load wallet
     let wallet = wallet_manager.load_wallet()?;
 
@@ -26,8 +49,16 @@ pub async fn run(config: &Config, amount: f64, dry_run: bool) -> Result<()> {
     ))
     .context("Failed to read password")?;
 
-    // Unlock wallet
-    let signer = wallet_manager.unlock(&wallet, &password)?;
+    // Unlock wallet, keeping a local key to sign token permits with
+    let local_key = (config.signer.kind == SignerKind::Local)
+        .then(|| wallet_manager.unlock(&wallet, &password))
+        .transpose()?;
+    let signing = signer::signing_wallet(config, || {
+        local_key
+            .clone()
+            .ok_or_else(|| anyhow!("Local wallet key not unlocked"))
+    })
+    .await?;
     let veilocity_secret = wallet_manager.get_veilocity_secret(&wallet, &password)?;
 
     // Check vault address is configured
@@ -44,7 +75,11 @@ pub async fn run(config: &Config, amount: f64, dry_run: bool) -> Result<()> {
         .context("Invalid vault address")?;
 
     // Parse amount
-    let amount_wei = parse_mnt(amount);
+    let amount_wei = if token.is_native() {
+        parse_mnt(amount)
+    } else {
+        u128::try_from(token.parse(&amount.to_string())?).context("Amount too large")?
+    };
     let amount_u256 = U256::from(amount_wei);
 
     println!();
@@ -55,7 +90,7 @@ pub async fn run(config: &Config, amount: f64, dry_run: bool) -> Result<()> {
     println!(
         "  {} {}  {}",
         "↓".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2).bold(),
-        format_mnt(amount_wei).truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2).bold(),
+        format_asset(amount_wei, &token).truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2).bold(),
         "(entering shielded pool)".dimmed()
     );
     println!();
@@ -65,13 +100,21 @@ pub async fn run(config: &Config, amount: f64, dry_run: bool) -> Result<()> {
     println!(
         "  {} {}",
         "From:   ".truecolor(120, 120, 120),
-        wallet.address.bright_white()
+        signing.address().to_string().bright_white()
     );
     println!(
         "  {} {}",
         "To:     ".truecolor(120, 120, 120),
         "Veilocity Shielded Pool".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
     );
+    if !token.is_native() {
+        println!(
+            "  {} {} {}",
+            "Token:  ".truecolor(120, 120, 120),
+            token.symbol.bright_white(),
+            format!("({})", token.address).dimmed()
+        );
+    }
     println!(
         "  {} {}",
         "Network:".truecolor(120, 120, 120),
@@ -113,10 +156,10 @@ pub async fn run(config: &Config, amount: f64, dry_run: bool) -> Result<()> {
     );
     io::stdout().flush().unwrap();
 
-    let mut hasher = PoseidonHasher::new();
-    let pubkey = veilocity_secret.derive_pubkey(&mut hasher);
+    let hasher = PoseidonHasher::new();
+    let pubkey = veilocity_secret.derive_pubkey(&hasher);
     let pubkey_bytes = field_to_bytes(&pubkey);
-    let pubkey_hex = hex::encode(&pubkey_bytes);
+    let pubkey_hex = hex::encode(pubkey_bytes);
 
     println!(
         "\r  {} Public key derived                         ",
@@ -136,10 +179,10 @@ pub async fn run(config: &Config, amount: f64, dry_run: bool) -> Result<()> {
     );
     io::stdout().flush().unwrap();
 
-    let commitment = veilocity_secret.compute_deposit_commitment(&mut hasher, amount_wei);
+    let commitment = veilocity_secret.compute_asset_deposit_commitment(&hasher, amount_wei, &asset);
     let commitment_bytes = field_to_bytes(&commitment);
     let commitment_b256 = B256::from(commitment_bytes);
-    let commitment_hex = hex::encode(&commitment_bytes);
+    let commitment_hex = hex::encode(commitment_bytes);
 
     println!(
         "\r  {} Commitment computed                        ",
@@ -168,25 +211,24 @@ pub async fn run(config: &Config, amount: f64, dry_run: bool) -> Result<()> {
         "████████████████".truecolor(80, 80, 80)
     );
     println!(
-        "    {} amount: {} wei",
+        "    {} amount: {} {}",
         "├".truecolor(60, 60, 60),
-        amount_wei.to_string().bright_white()
+        amount_wei.to_string().bright_white(),
+        if token.is_native() { "wei" } else { "base units" }
     );
+    if !token.is_native() {
+        println!(
+            "    {} asset: {}",
+            "├".truecolor(60, 60, 60),
+            asset.to_string().bright_white()
+        );
+    }
     println!(
         "    {} {}",
         "└".truecolor(60, 60, 60),
         "(only you can spend this note)".truecolor(100, 100, 100).italic()
     );
 
-    if dry_run {
-        println!();
-        ui::print_notice(
-            "DRY RUN",
-            "No transaction submitted. Remove --dry-run to execute.",
-        );
-        return Ok(());
-    }
-
     // =========================================================================
     // ON-CHAIN TRANSACTION
     // =========================================================================
@@ -208,40 +250,208 @@ pub async fn run(config: &Config, amount: f64, dry_run: bool) -> Result<()> {
     );
     io::stdout().flush().unwrap();
 
-    let vault = create_vault_client(&config.network.rpc_url, vault_address, signer).await?;
+    let vault = create_vault_client(&config.network.rpc(), vault_address, signing)
+        .await?
+        .with_gas_policy(config.gas.policy())
+        .with_receipt_timeout(config.gas.receipt_timeout());
 
     println!(
         "\r  {} Connected to Mantle                        ",
         "✓".green().bold()
     );
 
-    // Submit deposit
+    // Token deposits need the vault allowed to pull the tokens: by a signed
+    // permit when the token and signer support it, otherwise by approving first
+    let mut permit = None;
+    let mut needs_approval = false;
+    if !token.is_native() && vault.token_allowance(token.address).await? < amount_u256 {
+        match (&local_key, vault.permit_domain(token.address).await?) {
+            (Some(key), Some(domain)) => {
+                let nonce = vault.permit_nonce(token.address).await?;
+                let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                let deadline = U256::from(now + PERMIT_VALIDITY_SECS);
+                permit = Some(
+                    sign_permit(key, &domain, vault.address(), amount_u256, nonce, deadline)
+                        .await?,
+                );
+                println!(
+                    "  {} Permit signed for {}",
+                    "✓".green().bold(),
+                    format_asset(amount_wei, &token)
+                );
+            }
+            _ => needs_approval = true,
+        }
+    }
+
+    if needs_approval {
+        if dry_run {
+            println!();
+            ui::print_notice(
+                "DRY RUN",
+                &format!(
+                    "The vault must be approved to spend {} before the deposit can be \
+                     simulated. No transaction submitted.",
+                    format_asset(amount_wei, &token)
+                ),
+            );
+            return Ok(());
+        }
+
+        println!();
+        if !yes
+            && !ui::confirm(&format!(
+                "Approve the vault to spend {}?",
+                format_asset(amount_wei, &token)
+            ))?
+        {
+            println!("  {}", "Deposit cancelled.".dimmed());
+            return Ok(());
+        }
+
+        print!(
+            "  {} Approving {}...",
+            "◐".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2),
+            token.symbol
+        );
+        io::stdout().flush().unwrap();
+
+        let approval = vault.approve_token(token.address, amount_u256).await?;
+        if !vault.wait_for_receipt(approval).await?.success {
+            return Err(anyhow!(
+                "Approval transaction 0x{} reverted",
+                hex::encode(approval)
+            ));
+        }
+
+        println!(
+            "\r  {} Approval confirmed                         ",
+            "✓".green().bold()
+        );
+    }
+
+    // Simulate first so a deposit the vault would reject never costs gas
     print!(
-        "  {} Submitting deposit transaction...",
+        "  {} Simulating deposit...",
         "◐".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
     );
     io::stdout().flush().unwrap();
 
-    let tx_hash = vault.deposit(commitment_b256, amount_u256).await?;
-    let tx_hash_hex = hex::encode(tx_hash);
+    let plan = if token.is_native() {
+        vault.plan_deposit(commitment_b256, amount_u256).await?
+    } else {
+        vault
+            .plan_deposit_token(token.address, commitment_b256, amount_u256, permit.as_ref())
+            .await?
+    };
 
     println!(
-        "\r  {} Transaction confirmed!                     ",
+        "\r  {} Simulation succeeded                       ",
         "✓".green().bold()
     );
+    println!();
+    ui::print_tx_plan(&plan);
 
-    // Record transaction in local database
-    config.ensure_data_dir()?;
-    if let Ok(mut state) = StateManager::new(&config.db_path()) {
-        let _ = state.record_transaction(
-            "deposit",
-            amount_wei,
-            Some(tx_hash.as_slice()),
-            None,
-            "confirmed",
+    if dry_run {
+        println!();
+        ui::print_notice(
+            "DRY RUN",
+            "No transaction submitted. Remove --dry-run to execute.",
         );
+        return Ok(());
     }
 
+    println!();
+    if !yes && !ui::confirm("Send deposit?")? {
+        println!("  {}", "Deposit cancelled.".dimmed());
+        return Ok(());
+    }
+
+    // Record the deposit before broadcasting so it is tracked even if we exit early
+    config.ensure_data_dir()?;
+    let mut state =
+        StateManager::new(&config.db_path()).context("Failed to open local state")?;
+    let mut tx = Transaction::new(TransactionType::Deposit, amount_wei).with_asset(asset);
+    tx.id = state.record_transaction(&tx)?;
+
+    // Submit deposit
+    print!(
+        "  {} Submitting deposit transaction...",
+        "◐".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
+    );
+    io::stdout().flush().unwrap();
+
+    let submitted = if token.is_native() {
+        vault
+            .submit_deposit(commitment_b256, amount_u256, &plan)
+            .await
+    } else {
+        vault
+            .submit_deposit_token(
+                token.address,
+                commitment_b256,
+                amount_u256,
+                permit.as_ref(),
+                &plan,
+            )
+            .await
+    };
+    let tx_hash = match submitted {
+        Ok(tx_hash) => tx_hash,
+        Err(e) => {
+            tx.status = TransactionStatus::Failed;
+            state.update_transaction(&tx)?;
+            return Err(e.into());
+        }
+    };
+    let tx_hash_hex = hex::encode(tx_hash);
+
+    tx.status = TransactionStatus::Submitted;
+    tx.tx_hash = Some(tx_hash.0);
+    state.update_transaction(&tx)?;
+
+    println!(
+        "\r  {} Transaction submitted, waiting for confirmation...",
+        "◐".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
+    );
+
+    let confirmed = match vault.wait_for_receipt(tx_hash).await {
+        Ok(outcome) if outcome.success => {
+            tx.status = TransactionStatus::Confirmed;
+            tx.block_number = outcome.block_number;
+            state.update_transaction(&tx)?;
+            println!(
+                "  {} Transaction confirmed!                     ",
+                "✓".green().bold()
+            );
+            true
+        }
+        Ok(_) => {
+            state.fail_transaction(tx.id)?;
+            println!(
+                "  {} Transaction reverted                       ",
+                "✗".red().bold()
+            );
+            return Err(anyhow!("Deposit transaction 0x{} reverted", tx_hash_hex));
+        }
+        Err(e) => {
+            println!(
+                "  {} Confirmation not received: {}",
+                "⚠".yellow().bold(),
+                e
+            );
+            ui::print_notice(
+                "Pending",
+                &format!(
+                    "Run 'veilocity sync' to pick up the deposit once it is mined, or \
+                     'veilocity tx speedup 0x{}' if it is stuck.",
+                    tx_hash_hex
+                ),
+            );
+            false
+        }
+    };
+
     // =========================================================================
     // SUCCESS SUMMARY
     // =========================================================================
@@ -249,11 +459,19 @@ pub async fn run(config: &Config, amount: f64, dry_run: bool) -> Result<()> {
     println!();
     ui::divider_double(55);
     println!();
-    println!(
-        "  {} {}",
-        "✓".green().bold(),
-        "DEPOSIT SUCCESSFUL".green().bold()
-    );
+    if confirmed {
+        println!(
+            "  {} {}",
+            "✓".green().bold(),
+            "DEPOSIT SUCCESSFUL".green().bold()
+        );
+    } else {
+        println!(
+            "  {} {}",
+            "◐".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2).bold(),
+            "DEPOSIT SUBMITTED".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2).bold()
+        );
+    }
     println!();
 
     println!(
@@ -281,7 +499,7 @@ pub async fn run(config: &Config, amount: f64, dry_run: bool) -> Result<()> {
     println!(
         "  {} {} is now in the shielded pool",
         "◈".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2),
-        format_mnt(amount_wei).truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2).bold()
+        format_asset(amount_wei, &token).truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2).bold()
     );
 
     // Privacy summary
@@ -331,7 +549,7 @@ pub async fn run(config: &Config, amount: f64, dry_run: bool) -> Result<()> {
     );
     println!();
 
-    info!("Deposit of {} wei completed", amount_wei);
+    info!("Deposit of {} completed", format_asset(amount_wei, &token));
 
     Ok(())
 }
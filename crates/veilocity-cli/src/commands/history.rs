@@ -2,13 +2,51 @@
 
 use crate::config::Config;
 use crate::ui;
-use crate::wallet::{format_mnt, WalletManager};
+use crate::wallet::{format_asset, WalletManager};
 use anyhow::{anyhow, Result};
 use colored::Colorize;
 use veilocity_core::state::StateManager;
+use veilocity_core::transaction::{TransactionFilter, TransactionStatus, TransactionType};
+
+/// Options for the history command
+#[derive(Debug, Clone)]
+pub struct HistoryOptions {
+    /// Only show this type (deposit, transfer, withdraw)
+    pub tx_type: Option<String>,
+    /// Only show this status (pending, proven, submitted, confirmed, failed)
+    pub status: Option<String>,
+    /// Only show transactions from the last N days
+    pub days: Option<u64>,
+    /// Maximum number of transactions to show
+    pub limit: u32,
+}
+
+impl HistoryOptions {
+    /// Turn the command-line options into a history filter
+    fn filter(&self) -> Result<TransactionFilter> {
+        let mut filter = TransactionFilter::new().with_limit(self.limit);
+
+        if let Some(tx_type) = &self.tx_type {
+            filter = filter.with_type(tx_type.parse::<TransactionType>()?);
+        }
+        if let Some(status) = &self.status {
+            filter = filter.with_status(status.parse::<TransactionStatus>()?);
+        }
+        if let Some(days) = self.days {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            filter = filter.since(now.saturating_sub(days * 86400));
+        }
+
+        Ok(filter)
+    }
+}
 
 /// Run the history command
-pub async fn run(config: &Config) -> Result<()> {
+pub async fn run(config: &Config, options: &HistoryOptions) -> Result<()> {
+    let filter = options.filter()?;
     let wallet_manager = WalletManager::new(config.clone());
 
     // Check wallet exists
@@ -52,7 +90,7 @@ pub async fn run(config: &Config) -> Result<()> {
     }
 
     let state = StateManager::new(&db_path)?;
-    let transactions = state.get_transactions(50)?;
+    let transactions = state.get_transactions(&filter)?;
 
     if transactions.is_empty() {
         println!();
@@ -81,23 +119,19 @@ pub async fn run(config: &Config) -> Result<()> {
 
     // Print transactions
     for tx in transactions {
-        let tx_type = match tx.tx_type.as_str() {
-            "deposit" => "DEPOSIT".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2),
-            "withdraw" => "WITHDRAW".red(),
-            "transfer" => "TRANSFER".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2),
-            _ => tx.tx_type.normal(),
+        let tx_type = match tx.tx_type {
+            TransactionType::Deposit => "DEPOSIT".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2),
+            TransactionType::Withdraw => "WITHDRAW".red(),
+            TransactionType::Transfer => "TRANSFER".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2),
         };
 
-        let amount = tx
-            .amount()
-            .map(format_mnt)
-            .unwrap_or_else(|| "N/A".to_string());
+        let amount = format_asset(tx.amount, &config.asset_info(&tx.asset));
 
-        let status = match tx.status.as_str() {
-            "confirmed" => "confirmed".green(),
-            "pending" => "pending".yellow(),
-            "failed" => "failed".red(),
-            _ => tx.status.normal(),
+        let status = match tx.status {
+            TransactionStatus::Confirmed => "confirmed".green(),
+            TransactionStatus::Pending => "pending".yellow(),
+            TransactionStatus::Failed => "failed".red(),
+            other => other.as_str().normal(),
         };
 
         let time = format_timestamp(tx.created_at);
@@ -108,11 +142,11 @@ pub async fn run(config: &Config) -> Result<()> {
         );
 
         // Show tx hash if available
-        if let Some(hash) = tx.tx_hash() {
+        if let Some(hash) = tx.tx_hash {
             println!(
                 "    {} 0x{}...",
                 "→".truecolor(80, 80, 80),
-                &hash[..16].dimmed()
+                hex::encode(&hash[..8]).dimmed()
             );
         }
     }
@@ -1,5 +1,6 @@
 //! CLI command implementations
 
+pub mod admin;
 pub mod balance;
 pub mod config;
 pub mod deposit;
@@ -7,4 +8,6 @@ pub mod history;
 pub mod init;
 pub mod sync;
 pub mod transfer;
+pub mod tx;
+pub mod vk;
 pub mod withdraw;
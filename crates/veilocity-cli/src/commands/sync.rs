@@ -6,17 +6,22 @@
 
 use crate::config::Config;
 use crate::ui;
-use crate::wallet::WalletManager;
+use crate::wallet::{format_asset, WalletManager};
+use alloy::primitives::{Address, B256};
+use alloy::providers::Provider;
 use anyhow::{anyhow, Context, Result};
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::time::Instant;
 use tracing::{debug, info, warn};
-use veilocity_contracts::{create_vault_reader, DepositEvent, EventFilter, VeilocityEvent};
+use veilocity_contracts::{create_vault_reader, DepositEvent, VaultReader, VeilocityEvent};
 use veilocity_core::account::AccountSecret;
-use veilocity_core::poseidon::{bytes_to_field, field_to_bytes, u128_to_field, PoseidonHasher};
+use veilocity_core::poseidon::{bytes_to_field, field_to_bytes, PoseidonHasher};
 use veilocity_core::state::StateManager;
+use veilocity_core::transaction::{TransactionFilter, TransactionStatus};
+use veilocity_core::AssetId;
 
 /// Maximum blocks to scan per batch (to avoid RPC timeouts)
 const BLOCKS_PER_BATCH: u64 = 10000;
@@ -46,8 +51,12 @@ struct IndexerDepositsResponse {
 #[allow(dead_code)]
 struct IndexerDeposit {
     commitment: String,
+    /// Token address, absent from indexers that predate token pools
+    #[serde(default)]
+    token: Option<String>,
     amount_wei: String,
-    amount_mnt: f64,
+    #[serde(default)]
+    amount_mnt: Option<f64>,
     leaf_index: u64,
     block_number: u64,
     tx_hash: String,
@@ -188,8 +197,9 @@ async fn sync_via_indexer(
     let mut local_state = StateManager::new(&db_path)?;
 
     // Rebuild local Merkle tree from indexer leaves
-    let mut hasher = PoseidonHasher::new();
+    let hasher = PoseidonHasher::new();
     let mut own_deposits_found = 0u64;
+    let mut own_deposits_by_asset: HashMap<AssetId, u64> = HashMap::new();
 
     println!();
     println!("{}", ui::header("Processing Deposits"));
@@ -212,27 +222,32 @@ async fn sync_via_indexer(
         // Check if this is our deposit
         if let Some(deposit) = deposits_resp.deposits.iter().find(|d| d.leaf_index == i as u64) {
             let amount_wei: u128 = deposit.amount_wei.parse().unwrap_or(0);
-            let amount_field = u128_to_field(amount_wei);
+            let asset = match &deposit.token {
+                Some(token) => token.parse().context("Invalid token address from indexer")?,
+                None => AssetId::NATIVE,
+            };
             let expected_commitment =
-                hasher.compute_deposit_commitment(veilocity_secret.secret(), &amount_field);
+                veilocity_secret.compute_asset_deposit_commitment(&hasher, amount_wei, &asset);
             let expected_bytes = field_to_bytes(&expected_commitment);
 
             if expected_bytes == leaf_arr {
                 own_deposits_found += 1;
+                let asset_deposits = own_deposits_by_asset.entry(asset).or_insert(0);
+                *asset_deposits += 1;
 
-                // Create/update account
-                let pubkey_field = veilocity_secret.derive_pubkey(&mut hasher);
+                // Create/update the account for this deposit's pool
+                let pubkey_field = veilocity_secret.derive_pubkey(&hasher);
                 let pubkey_bytes = field_to_bytes(&pubkey_field);
 
-                if let Some(mut existing) = local_state.get_account(&pubkey_bytes)? {
+                if let Some(mut existing) = local_state.get_asset_account(&pubkey_bytes, &asset)? {
                     // Check if we already counted this deposit
-                    let expected_balance = own_deposits_found as u128 * amount_wei;
+                    let expected_balance = *asset_deposits as u128 * amount_wei;
                     if existing.balance < expected_balance {
                         existing.balance = expected_balance;
                         local_state.update_account(&existing)?;
                     }
                 } else {
-                    local_state.create_account(veilocity_secret, amount_wei)?;
+                    local_state.create_asset_account(veilocity_secret, asset, amount_wei)?;
                 }
 
                 println!(
@@ -240,7 +255,7 @@ async fn sync_via_indexer(
                     "◈".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
                         .bold(),
                     deposit.leaf_index,
-                    format!("{:.6} MNT", deposit.amount_mnt)
+                    format_asset(amount_wei, &config.asset_info(&asset))
                         .truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
                         .bold(),
                     "[YOUR DEPOSIT]".green().bold()
@@ -262,6 +277,17 @@ async fn sync_via_indexer(
         }
     }
 
+    // Receipts come from the chain; the indexer only serves state
+    let vault_address = config
+        .network
+        .vault_address
+        .parse()
+        .context("Invalid vault address")?;
+    let vault = create_vault_reader(&config.network.rpc(), vault_address)?;
+    if let Err(e) = reconcile_transactions(&mut local_state, &vault).await {
+        warn!("Could not reconcile submitted transactions: {}", e);
+    }
+
     // Update sync checkpoint
     local_state.set_sync_checkpoint(state.last_block)?;
 
@@ -349,7 +375,7 @@ async fn sync_via_rpc(config: &Config, veilocity_secret: &AccountSecret) -> Resu
     );
 
     // Create read-only vault client
-    let vault = create_vault_reader(&config.network.rpc_url, vault_address)?;
+    let vault = create_vault_reader(&config.network.rpc(), vault_address)?;
 
     // Get current chain state
     let current_block = vault.get_block_number().await?;
@@ -390,6 +416,10 @@ async fn sync_via_rpc(config: &Config, veilocity_secret: &AccountSecret) -> Resu
 
     let mut state = StateManager::new(&db_path)?;
 
+    if let Err(e) = reconcile_transactions(&mut state, &vault).await {
+        warn!("Could not reconcile submitted transactions: {}", e);
+    }
+
     // Get last synced block from database (default to 0 if not set)
     let deployment_block = config.sync.deployment_block.unwrap_or(0);
     let last_synced_block = state.get_sync_checkpoint().unwrap_or(deployment_block);
@@ -438,28 +468,29 @@ async fn sync_via_rpc(config: &Config, veilocity_secret: &AccountSecret) -> Resu
     );
 
     // Fetch and process events in batches
-    let mut from_block = last_synced_block + 1;
+    let mut stream = vault
+        .event_stream(last_synced_block + 1)
+        .with_max_range(BLOCKS_PER_BATCH);
     let mut total_deposits_processed = 0u64;
     let mut own_deposits_found = 0u64;
     let mut total_withdrawals_processed = 0u64;
 
     // Create hasher for commitment verification
-    let mut hasher = PoseidonHasher::new();
-
-    while from_block <= current_block {
-        let to_block = std::cmp::min(from_block + BLOCKS_PER_BATCH - 1, current_block);
+    let hasher = PoseidonHasher::new();
 
-        debug!("Fetching events from block {} to {}", from_block, to_block);
-
-        let filter = EventFilter::block_range(from_block, to_block);
-        let events = vault.get_all_events(&filter).await?;
+    while let Some(batch) = stream.next_batch(current_block).await? {
+        debug!(
+            "Fetched {} events from block {} to {}",
+            batch.events.len(),
+            batch.from_block,
+            batch.to_block
+        );
 
-        for event in events {
+        for event in batch.events {
             match event {
                 VeilocityEvent::Deposit(deposit) => {
                     // Check if this deposit belongs to us
-                    let is_ours =
-                        check_deposit_ownership(veilocity_secret, &deposit, &mut hasher);
+                    let is_ours = check_deposit_ownership(veilocity_secret, &deposit, &hasher);
 
                     process_deposit(&mut state, &deposit)?;
                     total_deposits_processed += 1;
@@ -470,7 +501,7 @@ async fn sync_via_rpc(config: &Config, veilocity_secret: &AccountSecret) -> Resu
                             &mut state,
                             veilocity_secret,
                             &deposit,
-                            &mut hasher,
+                            &hasher,
                         )?;
                     }
                 }
@@ -486,9 +517,8 @@ async fn sync_via_rpc(config: &Config, veilocity_secret: &AccountSecret) -> Resu
         }
 
         // Update sync checkpoint
-        state.set_sync_checkpoint(to_block)?;
-        pb.set_position(to_block - last_synced_block);
-        from_block = to_block + 1;
+        state.set_sync_checkpoint(batch.to_block)?;
+        pb.set_position(batch.to_block - last_synced_block);
     }
 
     pb.finish_and_clear();
@@ -551,14 +581,22 @@ async fn sync_via_rpc(config: &Config, veilocity_secret: &AccountSecret) -> Resu
     Ok(())
 }
 
+/// Pool a deposit or withdrawal event's token belongs to
+fn asset_of(token: Address) -> AssetId {
+    AssetId::from(token.0 .0)
+}
+
 /// Check if a deposit belongs to the user by verifying the commitment
 fn check_deposit_ownership(
     secret: &AccountSecret,
     deposit: &DepositEvent,
-    hasher: &mut PoseidonHasher,
+    hasher: &PoseidonHasher,
 ) -> bool {
-    let amount_field = u128_to_field(deposit.amount.to::<u128>());
-    let expected_commitment = hasher.compute_deposit_commitment(secret.secret(), &amount_field);
+    let expected_commitment = secret.compute_asset_deposit_commitment(
+        hasher,
+        deposit.amount.to::<u128>(),
+        &asset_of(deposit.token),
+    );
     let expected_bytes = field_to_bytes(&expected_commitment);
     expected_bytes == deposit.commitment.0
 }
@@ -568,13 +606,14 @@ fn create_account_from_deposit(
     state: &mut StateManager,
     secret: &AccountSecret,
     deposit: &DepositEvent,
-    hasher: &mut PoseidonHasher,
+    hasher: &PoseidonHasher,
 ) -> Result<()> {
     let pubkey_field = secret.derive_pubkey(hasher);
     let pubkey_bytes = field_to_bytes(&pubkey_field);
     let deposit_amount = deposit.amount.to::<u128>();
+    let asset = asset_of(deposit.token);
 
-    if let Some(mut existing) = state.get_account(&pubkey_bytes)? {
+    if let Some(mut existing) = state.get_asset_account(&pubkey_bytes, &asset)? {
         existing.balance += deposit_amount;
         state.update_account(&existing)?;
         debug!(
@@ -582,7 +621,7 @@ fn create_account_from_deposit(
             existing.balance
         );
     } else {
-        let account = state.create_account(secret, deposit_amount)?;
+        let account = state.create_asset_account(secret, asset, deposit_amount)?;
         debug!(
             "Created new account at index {} with balance {} wei",
             account.index, account.balance
@@ -603,7 +642,7 @@ fn process_deposit(state: &mut StateManager, deposit: &DepositEvent) -> Result<(
                 "Gap detected: expected index {}, got {}. Filling with empty leaves.",
                 expected_index, deposit_index
             );
-            let mut hasher = PoseidonHasher::new();
+            let hasher = PoseidonHasher::new();
             for _ in expected_index..deposit_index {
                 let empty_leaf = hasher.hash2(
                     &veilocity_core::poseidon::FieldElement::from(0u64),
@@ -637,7 +676,7 @@ pub async fn watch(config: &Config) -> Result<()> {
         .parse()
         .context("Invalid vault address")?;
 
-    let vault = create_vault_reader(&config.network.rpc_url, vault_address)?;
+    let vault = create_vault_reader(&config.network.rpc(), vault_address)?;
 
     println!(
         "{} Watching for new events...",
@@ -648,34 +687,37 @@ pub async fn watch(config: &Config) -> Result<()> {
 
     let db_path = config.db_path();
     let mut state = StateManager::new(&db_path)?;
-    let mut last_block = vault.get_block_number().await?;
+    let mut stream = vault.event_stream(vault.get_block_number().await? + 1);
 
     loop {
         tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
 
         let current_block = vault.get_block_number().await?;
-        if current_block > last_block {
-            let filter = EventFilter::block_range(last_block + 1, current_block);
-            let events = vault.get_all_events(&filter).await?;
+        if stream.next_block() > current_block {
+            continue;
+        }
 
-            for event in events {
+        while let Some(batch) = stream.next_batch(current_block).await? {
+            for event in batch.events {
                 match event {
                     VeilocityEvent::Deposit(deposit) => {
+                        let token = config.asset_info(&asset_of(deposit.token));
                         println!(
-                            "  {} [Block {}] Deposit: {} MNT (index {})",
+                            "  {} [Block {}] Deposit: {} (index {})",
                             "◈".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2),
                             deposit.block_number,
-                            deposit.amount_mnt(),
+                            format_asset(deposit.amount.to::<u128>(), &token),
                             deposit.leaf_index
                         );
                         process_deposit(&mut state, &deposit)?;
                     }
                     VeilocityEvent::Withdrawal(withdrawal) => {
+                        let token = config.asset_info(&asset_of(withdrawal.token));
                         println!(
-                            "  {} [Block {}] Withdrawal: {} MNT to {:?}",
+                            "  {} [Block {}] Withdrawal: {} to {:?}",
                             "↑".red(),
                             withdrawal.block_number,
-                            withdrawal.amount_mnt(),
+                            format_asset(withdrawal.amount.to::<u128>(), &token),
                             withdrawal.recipient
                         );
                         let nullifier_bytes: [u8; 32] = withdrawal.nullifier.0;
@@ -694,8 +736,56 @@ pub async fn watch(config: &Config) -> Result<()> {
                 }
             }
 
-            state.set_sync_checkpoint(current_block)?;
-            last_block = current_block;
+            state.set_sync_checkpoint(batch.to_block)?;
+        }
+
+        if let Err(e) = reconcile_transactions(&mut state, &vault).await {
+            warn!("Could not reconcile submitted transactions: {}", e);
         }
     }
 }
+
+/// Settle transactions that were broadcast but not yet seen mined
+///
+/// A successful receipt confirms the transaction; a reverted one fails it
+/// and rolls back the balance change applied when it was submitted.
+/// Transactions still waiting to be mined are left for the next sync.
+async fn reconcile_transactions<P: Provider + Clone>(
+    state: &mut StateManager,
+    vault: &VaultReader<P>,
+) -> Result<()> {
+    let submitted =
+        state.get_transactions(&TransactionFilter::new().with_status(TransactionStatus::Submitted))?;
+
+    for mut tx in submitted {
+        let Some(tx_hash) = tx.tx_hash else {
+            continue;
+        };
+        let Some(outcome) = vault.transaction_outcome(B256::from(tx_hash)).await? else {
+            debug!("Transaction {} not mined yet", tx.id);
+            continue;
+        };
+
+        if outcome.success {
+            tx.status = TransactionStatus::Confirmed;
+            tx.block_number = outcome.block_number;
+            state.update_transaction(&tx)?;
+            println!(
+                "  {} {} 0x{}... confirmed",
+                "✓".green().bold(),
+                tx.tx_type,
+                &hex::encode(tx_hash)[..16]
+            );
+        } else {
+            state.fail_transaction(tx.id)?;
+            println!(
+                "  {} {} 0x{}... reverted, local state rolled back",
+                "✗".red().bold(),
+                tx.tx_type,
+                &hex::encode(tx_hash)[..16]
+            );
+        }
+    }
+
+    Ok(())
+}
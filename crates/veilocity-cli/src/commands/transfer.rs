@@ -1,16 +1,18 @@
 //! Transfer command - send private transfer to another user
 
+use crate::commands::vk;
 use crate::config::Config;
 use crate::ui;
 use crate::wallet::{format_mnt, parse_mnt, WalletManager};
 use anyhow::{anyhow, Context, Result};
 use colored::Colorize;
 use std::io::{self, Write};
-use std::path::PathBuf;
 use tracing::info;
+use veilocity_core::merkle::LeafId;
 use veilocity_core::poseidon::{field_to_bytes, hex_to_field, u128_to_field, u64_to_field, PoseidonHasher};
-use veilocity_core::state::StateManager;
-use veilocity_prover::{NoirProver, TransferWitness, TREE_DEPTH};
+use veilocity_core::state::{StateDelta, StateManager};
+use veilocity_core::transaction::{Transaction, TransactionStatus, TransactionType};
+use veilocity_prover::{CircuitType, NoirProver, Prover, TransferWitness};
 
 /// Run the transfer command
 pub async fn run(config: &Config, recipient: &str, amount: f64, dry_run: bool) -> Result<()> {
@@ -73,6 +75,7 @@ pub async fn run(config: &Config, recipient: &str, amount: f64, dry_run: bool) -
     // Load state
     let mut state = StateManager::new(&config.db_path())
         .context("Failed to load state. Run 'veilocity sync' first.")?;
+    vk::ensure_tree_depth(&state)?;
 
     // Get sender's account
     println!();
@@ -82,8 +85,8 @@ pub async fn run(config: &Config, recipient: &str, amount: f64, dry_run: bool) -
     );
     io::stdout().flush().unwrap();
 
-    let mut hasher = PoseidonHasher::new();
-    let sender_pubkey_field = veilocity_secret.derive_pubkey(&mut hasher);
+    let hasher = PoseidonHasher::new();
+    let sender_pubkey_field = veilocity_secret.derive_pubkey(&hasher);
     let sender_pubkey_bytes = field_to_bytes(&sender_pubkey_field);
 
     let sender_account = state
@@ -146,13 +149,16 @@ pub async fn run(config: &Config, recipient: &str, amount: f64, dry_run: bool) -
     );
     io::stdout().flush().unwrap();
 
+    // The nullifier commits to the circuits' global index; the circuit works
+    // with the index inside the sender's tree
+    let sender_leaf = LeafId::from_global(sender_account.index, state.tree_depth());
     let nullifier = veilocity_secret.compute_nullifier(
         state.hasher(),
-        sender_account.index,
+        sender_leaf.nullifier_index(),
         sender_account.nonce,
     );
     let nullifier_bytes = field_to_bytes(&nullifier);
-    let nullifier_hex = hex::encode(&nullifier_bytes);
+    let nullifier_hex = hex::encode(nullifier_bytes);
 
     println!(
         "\r  {} Nullifier computed                         ",
@@ -177,18 +183,19 @@ pub async fn run(config: &Config, recipient: &str, amount: f64, dry_run: bool) -
     );
     io::stdout().flush().unwrap();
 
-    let sender_path = state.get_merkle_proof(sender_account.index);
-    let sender_path_fields: Vec<_> = sender_path.into_iter().collect();
+    let sender_proof = state.get_merkle_proof(sender_account.index)?;
+    let sender_path_fields = sender_proof.path;
 
     println!(
         "\r  {} Merkle proof generated                     ",
         "✓".green().bold()
     );
     println!(
-        "    {} depth={}, leaf_index={}",
+        "    {} depth={}, tree={}, leaf_index={}",
         "├".truecolor(60, 60, 60),
-        TREE_DEPTH.to_string().bright_white(),
-        sender_account.index.to_string().bright_white()
+        state.tree_depth().to_string().bright_white(),
+        sender_leaf.tree_id.to_string().bright_white(),
+        sender_leaf.index.to_string().bright_white()
     );
     println!(
         "    {} {}",
@@ -204,9 +211,10 @@ pub async fn run(config: &Config, recipient: &str, amount: f64, dry_run: bool) -
     );
     io::stdout().flush().unwrap();
 
-    let state_root = state.state_root();
+    // Root of the tree holding the sender (sealed trees keep their final root)
+    let state_root = sender_proof.root;
     let state_root_bytes = field_to_bytes(&state_root);
-    let state_root_hex = hex::encode(&state_root_bytes);
+    let state_root_hex = hex::encode(state_root_bytes);
 
     println!(
         "\r  {} State root computed                        ",
@@ -232,11 +240,12 @@ pub async fn run(config: &Config, recipient: &str, amount: f64, dry_run: bool) -
         *veilocity_secret.secret(),
         u128_to_field(sender_account.balance),
         u64_to_field(sender_account.nonce),
-        u64_to_field(sender_account.index),
+        u64_to_field(sender_leaf.index),
         sender_path_fields,
         recipient_pubkey,
         u128_to_field(amount_wei),
-    )?;
+    )?
+    .with_tree_id(sender_leaf.tree_id);
 
     println!(
         "\r  {} Witness constructed                        ",
@@ -274,108 +283,87 @@ pub async fn run(config: &Config, recipient: &str, amount: f64, dry_run: bool) -
 
     println!();
 
-    // Stage 5: Initialize prover
-    let prover = NoirProver::new(PathBuf::from("circuits"));
-
-    if !prover.is_compiled() {
-        print!(
-            "  {} Compiling Noir circuits...",
-            "◐".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2)
+    // Recorded before proving so an interrupted transfer still shows in history
+    let mut tx = Transaction::new(TransactionType::Transfer, amount_wei)
+        .with_nullifier(nullifier_bytes)
+        .with_leaf_index(sender_account.index)
+        .with_nonce(sender_account.nonce)
+        .with_counterparty(recipient);
+    tx.id = state.record_transaction(&tx)?;
+
+    let proven: Result<Vec<u8>> = async {
+        // Stage 5: Initialize prover (remote when prover.remote_url is set)
+        let prover = NoirProver::new(vk::circuits_dir(config)).with_progress(ui::print_proof_event);
+        let remote = vk::remote_prover(config);
+
+        if remote.is_none() && !prover.is_compiled() {
+            print!(
+                "  {} Compiling Noir circuits...",
+                "◐".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2)
+            );
+            io::stdout().flush().unwrap();
+            prover.compile().await?;
+            println!(
+                "\r  {} Circuits compiled                          ",
+                "✓".green().bold()
+            );
+        }
+
+        // Stage 6: Generate ZK proof with real-time updates
+        println!(
+            "  {} {} {}",
+            "▶".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2).bold(),
+            "Generating ZK-SNARK proof".bright_white().bold(),
+            "(Barretenberg)".dimmed()
         );
-        io::stdout().flush().unwrap();
-        prover.compile().await?;
         println!(
-            "\r  {} Circuits compiled                          ",
-            "✓".green().bold()
+            "    {}",
+            "├─ Protocol: UltraPlonk with Plookup".truecolor(100, 100, 100)
         );
-    }
-
-    // Stage 6: Generate ZK proof with real-time updates
-    println!(
-        "  {} {} {}",
-        "▶".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2).bold(),
-        "Generating ZK-SNARK proof".bright_white().bold(),
-        "(Barretenberg)".dimmed()
-    );
-    println!(
-        "    {}",
-        "├─ Protocol: UltraPlonk with Plookup".truecolor(100, 100, 100)
-    );
-    println!(
-        "    {}",
-        "├─ Curve: BN254 (alt_bn128)".truecolor(100, 100, 100)
-    );
-    println!(
-        "    {}",
-        "├─ Commitment: KZG polynomial".truecolor(100, 100, 100)
-    );
-    println!(
-        "    {}",
-        "└─ Security: 128-bit soundness".truecolor(100, 100, 100)
-    );
-    println!();
-
-    // Show progress during actual proof generation
-    print!(
-        "    {} Computing proof ",
-        "◐".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2)
-    );
-    io::stdout().flush().unwrap();
-
-    let proof = prover.prove_transfer(&witness).await?;
-
-    println!(
-        "\r    {} Proof computation complete                ",
-        "✓".green().bold()
-    );
+        println!(
+            "    {}",
+            "├─ Curve: BN254 (alt_bn128)".truecolor(100, 100, 100)
+        );
+        println!(
+            "    {}",
+            "├─ Commitment: KZG polynomial".truecolor(100, 100, 100)
+        );
+        println!(
+            "    {}",
+            "└─ Security: 128-bit soundness".truecolor(100, 100, 100)
+        );
+        println!();
 
-    // Show proof verification box
-    println!();
-    println!(
-        "  {}",
-        "┌─────────────────────────────────────────────────────┐"
-            .truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2)
-    );
-    println!(
-        "  {} {} {}",
-        "│".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2),
-        "ZK-PROOF VERIFIED".green().bold(),
-        "                                │".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2)
-    );
-    println!(
-        "  {}",
-        "├─────────────────────────────────────────────────────┤"
-            .truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2)
-    );
-    println!(
-        "  {} Proof size: {} bytes                              {}",
-        "│".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2),
-        format!("{}", proof.len()).bright_white().bold(),
-        "│".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2)
-    );
-    println!(
-        "  {} {} Zero-Knowledge: Private inputs hidden        {}",
-        "│".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2),
-        "✓".green(),
-        "│".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2)
-    );
-    println!(
-        "  {} {} Soundness: Proof mathematically valid        {}",
-        "│".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2),
-        "✓".green(),
-        "│".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2)
-    );
-    println!(
-        "  {} {} Completeness: All constraints satisfied      {}",
-        "│".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2),
-        "✓".green(),
-        "│".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2)
-    );
-    println!(
-        "  {}",
-        "└─────────────────────────────────────────────────────┘"
-            .truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2)
-    );
+        // Progress lines are driven by the prover as each step actually runs
+        let proof = match &remote {
+            Some(remote) => remote.prove_transfer(&witness).await?,
+            None => prover.prove_transfer(&witness).await?,
+        };
+
+        // Verify locally before applying the transfer to local state
+        if remote.is_some() {
+            ui::print_zk_step("Local verification", "skipped (remote prover)", false);
+        } else if prover.has_vk(CircuitType::Transfer) {
+            if !prover.verify_proof(&proof, CircuitType::Transfer).await? {
+                return Err(anyhow!("Generated proof failed local verification"));
+            }
+            ui::print_proof_verified(proof.len());
+        } else {
+            ui::print_zk_step("Local verification", "skipped (no verification key)", false);
+        }
+
+        Ok(proof)
+    }
+    .await;
+
+    let proof = match proven {
+        Ok(proof) => proof,
+        Err(e) => {
+            tx.status = TransactionStatus::Failed;
+            state.update_transaction(&tx)?;
+            return Err(e);
+        }
+    };
 
     // =========================================================================
     // UPDATE STATE
@@ -398,33 +386,29 @@ pub async fn run(config: &Config, recipient: &str, amount: f64, dry_run: bool) -
     );
     io::stdout().flush().unwrap();
 
+    // Transfers are applied locally; there is nothing to submit on chain
+    tx.status = TransactionStatus::Proven;
     let mut sender_updated = sender_account.clone();
     sender_updated.balance -= amount_wei;
     sender_updated.nonce += 1;
-    state.update_account(&sender_updated)?;
+
+    // Balance, nullifier and history land together or not at all
+    state.apply(
+        &StateDelta::new()
+            .with_account(sender_updated.clone())
+            .with_nullifier(nullifier_bytes)
+            .with_transaction(tx),
+    )?;
 
     println!(
         "\r  {} Sender note updated                        ",
         "✓".green().bold()
     );
-
-    // Mark nullifier as used
-    print!(
-        "  {} Recording nullifier...",
-        "◐".truecolor(ui::PURPLE.0, ui::PURPLE.1, ui::PURPLE.2)
-    );
-    io::stdout().flush().unwrap();
-
-    state.mark_nullifier_used(&nullifier_bytes)?;
-
     println!(
-        "\r  {} Nullifier recorded                         ",
+        "  {} Nullifier recorded                         ",
         "✓".green().bold()
     );
 
-    // Record transaction
-    let _ = state.record_transaction("transfer", amount_wei, None, Some(recipient), "confirmed");
-
     // =========================================================================
     // SUCCESS SUMMARY
     // =========================================================================
@@ -0,0 +1,166 @@
+//! Tx command - speed up or cancel a stuck vault transaction
+
+use crate::config::Config;
+use crate::signer;
+use crate::ui;
+use crate::wallet::WalletManager;
+use alloy::primitives::B256;
+use anyhow::{anyhow, Context, Result};
+use colored::Colorize;
+use veilocity_contracts::create_vault_client;
+use veilocity_core::state::StateManager;
+use veilocity_core::transaction::TransactionStatus;
+
+/// What to do with the stuck transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxAction {
+    /// Resend it with higher fees
+    SpeedUp,
+    /// Replace it with an empty transfer so it can never be mined
+    Cancel,
+}
+
+/// Run the tx command
+pub async fn run(config: &Config, action: TxAction, hash: &str) -> Result<()> {
+    let tx_hash = parse_tx_hash(hash)?;
+
+    if config.network.vault_address.is_empty() {
+        return Err(anyhow!(
+            "Vault address not configured. Please update your config with the deployed vault address."
+        ));
+    }
+    let vault_address = config
+        .network
+        .vault_address
+        .parse()
+        .context("Invalid vault address")?;
+
+    // Transactions sent outside the CLI have no history row; they are still replaced
+    config.ensure_data_dir()?;
+    let mut state = StateManager::new(&config.db_path()).context("Failed to open local state")?;
+    let record = state.get_transaction_by_hash(&tx_hash.0)?;
+    if let Some(tx) = &record {
+        if tx.status != TransactionStatus::Submitted {
+            return Err(anyhow!(
+                "{} 0x{} is already {}",
+                tx.tx_type,
+                hex::encode(tx_hash),
+                tx.status
+            ));
+        }
+    }
+
+    // Replacements only need the transaction signer, not the Veilocity secret
+    let signing = signer::signing_wallet(config, || {
+        let wallet_manager = WalletManager::new(config.clone());
+        let wallet = wallet_manager.load_wallet()?;
+        let password = rpassword::prompt_password(format!(
+            "{} ",
+            "Enter wallet password:".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
+        ))
+        .context("Failed to read password")?;
+        wallet_manager.unlock(&wallet, &password)
+    })
+    .await?;
+
+    let vault = create_vault_client(&config.network.rpc(), vault_address, signing)
+        .await?
+        .with_gas_policy(config.gas.policy())
+        .with_receipt_timeout(config.gas.receipt_timeout());
+
+    println!();
+    println!(
+        "{}",
+        ui::header(match action {
+            TxAction::SpeedUp => "Speed Up Transaction",
+            TxAction::Cancel => "Cancel Transaction",
+        })
+    );
+    println!();
+    println!(
+        "  {} 0x{}",
+        "Original:   ".truecolor(120, 120, 120),
+        hex::encode(tx_hash).bright_white()
+    );
+
+    let replacement = match action {
+        TxAction::SpeedUp => vault.speed_up(tx_hash).await?,
+        TxAction::Cancel => vault.cancel(tx_hash).await?,
+    };
+    println!(
+        "  {} 0x{}",
+        "Replacement:".truecolor(120, 120, 120),
+        hex::encode(replacement).bright_white()
+    );
+
+    println!();
+    println!(
+        "  {} Replacement sent, waiting for confirmation...",
+        "◐".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
+    );
+
+    let outcome = match vault.wait_for_receipt(replacement).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            println!(
+                "  {} Confirmation not received: {}",
+                "⚠".yellow().bold(),
+                e
+            );
+            ui::print_notice(
+                "Pending",
+                &format!(
+                    "Either transaction may still be mined. Run 'veilocity tx speedup 0x{}' \
+                     if the replacement is stuck too.",
+                    hex::encode(replacement)
+                ),
+            );
+            return Ok(());
+        }
+    };
+
+    // The history row keeps the original hash until the replacement is
+    // mined: if the original wins instead, sync still finds its receipt
+    match (action, record) {
+        (TxAction::SpeedUp, Some(mut tx)) if outcome.success => {
+            tx.status = TransactionStatus::Confirmed;
+            tx.tx_hash = Some(replacement.0);
+            tx.block_number = outcome.block_number;
+            state.update_transaction(&tx)?;
+        }
+        (TxAction::SpeedUp, Some(mut tx)) => {
+            tx.tx_hash = Some(replacement.0);
+            state.update_transaction(&tx)?;
+            state.fail_transaction(tx.id)?;
+        }
+        // The original can no longer be mined, so undo whatever it did locally
+        (TxAction::Cancel, Some(tx)) if outcome.success => {
+            state.fail_transaction(tx.id)?;
+        }
+        _ => {}
+    }
+
+    println!();
+    match (action, outcome.success) {
+        (TxAction::SpeedUp, true) => ui::print_success("Transaction confirmed!"),
+        (TxAction::Cancel, true) => ui::print_success("Transaction cancelled."),
+        (_, false) => {
+            return Err(anyhow!(
+                "Replacement 0x{} reverted",
+                hex::encode(replacement)
+            ))
+        }
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Parse a 0x-prefixed (or bare) 32-byte transaction hash
+fn parse_tx_hash(hash: &str) -> Result<B256> {
+    let bytes = hex::decode(hash.trim_start_matches("0x")).context("Invalid transaction hash")?;
+    if bytes.len() != 32 {
+        return Err(anyhow!("Transaction hash must be 32 bytes"));
+    }
+    Ok(B256::from_slice(&bytes))
+}
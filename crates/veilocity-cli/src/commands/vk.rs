@@ -0,0 +1,179 @@
+//! Vk command - check the local verification key against the deployed verifier
+
+use crate::config::Config;
+use crate::ui;
+use alloy::primitives::B256;
+use anyhow::{anyhow, Context, Result};
+use colored::Colorize;
+use std::path::PathBuf;
+use veilocity_contracts::create_vault_reader;
+use veilocity_core::state::StateManager;
+use veilocity_prover::{CircuitType, NoirProver, Prover, ProverError, RemoteProver, TREE_DEPTH};
+
+/// Where the expected vk hash came from
+enum VkSource {
+    /// `prover.vk_hash` in config
+    Pinned([u8; 32]),
+    /// The verifier deployed behind the vault
+    OnChain { verifier: String, matches: bool },
+}
+
+/// Run the vk command
+pub async fn run(config: &Config) -> Result<()> {
+    match remote_prover(config) {
+        Some(remote) => report(config, &remote).await,
+        None => report(config, &NoirProver::new(circuits_dir(config))).await,
+    }
+}
+
+/// Print the prover's vk hash next to the expected one
+async fn report<P: Prover>(config: &Config, prover: &P) -> Result<()> {
+    println!();
+    println!("{}", ui::header("Verification Key"));
+    println!();
+
+    let local = prover
+        .vk_hash(CircuitType::Withdraw)
+        .await
+        .context("Failed to read local withdraw vk hash")?;
+
+    println!(
+        "  {} 0x{}",
+        "Local VK hash: ".truecolor(120, 120, 120),
+        hex::encode(local).bright_white()
+    );
+
+    let matches = match resolve(config, &local).await? {
+        VkSource::Pinned(pinned) => {
+            println!(
+                "  {} 0x{} {}",
+                "Pinned VK hash:".truecolor(120, 120, 120),
+                hex::encode(pinned).bright_white(),
+                "(config)".dimmed()
+            );
+            pinned == local
+        }
+        VkSource::OnChain { verifier, matches } => {
+            println!(
+                "  {} {}",
+                "Verifier:      ".truecolor(120, 120, 120),
+                verifier.bright_white()
+            );
+            matches
+        }
+    };
+
+    println!();
+    if matches {
+        ui::print_success("Local verification key matches the deployed verifier.");
+        println!();
+        Ok(())
+    } else {
+        ui::print_notice(
+            "VK Mismatch",
+            "Proofs from this circuit build will be rejected on-chain. Recompile the circuit or update the verifier.",
+        );
+        println!();
+        Err(anyhow!("Verification key mismatch"))
+    }
+}
+
+/// Refuse to prove withdrawals when the prover's vk does not match the verifier
+///
+/// Uses the pinned hash from config when set, otherwise inspects the verifier
+/// deployed behind the vault.
+pub async fn ensure_withdraw_vk<P: Prover>(config: &Config, prover: &P) -> Result<()> {
+    let local = prover
+        .vk_hash(CircuitType::Withdraw)
+        .await
+        .context("Failed to read local withdraw vk hash")?;
+
+    match resolve(config, &local).await? {
+        VkSource::Pinned(pinned) => {
+            if pinned != local {
+                return Err(ProverError::VkMismatch {
+                    local: format!("0x{}", hex::encode(local)),
+                    expected: format!("0x{}", hex::encode(pinned)),
+                }
+                .into());
+            }
+        }
+        VkSource::OnChain { verifier, matches } => {
+            if !matches {
+                return Err(anyhow!(
+                    "Local withdraw vk 0x{} does not match verifier {}. Run 'veilocity vk' for details.",
+                    hex::encode(local),
+                    verifier
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Refuse to prove when the local tree depth differs from the circuits'
+pub fn ensure_tree_depth(state: &StateManager) -> Result<()> {
+    if state.tree_depth() != TREE_DEPTH {
+        return Err(anyhow!(
+            "Local state uses Merkle tree depth {} but the circuits are compiled for depth {}",
+            state.tree_depth(),
+            TREE_DEPTH
+        ));
+    }
+    Ok(())
+}
+
+/// Circuits directory from config, defaulting to ./circuits
+pub fn circuits_dir(config: &Config) -> PathBuf {
+    config
+        .prover
+        .circuits_path
+        .as_deref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("circuits"))
+}
+
+/// Remote prover from config, if `prover.remote_url` is set
+pub fn remote_prover(config: &Config) -> Option<RemoteProver> {
+    config
+        .prover
+        .remote_url
+        .as_deref()
+        .map(|url| RemoteProver::new(url).with_progress(ui::print_proof_event))
+}
+
+/// Parse a pinned vk hash from config
+pub fn parse_vk_hash(value: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(value.trim_start_matches("0x")).context("Invalid vk hash hex")?;
+    bytes
+        .try_into()
+        .map_err(|_| anyhow!("Invalid vk hash. Expected 32 bytes (0x + 64 hex characters)"))
+}
+
+async fn resolve(config: &Config, local: &[u8; 32]) -> Result<VkSource> {
+    if let Some(pinned) = &config.prover.vk_hash {
+        return Ok(VkSource::Pinned(parse_vk_hash(pinned)?));
+    }
+
+    if config.network.vault_address.is_empty() {
+        return Err(anyhow!(
+            "No vk hash pinned and vault address not configured. Set one with 'veilocity config set vk_hash <hash>'."
+        ));
+    }
+
+    let vault_address = config
+        .network
+        .vault_address
+        .parse()
+        .context("Invalid vault address")?;
+
+    let vault = create_vault_reader(&config.network.rpc(), vault_address)?;
+    let verifier = vault.verifier().await?;
+    let matches = vault.verifier_matches_vk(B256::from(*local)).await?;
+
+    Ok(VkSource::OnChain {
+        verifier: format!("{:?}", verifier),
+        matches,
+    })
+}
@@ -1,23 +1,43 @@
 //! Withdraw command - withdraw funds from Veilocity to Mantle
 
+use crate::commands::vk;
 use crate::config::Config;
+use crate::signer;
 use crate::ui;
-use crate::wallet::{format_mnt, parse_mnt, WalletManager};
+use crate::wallet::{format_asset, parse_mnt, WalletManager};
 use alloy::primitives::{Address, B256, U256};
 use anyhow::{anyhow, Context, Result};
 use colored::Colorize;
 use std::io::{self, Write};
-use std::path::PathBuf;
 use tracing::info;
-use veilocity_contracts::create_vault_client;
+use veilocity_contracts::{create_vault_client, ContractError, TokenInfo};
+use veilocity_core::merkle::LeafId;
 use veilocity_core::poseidon::{field_to_bytes, u128_to_field, u64_to_field, PoseidonHasher};
-use veilocity_core::state::StateManager;
-use veilocity_prover::{NoirProver, WithdrawWitness, TREE_DEPTH};
+use veilocity_core::state::{StateDelta, StateManager};
+use veilocity_core::transaction::{Transaction, TransactionStatus, TransactionType};
+use veilocity_core::AssetId;
+use veilocity_prover::{NoirProver, Prover, WithdrawWitness};
 
 /// Run the withdraw command
-pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_run: bool) -> Result<()> {
+///
+/// `token` is a configured token symbol or address; without it the
+/// withdrawal is native MNT.
+pub async fn run(
+    config: &Config,
+    amount: f64,
+    token: Option<&str>,
+    recipient: Option<String>,
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
     let wallet_manager = WalletManager::new(config.clone());
 
+    let token = match token {
+        Some(token) => config.token(token)?,
+        None => TokenInfo::native(),
+    };
+    let asset = AssetId::from(token.address.0 .0);
+
     // Load wallet
     let wallet = wallet_manager.load_wallet()?;
 
@@ -29,14 +49,15 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
     .context("Failed to read password")?;
 
     // Unlock wallet and get secrets
-    let signer = wallet_manager.unlock(&wallet, &password)?;
+    let signing =
+        signer::signing_wallet(config, || wallet_manager.unlock(&wallet, &password)).await?;
     let veilocity_secret = wallet_manager.get_veilocity_secret(&wallet, &password)?;
 
     // Determine recipient address
     let recipient_address: Address = if let Some(ref addr) = recipient {
         addr.parse().context("Invalid recipient address")?
     } else {
-        wallet.address()?
+        signing.address()
     };
 
     // Check vault address is configured
@@ -53,7 +74,11 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
         .context("Invalid vault address")?;
 
     // Parse amount
-    let amount_wei = parse_mnt(amount);
+    let amount_wei = if token.is_native() {
+        parse_mnt(amount)
+    } else {
+        u128::try_from(token.parse(&amount.to_string())?).context("Amount too large")?
+    };
 
     println!();
     println!("{}", ui::header("Private Withdrawal"));
@@ -63,8 +88,9 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
     println!(
         "  {} {}  {}",
         "↑".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2).bold(),
-        format_mnt(amount_wei).truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2).bold(),
-        format!("({} wei)", amount_wei).dimmed()
+        format_asset(amount_wei, &token).truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2).bold(),
+        format!("({} {})", amount_wei, if token.is_native() { "wei" } else { "base units" })
+            .dimmed()
     );
     println!();
 
@@ -84,6 +110,7 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
     // Load state
     let mut state = StateManager::new(&config.db_path())
         .context("Failed to load state. Run 'veilocity sync' first.")?;
+    vk::ensure_tree_depth(&state)?;
 
     // Get account
     println!();
@@ -93,13 +120,18 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
     );
     io::stdout().flush().unwrap();
 
-    let mut hasher = PoseidonHasher::new();
-    let pubkey_field = veilocity_secret.derive_pubkey(&mut hasher);
+    let hasher = PoseidonHasher::new();
+    let pubkey_field = veilocity_secret.derive_pubkey(&hasher);
     let pubkey_bytes = field_to_bytes(&pubkey_field);
 
     let account = state
-        .get_account(&pubkey_bytes)?
-        .ok_or_else(|| anyhow!("Account not found. Have you made a deposit?"))?;
+        .get_asset_account(&pubkey_bytes, &asset)?
+        .ok_or_else(|| {
+            anyhow!(
+                "No {} account found. Have you made a deposit?",
+                token.symbol
+            )
+        })?;
 
     println!(
         "\r  {} Private account loaded                    ",
@@ -110,15 +142,15 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
     if account.balance < amount_wei {
         return Err(anyhow!(
             "Insufficient balance. Have: {}, Need: {}",
-            format_mnt(account.balance),
-            format_mnt(amount_wei)
+            format_asset(account.balance, &token),
+            format_asset(amount_wei, &token)
         ));
     }
 
     println!(
         "  {} {} {}",
         "Balance:  ".truecolor(120, 120, 120),
-        format_mnt(account.balance).green(),
+        format_asset(account.balance, &token).green(),
         "(shielded)".dimmed()
     );
 
@@ -157,9 +189,14 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
     );
     io::stdout().flush().unwrap();
 
-    let nullifier = veilocity_secret.compute_nullifier(state.hasher(), account.index, account.nonce);
+    // The nullifier commits to the circuits' global index, so it differs from
+    // that of an account at the same position of another tree; the circuit
+    // works with the index inside the account's tree
+    let leaf = LeafId::from_global(account.index, state.tree_depth());
+    let nullifier =
+        veilocity_secret.compute_nullifier(state.hasher(), leaf.nullifier_index(), account.nonce);
     let nullifier_bytes = field_to_bytes(&nullifier);
-    let nullifier_hex = hex::encode(&nullifier_bytes);
+    let nullifier_hex = hex::encode(nullifier_bytes);
 
     println!(
         "\r  {} Nullifier computed                         ",
@@ -184,18 +221,19 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
     );
     io::stdout().flush().unwrap();
 
-    let merkle_path = state.get_merkle_proof(account.index);
-    let merkle_path_fields: Vec<_> = merkle_path.into_iter().collect();
+    let merkle_proof = state.get_merkle_proof(account.index)?;
+    let merkle_path_fields = merkle_proof.path;
 
     println!(
         "\r  {} Merkle proof generated                     ",
         "✓".green().bold()
     );
     println!(
-        "    {} depth={}, leaf_index={}",
+        "    {} depth={}, tree={}, leaf_index={}",
         "├".truecolor(60, 60, 60),
-        TREE_DEPTH.to_string().bright_white(),
-        account.index.to_string().bright_white()
+        state.tree_depth().to_string().bright_white(),
+        leaf.tree_id.to_string().bright_white(),
+        leaf.index.to_string().bright_white()
     );
     println!(
         "    {} {}",
@@ -211,9 +249,10 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
     );
     io::stdout().flush().unwrap();
 
-    let state_root = state.state_root();
+    // Root of the tree holding the account (sealed trees keep their final root)
+    let state_root = merkle_proof.root;
     let state_root_bytes = field_to_bytes(&state_root);
-    let state_root_hex = hex::encode(&state_root_bytes);
+    let state_root_hex = hex::encode(state_root_bytes);
 
     println!(
         "\r  {} State root retrieved                       ",
@@ -250,9 +289,16 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
         *veilocity_secret.secret(),
         u128_to_field(account.balance),
         u64_to_field(account.nonce),
-        u64_to_field(account.index),
+        u64_to_field(leaf.index),
         merkle_path_fields,
-    )?;
+    )?
+    .with_tree_id(leaf.tree_id);
+    // Token balances are proven with the withdraw_token circuit, bound to the asset
+    let witness = if asset.is_native() {
+        witness
+    } else {
+        witness.with_asset(asset.to_field())
+    };
 
     println!(
         "\r  {} Witness constructed                        ",
@@ -269,9 +315,10 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
         "████████".truecolor(80, 80, 80)
     );
     println!(
-        "    {} amount: {} wei",
+        "    {} amount: {} {}",
         "├".truecolor(60, 60, 60),
-        amount_wei.to_string().bright_white()
+        amount_wei.to_string().bright_white(),
+        if token.is_native() { "wei" } else { "base units" }
     );
     println!(
         "    {} recipient: 0x{}...",
@@ -279,119 +326,119 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
         &recipient_address.to_string()[2..10].bright_white()
     );
 
-    if dry_run {
-        println!();
-        ui::print_notice(
-            "DRY RUN",
-            "Proof would be generated and withdrawal submitted. Remove --dry-run to execute.",
-        );
-        return Ok(());
-    }
-
     println!();
 
-    // Stage 5: Initialize prover
-    let prover = NoirProver::new(PathBuf::from("circuits"));
+    // Recorded before proving so an interrupted withdrawal still shows in
+    // history; a dry run proves and simulates but leaves no history behind
+    let mut tx = Transaction::new(TransactionType::Withdraw, amount_wei)
+        .with_asset(asset)
+        .with_nullifier(nullifier_bytes)
+        .with_leaf_index(account.index)
+        .with_nonce(account.nonce)
+        .with_counterparty(format!("{:?}", recipient_address));
+    if !dry_run {
+        tx.id = state.record_transaction(&tx)?;
+    }
 
-    if !prover.is_compiled() {
+    let proven: Result<Vec<u8>> = async {
+        // Stage 5: Initialize prover (remote when prover.remote_url is set)
+        let prover = NoirProver::new(vk::circuits_dir(config)).with_progress(ui::print_proof_event);
+        let remote = vk::remote_prover(config);
+
+        if remote.is_none() && !prover.is_compiled() {
+            print!(
+                "  {} Compiling Noir circuits...",
+                "◐".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
+            );
+            io::stdout().flush().unwrap();
+            prover.compile().await?;
+            println!(
+                "\r  {} Circuits compiled                          ",
+                "✓".green().bold()
+            );
+        }
+
+        // Refuse to spend minutes proving against a verifier that will reject the proof
         print!(
-            "  {} Compiling Noir circuits...",
+            "  {} Checking verification key...",
             "◐".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
         );
         io::stdout().flush().unwrap();
-        prover.compile().await?;
+
+        // The withdraw vk is checked against the vault's verifier; token
+        // withdrawals go through the separate token verifier
+        if !asset.is_native() {
+            println!(
+                "\r  {} Verification key check skipped (token verifier)",
+                "◇".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
+            );
+        } else {
+            match &remote {
+                Some(remote) => vk::ensure_withdraw_vk(config, remote).await?,
+                None => vk::ensure_withdraw_vk(config, &prover).await?,
+            }
+
+            println!(
+                "\r  {} Verification key matches verifier          ",
+                "✓".green().bold()
+            );
+        }
+
+        // Stage 6: Generate ZK proof with real-time updates
         println!(
-            "\r  {} Circuits compiled                          ",
-            "✓".green().bold()
+            "  {} {} {}",
+            "▶".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2).bold(),
+            "Generating ZK-SNARK proof".bright_white().bold(),
+            "(Barretenberg)".dimmed()
         );
-    }
-
-    // Stage 6: Generate ZK proof with real-time updates
-    println!(
-        "  {} {} {}",
-        "▶".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2).bold(),
-        "Generating ZK-SNARK proof".bright_white().bold(),
-        "(Barretenberg)".dimmed()
-    );
-    println!(
-        "    {}",
-        "├─ Protocol: UltraPlonk with Plookup".truecolor(100, 100, 100)
-    );
-    println!(
-        "    {}",
-        "├─ Curve: BN254 (alt_bn128)".truecolor(100, 100, 100)
-    );
-    println!(
-        "    {}",
-        "├─ Commitment: KZG polynomial".truecolor(100, 100, 100)
-    );
-    println!(
-        "    {}",
-        "└─ Security: 128-bit soundness".truecolor(100, 100, 100)
-    );
-    println!();
-
-    // Show progress during actual proof generation
-    print!(
-        "    {} Computing proof ",
-        "◐".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
-    );
-    io::stdout().flush().unwrap();
-
-    let proof = prover.prove_withdraw(&witness).await?;
-
-    println!(
-        "\r    {} Proof computation complete                ",
-        "✓".green().bold()
-    );
+        println!(
+            "    {}",
+            "├─ Protocol: UltraPlonk with Plookup".truecolor(100, 100, 100)
+        );
+        println!(
+            "    {}",
+            "├─ Curve: BN254 (alt_bn128)".truecolor(100, 100, 100)
+        );
+        println!(
+            "    {}",
+            "├─ Commitment: KZG polynomial".truecolor(100, 100, 100)
+        );
+        println!(
+            "    {}",
+            "└─ Security: 128-bit soundness".truecolor(100, 100, 100)
+        );
+        println!();
 
-    // Show proof verification box
-    println!();
-    println!(
-        "  {}",
-        "┌─────────────────────────────────────────────────────┐"
-            .truecolor(ui::ORANGE_DARK.0, ui::ORANGE_DARK.1, ui::ORANGE_DARK.2)
-    );
-    println!(
-        "  {} {} {}",
-        "│".truecolor(ui::ORANGE_DARK.0, ui::ORANGE_DARK.1, ui::ORANGE_DARK.2),
-        "ZK-PROOF VERIFIED".green().bold(),
-        "                                │".truecolor(ui::ORANGE_DARK.0, ui::ORANGE_DARK.1, ui::ORANGE_DARK.2)
-    );
-    println!(
-        "  {}",
-        "├─────────────────────────────────────────────────────┤"
-            .truecolor(ui::ORANGE_DARK.0, ui::ORANGE_DARK.1, ui::ORANGE_DARK.2)
-    );
-    println!(
-        "  {} Proof size: {} bytes                              {}",
-        "│".truecolor(ui::ORANGE_DARK.0, ui::ORANGE_DARK.1, ui::ORANGE_DARK.2),
-        format!("{}", proof.len()).bright_white().bold(),
-        "│".truecolor(ui::ORANGE_DARK.0, ui::ORANGE_DARK.1, ui::ORANGE_DARK.2)
-    );
-    println!(
-        "  {} {} Zero-Knowledge: Private inputs hidden        {}",
-        "│".truecolor(ui::ORANGE_DARK.0, ui::ORANGE_DARK.1, ui::ORANGE_DARK.2),
-        "✓".green(),
-        "│".truecolor(ui::ORANGE_DARK.0, ui::ORANGE_DARK.1, ui::ORANGE_DARK.2)
-    );
-    println!(
-        "  {} {} Soundness: Proof mathematically valid        {}",
-        "│".truecolor(ui::ORANGE_DARK.0, ui::ORANGE_DARK.1, ui::ORANGE_DARK.2),
-        "✓".green(),
-        "│".truecolor(ui::ORANGE_DARK.0, ui::ORANGE_DARK.1, ui::ORANGE_DARK.2)
-    );
-    println!(
-        "  {} {} Completeness: All constraints satisfied      {}",
-        "│".truecolor(ui::ORANGE_DARK.0, ui::ORANGE_DARK.1, ui::ORANGE_DARK.2),
-        "✓".green(),
-        "│".truecolor(ui::ORANGE_DARK.0, ui::ORANGE_DARK.1, ui::ORANGE_DARK.2)
-    );
-    println!(
-        "  {}",
-        "└─────────────────────────────────────────────────────┘"
-            .truecolor(ui::ORANGE_DARK.0, ui::ORANGE_DARK.1, ui::ORANGE_DARK.2)
-    );
+        // Progress lines are driven by the prover as each step actually runs
+        let proof = match &remote {
+            Some(remote) => remote.prove_withdraw(&witness).await?,
+            None => prover.prove_withdraw(&witness).await?,
+        };
+
+        // Verify locally before spending gas on a proof the verifier would reject
+        if remote.is_some() {
+            ui::print_zk_step("Local verification", "skipped (remote prover)", false);
+        } else if prover.has_vk(witness.circuit()) {
+            if !prover.verify_proof(&proof, witness.circuit()).await? {
+                return Err(anyhow!("Generated proof failed local verification"));
+            }
+            ui::print_proof_verified(proof.len());
+        } else {
+            ui::print_zk_step("Local verification", "skipped (no verification key)", false);
+        }
+
+        Ok(proof)
+    }
+    .await;
+
+    let proof = match proven {
+        Ok(proof) => proof,
+        Err(e) => {
+            record_status(&mut state, &mut tx, TransactionStatus::Failed)?;
+            return Err(e);
+        }
+    };
+    record_status(&mut state, &mut tx, TransactionStatus::Proven)?;
 
     // =========================================================================
     // ON-CHAIN VERIFICATION
@@ -414,7 +461,10 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
     );
     io::stdout().flush().unwrap();
 
-    let vault = create_vault_client(&config.network.rpc_url, vault_address, signer).await?;
+    let vault = create_vault_client(&config.network.rpc(), vault_address, signing)
+        .await?
+        .with_gas_policy(config.gas.policy())
+        .with_receipt_timeout(config.gas.receipt_timeout());
 
     println!(
         "\r  {} Connected to Mantle                         ",
@@ -433,6 +483,7 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
             "\r  {} Nullifier already used!                     ",
             "✗".red().bold()
         );
+        record_status(&mut state, &mut tx, TransactionStatus::Failed)?;
         return Err(anyhow!("This withdrawal has already been processed"));
     }
 
@@ -465,6 +516,72 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
         );
     }
 
+    // Simulate so a withdrawal the vault would reject fails here, not on chain
+    print!(
+        "  {} Simulating withdrawal...",
+        "◐".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
+    );
+    io::stdout().flush().unwrap();
+
+    let planned = if asset.is_native() {
+        vault
+            .plan_withdraw(
+                B256::from(nullifier_bytes),
+                recipient_address,
+                U256::from(amount_wei),
+                B256::from(state_root_bytes),
+                &proof,
+            )
+            .await
+    } else {
+        vault
+            .plan_withdraw_token(
+                token.address,
+                B256::from(nullifier_bytes),
+                recipient_address,
+                U256::from(amount_wei),
+                B256::from(state_root_bytes),
+                &proof,
+            )
+            .await
+    };
+
+    let plan = match planned {
+        Ok(plan) => plan,
+        Err(e) => {
+            record_status(&mut state, &mut tx, TransactionStatus::Failed)?;
+            println!(
+                "\r  {} Simulation failed                           ",
+                "✗".red().bold()
+            );
+            return Err(explain_rejection(e));
+        }
+    };
+
+    println!(
+        "\r  {} Simulation succeeded                        ",
+        "✓".green().bold()
+    );
+    println!();
+    ui::print_tx_plan(&plan);
+
+    if dry_run {
+        println!();
+        ui::print_notice(
+            "DRY RUN",
+            "Proof generated and simulated; no transaction submitted. Remove --dry-run to execute.",
+        );
+        return Ok(());
+    }
+
+    println!();
+    if !yes && !ui::confirm("Send withdrawal?")? {
+        tx.status = TransactionStatus::Failed;
+        state.update_transaction(&tx)?;
+        println!("  {}", "Withdrawal cancelled.".dimmed());
+        return Ok(());
+    }
+
     // =========================================================================
     // SUBMIT TRANSACTION
     // =========================================================================
@@ -476,39 +593,103 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
     );
     io::stdout().flush().unwrap();
 
-    let tx_hash = vault
-        .withdraw(
-            B256::from(nullifier_bytes),
-            recipient_address,
-            U256::from(amount_wei),
-            B256::from(state_root_bytes),
-            proof,
-        )
-        .await?;
+    let submitted = if asset.is_native() {
+        vault
+            .submit_withdraw(
+                B256::from(nullifier_bytes),
+                recipient_address,
+                U256::from(amount_wei),
+                B256::from(state_root_bytes),
+                proof,
+                &plan,
+            )
+            .await
+    } else {
+        vault
+            .submit_withdraw_token(
+                token.address,
+                B256::from(nullifier_bytes),
+                recipient_address,
+                U256::from(amount_wei),
+                B256::from(state_root_bytes),
+                proof,
+                &plan,
+            )
+            .await
+    };
 
+    let tx_hash = match submitted {
+        Ok(tx_hash) => tx_hash,
+        Err(e) => {
+            tx.status = TransactionStatus::Failed;
+            state.update_transaction(&tx)?;
+            println!(
+                "\r  {} Withdrawal rejected                         ",
+                "✗".red().bold()
+            );
+            return Err(explain_rejection(e));
+        }
+    };
     let tx_hash_hex = hex::encode(tx_hash);
 
-    println!(
-        "\r  {} Transaction confirmed!                      ",
-        "✓".green().bold()
-    );
-
-    // Update local state
+    // Applied optimistically; rolled back if the withdrawal reverts
+    tx.status = TransactionStatus::Submitted;
+    tx.tx_hash = Some(tx_hash.0);
     let mut account_updated = account.clone();
     account_updated.balance -= amount_wei;
     account_updated.nonce += 1;
-    state.update_account(&account_updated)?;
-    state.mark_nullifier_used(&nullifier_bytes)?;
+    state.apply(
+        &StateDelta::new()
+            .with_account(account_updated.clone())
+            .with_nullifier(nullifier_bytes)
+            .with_transaction(tx.clone()),
+    )?;
 
-    // Record transaction
-    let _ = state.record_transaction(
-        "withdraw",
-        amount_wei,
-        Some(tx_hash.as_slice()),
-        Some(&format!("{:?}", recipient_address)),
-        "confirmed",
+    println!(
+        "\r  {} Transaction submitted, waiting for confirmation...",
+        "◐".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
     );
 
+    let confirmed = match vault.wait_for_receipt(tx_hash).await {
+        Ok(outcome) if outcome.success => {
+            tx.status = TransactionStatus::Confirmed;
+            tx.block_number = outcome.block_number;
+            state.update_transaction(&tx)?;
+            println!(
+                "  {} Transaction confirmed!                      ",
+                "✓".green().bold()
+            );
+            true
+        }
+        Ok(_) => {
+            state.fail_transaction(tx.id)?;
+            println!(
+                "  {} Transaction reverted                        ",
+                "✗".red().bold()
+            );
+            return Err(anyhow!(
+                "Withdrawal transaction 0x{} reverted; private balance restored",
+                tx_hash_hex
+            ));
+        }
+        Err(e) => {
+            println!(
+                "  {} Confirmation not received: {}",
+                "⚠".yellow().bold(),
+                e
+            );
+            ui::print_notice(
+                "Pending",
+                &format!(
+                    "Run 'veilocity sync' to confirm the withdrawal or roll it back if it \
+                     reverted, or 'veilocity tx speedup 0x{}' if it is stuck.",
+                    tx_hash_hex
+                ),
+            );
+            false
+        }
+    };
+
     // =========================================================================
     // SUCCESS SUMMARY
     // =========================================================================
@@ -516,11 +697,19 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
     println!();
     ui::divider_double(55);
     println!();
-    println!(
-        "  {} {}",
-        "✓".green().bold(),
-        "WITHDRAWAL SUCCESSFUL".green().bold()
-    );
+    if confirmed {
+        println!(
+            "  {} {}",
+            "✓".green().bold(),
+            "WITHDRAWAL SUCCESSFUL".green().bold()
+        );
+    } else {
+        println!(
+            "  {} {}",
+            "◐".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2).bold(),
+            "WITHDRAWAL SUBMITTED".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2).bold()
+        );
+    }
     println!();
     println!(
         "  {} 0x{}",
@@ -542,12 +731,12 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
     println!(
         "  {} {} sent to {}",
         "◈".truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2),
-        format_mnt(amount_wei).green().bold(),
+        format_asset(amount_wei, &token).green().bold(),
         format!("{:?}", recipient_address).bright_white()
     );
     println!(
         "  New private balance: {}",
-        format_mnt(account_updated.balance)
+        format_asset(account_updated.balance, &token)
             .truecolor(ui::ORANGE.0, ui::ORANGE.1, ui::ORANGE.2)
             .bold()
     );
@@ -586,7 +775,38 @@ pub async fn run(config: &Config, amount: f64, recipient: Option<String>, dry_ru
     ui::divider_double(55);
     println!();
 
-    info!("Withdrawal of {} wei completed", amount_wei);
+    info!("Withdrawal of {} completed", format_asset(amount_wei, &token));
+
+    Ok(())
+}
 
+/// Move a recorded withdrawal to `status`; dry runs have no history row
+fn record_status(
+    state: &mut StateManager,
+    tx: &mut Transaction,
+    status: TransactionStatus,
+) -> Result<()> {
+    tx.status = status;
+    if tx.id != 0 {
+        state.update_transaction(tx)?;
+    }
     Ok(())
 }
+
+/// Turn a vault rejection into advice the user can act on
+fn explain_rejection(err: ContractError) -> anyhow::Error {
+    match err {
+        ContractError::InvalidRoot => anyhow!(
+            "The vault no longer accepts this state root (it has expired). Run 'veilocity sync' and retry."
+        ),
+        ContractError::NullifierUsed(_) => anyhow!("This withdrawal has already been processed"),
+        ContractError::InvalidProof => anyhow!(
+            "The vault rejected the proof. Check that the circuits match the deployed verifier ('veilocity vk')."
+        ),
+        ContractError::TransferFailed => {
+            anyhow!("The vault could not send funds to the recipient. Try a different address.")
+        }
+        ContractError::Paused => anyhow!("The vault is paused; withdrawals are disabled for now."),
+        other => other.into(),
+    }
+}
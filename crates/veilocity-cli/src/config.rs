@@ -1,16 +1,25 @@
 //! Configuration management for Veilocity CLI
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+use veilocity_contracts::{GasPolicy, RpcConfig, TokenInfo};
+use veilocity_core::AssetId;
 
 /// Network configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkConfig {
     /// RPC URL for the network
     pub rpc_url: String,
+    /// Extra RPC URLs to fail over to when `rpc_url` errors or rate-limits
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    /// Number of RPC URLs that must agree on state root and nullifier reads
+    #[serde(default)]
+    pub rpc_quorum: Option<usize>,
     /// Chain ID
     pub chain_id: u64,
     /// VeilocityVault contract address
@@ -30,6 +39,8 @@ impl NetworkConfig {
     pub fn sepolia() -> Self {
         Self {
             rpc_url: "https://rpc.sepolia.mantle.xyz".to_string(),
+            fallback_rpc_urls: Vec::new(),
+            rpc_quorum: None,
             chain_id: 5003,
             vault_address: String::new(), // To be set after deployment
             explorer_url: Some("https://explorer.sepolia.mantle.xyz".to_string()),
@@ -40,11 +51,23 @@ impl NetworkConfig {
     pub fn mainnet() -> Self {
         Self {
             rpc_url: "https://rpc.mantle.xyz".to_string(),
+            fallback_rpc_urls: Vec::new(),
+            rpc_quorum: None,
             chain_id: 5000,
             vault_address: String::new(),
             explorer_url: Some("https://explorer.mantle.xyz".to_string()),
         }
     }
+
+    /// All RPC endpoints, primary first, with the quorum setting
+    pub fn rpc(&self) -> RpcConfig {
+        let urls = std::iter::once(&self.rpc_url).chain(&self.fallback_rpc_urls);
+        let mut rpc = RpcConfig::new(urls.cloned());
+        if let Some(quorum) = self.rpc_quorum {
+            rpc = rpc.with_quorum(quorum);
+        }
+        rpc
+    }
 }
 
 /// Prover configuration
@@ -56,6 +79,12 @@ pub struct ProverConfig {
     pub cache_proofs: bool,
     /// Path to circuits directory
     pub circuits_path: Option<String>,
+    /// Pinned withdraw vk hash (hex); checked instead of the on-chain verifier when set
+    #[serde(default)]
+    pub vk_hash: Option<String>,
+    /// Remote prover server URL; proofs are generated there instead of locally when set
+    #[serde(default)]
+    pub remote_url: Option<String>,
 }
 
 impl Default for ProverConfig {
@@ -64,6 +93,8 @@ impl Default for ProverConfig {
             threads: 4,
             cache_proofs: true,
             circuits_path: None,
+            vk_hash: None,
+            remote_url: None,
         }
     }
 }
@@ -92,6 +123,123 @@ impl Default for SyncConfig {
     }
 }
 
+/// Gas configuration for vault transactions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasConfig {
+    /// Multiplier applied to the node's gas estimate
+    pub gas_multiplier: f64,
+    /// Priority fee in gwei (node suggestion when unset)
+    pub priority_fee_gwei: Option<f64>,
+    /// Refuse to send when the max fee per gas exceeds this many gwei
+    pub max_fee_gwei: Option<f64>,
+    /// Percent by which `tx speedup` and `tx cancel` raise fees
+    #[serde(default = "default_replacement_bump")]
+    pub replacement_bump_percent: u64,
+    /// Seconds to wait for a receipt before reporting the transaction as pending
+    #[serde(default = "default_receipt_timeout")]
+    pub receipt_timeout_secs: u64,
+}
+
+fn default_replacement_bump() -> u64 {
+    GasPolicy::default().replacement_bump_percent
+}
+
+fn default_receipt_timeout() -> u64 {
+    120
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            gas_multiplier: 1.2,
+            priority_fee_gwei: None,
+            max_fee_gwei: None,
+            replacement_bump_percent: default_replacement_bump(),
+            receipt_timeout_secs: default_receipt_timeout(),
+        }
+    }
+}
+
+impl GasConfig {
+    /// Gas policy for the vault client
+    pub fn policy(&self) -> GasPolicy {
+        let mut policy = GasPolicy::default()
+            .with_gas_multiplier(self.gas_multiplier)
+            .with_replacement_bump(self.replacement_bump_percent);
+        if let Some(gwei) = self.priority_fee_gwei {
+            policy = policy.with_priority_fee(gwei_to_wei(gwei));
+        }
+        if let Some(gwei) = self.max_fee_gwei {
+            policy = policy.with_max_fee_ceiling(gwei_to_wei(gwei));
+        }
+        policy
+    }
+
+    /// How long to wait for a transaction receipt
+    pub fn receipt_timeout(&self) -> Duration {
+        Duration::from_secs(self.receipt_timeout_secs)
+    }
+}
+
+fn gwei_to_wei(gwei: f64) -> u128 {
+    (gwei * 1e9) as u128
+}
+
+/// Where the key that signs on-chain transactions lives
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignerKind {
+    /// The key encrypted in wallet.json
+    #[default]
+    Local,
+    /// A Ledger device (needs the `ledger` build feature)
+    Ledger,
+    /// A remote `eth_signTransaction` service
+    Remote,
+}
+
+/// Transaction signer configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignerConfig {
+    /// Which signer to use
+    #[serde(default)]
+    pub kind: SignerKind,
+    /// Ledger Live account index
+    #[serde(default)]
+    pub ledger_index: usize,
+    /// URL of the remote signer
+    #[serde(default)]
+    pub remote_url: Option<String>,
+    /// Account the remote signer signs for
+    #[serde(default)]
+    pub remote_address: Option<String>,
+}
+
+/// An ERC-20 token that can be deposited and withdrawn by symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenConfig {
+    /// Ticker symbol, as passed to `--token`
+    pub symbol: String,
+    /// Token contract address
+    pub address: String,
+    /// Number of decimals in one whole token
+    pub decimals: u8,
+}
+
+impl TokenConfig {
+    /// Token metadata for formatting and parsing amounts
+    pub fn info(&self) -> Result<TokenInfo> {
+        Ok(TokenInfo {
+            address: self
+                .address
+                .parse()
+                .with_context(|| format!("Invalid address for token {}", self.symbol))?,
+            symbol: self.symbol.clone(),
+            decimals: self.decimals,
+        })
+    }
+}
+
 /// Main configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -101,6 +249,15 @@ pub struct Config {
     pub prover: ProverConfig,
     /// Sync configuration
     pub sync: SyncConfig,
+    /// Gas configuration
+    #[serde(default)]
+    pub gas: GasConfig,
+    /// Transaction signer configuration
+    #[serde(default)]
+    pub signer: SignerConfig,
+    /// Known ERC-20 tokens
+    #[serde(default)]
+    pub tokens: Vec<TokenConfig>,
     /// Data directory path
     #[serde(skip)]
     pub data_dir: PathBuf,
@@ -112,6 +269,9 @@ impl Default for Config {
             network: NetworkConfig::default(),
             prover: ProverConfig::default(),
             sync: SyncConfig::default(),
+            gas: GasConfig::default(),
+            signer: SignerConfig::default(),
+            tokens: Vec::new(),
             data_dir: get_data_dir(),
         }
     }
@@ -122,7 +282,7 @@ impl Config {
     pub fn for_network(network: &str) -> Self {
         let network_config = match network.to_lowercase().as_str() {
             "mainnet" => NetworkConfig::mainnet(),
-            "sepolia" | _ => NetworkConfig::sepolia(),
+            _ => NetworkConfig::sepolia(),
         };
 
         Self {
@@ -131,6 +291,38 @@ impl Config {
         }
     }
 
+    /// Look up a token by its configured symbol or address
+    pub fn token(&self, token: &str) -> Result<TokenInfo> {
+        self.tokens
+            .iter()
+            .find(|t| t.symbol.eq_ignore_ascii_case(token) || t.address.eq_ignore_ascii_case(token))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown token '{}'. Add it with 'veilocity config set token <SYMBOL>:<address>:<decimals>'.",
+                    token
+                )
+            })?
+            .info()
+    }
+
+    /// Token metadata for a pool, falling back to raw base units for unknown tokens
+    pub fn asset_info(&self, asset: &AssetId) -> TokenInfo {
+        if asset.is_native() {
+            return TokenInfo::native();
+        }
+
+        let address = asset.to_string();
+        self.tokens
+            .iter()
+            .filter(|t| t.address.eq_ignore_ascii_case(&address))
+            .find_map(|t| t.info().ok())
+            .unwrap_or_else(|| TokenInfo {
+                address: asset.0.into(),
+                symbol: address,
+                decimals: 0,
+            })
+    }
+
     /// Get the database path
     pub fn db_path(&self) -> PathBuf {
         self.data_dir.join("state.db")
@@ -207,9 +399,9 @@ pub fn get_data_dir() -> PathBuf {
 
 /// Expand ~ in paths
 pub fn expand_path(path: &str) -> PathBuf {
-    if path.starts_with("~/") {
+    if let Some(rest) = path.strip_prefix("~/") {
         if let Some(home) = dirs::home_dir() {
-            return home.join(&path[2..]);
+            return home.join(rest);
         }
     }
     PathBuf::from(path)
@@ -257,6 +449,37 @@ mod tests {
         assert_eq!(mainnet.chain_id, 5000);
     }
 
+    #[test]
+    fn test_rpc_endpoints() {
+        let mut network = NetworkConfig::sepolia();
+        network.fallback_rpc_urls = vec!["https://backup.example".to_string()];
+        network.rpc_quorum = Some(2);
+
+        let rpc = network.rpc();
+        assert_eq!(rpc.urls, vec!["https://rpc.sepolia.mantle.xyz", "https://backup.example"]);
+        assert_eq!(rpc.quorum, Some(2));
+    }
+
+    #[test]
+    fn test_token_lookup() {
+        let mut config = Config::default();
+        config.tokens.push(TokenConfig {
+            symbol: "USDC".to_string(),
+            address: "0x09Bc4E0D864854c6aFB6eB9A9cdF58aC190D0dF9".to_string(),
+            decimals: 6,
+        });
+
+        let usdc = config.token("usdc").unwrap();
+        assert_eq!(usdc.decimals, 6);
+        assert_eq!(config.token("0x09bc4e0d864854c6afb6eb9a9cdf58ac190d0df9").unwrap(), usdc);
+        assert!(config.token("USDT").is_err());
+
+        let asset = AssetId(usdc.address.0 .0);
+        assert_eq!(config.asset_info(&asset).symbol, "USDC");
+        assert_eq!(config.asset_info(&AssetId::NATIVE).symbol, "MNT");
+        assert_eq!(config.asset_info(&AssetId([1; 20])).decimals, 0);
+    }
+
     #[test]
     fn test_expand_path() {
         let path = expand_path("~/.veilocity/config.toml");
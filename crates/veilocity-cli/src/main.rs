@@ -13,6 +13,7 @@ use tracing_subscriber::EnvFilter;
 
 pub mod commands;
 pub mod config;
+pub mod signer;
 pub mod ui;
 pub mod wallet;
 
@@ -30,13 +31,17 @@ pub mod wallet;
 #[command(after_help = "Examples:
   veilocity init                    Create a new wallet
   veilocity deposit 0.1             Deposit 0.1 MNT
+  veilocity deposit 25 --token USDC Deposit 25 USDC
   veilocity transfer <pubkey> 0.05  Send 0.05 MNT privately
   veilocity withdraw 0.1            Withdraw 0.1 MNT
   veilocity balance                 Check your balance
   veilocity sync                    Sync with network
   veilocity history                 View transaction history
+  veilocity tx speedup <hash>       Resend a stuck transaction with higher fees
   veilocity config                  Show current configuration
-  veilocity config set vault <addr> Set vault contract address")]
+  veilocity config set vault <addr> Set vault contract address
+  veilocity vk                      Check local vk against the verifier
+  veilocity admin deploy --save     Deploy the vault from contracts/out")]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
@@ -67,11 +72,17 @@ enum Commands {
     /// Deposit funds from Mantle into Veilocity
     #[command(alias = "d", alias = "dep")]
     Deposit {
-        /// Amount to deposit in MNT
+        /// Amount to deposit in MNT (or in whole tokens with --token)
         amount: f64,
-        /// Preview the deposit without executing
+        /// Deposit a configured ERC-20 token (symbol or address) instead of MNT
+        #[arg(long)]
+        token: Option<String>,
+        /// Preview the deposit (simulated, with gas and fees) without executing
         #[arg(long)]
         dry_run: bool,
+        /// Send without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
     },
 
     /// Send private transfer to another user
@@ -91,12 +102,18 @@ enum Commands {
     Withdraw {
         /// Amount to withdraw
         amount: f64,
+        /// Withdraw a configured ERC-20 token (symbol or address) instead of MNT
+        #[arg(long)]
+        token: Option<String>,
         /// Recipient address (default: connected wallet)
         #[arg(short, long)]
         recipient: Option<String>,
-        /// Preview the withdrawal without executing
+        /// Prove and simulate the withdrawal without sending it
         #[arg(long)]
         dry_run: bool,
+        /// Send without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
     },
 
     /// Display current private balance
@@ -109,7 +126,36 @@ enum Commands {
 
     /// Show transaction history
     #[command(alias = "h", alias = "hist")]
-    History,
+    History {
+        /// Only show this type: deposit, transfer, withdraw
+        #[arg(long = "type", value_name = "TYPE")]
+        tx_type: Option<String>,
+        /// Only show this status: pending, proven, submitted, confirmed, failed
+        #[arg(long)]
+        status: Option<String>,
+        /// Only show transactions from the last N days
+        #[arg(long)]
+        days: Option<u64>,
+        /// Maximum number of transactions to show
+        #[arg(short, long, default_value_t = 50)]
+        limit: u32,
+    },
+
+    /// Check the local verification key against the deployed verifier
+    #[command(alias = "verify-key")]
+    Vk,
+
+    /// Speed up or cancel a stuck transaction
+    Tx {
+        #[command(subcommand)]
+        action: TxCommands,
+    },
+
+    /// Deploy the vault or call its owner-only functions
+    Admin {
+        #[command(subcommand)]
+        action: AdminCommands,
+    },
 
     /// View or update configuration
     #[command(alias = "cfg")]
@@ -126,6 +172,80 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum TxCommands {
+    /// Resend with the same nonce and higher fees
+    #[command(alias = "bump")]
+    Speedup {
+        /// Hash of the stuck transaction
+        hash: String,
+    },
+    /// Replace with an empty transfer so it is never mined
+    Cancel {
+        /// Hash of the stuck transaction
+        hash: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AdminCommands {
+    /// Deploy the verifier and vault from compiled Foundry artifacts
+    Deploy {
+        /// Foundry output directory (run 'forge build' in contracts/ first)
+        #[arg(long, default_value = "contracts/out")]
+        out: String,
+        /// Use an already deployed verifier instead of deploying HonkVerifier
+        #[arg(long)]
+        verifier: Option<String>,
+        /// Deploy MockVerifier, which accepts every proof (local testing only)
+        #[arg(long)]
+        mock_verifier: bool,
+        /// Initial state root (default: root of the empty state tree)
+        #[arg(long)]
+        initial_root: Option<String>,
+        /// Save the vault address and deployment block to the config
+        #[arg(long)]
+        save: bool,
+        /// Deploy without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Pause deposits, withdrawals and root updates
+    Pause,
+    /// Resume a paused vault
+    Unpause,
+    /// Send a paused vault's whole MNT balance (or one token pool) to a recipient
+    EmergencyWithdraw {
+        /// Address to receive the funds
+        recipient: String,
+        /// Drain a configured ERC-20 pool (symbol or address) instead of MNT
+        #[arg(long)]
+        token: Option<String>,
+        /// Send without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Anchor a state root proven by a state-transition proof
+    UpdateRoot {
+        /// Root to anchor (default: the local state root)
+        root: Option<String>,
+        /// Number of unanchored deposits the proof inserts, oldest first
+        #[arg(long, default_value_t = 0)]
+        deposits: u64,
+        /// Nullifier of a transfer the proof covers (repeat for each, in proof order)
+        #[arg(long = "nullifier")]
+        nullifiers: Vec<String>,
+        /// File with the state-transition proof bytes
+        #[arg(long)]
+        proof: Option<String>,
+    },
+    /// Check root updates against a state_transition verifier (zero address disables them)
+    SetTransitionVerifier {
+        /// Verifier contract address
+        verifier: String,
+    },
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
@@ -144,14 +264,15 @@ async fn main() -> anyhow::Result<()> {
         Commands::Init { recover } => {
             commands::init::run(recover).await
         }
-        Commands::Deposit { amount, dry_run } => {
-            commands::deposit::run(&config, amount, dry_run).await
+        Commands::Deposit { amount, token, dry_run, yes } => {
+            commands::deposit::run(&config, amount, token.as_deref(), dry_run, yes).await
         }
         Commands::Transfer { recipient, amount, dry_run } => {
             commands::transfer::run(&config, &recipient, amount, dry_run).await
         }
-        Commands::Withdraw { amount, recipient, dry_run } => {
-            commands::withdraw::run(&config, amount, recipient, dry_run).await
+        Commands::Withdraw { amount, token, recipient, dry_run, yes } => {
+            commands::withdraw::run(&config, amount, token.as_deref(), recipient, dry_run, yes)
+                .await
         }
         Commands::Balance => {
             commands::balance::run(&config).await
@@ -159,8 +280,47 @@ async fn main() -> anyhow::Result<()> {
         Commands::Sync => {
             commands::sync::run(&config).await
         }
-        Commands::History => {
-            commands::history::run(&config).await
+        Commands::History { tx_type, status, days, limit } => {
+            let options = commands::history::HistoryOptions { tx_type, status, days, limit };
+            commands::history::run(&config, &options).await
+        }
+        Commands::Vk => {
+            commands::vk::run(&config).await
+        }
+        Commands::Tx { action } => {
+            let (action, hash) = match action {
+                TxCommands::Speedup { hash } => (commands::tx::TxAction::SpeedUp, hash),
+                TxCommands::Cancel { hash } => (commands::tx::TxAction::Cancel, hash),
+            };
+            commands::tx::run(&config, action, &hash).await
+        }
+        Commands::Admin { action } => {
+            use commands::admin::{AdminAction, DeployOptions};
+            let (action, yes) = match action {
+                AdminCommands::Deploy { out, verifier, mock_verifier, initial_root, save, yes } => {
+                    let options = DeployOptions {
+                        out_dir: config::expand_path(&out),
+                        verifier,
+                        mock_verifier,
+                        initial_root,
+                        save,
+                    };
+                    (AdminAction::Deploy(options), yes)
+                }
+                AdminCommands::Pause => (AdminAction::Pause, false),
+                AdminCommands::Unpause => (AdminAction::Unpause, false),
+                AdminCommands::EmergencyWithdraw { recipient, token, yes } => {
+                    (AdminAction::EmergencyWithdraw { recipient, token }, yes)
+                }
+                AdminCommands::UpdateRoot { root, deposits, nullifiers, proof } => {
+                    let proof = proof.map(|p| config::expand_path(&p));
+                    (AdminAction::UpdateRoot { root, deposits, nullifiers, proof }, false)
+                }
+                AdminCommands::SetTransitionVerifier { verifier } => {
+                    (AdminAction::SetTransitionVerifier { verifier }, false)
+                }
+            };
+            commands::admin::run(&config, action, yes).await
         }
         Commands::Config { action, key, value } => {
             let config_action = match action.as_deref() {
@@ -0,0 +1,65 @@
+//! Choosing the signer for on-chain transactions
+//!
+//! The EVM key in wallet.json is the default. A Ledger device or a remote
+//! `eth_signTransaction` service can sign instead, in which case the key in
+//! wallet.json is never decrypted for sending.
+
+use crate::config::{Config, SignerKind};
+use alloy::network::EthereumWallet;
+use alloy::signers::local::PrivateKeySigner;
+use anyhow::{anyhow, Context, Result};
+use veilocity_contracts::{RemoteSigner, SigningWallet};
+
+/// Wallet that signs vault transactions, as configured
+///
+/// `unlock_local` decrypts the wallet.json key; it is only called when the
+/// local signer is configured.
+pub async fn signing_wallet(
+    config: &Config,
+    unlock_local: impl FnOnce() -> Result<PrivateKeySigner>,
+) -> Result<SigningWallet> {
+    match config.signer.kind {
+        SignerKind::Local => Ok(EthereumWallet::from(unlock_local()?).into()),
+        SignerKind::Ledger => ledger_wallet(config).await,
+        SignerKind::Remote => {
+            let url = config.signer.remote_url.as_deref().ok_or_else(|| {
+                anyhow!("Remote signer URL not set. Run 'veilocity config set signer_url <url>'.")
+            })?;
+            let address = config
+                .signer
+                .remote_address
+                .as_deref()
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Remote signer address not set. Run 'veilocity config set signer_address <addr>'."
+                    )
+                })?
+                .parse()
+                .context("Invalid remote signer address")?;
+
+            Ok(RemoteSigner::new(url, address)?.into())
+        }
+    }
+}
+
+/// Connect to the Ledger and use the configured Ledger Live account
+#[cfg(feature = "ledger")]
+async fn ledger_wallet(config: &Config) -> Result<SigningWallet> {
+    use alloy::signers::ledger::{HDPath, LedgerSigner};
+
+    let signer = LedgerSigner::new(
+        HDPath::LedgerLive(config.signer.ledger_index),
+        Some(config.network.chain_id),
+    )
+    .await
+    .context("Failed to connect to Ledger. Is it unlocked with the Ethereum app open?")?;
+
+    Ok(EthereumWallet::from(signer).into())
+}
+
+#[cfg(not(feature = "ledger"))]
+async fn ledger_wallet(_config: &Config) -> Result<SigningWallet> {
+    Err(anyhow!(
+        "This build has no Ledger support. Rebuild with '--features ledger'."
+    ))
+}
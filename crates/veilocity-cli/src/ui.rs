@@ -150,12 +150,12 @@ pub fn print_box(lines: &[&str], width: usize) {
 
     // Content
     for line in lines {
-        let padding = width - 4 - line.chars().count();
+        let padding = width.saturating_sub(4 + line.chars().count());
         println!(
             "{} {}{} {}",
             "│".truecolor(border_color.0, border_color.1, border_color.2),
             line,
-            " ".repeat(padding.max(0)),
+            " ".repeat(padding),
             "│".truecolor(border_color.0, border_color.1, border_color.2)
         );
     }
@@ -224,247 +224,78 @@ pub const SPINNER: &[char] = &['◐', '◓', '◑', '◒'];
 pub const LOADING: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 
 // ============================================================================
-// ZK-PROOF SIMULATION DISPLAY
+// ZK-PROOF PROGRESS DISPLAY
 // ============================================================================
 
 use std::io::{self, Write};
 use std::time::Duration;
+use veilocity_prover::ProofEvent;
 
-/// ZK Proof visualization stages
-#[derive(Debug, Clone, Copy)]
-pub enum ZkStage {
-    WitnessGeneration,
-    ConstraintSatisfaction,
-    PolynomialCommitment,
-    ProofComputation,
-    Verification,
-}
-
-impl ZkStage {
-    pub fn name(&self) -> &'static str {
-        match self {
-            ZkStage::WitnessGeneration => "Witness Generation",
-            ZkStage::ConstraintSatisfaction => "Constraint Satisfaction",
-            ZkStage::PolynomialCommitment => "Polynomial Commitment",
-            ZkStage::ProofComputation => "Proof Computation",
-            ZkStage::Verification => "Local Verification",
-        }
+/// Format an elapsed duration for progress lines
+fn format_elapsed(elapsed: Duration) -> String {
+    if elapsed.as_secs() >= 1 {
+        format!("{:.1}s", elapsed.as_secs_f64())
+    } else {
+        format!("{}ms", elapsed.as_millis())
     }
+}
 
-    pub fn description(&self) -> &'static str {
-        match self {
-            ZkStage::WitnessGeneration => "Computing private inputs and public outputs",
-            ZkStage::ConstraintSatisfaction => "Validating circuit constraints are satisfied",
-            ZkStage::PolynomialCommitment => "Generating KZG polynomial commitments",
-            ZkStage::ProofComputation => "Computing UltraPlonk proof using Barretenberg",
-            ZkStage::Verification => "Verifying proof integrity locally",
+/// Print a progress line for a real prover event
+///
+/// Started events leave the cursor on the line; the matching finished event
+/// overwrites it with the outcome and elapsed time.
+pub fn print_proof_event(event: &ProofEvent) {
+    match event {
+        ProofEvent::WitnessStarted { .. } => {
+            print!(
+                "    {} Solving witness (nargo execute)...",
+                "◐".truecolor(ORANGE.0, ORANGE.1, ORANGE.2)
+            );
+            io::stdout().flush().unwrap();
         }
-    }
-
-    pub fn icon(&self) -> &'static str {
-        match self {
-            ZkStage::WitnessGeneration => "◈",
-            ZkStage::ConstraintSatisfaction => "◇",
-            ZkStage::PolynomialCommitment => "◆",
-            ZkStage::ProofComputation => "▣",
-            ZkStage::Verification => "✓",
+        ProofEvent::WitnessFinished { elapsed, .. } => {
+            println!(
+                "\r    {} Witness solved {}                    ",
+                "✓".green().bold(),
+                format!("({})", format_elapsed(*elapsed)).dimmed()
+            );
         }
-    }
-}
-
-/// Simulate and display ZK proof generation with detailed stages
-pub async fn display_zk_proof_generation(circuit_name: &str) {
-    println!();
-    println!(
-        "  {}",
-        format!("╔═══════════════════════════════════════════════════════╗")
-            .truecolor(ORANGE.0, ORANGE.1, ORANGE.2)
-    );
-    println!(
-        "  {}  {}  {}",
-        "║".truecolor(ORANGE.0, ORANGE.1, ORANGE.2),
-        format!("ZK-PROOF GENERATION: {}", circuit_name.to_uppercase())
-            .truecolor(ORANGE.0, ORANGE.1, ORANGE.2)
-            .bold(),
-        "            ║".truecolor(ORANGE.0, ORANGE.1, ORANGE.2)
-    );
-    println!(
-        "  {}",
-        format!("╚═══════════════════════════════════════════════════════╝")
-            .truecolor(ORANGE.0, ORANGE.1, ORANGE.2)
-    );
-    println!();
-
-    let stages = [
-        ZkStage::WitnessGeneration,
-        ZkStage::ConstraintSatisfaction,
-        ZkStage::PolynomialCommitment,
-        ZkStage::ProofComputation,
-        ZkStage::Verification,
-    ];
-
-    for (i, stage) in stages.iter().enumerate() {
-        // Show stage header
-        print!(
-            "  {} {} ",
-            stage.icon().truecolor(ORANGE.0, ORANGE.1, ORANGE.2),
-            stage.name().truecolor(200, 200, 200)
-        );
-        io::stdout().flush().unwrap();
-
-        // Simulate progress with spinner
-        let spinners = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
-        let iterations = match stage {
-            ZkStage::WitnessGeneration => 8,
-            ZkStage::ConstraintSatisfaction => 12,
-            ZkStage::PolynomialCommitment => 15,
-            ZkStage::ProofComputation => 20,
-            ZkStage::Verification => 6,
-        };
-
-        for j in 0..iterations {
+        ProofEvent::ProvingStarted { .. } => {
             print!(
-                "\r  {} {} {}",
-                stage.icon().truecolor(ORANGE.0, ORANGE.1, ORANGE.2),
-                stage.name().truecolor(200, 200, 200),
-                spinners[j % spinners.len()].truecolor(ORANGE.0, ORANGE.1, ORANGE.2)
+                "    {} Computing proof (bb prove)...",
+                "◐".truecolor(ORANGE.0, ORANGE.1, ORANGE.2)
             );
             io::stdout().flush().unwrap();
-            tokio::time::sleep(Duration::from_millis(50)).await;
         }
-
-        // Show completion
-        println!(
-            "\r  {} {} {}",
-            "✓".green().bold(),
-            stage.name().green(),
-            "".to_string()
-        );
-
-        // Show description on next line
-        println!(
-            "    {}",
-            stage.description().truecolor(100, 100, 100).italic()
-        );
-
-        // Show cryptographic details for key stages
-        match stage {
-            ZkStage::WitnessGeneration => {
-                display_witness_details().await;
-            }
-            ZkStage::ConstraintSatisfaction => {
-                display_constraint_details().await;
-            }
-            ZkStage::PolynomialCommitment => {
-                display_commitment_details().await;
-            }
-            ZkStage::ProofComputation => {
-                display_proof_details().await;
-            }
-            ZkStage::Verification => {
-                // Nothing extra for verification
-            }
+        ProofEvent::ProofWritten { bytes, elapsed, .. } => {
+            println!(
+                "\r    {} Proof written: {} bytes {}              ",
+                "✓".green().bold(),
+                bytes.to_string().bright_white(),
+                format!("({})", format_elapsed(*elapsed)).dimmed()
+            );
         }
-
-        if i < stages.len() - 1 {
-            println!("    {}", "│".truecolor(60, 60, 60));
+        ProofEvent::VerificationStarted { .. } => {
+            print!(
+                "    {} Verifying proof locally (bb verify)...",
+                "◐".truecolor(ORANGE.0, ORANGE.1, ORANGE.2)
+            );
+            io::stdout().flush().unwrap();
+        }
+        ProofEvent::Verified { valid, elapsed, .. } => {
+            let (mark, status) = if *valid {
+                ("✓".green().bold(), "Proof verified locally".normal())
+            } else {
+                ("✗".red().bold(), "Local verification failed".red())
+            };
+            println!(
+                "\r    {} {} {}           ",
+                mark,
+                status,
+                format!("({})", format_elapsed(*elapsed)).dimmed()
+            );
         }
     }
-
-    println!();
-}
-
-/// Display witness generation details
-async fn display_witness_details() {
-    let items = [
-        ("secret_key", "0x████████...████████"),
-        ("nullifier", "0x████████...████████"),
-        ("merkle_path", "[32 field elements]"),
-        ("balance", "███████ wei"),
-    ];
-
-    for (key, value) in items {
-        println!(
-            "    {} {}: {}",
-            "├".truecolor(60, 60, 60),
-            key.truecolor(130, 130, 130),
-            value.truecolor(80, 80, 80)
-        );
-        tokio::time::sleep(Duration::from_millis(30)).await;
-    }
-}
-
-/// Display constraint satisfaction details
-async fn display_constraint_details() {
-    println!(
-        "    {} {} {}",
-        "├".truecolor(60, 60, 60),
-        "Constraints:".truecolor(130, 130, 130),
-        "~50,000 R1CS constraints".truecolor(80, 80, 80)
-    );
-    println!(
-        "    {} {} {}",
-        "├".truecolor(60, 60, 60),
-        "Gates:".truecolor(130, 130, 130),
-        "UltraPlonk arithmetic gates".truecolor(80, 80, 80)
-    );
-    println!(
-        "    {} {} {}",
-        "├".truecolor(60, 60, 60),
-        "Satisfied:".truecolor(130, 130, 130),
-        "All constraints pass".green()
-    );
-}
-
-/// Display polynomial commitment details
-async fn display_commitment_details() {
-    println!(
-        "    {} {} {}",
-        "├".truecolor(60, 60, 60),
-        "Scheme:".truecolor(130, 130, 130),
-        "KZG (Kate-Zaverucha-Goldberg)".truecolor(80, 80, 80)
-    );
-    println!(
-        "    {} {} {}",
-        "├".truecolor(60, 60, 60),
-        "Curve:".truecolor(130, 130, 130),
-        "BN254 (alt_bn128)".truecolor(80, 80, 80)
-    );
-
-    // Show commitment progress
-    let commit_stages = ["W_L", "W_R", "W_O", "Z", "T"];
-    for stage in commit_stages.iter() {
-        print!(
-            "    {} Committing {}... ",
-            "├".truecolor(60, 60, 60),
-            stage.truecolor(100, 100, 100)
-        );
-        io::stdout().flush().unwrap();
-        tokio::time::sleep(Duration::from_millis(40)).await;
-        println!("{}", "done".green());
-    }
-}
-
-/// Display proof computation details
-async fn display_proof_details() {
-    println!(
-        "    {} {} {}",
-        "├".truecolor(60, 60, 60),
-        "Backend:".truecolor(130, 130, 130),
-        "Aztec Barretenberg (bb)".truecolor(80, 80, 80)
-    );
-    println!(
-        "    {} {} {}",
-        "├".truecolor(60, 60, 60),
-        "Protocol:".truecolor(130, 130, 130),
-        "UltraPlonk with Plookup".truecolor(80, 80, 80)
-    );
-    println!(
-        "    {} {} {}",
-        "├".truecolor(60, 60, 60),
-        "Security:".truecolor(130, 130, 130),
-        "128-bit soundness".truecolor(80, 80, 80)
-    );
 }
 
 /// Display a simpler inline proof progress for faster feedback
@@ -625,6 +456,52 @@ pub fn print_privacy_summary() {
     );
 }
 
+// ============================================================================
+// TRANSACTION PLANS
+// ============================================================================
+
+use veilocity_contracts::TxPlan;
+
+/// Format a per-gas fee in gwei
+fn format_gwei(wei: u128) -> String {
+    format!("{:.3} gwei", wei as f64 / 1e9)
+}
+
+/// Display the simulated gas and fees of a transaction
+pub fn print_tx_plan(plan: &TxPlan) {
+    println!(
+        "  {} {} {}",
+        "Gas limit:   ".truecolor(120, 120, 120),
+        plan.gas_limit.to_string().bright_white(),
+        format!("(estimate {})", plan.gas_estimate).dimmed()
+    );
+    println!(
+        "  {} {} {}",
+        "Max fee:     ".truecolor(120, 120, 120),
+        format_gwei(plan.max_fee_per_gas).bright_white(),
+        format!("(priority {})", format_gwei(plan.max_priority_fee_per_gas)).dimmed()
+    );
+    println!(
+        "  {} {}",
+        "Max gas cost:".truecolor(120, 120, 120),
+        crate::wallet::format_mnt(plan.max_fee()).truecolor(ORANGE.0, ORANGE.1, ORANGE.2)
+    );
+}
+
+/// Ask a yes/no question on the terminal; anything but y/yes is a no
+pub fn confirm(question: &str) -> io::Result<bool> {
+    print!(
+        "  {} {} ",
+        question.truecolor(ORANGE.0, ORANGE.1, ORANGE.2),
+        "[y/N]".dimmed()
+    );
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -637,6 +514,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_millis(250)), "250ms");
+        assert_eq!(format_elapsed(Duration::from_millis(2500)), "2.5s");
+    }
+
+    #[test]
+    fn test_format_gwei() {
+        assert_eq!(format_gwei(1_500_000_000), "1.500 gwei");
+    }
+
     #[test]
     fn test_format_hash() {
         assert_eq!(
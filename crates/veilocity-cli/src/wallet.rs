@@ -8,13 +8,14 @@ use aes_gcm::{
     aead::{Aead, KeyInit},
     Aes256Gcm, Nonce,
 };
-use alloy::primitives::{Address, B256};
+use alloy::primitives::{Address, B256, U256};
 use alloy::signers::local::PrivateKeySigner;
 use anyhow::{anyhow, Context, Result};
 use argon2::{password_hash::SaltString, Argon2, PasswordHasher};
 use rand::{rngs::OsRng, RngCore};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use veilocity_contracts::TokenInfo;
 use veilocity_core::account::AccountSecret;
 use veilocity_core::poseidon::{field_to_hex, PoseidonHasher};
 use zeroize::{Zeroize, ZeroizeOnDrop};
@@ -83,8 +84,8 @@ impl WalletManager {
 
         // Generate Veilocity secret
         let veilocity_secret = AccountSecret::generate();
-        let mut hasher = PoseidonHasher::new();
-        let veilocity_pubkey = veilocity_secret.derive_pubkey(&mut hasher);
+        let hasher = PoseidonHasher::new();
+        let veilocity_pubkey = veilocity_secret.derive_pubkey(&hasher);
         let veilocity_pubkey_hex = field_to_hex(&veilocity_pubkey);
 
         // Create wallet
@@ -272,6 +273,15 @@ pub fn format_mnt(wei: u128) -> String {
     }
 }
 
+/// Format an amount of native MNT or a token for display
+pub fn format_asset(amount: u128, token: &TokenInfo) -> String {
+    if token.is_native() {
+        format_mnt(amount)
+    } else {
+        token.format(U256::from(amount))
+    }
+}
+
 /// Parse MNT amount to wei
 pub fn parse_mnt(amount: f64) -> u128 {
     (amount * 1e18) as u128
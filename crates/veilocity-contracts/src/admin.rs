@@ -0,0 +1,419 @@
+//! Vault deployment and owner-only administration
+//!
+//! Contracts are deployed from Foundry's compiled artifacts (`forge build`
+//! writes them to `contracts/out`), so a local anvil deployment can be scripted
+//! without the Solidity deploy scripts.
+
+use crate::bindings::IVeilocityVault;
+use crate::error::ContractError;
+use crate::rpc::RpcConfig;
+use crate::vault::{create_vault_client, TxOutcome, VaultClient};
+use alloy::contract::{CallBuilder, CallDecoder};
+use alloy::network::{Ethereum, NetworkWallet, TransactionBuilder};
+use alloy::primitives::{Address, Bytes, B256, U256};
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::rpc::types::TransactionRequest;
+use alloy::sol_types::SolValue;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Artifact of the vault contract
+pub const VAULT_CONTRACT: &str = "VeilocityVault";
+
+/// Artifact of the UltraHonk verifier generated from the circuits
+pub const HONK_VERIFIER_CONTRACT: &str = "HonkVerifier";
+
+/// Artifact of the verifier that accepts every proof (testing only)
+pub const MOCK_VERIFIER_CONTRACT: &str = "MockVerifier";
+
+/// Creation bytecode of a compiled contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    /// Contract name
+    pub name: String,
+    /// Creation bytecode, without constructor arguments
+    pub bytecode: Bytes,
+}
+
+/// The parts of a Foundry artifact needed to deploy it
+#[derive(Deserialize)]
+struct ArtifactJson {
+    bytecode: BytecodeJson,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BytecodeJson {
+    object: String,
+    #[serde(default)]
+    link_references: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Artifact {
+    /// Path of a contract's artifact in a Foundry output directory
+    ///
+    /// Foundry writes `<out>/<File>.sol/<Contract>.json`; every contract here
+    /// lives in a file of the same name.
+    pub fn path(out_dir: &Path, name: &str) -> PathBuf {
+        out_dir
+            .join(format!("{}.sol", name))
+            .join(format!("{}.json", name))
+    }
+
+    /// Load a contract's artifact from a Foundry output directory
+    pub fn load(out_dir: &Path, name: &str) -> Result<Self, ContractError> {
+        let path = Self::path(out_dir, name);
+        let json = std::fs::read_to_string(&path).map_err(|e| {
+            ContractError::Artifact(format!(
+                "failed to read {} ({}); run 'forge build' in contracts/",
+                path.display(),
+                e
+            ))
+        })?;
+        Self::from_json(name, &json)
+    }
+
+    /// Parse a Foundry artifact
+    pub fn from_json(name: &str, json: &str) -> Result<Self, ContractError> {
+        let artifact: ArtifactJson = serde_json::from_str(json)
+            .map_err(|e| ContractError::Artifact(format!("{}: {}", name, e)))?;
+
+        if !artifact.bytecode.link_references.is_empty() {
+            return Err(ContractError::Artifact(format!(
+                "{} needs linked libraries",
+                name
+            )));
+        }
+
+        let bytecode = hex::decode(artifact.bytecode.object.trim_start_matches("0x"))?;
+        if bytecode.is_empty() {
+            return Err(ContractError::Artifact(format!(
+                "{} has no bytecode (abstract contract or interface)",
+                name
+            )));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            bytecode: bytecode.into(),
+        })
+    }
+
+    /// Creation code with ABI-encoded constructor arguments appended
+    pub fn deploy_code(&self, constructor_args: &[u8]) -> Bytes {
+        [self.bytecode.as_ref(), constructor_args].concat().into()
+    }
+}
+
+/// Verifier the vault is deployed against
+#[derive(Debug, Clone)]
+pub enum VerifierSource {
+    /// Reuse a verifier that is already deployed
+    Existing(Address),
+    /// Deploy this verifier first
+    Deploy(Artifact),
+}
+
+/// Addresses of a fresh deployment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deployment {
+    /// Verifier behind the vault
+    pub verifier: Address,
+    /// The vault
+    pub vault: Address,
+    /// Root the vault started from
+    pub initial_root: B256,
+    /// Block the vault was created in (where event scans can start)
+    pub block_number: Option<u64>,
+}
+
+/// Deploy a contract and wait for it to be mined
+///
+/// Returns the new contract's address and the creation transaction's outcome.
+pub async fn deploy_contract<P: Provider>(
+    provider: &P,
+    code: Bytes,
+) -> Result<(Address, TxOutcome), ContractError> {
+    let tx = TransactionRequest::default().with_deploy_code(code);
+    let pending = provider
+        .send_transaction(tx)
+        .await
+        .map_err(|e| ContractError::TransactionFailed(e.to_string()))?;
+    let receipt = pending
+        .get_receipt()
+        .await
+        .map_err(|e| ContractError::TransactionFailed(e.to_string()))?;
+
+    let outcome = TxOutcome::from(&receipt);
+    if !outcome.success {
+        return Err(ContractError::TransactionReverted(
+            "Contract creation reverted".to_string(),
+        ));
+    }
+
+    let address = receipt.contract_address.ok_or_else(|| {
+        ContractError::TransactionFailed("receipt has no contract address".to_string())
+    })?;
+    Ok((address, outcome))
+}
+
+/// Deploy the vault (and its verifier, unless an existing one is given)
+pub async fn deploy_vault<P: Provider>(
+    provider: &P,
+    vault: &Artifact,
+    verifier: &VerifierSource,
+    initial_root: B256,
+) -> Result<Deployment, ContractError> {
+    let verifier = match verifier {
+        VerifierSource::Existing(address) => *address,
+        VerifierSource::Deploy(artifact) => {
+            info!("Deploying {}", artifact.name);
+            let (address, _) = deploy_contract(provider, artifact.deploy_code(&[])).await?;
+            info!("{} deployed at {:?}", artifact.name, address);
+            address
+        }
+    };
+
+    info!("Deploying {} with root {:?}", vault.name, initial_root);
+    let constructor_args = (verifier, initial_root).abi_encode_params();
+    let (address, outcome) =
+        deploy_contract(provider, vault.deploy_code(&constructor_args)).await?;
+    info!("{} deployed at {:?}", vault.name, address);
+
+    Ok(Deployment {
+        verifier,
+        vault: address,
+        initial_root,
+        block_number: outcome.block_number,
+    })
+}
+
+/// Deploy the vault over the configured RPC endpoints, signing with `wallet`
+///
+/// The wallet's account becomes the vault owner.
+pub async fn deploy_vault_with<W>(
+    rpc: &RpcConfig,
+    wallet: W,
+    vault: &Artifact,
+    verifier: &VerifierSource,
+    initial_root: B256,
+) -> Result<Deployment, ContractError>
+where
+    W: NetworkWallet<Ethereum> + Clone + 'static,
+{
+    let provider = ProviderBuilder::new()
+        .wallet(wallet)
+        .connect_client(rpc.client()?);
+
+    deploy_vault(&provider, vault, verifier, initial_root).await
+}
+
+/// Client for the vault owner's functions
+///
+/// Transactions are simulated first, so calls from an account that is not the
+/// owner (or that the pause state forbids) fail with the decoded vault error
+/// before anything is sent.
+pub struct AdminClient<P> {
+    vault: VaultClient<P>,
+}
+
+impl<P: Provider + Clone> AdminClient<P> {
+    /// Administer the vault behind a client
+    pub fn new(vault: VaultClient<P>) -> Self {
+        Self { vault }
+    }
+
+    /// The underlying vault client
+    pub fn vault(&self) -> &VaultClient<P> {
+        &self.vault
+    }
+
+    /// Get the vault owner
+    pub async fn owner(&self) -> Result<Address, ContractError> {
+        let contract = IVeilocityVault::new(self.vault.address(), self.vault.provider());
+        contract
+            .owner()
+            .call()
+            .await
+            .map_err(ContractError::from_call)
+    }
+
+    /// Check whether the vault is paused
+    pub async fn is_paused(&self) -> Result<bool, ContractError> {
+        let contract = IVeilocityVault::new(self.vault.address(), self.vault.provider());
+        contract
+            .paused()
+            .call()
+            .await
+            .map_err(ContractError::from_call)
+    }
+
+    /// Plan, send and wait for an owner call, failing if it reverts
+    async fn execute<T: Provider, D: CallDecoder>(
+        &self,
+        call: CallBuilder<T, D>,
+        action: &str,
+    ) -> Result<TxOutcome, ContractError> {
+        let plan = self.vault.plan_call(&call).await?;
+        let tx_hash = self.vault.send_planned(call, &plan).await?;
+
+        let outcome = self.vault.wait_for_receipt(tx_hash).await?;
+        if !outcome.success {
+            return Err(ContractError::TransactionReverted(format!(
+                "{} reverted",
+                action
+            )));
+        }
+
+        info!("{} confirmed in tx {:?}", action, tx_hash);
+        Ok(outcome)
+    }
+
+    /// Pause deposits, withdrawals and root updates
+    pub async fn pause(&self) -> Result<TxOutcome, ContractError> {
+        let contract = IVeilocityVault::new(self.vault.address(), self.vault.provider());
+        self.execute(contract.pause(), "Pause").await
+    }
+
+    /// Resume a paused vault
+    pub async fn unpause(&self) -> Result<TxOutcome, ContractError> {
+        let contract = IVeilocityVault::new(self.vault.address(), self.vault.provider());
+        self.execute(contract.unpause(), "Unpause").await
+    }
+
+    /// Send the vault's whole MNT balance to `recipient` (only while paused)
+    pub async fn emergency_withdraw(&self, recipient: Address) -> Result<TxOutcome, ContractError> {
+        info!("Emergency withdrawal of MNT to {:?}", recipient);
+
+        let contract = IVeilocityVault::new(self.vault.address(), self.vault.provider());
+        self.execute(
+            contract.emergencyWithdraw(recipient),
+            "Emergency withdrawal",
+        )
+        .await
+    }
+
+    /// Send the vault's whole balance of a token to `recipient` (only while paused)
+    pub async fn emergency_withdraw_token(
+        &self,
+        token: Address,
+        recipient: Address,
+    ) -> Result<TxOutcome, ContractError> {
+        info!(
+            "Emergency withdrawal of token {:?} to {:?}",
+            token, recipient
+        );
+
+        let contract = IVeilocityVault::new(self.vault.address(), self.vault.provider());
+        self.execute(
+            contract.emergencyWithdrawToken(token, recipient),
+            "Emergency token withdrawal",
+        )
+        .await
+    }
+
+    /// Anchor a new state root, with the number of deposits and the
+    /// nullifiers of the transfers the proof covers
+    pub async fn update_state_root(
+        &self,
+        new_root: B256,
+        transfer_nullifiers: &[B256],
+        deposits: u64,
+        proof: Vec<u8>,
+    ) -> Result<B256, ContractError> {
+        self.vault
+            .update_state_root(new_root, transfer_nullifiers, deposits, proof)
+            .await
+    }
+
+    /// Set the withdraw_batch verifier (zero disables batching)
+    pub async fn set_batch_verifier(&self, verifier: Address) -> Result<TxOutcome, ContractError> {
+        let contract = IVeilocityVault::new(self.vault.address(), self.vault.provider());
+        self.execute(contract.setBatchVerifier(verifier), "Batch verifier update")
+            .await
+    }
+
+    /// Set the withdraw_token verifier (zero disables token withdrawals)
+    pub async fn set_token_verifier(&self, verifier: Address) -> Result<TxOutcome, ContractError> {
+        let contract = IVeilocityVault::new(self.vault.address(), self.vault.provider());
+        self.execute(contract.setTokenVerifier(verifier), "Token verifier update")
+            .await
+    }
+
+    /// Set the state_transition verifier (zero disables root updates)
+    pub async fn set_transition_verifier(
+        &self,
+        verifier: Address,
+    ) -> Result<TxOutcome, ContractError> {
+        let contract = IVeilocityVault::new(self.vault.address(), self.vault.provider());
+        self.execute(
+            contract.setTransitionVerifier(verifier),
+            "Transition verifier update",
+        )
+        .await
+    }
+
+    /// Accept deposits of a token of at least `min_deposit` (zero removes support)
+    pub async fn set_token_support(
+        &self,
+        token: Address,
+        min_deposit: U256,
+    ) -> Result<TxOutcome, ContractError> {
+        let contract = IVeilocityVault::new(self.vault.address(), self.vault.provider());
+        self.execute(
+            contract.setTokenSupport(token, min_deposit),
+            "Token support update",
+        )
+        .await
+    }
+}
+
+/// Create an admin client for a deployed vault over the configured RPC endpoints
+pub async fn create_admin_client<W>(
+    rpc: &RpcConfig,
+    vault_address: Address,
+    wallet: W,
+) -> Result<AdminClient<impl Provider + Clone>, ContractError>
+where
+    W: NetworkWallet<Ethereum> + Clone + 'static,
+{
+    Ok(AdminClient::new(
+        create_vault_client(rpc, vault_address, wallet).await?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_artifact() {
+        let json = r#"{
+            "abi": [],
+            "bytecode": { "object": "0x6080604052", "sourceMap": "", "linkReferences": {} },
+            "deployedBytecode": { "object": "0x6080" }
+        }"#;
+        let artifact = Artifact::from_json(VAULT_CONTRACT, json).unwrap();
+        assert_eq!(artifact.bytecode.as_ref(), &[0x60, 0x80, 0x60, 0x40, 0x52]);
+
+        let args = (Address::repeat_byte(1), B256::repeat_byte(2)).abi_encode_params();
+        let code = artifact.deploy_code(&args);
+        assert_eq!(code.len(), 5 + 64);
+        assert_eq!(&code[5 + 12..5 + 32], Address::repeat_byte(1).as_slice());
+
+        let linked =
+            r#"{ "bytecode": { "object": "0x60", "linkReferences": { "src/Lib.sol": {} } } }"#;
+        assert!(matches!(
+            Artifact::from_json("Linked", linked),
+            Err(ContractError::Artifact(_))
+        ));
+        let empty = r#"{ "bytecode": { "object": "0x" } }"#;
+        assert!(Artifact::from_json("IVerifier", empty).is_err());
+
+        assert_eq!(
+            Artifact::path(Path::new("contracts/out"), VAULT_CONTRACT),
+            Path::new("contracts/out/VeilocityVault.sol/VeilocityVault.json")
+        );
+    }
+}
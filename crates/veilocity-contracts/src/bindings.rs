@@ -2,6 +2,9 @@
 //!
 //! This module contains the ABI definitions for interacting with Veilocity contracts.
 
+// Generated methods take every ABI argument (`depositTokenWithPermit` has seven)
+#![allow(clippy::too_many_arguments)]
+
 use alloy::sol;
 
 // VeilocityVault contract bindings
@@ -22,6 +25,12 @@ sol! {
             uint256 amount
         );
 
+        event BatchWithdrawal(
+            bytes32 indexed root,
+            uint256 count,
+            uint256 totalAmount
+        );
+
         event StateRootUpdated(
             bytes32 indexed oldRoot,
             bytes32 indexed newRoot,
@@ -29,19 +38,96 @@ sol! {
             uint256 timestamp
         );
 
+        event TokenDeposit(
+            bytes32 indexed commitment,
+            address indexed token,
+            uint256 amount,
+            uint256 leafIndex,
+            uint256 timestamp
+        );
+
+        event TokenWithdrawal(
+            bytes32 indexed nullifier,
+            address indexed token,
+            address indexed recipient,
+            uint256 amount
+        );
+
+        event TokenSupportUpdated(address indexed token, uint256 minDeposit);
+        event TokenVerifierUpdated(address indexed tokenVerifier);
+        event TransitionVerifierUpdated(address indexed transitionVerifier);
+
+        // Errors
+        error InvalidVerifier();
+        error DepositTooSmall();
+        error InvalidCommitment();
+        error InvalidProof();
+        error InvalidRoot();
+        error NullifierAlreadyUsed();
+        error InvalidRecipient();
+        error InvalidAmount();
+        error TransferFailed();
+        error InvalidNullifier();
+        error InvalidBatch();
+        error BatchVerifierNotSet();
+        error TokenNotSupported();
+        error TokenVerifierNotSet();
+        error TransitionVerifierNotSet();
+
+        // Inherited from OpenZeppelin Pausable, Ownable, ReentrancyGuard and SafeERC20
+        error EnforcedPause();
+        error ExpectedPause();
+        error OwnableUnauthorizedAccount(address account);
+        error ReentrancyGuardReentrantCall();
+        error SafeERC20FailedOperation(address token);
+
         // View functions
         function currentRoot() external view returns (bytes32);
         function depositCount() external view returns (uint256);
+        function anchoredDepositCount() external view returns (uint256);
+        function depositCommitments(uint256 leafIndex) external view returns (bytes32);
         function totalValueLocked() external view returns (uint256);
         function isValidRoot(bytes32 root) external view returns (bool);
         function isNullifierUsed(bytes32 nullifier) external view returns (bool);
         function getDepositCount() external view returns (uint256);
         function getTotalValueLocked() external view returns (uint256);
         function MIN_DEPOSIT() external view returns (uint256);
+        function verifier() external view returns (address);
+        function batchVerifier() external view returns (address);
+        function WITHDRAW_BATCH_SIZE() external view returns (uint256);
+        function tokenVerifier() external view returns (address);
+        function transitionVerifier() external view returns (address);
+        function TRANSFER_BATCH_SIZE() external view returns (uint256);
+        function tokenMinDeposit(address token) external view returns (uint256);
+        function tokenValueLocked(address token) external view returns (uint256);
+        function isTokenSupported(address token) external view returns (bool);
+        function owner() external view returns (address);
+        function paused() external view returns (bool);
 
         // State-changing functions
         function deposit(bytes32 commitment) external payable;
 
+        function depositToken(address token, uint256 amount, bytes32 commitment) external;
+
+        function depositTokenWithPermit(
+            address token,
+            uint256 amount,
+            bytes32 commitment,
+            uint256 deadline,
+            uint8 v,
+            bytes32 r,
+            bytes32 s
+        ) external;
+
+        function withdrawToken(
+            address token,
+            bytes32 nullifier,
+            address recipient,
+            uint256 amount,
+            bytes32 root,
+            bytes calldata proof
+        ) external;
+
         function withdraw(
             bytes32 nullifier,
             address recipient,
@@ -50,8 +136,18 @@ sol! {
             bytes calldata proof
         ) external;
 
+        function withdrawBatch(
+            bytes32[] calldata batchNullifiers,
+            address[] calldata recipients,
+            uint256[] calldata amounts,
+            bytes32 root,
+            bytes calldata proof
+        ) external;
+
         function updateStateRoot(
             bytes32 newRoot,
+            bytes32[] calldata transferNullifiers,
+            uint256 deposits,
             bytes calldata proof
         ) external;
 
@@ -59,6 +155,40 @@ sol! {
         function pause() external;
         function unpause() external;
         function emergencyWithdraw(address recipient) external;
+        function setBatchVerifier(address batchVerifier) external;
+        function setTokenSupport(address token, uint256 minDeposit) external;
+        function setTokenVerifier(address tokenVerifier) external;
+        function setTransitionVerifier(address transitionVerifier) external;
+        function emergencyWithdrawToken(address token, address recipient) external;
+    }
+}
+
+// ERC-20 token bindings, with the EIP-2612 permit extension
+sol! {
+    #[sol(rpc)]
+    interface IERC20Token {
+        function name() external view returns (string);
+        function symbol() external view returns (string);
+        function decimals() external view returns (uint8);
+        function balanceOf(address account) external view returns (uint256);
+        function allowance(address owner, address spender) external view returns (uint256);
+        function approve(address spender, uint256 value) external returns (bool);
+
+        // EIP-2612
+        function nonces(address owner) external view returns (uint256);
+        function DOMAIN_SEPARATOR() external view returns (bytes32);
+        function version() external view returns (string);
+    }
+}
+
+// EIP-2612 permit message, signed as EIP-712 typed data
+sol! {
+    struct Permit {
+        address owner;
+        address spender;
+        uint256 value;
+        uint256 nonce;
+        uint256 deadline;
     }
 }
 
@@ -1,5 +1,7 @@
 //! Error types for contract interactions
 
+use crate::bindings::IVeilocityVault::IVeilocityVaultErrors;
+use alloy::sol_types::SolInterface;
 use thiserror::Error;
 
 /// Errors that can occur during contract interactions
@@ -37,6 +39,70 @@ pub enum ContractError {
     #[error("Invalid state root")]
     InvalidRoot,
 
+    /// Deposit below the vault's minimum
+    #[error("Deposit is below the vault minimum")]
+    DepositTooSmall,
+
+    /// Zero deposit commitment
+    #[error("Invalid deposit commitment")]
+    InvalidCommitment,
+
+    /// Zero nullifier
+    #[error("Invalid nullifier")]
+    InvalidNullifier,
+
+    /// Zero recipient address
+    #[error("Invalid recipient")]
+    InvalidRecipient,
+
+    /// Zero withdrawal amount
+    #[error("Invalid amount")]
+    InvalidAmount,
+
+    /// The vault could not send funds to the recipient
+    #[error("Vault transfer to recipient failed")]
+    TransferFailed,
+
+    /// Malformed withdrawal batch
+    #[error("Invalid withdrawal batch")]
+    InvalidBatch,
+
+    /// Batch withdrawals are not enabled on the vault
+    #[error("Batch verifier not set")]
+    BatchVerifierNotSet,
+
+    /// Token deposits and withdrawals are not enabled on the vault
+    #[error("Token verifier not set")]
+    TokenVerifierNotSet,
+
+    /// Transfer root updates are not enabled on the vault
+    #[error("Transition verifier not set")]
+    TransitionVerifierNotSet,
+
+    /// The vault has no pool for this token
+    #[error("Token not supported by the vault")]
+    TokenNotSupported,
+
+    /// An ERC-20 transfer or approval failed
+    #[error("Token operation failed on {0}")]
+    TokenTransferFailed(String),
+
+    /// Zero verifier address
+    #[error("Invalid verifier")]
+    InvalidVerifier,
+
+    /// The vault is paused
+    #[error("Vault is paused")]
+    Paused,
+
+    /// The vault is not paused
+    #[error("Vault is not paused")]
+    NotPaused,
+
+    /// Caller is not the vault owner
+    #[error("Not the vault owner: {0}")]
+    Unauthorized(String),
+
     /// Insufficient balance
     #[error("Insufficient balance: have {have}, need {need}")]
     InsufficientBalance { have: u128, need: u128 },
@@ -45,6 +111,10 @@ pub enum ContractError {
     #[error("Contract not deployed at {0}")]
     ContractNotDeployed(String),
 
+    /// Missing or unusable compiled contract artifact
+    #[error("Invalid contract artifact: {0}")]
+    Artifact(String),
+
     /// Event parsing error
     #[error("Failed to parse event: {0}")]
     EventParsing(String),
@@ -57,7 +127,103 @@ pub enum ContractError {
     #[error("Transaction timeout")]
     Timeout,
 
+    /// Network fees above the configured ceiling
+    #[error("Max fee of {max_fee} wei/gas exceeds the ceiling of {ceiling} wei/gas")]
+    FeeTooHigh { max_fee: u128, ceiling: u128 },
+
+    /// Too few RPC endpoints agreed on a quorum read
+    #[error("RPC quorum not reached: {agreeing} of {queried} endpoints agreed, {needed} needed")]
+    QuorumNotReached {
+        needed: usize,
+        agreeing: usize,
+        queried: usize,
+    },
+
     /// Hex decoding error
     #[error("Hex decode error: {0}")]
     HexDecode(#[from] hex::FromHexError),
 }
+
+impl ContractError {
+    /// Decode a vault custom error from revert data
+    ///
+    /// Returns `None` if the data is not one of the vault's errors.
+    pub fn from_revert_data(data: &[u8]) -> Option<Self> {
+        let error = match IVeilocityVaultErrors::abi_decode(data).ok()? {
+            IVeilocityVaultErrors::InvalidVerifier(_) => ContractError::InvalidVerifier,
+            IVeilocityVaultErrors::DepositTooSmall(_) => ContractError::DepositTooSmall,
+            IVeilocityVaultErrors::InvalidCommitment(_) => ContractError::InvalidCommitment,
+            IVeilocityVaultErrors::InvalidProof(_) => ContractError::InvalidProof,
+            IVeilocityVaultErrors::InvalidRoot(_) => ContractError::InvalidRoot,
+            IVeilocityVaultErrors::NullifierAlreadyUsed(_) => {
+                ContractError::NullifierUsed("rejected by vault".to_string())
+            }
+            IVeilocityVaultErrors::InvalidRecipient(_) => ContractError::InvalidRecipient,
+            IVeilocityVaultErrors::InvalidAmount(_) => ContractError::InvalidAmount,
+            IVeilocityVaultErrors::TransferFailed(_) => ContractError::TransferFailed,
+            IVeilocityVaultErrors::InvalidNullifier(_) => ContractError::InvalidNullifier,
+            IVeilocityVaultErrors::InvalidBatch(_) => ContractError::InvalidBatch,
+            IVeilocityVaultErrors::BatchVerifierNotSet(_) => ContractError::BatchVerifierNotSet,
+            IVeilocityVaultErrors::TokenNotSupported(_) => ContractError::TokenNotSupported,
+            IVeilocityVaultErrors::TokenVerifierNotSet(_) => ContractError::TokenVerifierNotSet,
+            IVeilocityVaultErrors::TransitionVerifierNotSet(_) => {
+                ContractError::TransitionVerifierNotSet
+            }
+            IVeilocityVaultErrors::SafeERC20FailedOperation(e) => {
+                ContractError::TokenTransferFailed(e.token.to_string())
+            }
+            IVeilocityVaultErrors::EnforcedPause(_) => ContractError::Paused,
+            IVeilocityVaultErrors::ExpectedPause(_) => ContractError::NotPaused,
+            IVeilocityVaultErrors::OwnableUnauthorizedAccount(e) => {
+                ContractError::Unauthorized(e.account.to_string())
+            }
+            IVeilocityVaultErrors::ReentrancyGuardReentrantCall(_) => {
+                ContractError::TransactionReverted("reentrant call".to_string())
+            }
+        };
+        Some(error)
+    }
+
+    /// Map a failed send, decoding the vault's revert reason if the node
+    /// returned one (from the `eth_estimateGas` or `eth_call` it ran first)
+    pub(crate) fn from_send(err: alloy::contract::Error) -> Self {
+        err.as_revert_data()
+            .and_then(|data| Self::from_revert_data(&data))
+            .unwrap_or_else(|| ContractError::TransactionFailed(err.to_string()))
+    }
+
+    /// Map a failed call, decoding the vault's revert reason if there is one
+    pub(crate) fn from_call(err: alloy::contract::Error) -> Self {
+        err.as_revert_data()
+            .and_then(|data| Self::from_revert_data(&data))
+            .unwrap_or_else(|| ContractError::ContractCall(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::IVeilocityVault;
+    use alloy::primitives::Address;
+    use alloy::sol_types::SolError;
+
+    #[test]
+    fn test_decode_vault_errors() {
+        let data = IVeilocityVault::InvalidRoot {}.abi_encode();
+        assert!(matches!(
+            ContractError::from_revert_data(&data),
+            Some(ContractError::InvalidRoot)
+        ));
+
+        let owner = Address::repeat_byte(0x11);
+        let data = IVeilocityVault::OwnableUnauthorizedAccount { account: owner }.abi_encode();
+        assert!(matches!(
+            ContractError::from_revert_data(&data),
+            Some(ContractError::Unauthorized(account)) if account == owner.to_string()
+        ));
+
+        // Error(string) and unknown selectors are not vault errors
+        assert!(ContractError::from_revert_data(&[0xde, 0xad, 0xbe, 0xef]).is_none());
+        assert!(ContractError::from_revert_data(&[]).is_none());
+    }
+}
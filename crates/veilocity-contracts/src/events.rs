@@ -4,42 +4,123 @@
 
 use crate::bindings::IVeilocityVault;
 use alloy::primitives::{Address, B256, U256};
+use alloy::rpc::types::Log;
+use alloy::sol_types::SolEvent;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Where a log sits in the chain
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LogMeta {
+    /// Block number
+    pub block_number: u64,
+    /// Block hash
+    pub block_hash: B256,
+    /// Position of the transaction within the block
+    pub transaction_index: u64,
+    /// Position of the log within the block
+    pub log_index: u64,
+    /// Transaction hash
+    pub tx_hash: B256,
+}
+
+impl LogMeta {
+    /// Read the position of an RPC log (pending logs have zeroes)
+    pub fn of(log: &Log) -> Self {
+        Self {
+            block_number: log.block_number.unwrap_or(0),
+            block_hash: log.block_hash.unwrap_or_default(),
+            transaction_index: log.transaction_index.unwrap_or(0),
+            log_index: log.log_index.unwrap_or(0),
+            tx_hash: log.transaction_hash.unwrap_or_default(),
+        }
+    }
+}
+
+/// Stable identifier of an event: its block and log index
+///
+/// Orders events the way the chain emitted them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct EventId {
+    /// Block number
+    pub block_number: u64,
+    /// Position of the log within the block
+    pub log_index: u64,
+}
+
+impl fmt::Display for EventId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.block_number, self.log_index)
+    }
+}
 
 /// Parsed deposit event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DepositEvent {
     /// The deposit commitment
     pub commitment: B256,
-    /// Amount deposited in wei
+    /// Amount deposited in the token's base units (wei for MNT)
     pub amount: U256,
+    /// Token deposited (the zero address for native MNT)
+    #[serde(default)]
+    pub token: Address,
     /// Assigned leaf index
     pub leaf_index: U256,
     /// Block timestamp
     pub timestamp: U256,
     /// Block number where the event was emitted
     pub block_number: u64,
+    /// Hash of the block
+    #[serde(default)]
+    pub block_hash: B256,
+    /// Position of the transaction within the block
+    #[serde(default)]
+    pub transaction_index: u64,
+    /// Position of the log within the block
+    #[serde(default)]
+    pub log_index: u64,
     /// Transaction hash
     pub tx_hash: B256,
 }
 
 impl DepositEvent {
     /// Create from raw log data
-    pub fn from_log(
-        log: &IVeilocityVault::Deposit,
-        block_number: u64,
-        tx_hash: B256,
-    ) -> Self {
+    pub fn from_log(log: &IVeilocityVault::Deposit, meta: &LogMeta) -> Self {
+        Self {
+            commitment: log.commitment,
+            amount: log.amount,
+            token: Address::ZERO,
+            leaf_index: log.leafIndex,
+            timestamp: log.timestamp,
+            block_number: meta.block_number,
+            block_hash: meta.block_hash,
+            transaction_index: meta.transaction_index,
+            log_index: meta.log_index,
+            tx_hash: meta.tx_hash,
+        }
+    }
+
+    /// Create from a token deposit log
+    pub fn from_token_log(log: &IVeilocityVault::TokenDeposit, meta: &LogMeta) -> Self {
         Self {
             commitment: log.commitment,
             amount: log.amount,
+            token: log.token,
             leaf_index: log.leafIndex,
             timestamp: log.timestamp,
-            block_number,
-            tx_hash,
+            block_number: meta.block_number,
+            block_hash: meta.block_hash,
+            transaction_index: meta.transaction_index,
+            log_index: meta.log_index,
+            tx_hash: meta.tx_hash,
         }
     }
 
+    /// Whether this deposit was native MNT
+    pub fn is_native(&self) -> bool {
+        self.token == Address::ZERO
+    }
+
     /// Get commitment as hex string
     pub fn commitment_hex(&self) -> String {
         format!("0x{}", hex::encode(self.commitment))
@@ -47,8 +128,12 @@ impl DepositEvent {
 
     /// Get amount in MNT
     pub fn amount_mnt(&self) -> f64 {
-        let wei: u128 = self.amount.try_into().unwrap_or(0);
-        wei as f64 / 1e18
+        self.amount_units(18)
+    }
+
+    /// Get amount in whole units of a token with `decimals` decimals
+    pub fn amount_units(&self, decimals: u8) -> f64 {
+        to_units(self.amount, decimals)
     }
 }
 
@@ -59,30 +144,62 @@ pub struct WithdrawalEvent {
     pub nullifier: B256,
     /// Recipient address
     pub recipient: Address,
-    /// Amount withdrawn in wei
+    /// Amount withdrawn in the token's base units (wei for MNT)
     pub amount: U256,
+    /// Token withdrawn (the zero address for native MNT)
+    #[serde(default)]
+    pub token: Address,
     /// Block number
     pub block_number: u64,
+    /// Hash of the block
+    #[serde(default)]
+    pub block_hash: B256,
+    /// Position of the transaction within the block
+    #[serde(default)]
+    pub transaction_index: u64,
+    /// Position of the log within the block
+    #[serde(default)]
+    pub log_index: u64,
     /// Transaction hash
     pub tx_hash: B256,
 }
 
 impl WithdrawalEvent {
     /// Create from raw log data
-    pub fn from_log(
-        log: &IVeilocityVault::Withdrawal,
-        block_number: u64,
-        tx_hash: B256,
-    ) -> Self {
+    pub fn from_log(log: &IVeilocityVault::Withdrawal, meta: &LogMeta) -> Self {
+        Self {
+            nullifier: log.nullifier,
+            recipient: log.recipient,
+            amount: log.amount,
+            token: Address::ZERO,
+            block_number: meta.block_number,
+            block_hash: meta.block_hash,
+            transaction_index: meta.transaction_index,
+            log_index: meta.log_index,
+            tx_hash: meta.tx_hash,
+        }
+    }
+
+    /// Create from a token withdrawal log
+    pub fn from_token_log(log: &IVeilocityVault::TokenWithdrawal, meta: &LogMeta) -> Self {
         Self {
             nullifier: log.nullifier,
             recipient: log.recipient,
             amount: log.amount,
-            block_number,
-            tx_hash,
+            token: log.token,
+            block_number: meta.block_number,
+            block_hash: meta.block_hash,
+            transaction_index: meta.transaction_index,
+            log_index: meta.log_index,
+            tx_hash: meta.tx_hash,
         }
     }
 
+    /// Whether this withdrawal was native MNT
+    pub fn is_native(&self) -> bool {
+        self.token == Address::ZERO
+    }
+
     /// Get nullifier as hex string
     pub fn nullifier_hex(&self) -> String {
         format!("0x{}", hex::encode(self.nullifier))
@@ -90,11 +207,21 @@ impl WithdrawalEvent {
 
     /// Get amount in MNT
     pub fn amount_mnt(&self) -> f64 {
-        let wei: u128 = self.amount.try_into().unwrap_or(0);
-        wei as f64 / 1e18
+        self.amount_units(18)
+    }
+
+    /// Get amount in whole units of a token with `decimals` decimals
+    pub fn amount_units(&self, decimals: u8) -> f64 {
+        to_units(self.amount, decimals)
     }
 }
 
+/// Convert base units to whole units for display
+fn to_units(amount: U256, decimals: u8) -> f64 {
+    let base: u128 = amount.try_into().unwrap_or(0);
+    base as f64 / 10f64.powi(decimals as i32)
+}
+
 /// Parsed state root update event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StateRootUpdatedEvent {
@@ -108,24 +235,32 @@ pub struct StateRootUpdatedEvent {
     pub timestamp: U256,
     /// Block number
     pub block_number: u64,
+    /// Hash of the block
+    #[serde(default)]
+    pub block_hash: B256,
+    /// Position of the transaction within the block
+    #[serde(default)]
+    pub transaction_index: u64,
+    /// Position of the log within the block
+    #[serde(default)]
+    pub log_index: u64,
     /// Transaction hash
     pub tx_hash: B256,
 }
 
 impl StateRootUpdatedEvent {
     /// Create from raw log data
-    pub fn from_log(
-        log: &IVeilocityVault::StateRootUpdated,
-        block_number: u64,
-        tx_hash: B256,
-    ) -> Self {
+    pub fn from_log(log: &IVeilocityVault::StateRootUpdated, meta: &LogMeta) -> Self {
         Self {
             old_root: log.oldRoot,
             new_root: log.newRoot,
             batch_index: log.batchIndex,
             timestamp: log.timestamp,
-            block_number,
-            tx_hash,
+            block_number: meta.block_number,
+            block_hash: meta.block_hash,
+            transaction_index: meta.transaction_index,
+            log_index: meta.log_index,
+            tx_hash: meta.tx_hash,
         }
     }
 }
@@ -139,6 +274,57 @@ pub enum VeilocityEvent {
 }
 
 impl VeilocityEvent {
+    /// Topic0 of every vault event, for fetching them all with one filter
+    pub const SIGNATURES: [B256; 5] = [
+        IVeilocityVault::Deposit::SIGNATURE_HASH,
+        IVeilocityVault::Withdrawal::SIGNATURE_HASH,
+        IVeilocityVault::StateRootUpdated::SIGNATURE_HASH,
+        IVeilocityVault::TokenDeposit::SIGNATURE_HASH,
+        IVeilocityVault::TokenWithdrawal::SIGNATURE_HASH,
+    ];
+
+    /// Decode a vault log, or `None` if it is not a vault event
+    ///
+    /// Token deposits and withdrawals decode into the same variants as
+    /// native ones, with their `token` set.
+    pub fn decode_log(log: &Log) -> Option<Self> {
+        let meta = LogMeta::of(log);
+        let topic0 = *log.topic0()?;
+
+        if topic0 == IVeilocityVault::Deposit::SIGNATURE_HASH {
+            let decoded = log.log_decode::<IVeilocityVault::Deposit>().ok()?;
+            Some(VeilocityEvent::Deposit(DepositEvent::from_log(
+                &decoded.inner.data,
+                &meta,
+            )))
+        } else if topic0 == IVeilocityVault::Withdrawal::SIGNATURE_HASH {
+            let decoded = log.log_decode::<IVeilocityVault::Withdrawal>().ok()?;
+            Some(VeilocityEvent::Withdrawal(WithdrawalEvent::from_log(
+                &decoded.inner.data,
+                &meta,
+            )))
+        } else if topic0 == IVeilocityVault::TokenDeposit::SIGNATURE_HASH {
+            let decoded = log.log_decode::<IVeilocityVault::TokenDeposit>().ok()?;
+            Some(VeilocityEvent::Deposit(DepositEvent::from_token_log(
+                &decoded.inner.data,
+                &meta,
+            )))
+        } else if topic0 == IVeilocityVault::TokenWithdrawal::SIGNATURE_HASH {
+            let decoded = log.log_decode::<IVeilocityVault::TokenWithdrawal>().ok()?;
+            Some(VeilocityEvent::Withdrawal(WithdrawalEvent::from_token_log(
+                &decoded.inner.data,
+                &meta,
+            )))
+        } else if topic0 == IVeilocityVault::StateRootUpdated::SIGNATURE_HASH {
+            let decoded = log.log_decode::<IVeilocityVault::StateRootUpdated>().ok()?;
+            Some(VeilocityEvent::StateRootUpdated(
+                StateRootUpdatedEvent::from_log(&decoded.inner.data, &meta),
+            ))
+        } else {
+            None
+        }
+    }
+
     /// Get the block number of the event
     pub fn block_number(&self) -> u64 {
         match self {
@@ -148,6 +334,23 @@ impl VeilocityEvent {
         }
     }
 
+    /// Get the position of the event's log within its block
+    pub fn log_index(&self) -> u64 {
+        match self {
+            VeilocityEvent::Deposit(e) => e.log_index,
+            VeilocityEvent::Withdrawal(e) => e.log_index,
+            VeilocityEvent::StateRootUpdated(e) => e.log_index,
+        }
+    }
+
+    /// Get the stable identifier of the event
+    pub fn id(&self) -> EventId {
+        EventId {
+            block_number: self.block_number(),
+            log_index: self.log_index(),
+        }
+    }
+
     /// Get the transaction hash of the event
     pub fn tx_hash(&self) -> B256 {
         match self {
@@ -189,3 +392,77 @@ impl EventFilter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_log() {
+        let deposit = IVeilocityVault::Deposit {
+            commitment: B256::repeat_byte(7),
+            amount: U256::from(1000u64),
+            leafIndex: U256::from(3u64),
+            timestamp: U256::from(1_700_000_000u64),
+        };
+        let log = Log {
+            inner: alloy::primitives::Log {
+                address: Address::ZERO,
+                data: deposit.encode_log_data(),
+            },
+            block_number: Some(42),
+            log_index: Some(5),
+            ..Default::default()
+        };
+
+        let Some(VeilocityEvent::Deposit(event)) = VeilocityEvent::decode_log(&log) else {
+            panic!("expected a deposit");
+        };
+        assert_eq!(event.commitment, deposit.commitment);
+        assert_eq!(event.leaf_index, U256::from(3u64));
+        assert!(event.is_native());
+        assert_eq!((event.block_number, event.log_index), (42, 5));
+        assert_eq!(
+            VeilocityEvent::Deposit(event).id(),
+            EventId {
+                block_number: 42,
+                log_index: 5
+            }
+        );
+
+        // Logs from other contracts' events are skipped
+        let mut other = log.clone();
+        other.inner.data = alloy::primitives::LogData::new_unchecked(
+            vec![B256::repeat_byte(1)],
+            Default::default(),
+        );
+        assert!(VeilocityEvent::decode_log(&other).is_none());
+    }
+
+    #[test]
+    fn test_decode_token_log() {
+        let token = Address::repeat_byte(0x11);
+        let deposit = IVeilocityVault::TokenDeposit {
+            commitment: B256::repeat_byte(7),
+            token,
+            amount: U256::from(2_500_000u64),
+            leafIndex: U256::from(4u64),
+            timestamp: U256::from(1_700_000_000u64),
+        };
+        let log = Log {
+            inner: alloy::primitives::Log {
+                address: Address::ZERO,
+                data: deposit.encode_log_data(),
+            },
+            ..Default::default()
+        };
+
+        let Some(VeilocityEvent::Deposit(event)) = VeilocityEvent::decode_log(&log) else {
+            panic!("expected a deposit");
+        };
+        assert_eq!(event.token, token);
+        assert!(!event.is_native());
+        assert_eq!(event.leaf_index, U256::from(4u64));
+        assert_eq!(event.amount_units(6), 2.5);
+    }
+}
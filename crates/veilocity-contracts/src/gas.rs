@@ -0,0 +1,184 @@
+//! Gas estimation and fee limits for vault transactions
+
+use crate::error::ContractError;
+
+/// Smallest fee increase nodes accept for a same-nonce replacement
+pub const MIN_REPLACEMENT_BUMP_PERCENT: u64 = 10;
+
+/// How gas limits and EIP-1559 fees are chosen for vault transactions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GasPolicy {
+    /// Multiplier applied to the node's gas estimate
+    pub gas_multiplier: f64,
+    /// Priority fee (wei per gas) to use instead of the node's suggestion
+    pub priority_fee_per_gas: Option<u128>,
+    /// Refuse to send when the max fee (wei per gas) would exceed this
+    pub max_fee_ceiling: Option<u128>,
+    /// Percent by which replacements raise the stuck transaction's fees
+    pub replacement_bump_percent: u64,
+}
+
+impl Default for GasPolicy {
+    fn default() -> Self {
+        Self {
+            gas_multiplier: 1.2,
+            priority_fee_per_gas: None,
+            max_fee_ceiling: None,
+            replacement_bump_percent: 15,
+        }
+    }
+}
+
+impl GasPolicy {
+    pub fn with_gas_multiplier(mut self, multiplier: f64) -> Self {
+        self.gas_multiplier = multiplier;
+        self
+    }
+
+    pub fn with_priority_fee(mut self, priority_fee_per_gas: u128) -> Self {
+        self.priority_fee_per_gas = Some(priority_fee_per_gas);
+        self
+    }
+
+    pub fn with_max_fee_ceiling(mut self, max_fee_per_gas: u128) -> Self {
+        self.max_fee_ceiling = Some(max_fee_per_gas);
+        self
+    }
+
+    pub fn with_replacement_bump(mut self, percent: u64) -> Self {
+        self.replacement_bump_percent = percent;
+        self
+    }
+
+    /// Fees for replacing a transaction sent with `old_max_fee` and
+    /// `old_priority_fee`, as (max fee, priority fee)
+    ///
+    /// Both are raised by at least the replacement bump, or to the node's
+    /// current suggestion if that is higher.
+    pub fn bump(
+        &self,
+        old_max_fee: u128,
+        old_priority_fee: u128,
+        suggested_max_fee: u128,
+        suggested_priority_fee: u128,
+    ) -> Result<(u128, u128), ContractError> {
+        let percent = self
+            .replacement_bump_percent
+            .max(MIN_REPLACEMENT_BUMP_PERCENT) as u128;
+        let raise = |fee: u128| (fee * (100 + percent)).div_ceil(100);
+
+        let priority_fee = raise(old_priority_fee).max(suggested_priority_fee);
+        let max_fee = raise(old_max_fee).max(suggested_max_fee).max(priority_fee);
+
+        if let Some(ceiling) = self.max_fee_ceiling {
+            if max_fee > ceiling {
+                return Err(ContractError::FeeTooHigh { max_fee, ceiling });
+            }
+        }
+
+        Ok((max_fee, priority_fee))
+    }
+
+    /// Build a plan from the node's gas estimate and fee suggestion
+    pub fn plan(
+        &self,
+        gas_estimate: u64,
+        suggested_max_fee: u128,
+        suggested_priority_fee: u128,
+    ) -> Result<TxPlan, ContractError> {
+        let max_priority_fee_per_gas = self.priority_fee_per_gas.unwrap_or(suggested_priority_fee);
+        let max_fee_per_gas = suggested_max_fee.max(max_priority_fee_per_gas);
+
+        if let Some(ceiling) = self.max_fee_ceiling {
+            if max_fee_per_gas > ceiling {
+                return Err(ContractError::FeeTooHigh {
+                    max_fee: max_fee_per_gas,
+                    ceiling,
+                });
+            }
+        }
+
+        let gas_limit = (gas_estimate as f64 * self.gas_multiplier.max(1.0)).ceil() as u64;
+
+        Ok(TxPlan {
+            gas_estimate,
+            gas_limit,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        })
+    }
+}
+
+/// A simulated transaction with the gas and fees it will be sent with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxPlan {
+    /// Gas used according to the node
+    pub gas_estimate: u64,
+    /// Gas limit to send with (estimate times the multiplier)
+    pub gas_limit: u64,
+    /// EIP-1559 max fee per gas (wei)
+    pub max_fee_per_gas: u128,
+    /// EIP-1559 priority fee per gas (wei)
+    pub max_priority_fee_per_gas: u128,
+}
+
+impl TxPlan {
+    /// Most the transaction can cost in fees (wei), excluding any value sent
+    pub fn max_fee(&self) -> u128 {
+        self.gas_limit as u128 * self.max_fee_per_gas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_applies_policy() {
+        let plan = GasPolicy::default().plan(100_000, 30, 2).unwrap();
+        assert_eq!(plan.gas_limit, 120_000);
+        assert_eq!(
+            (plan.max_fee_per_gas, plan.max_priority_fee_per_gas),
+            (30, 2)
+        );
+        assert_eq!(plan.max_fee(), 3_600_000);
+
+        // A priority fee above the suggested max fee raises the max fee
+        let plan = GasPolicy::default()
+            .with_priority_fee(50)
+            .plan(100_000, 30, 2)
+            .unwrap();
+        assert_eq!(
+            (plan.max_fee_per_gas, plan.max_priority_fee_per_gas),
+            (50, 50)
+        );
+
+        assert!(matches!(
+            GasPolicy::default()
+                .with_max_fee_ceiling(20)
+                .plan(100_000, 30, 2),
+            Err(ContractError::FeeTooHigh {
+                max_fee: 30,
+                ceiling: 20
+            })
+        ));
+    }
+
+    #[test]
+    fn test_bump_replacement_fees() {
+        let policy = GasPolicy::default();
+        assert_eq!(policy.bump(100, 10, 50, 5).unwrap(), (115, 12));
+
+        // The network moved past the bump: follow it
+        assert_eq!(policy.bump(100, 10, 200, 20).unwrap(), (200, 20));
+
+        // Below the minimum nodes accept is raised to it
+        let policy = GasPolicy::default().with_replacement_bump(1);
+        assert_eq!(policy.bump(100, 10, 0, 0).unwrap(), (110, 11));
+
+        assert!(GasPolicy::default()
+            .with_max_fee_ceiling(110)
+            .bump(100, 10, 0, 0)
+            .is_err());
+    }
+}
@@ -6,6 +6,11 @@
 //! - ABI bindings for VeilocityVault and Verifier contracts
 //! - High-level client for contract interactions
 //! - Event parsing and indexing utilities
+//! - Ordered event streaming with range splitting and live subscriptions
+//! - RPC failover, retry and quorum reads
+//! - Local, hardware and remote transaction signing
+//! - ERC-20 token pools with approve or EIP-2612 permit deposits
+//! - Deployment from Foundry artifacts and owner-only administration
 //!
 //! # Usage
 //!
@@ -22,14 +27,36 @@
 //! let tx_hash = client.withdraw(nullifier, recipient, amount, root, proof).await?;
 //! ```
 
+pub mod admin;
 pub mod anchor;
 pub mod bindings;
 pub mod error;
 pub mod events;
+pub mod gas;
+pub mod rpc;
+pub mod signer;
+pub mod stream;
+pub mod token;
 pub mod vault;
 
+pub use admin::{
+    create_admin_client, deploy_vault, deploy_vault_with, AdminClient, Artifact, Deployment,
+    VerifierSource,
+};
 pub use anchor::{StateRootEntry, StateRootHistory};
-pub use bindings::{IVeilocityVault, IVerifier};
+pub use bindings::{IERC20Token, IVeilocityVault, IVerifier};
 pub use error::ContractError;
-pub use events::{DepositEvent, EventFilter, StateRootUpdatedEvent, VeilocityEvent, WithdrawalEvent};
-pub use vault::{create_vault_client, create_vault_reader, VaultClient, VaultReader};
+pub use events::{
+    DepositEvent, EventFilter, EventId, LogMeta, StateRootUpdatedEvent, VeilocityEvent,
+    WithdrawalEvent,
+};
+pub use gas::{GasPolicy, TxPlan};
+pub use rpc::{Quorum, RpcConfig};
+pub use signer::{RemoteSigner, SigningWallet};
+pub use stream::{create_event_stream, EventBatch, EventStream, LiveEvents};
+pub use token::{PermitSignature, TokenInfo};
+pub use vault::{
+    create_vault_client, create_vault_reader, state_transition_public_inputs,
+    verifier_embeds_vk_hash, TxOutcome, VaultClient, VaultReader, DEPOSIT_BATCH_SIZE,
+    TRANSFER_BATCH_SIZE,
+};
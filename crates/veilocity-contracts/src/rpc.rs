@@ -0,0 +1,266 @@
+//! RPC endpoints with retry, failover and quorum reads
+//!
+//! Public RPCs rate-limit and occasionally go down, so vault clients talk to
+//! a list of endpoints through alloy's transport layers: rate-limited
+//! requests are retried with backoff, and requests go to whichever endpoint
+//! is currently healthiest. Reads a lying endpoint could abuse (the state
+//! root, nullifier status) can additionally require several endpoints to
+//! agree.
+
+use crate::error::ContractError;
+use alloy::providers::{DynProvider, Provider, ProviderBuilder};
+use alloy::rpc::client::RpcClient;
+use alloy::transports::http::reqwest::Url;
+use alloy::transports::http::{Client, Http};
+use alloy::transports::layers::{FallbackLayer, RetryBackoffLayer};
+use std::future::Future;
+use std::num::NonZeroUsize;
+use tokio::task::JoinSet;
+use tower::ServiceBuilder;
+use tracing::warn;
+
+/// RPC endpoints and how to use them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcConfig {
+    /// Endpoint URLs, preferred first
+    pub urls: Vec<String>,
+    /// Retries for a rate-limited request
+    pub max_retries: u32,
+    /// Backoff before the first retry (ms)
+    pub initial_backoff_ms: u64,
+    /// Compute units per second the endpoints allow, used to pace retries
+    pub compute_units_per_second: u64,
+    /// Endpoints that must agree on quorum reads (none: single endpoint)
+    pub quorum: Option<usize>,
+}
+
+impl RpcConfig {
+    /// Use these endpoints with the default retry policy and no quorum
+    pub fn new<S: Into<String>>(urls: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            urls: urls.into_iter().map(Into::into).collect(),
+            max_retries: 5,
+            initial_backoff_ms: 500,
+            compute_units_per_second: 330,
+            quorum: None,
+        }
+    }
+
+    pub fn with_retries(mut self, max_retries: u32, initial_backoff_ms: u64) -> Self {
+        self.max_retries = max_retries;
+        self.initial_backoff_ms = initial_backoff_ms;
+        self
+    }
+
+    pub fn with_quorum(mut self, quorum: usize) -> Self {
+        self.quorum = Some(quorum);
+        self
+    }
+
+    /// Client over all endpoints with retry and failover
+    pub fn client(&self) -> Result<RpcClient, ContractError> {
+        let transports = self
+            .parse_urls()?
+            .into_iter()
+            .map(Http::<Client>::new)
+            .collect::<Vec<_>>();
+
+        // One endpoint serves each request; failures lower its ranking so the
+        // retry goes elsewhere
+        let fallback = FallbackLayer::default().with_active_transport_count(NonZeroUsize::MIN);
+        let transport = ServiceBuilder::new().layer(fallback).service(transports);
+
+        Ok(RpcClient::builder()
+            .layer(self.retry_layer())
+            .transport(transport, false))
+    }
+
+    /// One provider per endpoint for quorum reads, if a quorum is configured
+    pub fn quorum(&self) -> Result<Option<Quorum>, ContractError> {
+        let Some(threshold) = self.quorum else {
+            return Ok(None);
+        };
+
+        let urls = self.parse_urls()?;
+        if threshold == 0 || threshold > urls.len() {
+            return Err(ContractError::Config(format!(
+                "RPC quorum of {} needs between 1 and {} endpoints",
+                threshold,
+                urls.len()
+            )));
+        }
+
+        let providers = urls
+            .into_iter()
+            .map(|url| {
+                let client = RpcClient::builder().layer(self.retry_layer()).http(url);
+                ProviderBuilder::new().connect_client(client).erased()
+            })
+            .collect();
+
+        Ok(Some(Quorum::new(providers, threshold)))
+    }
+
+    fn retry_layer(&self) -> RetryBackoffLayer {
+        RetryBackoffLayer::new(
+            self.max_retries,
+            self.initial_backoff_ms,
+            self.compute_units_per_second,
+        )
+    }
+
+    fn parse_urls(&self) -> Result<Vec<Url>, ContractError> {
+        if self.urls.is_empty() {
+            return Err(ContractError::Config("no RPC URL configured".to_string()));
+        }
+
+        self.urls
+            .iter()
+            .map(|url| {
+                url.parse()
+                    .map_err(|e| ContractError::Rpc(format!("{}: {}", url, e)))
+            })
+            .collect()
+    }
+}
+
+impl From<&str> for RpcConfig {
+    fn from(url: &str) -> Self {
+        Self::new([url])
+    }
+}
+
+/// Reads answered only when enough independent endpoints agree
+#[derive(Clone)]
+pub struct Quorum {
+    providers: Vec<DynProvider>,
+    threshold: usize,
+}
+
+impl Quorum {
+    /// Require `threshold` of `providers` to return the same value
+    pub fn new(providers: Vec<DynProvider>, threshold: usize) -> Self {
+        let threshold = threshold.clamp(1, providers.len().max(1));
+        Self {
+            providers,
+            threshold,
+        }
+    }
+
+    /// Run `read` against every endpoint and return the value enough agree on
+    ///
+    /// Endpoints that fail count as disagreeing. Outstanding requests are
+    /// dropped once the quorum is reached.
+    pub async fn read<T, F, Fut>(&self, read: F) -> Result<T, ContractError>
+    where
+        T: PartialEq + Send + 'static,
+        F: Fn(DynProvider) -> Fut,
+        Fut: Future<Output = Result<T, ContractError>> + Send + 'static,
+    {
+        let mut pending = JoinSet::new();
+        for provider in &self.providers {
+            pending.spawn(read(provider.clone()));
+        }
+
+        let mut tally = Tally::new(self.threshold);
+        while let Some(joined) = pending.join_next().await {
+            match joined {
+                Ok(Ok(value)) => {
+                    if let Some(agreed) = tally.add(value) {
+                        pending.abort_all();
+                        return Ok(agreed);
+                    }
+                }
+                Ok(Err(e)) => warn!("Quorum read failed on one endpoint: {}", e),
+                Err(e) => warn!("Quorum read task failed: {}", e),
+            }
+        }
+
+        Err(ContractError::QuorumNotReached {
+            needed: self.threshold,
+            agreeing: tally.best(),
+            queried: self.providers.len(),
+        })
+    }
+}
+
+/// Counts matching answers until one reaches the threshold
+struct Tally<T> {
+    threshold: usize,
+    answers: Vec<(T, usize)>,
+}
+
+impl<T: PartialEq> Tally<T> {
+    fn new(threshold: usize) -> Self {
+        Self {
+            threshold,
+            answers: Vec::new(),
+        }
+    }
+
+    /// Record an answer, returning it once enough endpoints agree on it
+    fn add(&mut self, value: T) -> Option<T> {
+        let index = match self.answers.iter().position(|(answer, _)| *answer == value) {
+            Some(index) => {
+                self.answers[index].1 += 1;
+                index
+            }
+            None => {
+                self.answers.push((value, 1));
+                self.answers.len() - 1
+            }
+        };
+
+        if self.answers[index].1 < self.threshold {
+            return None;
+        }
+        Some(self.answers.swap_remove(index).0)
+    }
+
+    /// Most endpoints that agreed on any one answer
+    fn best(&self) -> usize {
+        self.answers
+            .iter()
+            .map(|(_, count)| *count)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quorum_tally() {
+        let mut tally = Tally::new(2);
+        assert_eq!(tally.add("a"), None);
+        assert_eq!(tally.add("b"), None);
+        assert_eq!(tally.best(), 1);
+        assert_eq!(tally.add("b"), Some("b"));
+
+        // A single endpoint satisfies a quorum of one
+        assert_eq!(Tally::new(1).add(7), Some(7));
+    }
+
+    #[test]
+    fn test_quorum_config_checked() {
+        let config = RpcConfig::new(["http://localhost:8545", "http://localhost:8546"]);
+        assert!(config.quorum().unwrap().is_none());
+        assert!(config.clone().with_quorum(3).quorum().is_err());
+        assert!(RpcConfig::new(Vec::<String>::new()).client().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_client_over_several_endpoints() {
+        let config = RpcConfig::new([
+            "http://localhost:8545",
+            "http://localhost:8546",
+            "http://localhost:8547",
+        ]);
+        assert!(config.client().is_ok());
+        assert!(RpcConfig::new(["http://localhost:8545", "not a url"])
+            .client()
+            .is_err());
+    }
+}
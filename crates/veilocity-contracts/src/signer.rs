@@ -0,0 +1,315 @@
+//! Signing vault transactions with keys held outside the CLI
+//!
+//! The vault client accepts any alloy `NetworkWallet`. [`SigningWallet`]
+//! covers the ones the CLI offers: an in-process signer (a decrypted key or a
+//! hardware device, wrapped in `EthereumWallet`) or a [`RemoteSigner`] that
+//! asks a JSON-RPC service to sign with `eth_signTransaction`.
+
+use crate::error::ContractError;
+use alloy::consensus::{TxEnvelope, TypedTransaction};
+use alloy::eips::eip2718::Decodable2718;
+use alloy::network::{Ethereum, EthereumWallet, NetworkWallet};
+use alloy::primitives::{Address, Bytes};
+use alloy::rpc::client::RpcClient;
+use alloy::rpc::types::TransactionRequest;
+use serde::Deserialize;
+use std::fmt;
+
+/// Signs transactions through a remote `eth_signTransaction` endpoint
+///
+/// Works with Clef, Web3Signer and similar services, or a node with an
+/// unlocked account. The returned transaction must be signed by `address`.
+#[derive(Clone)]
+pub struct RemoteSigner {
+    client: RpcClient,
+    url: String,
+    address: Address,
+}
+
+impl RemoteSigner {
+    /// Sign as `address` through the service at `url`
+    pub fn new(url: &str, address: Address) -> Result<Self, ContractError> {
+        let parsed = url
+            .parse()
+            .map_err(|e| ContractError::Config(format!("invalid signer URL {}: {}", url, e)))?;
+
+        Ok(Self {
+            client: RpcClient::new_http(parsed),
+            url: url.to_string(),
+            address,
+        })
+    }
+
+    /// Account the service signs for
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Have the service sign `tx` and check who signed it
+    pub async fn sign(&self, tx: TypedTransaction) -> Result<TxEnvelope, ContractError> {
+        let request = TransactionRequest::from_transaction_with_sender(tx, self.address);
+
+        let response: SignTransactionResponse = self
+            .client
+            .request("eth_signTransaction", (request,))
+            .await
+            .map_err(|e| ContractError::Rpc(format!("remote signer: {}", e)))?;
+
+        let raw = response.into_raw();
+        let envelope = TxEnvelope::decode_2718(&mut raw.as_ref()).map_err(|e| {
+            ContractError::TransactionFailed(format!(
+                "remote signer returned an invalid transaction: {}",
+                e
+            ))
+        })?;
+
+        let signer = envelope.recover_signer().map_err(|e| {
+            ContractError::TransactionFailed(format!(
+                "remote signer returned a bad signature: {}",
+                e
+            ))
+        })?;
+        if signer != self.address {
+            return Err(ContractError::TransactionFailed(format!(
+                "remote signer signed as {} instead of {}",
+                signer, self.address
+            )));
+        }
+
+        Ok(envelope)
+    }
+}
+
+impl fmt::Debug for RemoteSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteSigner")
+            .field("url", &self.url)
+            .field("address", &self.address)
+            .finish()
+    }
+}
+
+impl NetworkWallet<Ethereum> for RemoteSigner {
+    fn default_signer_address(&self) -> Address {
+        self.address
+    }
+
+    fn has_signer_for(&self, address: &Address) -> bool {
+        *address == self.address
+    }
+
+    fn signer_addresses(&self) -> impl Iterator<Item = Address> {
+        std::iter::once(self.address)
+    }
+
+    async fn sign_transaction_from(
+        &self,
+        sender: Address,
+        tx: TypedTransaction,
+    ) -> alloy::signers::Result<TxEnvelope> {
+        if sender != self.address {
+            return Err(alloy::signers::Error::other(format!(
+                "remote signer cannot sign for {}",
+                sender
+            )));
+        }
+        self.sign(tx).await.map_err(alloy::signers::Error::other)
+    }
+}
+
+/// `eth_signTransaction` result: raw bytes (most nodes) or `{raw, tx}` (Clef, geth)
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SignTransactionResponse {
+    Raw(Bytes),
+    Object { raw: Bytes },
+}
+
+impl SignTransactionResponse {
+    fn into_raw(self) -> Bytes {
+        match self {
+            SignTransactionResponse::Raw(raw) | SignTransactionResponse::Object { raw } => raw,
+        }
+    }
+}
+
+/// Wallet that signs vault transactions, wherever the key lives
+#[derive(Debug, Clone)]
+pub enum SigningWallet {
+    /// In-process signer: a decrypted key or a hardware device
+    Local(EthereumWallet),
+    /// External `eth_signTransaction` service
+    Remote(RemoteSigner),
+}
+
+impl SigningWallet {
+    /// Account transactions are sent from
+    pub fn address(&self) -> Address {
+        NetworkWallet::<Ethereum>::default_signer_address(self)
+    }
+}
+
+impl From<EthereumWallet> for SigningWallet {
+    fn from(wallet: EthereumWallet) -> Self {
+        SigningWallet::Local(wallet)
+    }
+}
+
+impl From<RemoteSigner> for SigningWallet {
+    fn from(signer: RemoteSigner) -> Self {
+        SigningWallet::Remote(signer)
+    }
+}
+
+impl NetworkWallet<Ethereum> for SigningWallet {
+    fn default_signer_address(&self) -> Address {
+        match self {
+            SigningWallet::Local(wallet) => {
+                NetworkWallet::<Ethereum>::default_signer_address(wallet)
+            }
+            SigningWallet::Remote(signer) => signer.address(),
+        }
+    }
+
+    fn has_signer_for(&self, address: &Address) -> bool {
+        match self {
+            SigningWallet::Local(wallet) => {
+                NetworkWallet::<Ethereum>::has_signer_for(wallet, address)
+            }
+            SigningWallet::Remote(signer) => *address == signer.address(),
+        }
+    }
+
+    fn signer_addresses(&self) -> impl Iterator<Item = Address> {
+        let addresses: Vec<Address> = match self {
+            SigningWallet::Local(wallet) => {
+                NetworkWallet::<Ethereum>::signer_addresses(wallet).collect()
+            }
+            SigningWallet::Remote(signer) => vec![signer.address()],
+        };
+        addresses.into_iter()
+    }
+
+    async fn sign_transaction_from(
+        &self,
+        sender: Address,
+        tx: TypedTransaction,
+    ) -> alloy::signers::Result<TxEnvelope> {
+        match self {
+            SigningWallet::Local(wallet) => {
+                NetworkWallet::<Ethereum>::sign_transaction_from(wallet, sender, tx).await
+            }
+            SigningWallet::Remote(signer) => {
+                NetworkWallet::<Ethereum>::sign_transaction_from(signer, sender, tx).await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::consensus::TxEip1559;
+    use alloy::eips::eip2718::Encodable2718;
+    use alloy::primitives::{TxKind, U256};
+    use alloy::signers::local::PrivateKeySigner;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve one `eth_signTransaction` request, signing with `key`
+    async fn stand_in_signer(key: PrivateKeySigner) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+
+            // Read headers, then the body they announce
+            let mut buf = Vec::new();
+            let body_start = loop {
+                let mut chunk = [0u8; 4096];
+                let n = socket.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+                if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break pos + 4;
+                }
+            };
+            let headers = String::from_utf8_lossy(&buf[..body_start]).to_lowercase();
+            let length: usize = headers
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .map(|value| value.trim().parse().unwrap())
+                .unwrap();
+            while buf.len() < body_start + length {
+                let mut chunk = [0u8; 4096];
+                let n = socket.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+            }
+
+            let call: serde_json::Value = serde_json::from_slice(&buf[body_start..]).unwrap();
+            assert_eq!(call["method"], "eth_signTransaction");
+            let request: TransactionRequest =
+                serde_json::from_value(call["params"][0].clone()).unwrap();
+
+            let from = key.address();
+            let wallet = EthereumWallet::from(key);
+            let unsigned = request.build_typed_tx().unwrap();
+            let signed = NetworkWallet::<Ethereum>::sign_transaction_from(&wallet, from, unsigned)
+                .await
+                .unwrap();
+
+            let body = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": call["id"],
+                "result": { "raw": Bytes::from(signed.encoded_2718()) },
+            })
+            .to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        url
+    }
+
+    fn unsigned_tx() -> TypedTransaction {
+        TypedTransaction::Eip1559(TxEip1559 {
+            chain_id: 5003,
+            nonce: 7,
+            gas_limit: 21_000,
+            max_fee_per_gas: 30_000_000_000,
+            max_priority_fee_per_gas: 1_000_000_000,
+            to: TxKind::Call(Address::repeat_byte(0x11)),
+            value: U256::from(1_000u64),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer() {
+        let key = PrivateKeySigner::random();
+        let address = key.address();
+        let url = stand_in_signer(key).await;
+
+        let wallet = SigningWallet::from(RemoteSigner::new(&url, address).unwrap());
+        assert_eq!(wallet.address(), address);
+
+        let signed =
+            NetworkWallet::<Ethereum>::sign_transaction_from(&wallet, address, unsigned_tx())
+                .await
+                .unwrap();
+        assert_eq!(signed.recover_signer().unwrap(), address);
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_wrong_key() {
+        let address = PrivateKeySigner::random().address();
+        let url = stand_in_signer(PrivateKeySigner::random()).await;
+
+        let signer = RemoteSigner::new(&url, address).unwrap();
+        assert!(signer.sign(unsigned_tx()).await.is_err());
+    }
+}
@@ -0,0 +1,256 @@
+//! Ordered streaming of vault events
+//!
+//! [`EventStream`] walks the chain from a starting block in bounded ranges,
+//! fetching every vault event with a single log filter per range. RPC nodes
+//! cap how many logs or blocks one `eth_getLogs` call may cover, so a range
+//! the node rejects is split in half until it fits. Once caught up, the
+//! stream can tail new events over an `eth_subscribe` WebSocket subscription.
+
+use crate::error::ContractError;
+use crate::events::VeilocityEvent;
+use alloy::primitives::Address;
+use alloy::providers::{DynProvider, Provider, ProviderBuilder, WsConnect};
+use alloy::pubsub::Subscription;
+use alloy::rpc::types::{Filter, Log};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, warn};
+
+/// Largest block range fetched at once by default (Mantle RPC limits to 10k)
+pub const DEFAULT_MAX_RANGE: u64 = 10_000;
+
+/// Events from one fetched block range, in chain order
+#[derive(Debug, Clone)]
+pub struct EventBatch {
+    /// First block of the range
+    pub from_block: u64,
+    /// Last block of the range (inclusive)
+    pub to_block: u64,
+    /// Events ordered by block number, then log index
+    pub events: Vec<VeilocityEvent>,
+}
+
+/// Cursor over vault events, starting at a given block
+pub struct EventStream<P> {
+    provider: P,
+    address: Address,
+    next_block: u64,
+    max_range: u64,
+}
+
+impl<P: Provider + Clone> EventStream<P> {
+    /// Stream events of the vault at `address` starting at `from_block`
+    pub fn new(provider: P, address: Address, from_block: u64) -> Self {
+        Self {
+            provider,
+            address,
+            next_block: from_block,
+            max_range: DEFAULT_MAX_RANGE,
+        }
+    }
+
+    /// Fetch at most `blocks` blocks per request
+    pub fn with_max_range(mut self, blocks: u64) -> Self {
+        self.max_range = blocks.max(1);
+        self
+    }
+
+    /// First block not yet returned by `next_batch`
+    pub fn next_block(&self) -> u64 {
+        self.next_block
+    }
+
+    /// Fetch the next range of blocks, up to `head`
+    ///
+    /// Returns `None` once the stream has passed `head`.
+    pub async fn next_batch(&mut self, head: u64) -> Result<Option<EventBatch>, ContractError> {
+        if self.next_block > head {
+            return Ok(None);
+        }
+
+        let from_block = self.next_block;
+        let to_block = head.min(from_block.saturating_add(self.max_range - 1));
+        let events = self.fetch_range(from_block, to_block).await?;
+        self.next_block = to_block + 1;
+
+        Ok(Some(EventBatch {
+            from_block,
+            to_block,
+            events,
+        }))
+    }
+
+    /// Fetch every vault event in `from..=to`, ordered by block and log index
+    ///
+    /// Ranges the node refuses as too large are split in half and retried.
+    pub async fn fetch_range(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<VeilocityEvent>, ContractError> {
+        let mut ranges = vec![(from, to)];
+        let mut events = Vec::new();
+
+        while let Some((start, end)) = ranges.pop() {
+            let filter = self.filter().from_block(start).to_block(end);
+            match self.provider.get_logs(&filter).await {
+                Ok(logs) => events.extend(logs.iter().filter_map(VeilocityEvent::decode_log)),
+                Err(e) if start < end && is_range_limit_error(&e.to_string()) => {
+                    let mid = start + (end - start) / 2;
+                    debug!("Splitting blocks {}..={} at {}: {}", start, end, mid, e);
+                    // Pushed in reverse so the lower half is fetched first
+                    ranges.push((mid + 1, end));
+                    ranges.push((start, mid));
+                }
+                Err(e) => return Err(ContractError::Rpc(e.to_string())),
+            }
+        }
+
+        events.sort_by_key(VeilocityEvent::id);
+        Ok(events)
+    }
+
+    /// Subscribe to new vault events as they are mined
+    ///
+    /// Needs a provider with a pubsub transport (WebSocket or IPC). Events
+    /// before the subscription starts are not delivered; catch up with
+    /// `next_batch` first.
+    pub async fn subscribe(&self) -> Result<LiveEvents, ContractError> {
+        let subscription = self
+            .provider
+            .subscribe_logs(&self.filter())
+            .await
+            .map_err(|e| ContractError::Rpc(e.to_string()))?;
+
+        Ok(LiveEvents { subscription })
+    }
+
+    /// Log filter matching every vault event
+    fn filter(&self) -> Filter {
+        Filter::new()
+            .address(self.address)
+            .event_signature(VeilocityEvent::SIGNATURES.to_vec())
+    }
+}
+
+/// Live tail of vault events from an `eth_subscribe` subscription
+pub struct LiveEvents {
+    subscription: Subscription<Log>,
+}
+
+impl LiveEvents {
+    /// Wait for the next event
+    ///
+    /// Returns `None` when the subscription closes. Falling behind the
+    /// subscription buffer is an error, since events were dropped; resume
+    /// with `EventStream::next_batch` from the last block seen.
+    pub async fn next(&mut self) -> Result<Option<VeilocityEvent>, ContractError> {
+        loop {
+            let log = match self.subscription.recv().await {
+                Ok(log) => log,
+                Err(RecvError::Closed) => return Ok(None),
+                Err(RecvError::Lagged(missed)) => {
+                    return Err(ContractError::Rpc(format!(
+                        "event subscription fell behind, {} logs dropped",
+                        missed
+                    )))
+                }
+            };
+
+            if log.removed {
+                warn!(
+                    "Ignoring log removed by a reorg in block {:?}",
+                    log.block_number
+                );
+                continue;
+            }
+
+            if let Some(event) = VeilocityEvent::decode_log(&log) {
+                return Ok(Some(event));
+            }
+        }
+    }
+}
+
+/// Create an event stream, over WebSocket for `ws://` and `wss://` URLs
+/// (which allows `subscribe`) and over HTTP otherwise
+pub async fn create_event_stream(
+    rpc_url: &str,
+    contract_address: Address,
+    from_block: u64,
+) -> Result<EventStream<DynProvider>, ContractError> {
+    let provider = if rpc_url.starts_with("ws://") || rpc_url.starts_with("wss://") {
+        ProviderBuilder::new()
+            .connect_ws(WsConnect::new(rpc_url))
+            .await
+            .map_err(|e| ContractError::Rpc(e.to_string()))?
+            .erased()
+    } else {
+        ProviderBuilder::new()
+            .connect_http(
+                rpc_url
+                    .parse()
+                    .map_err(|e| ContractError::Rpc(format!("{}", e)))?,
+            )
+            .erased()
+    };
+
+    Ok(EventStream::new(provider, contract_address, from_block))
+}
+
+/// Whether an `eth_getLogs` error means the range should be made smaller
+///
+/// Providers word this differently ("query returned more than 10000 results",
+/// "block range is too wide", "exceed maximum block range", ...). Rate limits
+/// share the -32005 code and "limit exceeded" wording with these, so only
+/// log-count and block-range messages match; rate limits are left to the
+/// retry layer.
+fn is_range_limit_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    if ["rate limit", "request rate", "429", "too many requests"]
+        .iter()
+        .any(|needle| message.contains(needle))
+    {
+        return false;
+    }
+
+    [
+        "block range",
+        "range too large",
+        "too many blocks",
+        "returned more than",
+        "too many logs",
+        "too many results",
+        "response size exceeded",
+    ]
+    .iter()
+    .any(|needle| message.contains(needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_limit_errors() {
+        assert!(is_range_limit_error(
+            "server returned an error response: error code -32005: query returned more than 10000 results"
+        ));
+        assert!(is_range_limit_error("block range is too wide"));
+        assert!(is_range_limit_error("exceed maximum block range: 10000"));
+        assert!(is_range_limit_error("Log response size exceeded."));
+        assert!(!is_range_limit_error("connection refused"));
+        assert!(!is_range_limit_error("invalid params"));
+
+        // Rate limits are retried, not split
+        assert!(!is_range_limit_error(
+            "server returned an error response: error code -32005: limit exceeded"
+        ));
+        assert!(!is_range_limit_error(
+            "server returned an error response: error code -32005: daily request count exceeded, request rate limited"
+        ));
+        assert!(!is_range_limit_error(
+            "HTTP error 429 with body: Too Many Requests"
+        ));
+        assert!(!is_range_limit_error("project ID request rate exceeded"));
+    }
+}
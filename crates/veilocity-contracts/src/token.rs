@@ -0,0 +1,272 @@
+//! ERC-20 tokens held in the vault
+//!
+//! Token metadata, amount formatting and EIP-2612 permits. Tokens without
+//! permit support are deposited with a separate `approve` first.
+
+use crate::bindings::{IERC20Token, Permit};
+use crate::error::ContractError;
+use alloy::primitives::{Address, B256, U256};
+use alloy::providers::Provider;
+use alloy::signers::Signer;
+use alloy::sol_types::{Eip712Domain, SolStruct};
+
+/// Decimals of native MNT
+pub const NATIVE_DECIMALS: u8 = 18;
+
+/// Symbol, decimals and address of an asset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenInfo {
+    /// Token address (the zero address for native MNT)
+    pub address: Address,
+    /// Ticker symbol
+    pub symbol: String,
+    /// Number of decimals in one whole token
+    pub decimals: u8,
+}
+
+impl TokenInfo {
+    /// Native MNT
+    pub fn native() -> Self {
+        Self {
+            address: Address::ZERO,
+            symbol: "MNT".to_string(),
+            decimals: NATIVE_DECIMALS,
+        }
+    }
+
+    /// Whether this is native MNT rather than a token
+    pub fn is_native(&self) -> bool {
+        self.address == Address::ZERO
+    }
+
+    /// Format base units as whole tokens with the symbol, e.g. `12.5 USDC`
+    pub fn format(&self, amount: U256) -> String {
+        format!("{} {}", format_units(amount, self.decimals), self.symbol)
+    }
+
+    /// Parse an amount in whole tokens, e.g. `12.5`, into base units
+    pub fn parse(&self, amount: &str) -> Result<U256, ContractError> {
+        parse_units(amount, self.decimals)
+    }
+}
+
+/// Look up a token's symbol and decimals
+pub async fn token_info<P: Provider>(
+    provider: &P,
+    token: Address,
+) -> Result<TokenInfo, ContractError> {
+    let erc20 = IERC20Token::new(token, provider);
+    let symbol = erc20
+        .symbol()
+        .call()
+        .await
+        .map_err(ContractError::from_call)?;
+    let decimals = erc20
+        .decimals()
+        .call()
+        .await
+        .map_err(ContractError::from_call)?;
+
+    Ok(TokenInfo {
+        address: token,
+        symbol,
+        decimals,
+    })
+}
+
+/// Format base units as a decimal string without trailing zeros
+pub fn format_units(amount: U256, decimals: u8) -> String {
+    let unit = U256::from(10u64).pow(U256::from(decimals));
+    let whole = amount / unit;
+    let fraction = amount % unit;
+    if fraction.is_zero() {
+        return whole.to_string();
+    }
+
+    let fraction = format!(
+        "{:0>width$}",
+        fraction.to_string(),
+        width = decimals as usize
+    );
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+/// Parse a decimal string into base units
+///
+/// Fails on more fractional digits than the token has, rather than rounding.
+pub fn parse_units(amount: &str, decimals: u8) -> Result<U256, ContractError> {
+    let invalid = || ContractError::Config(format!("invalid amount: {}", amount));
+
+    let (whole, fraction) = amount.trim().split_once('.').unwrap_or((amount.trim(), ""));
+    if (whole.is_empty() && fraction.is_empty()) || fraction.len() > decimals as usize {
+        return Err(invalid());
+    }
+    if !whole
+        .chars()
+        .chain(fraction.chars())
+        .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let digits = format!("{}{:0<width$}", whole, fraction, width = decimals as usize);
+    U256::from_str_radix(&digits, 10).map_err(|_| invalid())
+}
+
+/// An EIP-2612 permit signature, ready for `depositTokenWithPermit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PermitSignature {
+    /// Timestamp after which the permit is no longer valid
+    pub deadline: U256,
+    /// Recovery id (27 or 28)
+    pub v: u8,
+    /// Signature r
+    pub r: B256,
+    /// Signature s
+    pub s: B256,
+}
+
+/// EIP-712 domain of a token's permits, or `None` if it has no permit support
+///
+/// The domain is rebuilt from the token's name, version and the chain id, and
+/// only trusted when it hashes to the token's own `DOMAIN_SEPARATOR`.
+pub async fn permit_domain<P: Provider>(
+    provider: &P,
+    token: Address,
+) -> Result<Option<Eip712Domain>, ContractError> {
+    let erc20 = IERC20Token::new(token, provider);
+    let Ok(separator) = erc20.DOMAIN_SEPARATOR().call().await else {
+        return Ok(None);
+    };
+
+    let name = erc20
+        .name()
+        .call()
+        .await
+        .map_err(ContractError::from_call)?;
+    // Most permit tokens without a version() getter use "1"
+    let version = erc20
+        .version()
+        .call()
+        .await
+        .unwrap_or_else(|_| "1".to_string());
+    let chain_id = provider
+        .get_chain_id()
+        .await
+        .map_err(|e| ContractError::Rpc(e.to_string()))?;
+
+    let domain = Eip712Domain::new(
+        Some(name.into()),
+        Some(version.into()),
+        Some(U256::from(chain_id)),
+        Some(token),
+        None,
+    );
+
+    Ok((domain.separator() == separator).then_some(domain))
+}
+
+/// Sign a permit letting `spender` take `value` of the signer's tokens
+pub async fn sign_permit<S: Signer + Sync>(
+    signer: &S,
+    domain: &Eip712Domain,
+    spender: Address,
+    value: U256,
+    nonce: U256,
+    deadline: U256,
+) -> Result<PermitSignature, ContractError> {
+    let permit = Permit {
+        owner: signer.address(),
+        spender,
+        value,
+        nonce,
+        deadline,
+    };
+
+    let signature = signer
+        .sign_hash(&permit.eip712_signing_hash(domain))
+        .await
+        .map_err(|e| ContractError::TransactionFailed(format!("failed to sign permit: {}", e)))?;
+
+    Ok(PermitSignature {
+        deadline,
+        v: 27 + signature.v() as u8,
+        r: signature.r().into(),
+        s: signature.s().into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::signers::local::PrivateKeySigner;
+
+    #[test]
+    fn test_units_roundtrip() {
+        assert_eq!(parse_units("12.5", 6).unwrap(), U256::from(12_500_000u64));
+        assert_eq!(parse_units("0.000001", 6).unwrap(), U256::from(1u64));
+        assert_eq!(
+            parse_units("3", 18).unwrap(),
+            U256::from(3_000_000_000_000_000_000u128)
+        );
+        assert!(parse_units("0.0000001", 6).is_err());
+        assert!(parse_units("1e6", 6).is_err());
+        assert!(parse_units(".", 6).is_err());
+
+        assert_eq!(format_units(U256::from(12_500_000u64), 6), "12.5");
+        assert_eq!(format_units(U256::from(1u64), 6), "0.000001");
+        assert_eq!(format_units(U256::from(7_000_000u64), 6), "7");
+
+        let usdc = TokenInfo {
+            address: Address::repeat_byte(1),
+            symbol: "USDC".to_string(),
+            decimals: 6,
+        };
+        assert_eq!(usdc.format(usdc.parse("1.25").unwrap()), "1.25 USDC");
+        assert!(!usdc.is_native());
+        assert!(TokenInfo::native().is_native());
+    }
+
+    #[tokio::test]
+    async fn test_permit_signature_recovers_owner() {
+        let signer = PrivateKeySigner::random();
+        let token = Address::repeat_byte(2);
+        let domain = Eip712Domain::new(
+            Some("USD Coin".into()),
+            Some("1".into()),
+            Some(U256::from(5000u64)),
+            Some(token),
+            None,
+        );
+
+        let spender = Address::repeat_byte(3);
+        let permit = sign_permit(
+            &signer,
+            &domain,
+            spender,
+            U256::from(10u64),
+            U256::ZERO,
+            U256::MAX,
+        )
+        .await
+        .unwrap();
+        assert!(permit.v == 27 || permit.v == 28);
+
+        let message = Permit {
+            owner: signer.address(),
+            spender,
+            value: U256::from(10u64),
+            nonce: U256::ZERO,
+            deadline: U256::MAX,
+        };
+        let signature = alloy::primitives::Signature::from_scalars_and_parity(
+            permit.r,
+            permit.s,
+            permit.v == 28,
+        );
+        let recovered = signature
+            .recover_address_from_prehash(&message.eip712_signing_hash(&domain))
+            .unwrap();
+        assert_eq!(recovered, signer.address());
+    }
+}
@@ -2,21 +2,104 @@
 //!
 //! This module provides high-level functions for interacting with the VeilocityVault contract.
 
-use crate::bindings::IVeilocityVault;
+use crate::bindings::{IERC20Token, IVeilocityVault, IVerifier};
 use crate::error::ContractError;
-use alloy::network::EthereumWallet;
+use crate::gas::{GasPolicy, TxPlan};
+use crate::rpc::{Quorum, RpcConfig};
+use crate::token::{self, PermitSignature, TokenInfo};
+use alloy::consensus::Transaction as _;
+use alloy::contract::{CallBuilder, CallDecoder};
+use alloy::eips::BlockId;
+use alloy::network::{Ethereum, NetworkWallet, TransactionBuilder};
 use alloy::primitives::{Address, Bytes, B256, U256};
-use alloy::providers::{Provider, ProviderBuilder};
-use alloy::signers::local::PrivateKeySigner;
-use std::sync::Arc;
+use alloy::providers::{PendingTransactionBuilder, Provider, ProviderBuilder};
+use alloy::rpc::types::{Transaction, TransactionReceipt, TransactionRequest};
+use alloy::sol_types::Eip712Domain;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tracing::{debug, info};
 
+/// How long to wait for a receipt before giving up by default
+pub const DEFAULT_RECEIPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Gas for a plain value transfer, used by cancellations
+const TRANSFER_GAS: u64 = 21_000;
+
+/// Transfer nullifiers per state-transition proof (the vault's `TRANSFER_BATCH_SIZE`)
+pub const TRANSFER_BATCH_SIZE: usize = 4;
+
+/// Deposits per state-transition proof (the vault's `DEPOSIT_BATCH_SIZE`)
+pub const DEPOSIT_BATCH_SIZE: usize = 4;
+
+/// Public inputs the vault's transition verifier checks a root update against
+///
+/// The vault builds `[old root, new root, nullifiers..., first deposit,
+/// commitments...]` with the nullifier slots zero-padded to
+/// `TRANSFER_BATCH_SIZE` and the commitment slots to `DEPOSIT_BATCH_SIZE`, in
+/// the same order as the state_transition circuit's public inputs.
+/// `first_deposit` is the vault's anchored deposit count and
+/// `deposit_commitments` the commitments it recorded from there on.
+pub fn state_transition_public_inputs(
+    old_root: B256,
+    new_root: B256,
+    transfer_nullifiers: &[B256],
+    first_deposit: U256,
+    deposit_commitments: &[B256],
+) -> Result<Vec<B256>, ContractError> {
+    if transfer_nullifiers.len() > TRANSFER_BATCH_SIZE
+        || deposit_commitments.len() > DEPOSIT_BATCH_SIZE
+    {
+        return Err(ContractError::InvalidBatch);
+    }
+
+    let mut inputs = Vec::with_capacity(3 + TRANSFER_BATCH_SIZE + DEPOSIT_BATCH_SIZE);
+    inputs.push(old_root);
+    inputs.push(new_root);
+    inputs.extend_from_slice(transfer_nullifiers);
+    inputs.resize(2 + TRANSFER_BATCH_SIZE, B256::ZERO);
+    inputs.push(B256::from(first_deposit));
+    inputs.extend_from_slice(deposit_commitments);
+    inputs.resize(3 + TRANSFER_BATCH_SIZE + DEPOSIT_BATCH_SIZE, B256::ZERO);
+    Ok(inputs)
+}
+
+/// Result of a mined transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxOutcome {
+    /// Transaction hash
+    pub tx_hash: B256,
+    /// Whether it executed without reverting
+    pub success: bool,
+    /// Block it was included in
+    pub block_number: Option<u64>,
+}
+
+impl From<&TransactionReceipt> for TxOutcome {
+    fn from(receipt: &TransactionReceipt) -> Self {
+        Self {
+            tx_hash: receipt.transaction_hash,
+            success: receipt.status(),
+            block_number: receipt.block_number,
+        }
+    }
+}
+
 /// VeilocityVault client for contract interactions
 pub struct VaultClient<P> {
     /// Contract address
     address: Address,
     /// Provider
     provider: Arc<P>,
+    /// Gas limit and fee settings for transactions
+    gas: GasPolicy,
+    /// How long `wait_for_receipt` waits
+    receipt_timeout: Duration,
+    /// Account transactions are sent from, when known
+    sender: Option<Address>,
+    /// Next nonce to use for `sender`, once looked up
+    next_nonce: Mutex<Option<u64>>,
+    /// Endpoints that must agree on root and nullifier reads
+    quorum: Option<Quorum>,
 }
 
 impl<P: Provider + Clone> VaultClient<P> {
@@ -25,22 +108,100 @@ impl<P: Provider + Clone> VaultClient<P> {
         Self {
             address: contract_address,
             provider: Arc::new(provider),
+            gas: GasPolicy::default(),
+            receipt_timeout: DEFAULT_RECEIPT_TIMEOUT,
+            sender: None,
+            next_nonce: Mutex::new(None),
+            quorum: None,
         }
     }
 
+    /// Answer root and nullifier reads only when the quorum agrees
+    pub fn with_quorum(mut self, quorum: Quorum) -> Self {
+        self.quorum = Some(quorum);
+        self
+    }
+
+    /// Use this gas policy for transactions
+    pub fn with_gas_policy(mut self, gas: GasPolicy) -> Self {
+        self.gas = gas;
+        self
+    }
+
+    /// Give up waiting for a receipt after `timeout`
+    pub fn with_receipt_timeout(mut self, timeout: Duration) -> Self {
+        self.receipt_timeout = timeout;
+        self
+    }
+
+    /// Send from `sender`, tracking its nonce locally
+    ///
+    /// Without a sender the provider picks nonces, and speed-up and cancel are
+    /// unavailable.
+    pub fn with_sender(mut self, sender: Address) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    /// Nonce for the next transaction from the sender
+    ///
+    /// Looked up from the pending block once, then counted locally so
+    /// back-to-back sends don't race the node's pending pool.
+    async fn take_nonce(&self) -> Result<Option<u64>, ContractError> {
+        let Some(sender) = self.sender else {
+            return Ok(None);
+        };
+
+        if let Some(nonce) = self.next_nonce.lock().unwrap().as_mut() {
+            *nonce += 1;
+            return Ok(Some(*nonce - 1));
+        }
+
+        let pending = self
+            .provider
+            .get_transaction_count(sender)
+            .pending()
+            .await
+            .map_err(|e| ContractError::Rpc(e.to_string()))?;
+
+        let mut next_nonce = self.next_nonce.lock().unwrap();
+        let nonce = next_nonce.map_or(pending, |cached| cached.max(pending));
+        *next_nonce = Some(nonce + 1);
+        Ok(Some(nonce))
+    }
+
+    /// Forget the tracked nonce after a failed send, so the next one asks the node
+    fn reset_nonce(&self) {
+        *self.next_nonce.lock().unwrap() = None;
+    }
+
     /// Get the contract address
     pub fn address(&self) -> Address {
         self.address
     }
 
+    /// Get the account transactions are sent from, when known
+    pub fn sender(&self) -> Option<Address> {
+        self.sender
+    }
+
+    /// Get the provider transactions are sent through
+    pub(crate) fn provider(&self) -> &P {
+        &self.provider
+    }
+
     /// Get the current state root
     pub async fn current_root(&self) -> Result<B256, ContractError> {
+        if let Some(quorum) = &self.quorum {
+            return quorum_current_root(quorum, self.address).await;
+        }
+
         let contract = IVeilocityVault::new(self.address, &*self.provider);
         let result = contract
             .currentRoot()
             .call()
             .await
-            .map_err(|e| ContractError::ContractCall(e.to_string()))?;
+            .map_err(ContractError::from_call)?;
 
         Ok(result)
     }
@@ -52,7 +213,31 @@ impl<P: Provider + Clone> VaultClient<P> {
             .depositCount()
             .call()
             .await
-            .map_err(|e| ContractError::ContractCall(e.to_string()))?;
+            .map_err(ContractError::from_call)?;
+
+        Ok(result)
+    }
+
+    /// Get the number of deposits covered by anchored roots
+    pub async fn anchored_deposit_count(&self) -> Result<U256, ContractError> {
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        let result = contract
+            .anchoredDepositCount()
+            .call()
+            .await
+            .map_err(ContractError::from_call)?;
+
+        Ok(result)
+    }
+
+    /// Get the commitment the vault recorded for the deposit at `leaf_index`
+    pub async fn deposit_commitment(&self, leaf_index: U256) -> Result<B256, ContractError> {
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        let result = contract
+            .depositCommitments(leaf_index)
+            .call()
+            .await
+            .map_err(ContractError::from_call)?;
 
         Ok(result)
     }
@@ -64,31 +249,69 @@ impl<P: Provider + Clone> VaultClient<P> {
             .totalValueLocked()
             .call()
             .await
-            .map_err(|e| ContractError::ContractCall(e.to_string()))?;
+            .map_err(ContractError::from_call)?;
 
         Ok(result)
     }
 
     /// Check if a root is valid
     pub async fn is_valid_root(&self, root: B256) -> Result<bool, ContractError> {
+        if let Some(quorum) = &self.quorum {
+            return quorum_is_valid_root(quorum, self.address, root).await;
+        }
+
         let contract = IVeilocityVault::new(self.address, &*self.provider);
         let result = contract
             .isValidRoot(root)
             .call()
             .await
-            .map_err(|e| ContractError::ContractCall(e.to_string()))?;
+            .map_err(ContractError::from_call)?;
 
         Ok(result)
     }
 
     /// Check if a nullifier has been used
     pub async fn is_nullifier_used(&self, nullifier: B256) -> Result<bool, ContractError> {
+        if let Some(quorum) = &self.quorum {
+            return quorum_is_nullifier_used(quorum, self.address, nullifier).await;
+        }
+
         let contract = IVeilocityVault::new(self.address, &*self.provider);
         let result = contract
             .isNullifierUsed(nullifier)
             .call()
             .await
-            .map_err(|e| ContractError::ContractCall(e.to_string()))?;
+            .map_err(ContractError::from_call)?;
+
+        Ok(result)
+    }
+
+    /// Get the address of the ZK verifier behind the vault
+    pub async fn verifier(&self) -> Result<Address, ContractError> {
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        let result = contract
+            .verifier()
+            .call()
+            .await
+            .map_err(ContractError::from_call)?;
+
+        Ok(result)
+    }
+
+    /// Check whether the deployed verifier was generated from a vk with this hash
+    pub async fn verifier_matches_vk(&self, vk_hash: B256) -> Result<bool, ContractError> {
+        let verifier = self.verifier().await?;
+        verifier_embeds_vk_hash(&*self.provider, verifier, vk_hash).await
+    }
+
+    /// Get the state-transition verifier (zero if root updates are owner-trusted)
+    pub async fn transition_verifier(&self) -> Result<Address, ContractError> {
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        let result = contract
+            .transitionVerifier()
+            .call()
+            .await
+            .map_err(ContractError::from_call)?;
 
         Ok(result)
     }
@@ -100,54 +323,450 @@ impl<P: Provider + Clone> VaultClient<P> {
             .MIN_DEPOSIT()
             .call()
             .await
-            .map_err(|e| ContractError::ContractCall(e.to_string()))?;
+            .map_err(ContractError::from_call)?;
 
         Ok(result)
     }
 
+    /// Get the minimum deposit of a token pool (zero if the token is unsupported)
+    pub async fn token_min_deposit(&self, token: Address) -> Result<U256, ContractError> {
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        let result = contract
+            .tokenMinDeposit(token)
+            .call()
+            .await
+            .map_err(ContractError::from_call)?;
+
+        Ok(result)
+    }
+
+    /// Get the value locked in a token pool
+    pub async fn token_value_locked(&self, token: Address) -> Result<U256, ContractError> {
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        let result = contract
+            .tokenValueLocked(token)
+            .call()
+            .await
+            .map_err(ContractError::from_call)?;
+
+        Ok(result)
+    }
+
+    /// Get a token's symbol and decimals
+    pub async fn token_info(&self, token: Address) -> Result<TokenInfo, ContractError> {
+        token::token_info(&*self.provider, token).await
+    }
+
+    /// Get how much of a token the vault may pull from the sender
+    pub async fn token_allowance(&self, token: Address) -> Result<U256, ContractError> {
+        let owner = self.require_sender()?;
+        let erc20 = IERC20Token::new(token, &*self.provider);
+        let result = erc20
+            .allowance(owner, self.address)
+            .call()
+            .await
+            .map_err(ContractError::from_call)?;
+
+        Ok(result)
+    }
+
+    /// Get the sender's balance of a token
+    pub async fn token_balance(&self, token: Address) -> Result<U256, ContractError> {
+        let owner = self.require_sender()?;
+        let erc20 = IERC20Token::new(token, &*self.provider);
+        let result = erc20
+            .balanceOf(owner)
+            .call()
+            .await
+            .map_err(ContractError::from_call)?;
+
+        Ok(result)
+    }
+
+    /// Get the EIP-712 domain of a token's permits, or `None` without permit support
+    pub async fn permit_domain(
+        &self,
+        token: Address,
+    ) -> Result<Option<Eip712Domain>, ContractError> {
+        token::permit_domain(&*self.provider, token).await
+    }
+
+    /// Get the sender's next permit nonce for a token
+    pub async fn permit_nonce(&self, token: Address) -> Result<U256, ContractError> {
+        let owner = self.require_sender()?;
+        let erc20 = IERC20Token::new(token, &*self.provider);
+        let result = erc20
+            .nonces(owner)
+            .call()
+            .await
+            .map_err(ContractError::from_call)?;
+
+        Ok(result)
+    }
+
+    /// Sender address, for calls that act on its own token balance
+    fn require_sender(&self) -> Result<Address, ContractError> {
+        self.sender.ok_or_else(|| {
+            ContractError::Config("token operations need the sender address".to_string())
+        })
+    }
+
+    /// Simulate a call against the pending block, then estimate its gas and fees
+    ///
+    /// A call that would revert fails here with the decoded vault error,
+    /// before anything is sent.
+    pub(crate) async fn plan_call<T: Provider, D: CallDecoder>(
+        &self,
+        call: &CallBuilder<T, D>,
+    ) -> Result<TxPlan, ContractError> {
+        call.call_raw()
+            .block(BlockId::pending())
+            .await
+            .map_err(ContractError::from_call)?;
+
+        let gas_estimate = call
+            .estimate_gas()
+            .await
+            .map_err(ContractError::from_call)?;
+        let fees = self
+            .provider
+            .estimate_eip1559_fees()
+            .await
+            .map_err(|e| ContractError::Rpc(e.to_string()))?;
+
+        self.gas.plan(
+            gas_estimate,
+            fees.max_fee_per_gas,
+            fees.max_priority_fee_per_gas,
+        )
+    }
+
+    /// Send a call with the gas and fees of its plan and the next tracked nonce
+    pub(crate) async fn send_planned<T: Provider, D: CallDecoder>(
+        &self,
+        call: CallBuilder<T, D>,
+        plan: &TxPlan,
+    ) -> Result<B256, ContractError> {
+        let mut call = call
+            .gas(plan.gas_limit)
+            .max_fee_per_gas(plan.max_fee_per_gas)
+            .max_priority_fee_per_gas(plan.max_priority_fee_per_gas);
+        if let Some(nonce) = self.take_nonce().await? {
+            call = call.nonce(nonce);
+        }
+
+        let tx = call.send().await.map_err(|e| {
+            self.reset_nonce();
+            ContractError::from_send(e)
+        })?;
+
+        Ok(*tx.tx_hash())
+    }
+
+    /// Simulate a deposit and plan its gas and fees
+    pub async fn plan_deposit(
+        &self,
+        commitment: B256,
+        amount: U256,
+    ) -> Result<TxPlan, ContractError> {
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        self.plan_call(&contract.deposit(commitment).value(amount))
+            .await
+    }
+
     /// Deposit funds into Veilocity
     pub async fn deposit(&self, commitment: B256, amount: U256) -> Result<B256, ContractError> {
+        let plan = self.plan_deposit(commitment, amount).await?;
+        let tx_hash = self.submit_deposit(commitment, amount, &plan).await?;
+
+        debug!("Transaction sent, waiting for confirmation...");
+
+        let outcome = self.wait_for_receipt(tx_hash).await?;
+        if !outcome.success {
+            return Err(ContractError::TransactionReverted(
+                "Deposit transaction reverted".to_string(),
+            ));
+        }
+
+        info!("Deposit confirmed in tx {:?}", tx_hash);
+        Ok(tx_hash)
+    }
+
+    /// Broadcast a planned deposit and return its hash without waiting for it
+    /// to be mined
+    pub async fn submit_deposit(
+        &self,
+        commitment: B256,
+        amount: U256,
+        plan: &TxPlan,
+    ) -> Result<B256, ContractError> {
         info!("Depositing {} wei with commitment {:?}", amount, commitment);
 
         let contract = IVeilocityVault::new(self.address, &*self.provider);
-        let tx = contract
-            .deposit(commitment)
-            .value(amount)
-            .send()
+        self.send_planned(contract.deposit(commitment).value(amount), plan)
+            .await
+    }
+
+    /// Approve the vault to pull `amount` of a token from the sender
+    ///
+    /// Returns the approval's hash without waiting for it to be mined; the
+    /// token deposit can only be planned once it is.
+    pub async fn approve_token(&self, token: Address, amount: U256) -> Result<B256, ContractError> {
+        info!("Approving {} of token {:?} for the vault", amount, token);
+
+        let erc20 = IERC20Token::new(token, &*self.provider);
+        let call = erc20.approve(self.address, amount);
+        let plan = self.plan_call(&call).await?;
+        self.send_planned(call, &plan).await
+    }
+
+    /// Simulate a token deposit and plan its gas and fees
+    ///
+    /// Without a permit the vault must already be approved for `amount`.
+    pub async fn plan_deposit_token(
+        &self,
+        token: Address,
+        commitment: B256,
+        amount: U256,
+        permit: Option<&PermitSignature>,
+    ) -> Result<TxPlan, ContractError> {
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        match permit {
+            Some(p) => {
+                let call = contract
+                    .depositTokenWithPermit(token, amount, commitment, p.deadline, p.v, p.r, p.s);
+                self.plan_call(&call).await
+            }
+            None => {
+                self.plan_call(&contract.depositToken(token, amount, commitment))
+                    .await
+            }
+        }
+    }
+
+    /// Broadcast a planned token deposit and return its hash without waiting
+    /// for it to be mined
+    pub async fn submit_deposit_token(
+        &self,
+        token: Address,
+        commitment: B256,
+        amount: U256,
+        permit: Option<&PermitSignature>,
+        plan: &TxPlan,
+    ) -> Result<B256, ContractError> {
+        info!(
+            "Depositing {} of token {:?} with commitment {:?}",
+            amount, token, commitment
+        );
+
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        match permit {
+            Some(p) => {
+                let call = contract
+                    .depositTokenWithPermit(token, amount, commitment, p.deadline, p.v, p.r, p.s);
+                self.send_planned(call, plan).await
+            }
+            None => {
+                self.send_planned(contract.depositToken(token, amount, commitment), plan)
+                    .await
+            }
+        }
+    }
+
+    /// Wait for a broadcast transaction to be mined
+    ///
+    /// Fails with `ContractError::Timeout` once the receipt timeout passes;
+    /// the transaction may still be mined later, or be sped up or cancelled.
+    pub async fn wait_for_receipt(&self, tx_hash: B256) -> Result<TxOutcome, ContractError> {
+        let pending = PendingTransactionBuilder::new(self.provider.root().clone(), tx_hash);
+        let receipt = tokio::time::timeout(self.receipt_timeout, pending.get_receipt())
             .await
+            .map_err(|_| ContractError::Timeout)?
             .map_err(|e| ContractError::TransactionFailed(e.to_string()))?;
 
-        debug!("Transaction sent, waiting for confirmation...");
+        Ok(TxOutcome::from(&receipt))
+    }
 
-        let receipt = tx
-            .get_receipt()
+    /// Resend a stuck transaction with the same nonce and higher fees
+    ///
+    /// Returns the hash of the replacement.
+    pub async fn speed_up(&self, tx_hash: B256) -> Result<B256, ContractError> {
+        let stuck = self.stuck_transaction(tx_hash).await?;
+
+        let mut request = TransactionRequest::default()
+            .with_input(stuck.input().clone())
+            .with_value(stuck.value())
+            .with_gas_limit(stuck.gas_limit());
+        if let Some(to) = stuck.to() {
+            request = request.with_to(to);
+        }
+
+        info!("Speeding up {:?} (nonce {})", tx_hash, stuck.nonce());
+        self.replace(&stuck, request).await
+    }
+
+    /// Replace a stuck transaction with an empty transfer to ourselves
+    ///
+    /// Once the replacement is mined the original can never be. Returns the
+    /// hash of the replacement.
+    pub async fn cancel(&self, tx_hash: B256) -> Result<B256, ContractError> {
+        let stuck = self.stuck_transaction(tx_hash).await?;
+        let sender = self.sender.ok_or_else(|| {
+            ContractError::Config("cancelling needs the sender address".to_string())
+        })?;
+
+        let request = TransactionRequest::default()
+            .with_to(sender)
+            .with_value(U256::ZERO)
+            .with_gas_limit(TRANSFER_GAS);
+
+        info!("Cancelling {:?} (nonce {})", tx_hash, stuck.nonce());
+        self.replace(&stuck, request).await
+    }
+
+    /// Look up a sent transaction that has not been mined yet
+    async fn stuck_transaction(&self, tx_hash: B256) -> Result<Transaction, ContractError> {
+        let tx = self
+            .provider
+            .get_transaction_by_hash(tx_hash)
+            .await
+            .map_err(|e| ContractError::Rpc(e.to_string()))?
+            .ok_or_else(|| {
+                ContractError::TransactionFailed(format!("{:?} is not known to the node", tx_hash))
+            })?;
+
+        if tx.block_number.is_some() {
+            return Err(ContractError::TransactionFailed(format!(
+                "{:?} is already mined",
+                tx_hash
+            )));
+        }
+
+        Ok(tx)
+    }
+
+    /// Send `request` in place of `stuck`: same nonce, bumped fees
+    ///
+    /// Only transactions sent by our sender can be replaced; the nonce of
+    /// anyone else's would just be one of ours, and replace something else.
+    async fn replace(
+        &self,
+        stuck: &Transaction,
+        request: TransactionRequest,
+    ) -> Result<B256, ContractError> {
+        let sender = self.sender.ok_or_else(|| {
+            ContractError::Config("replacing needs the sender address".to_string())
+        })?;
+        if stuck.inner.signer() != sender {
+            return Err(ContractError::TransactionFailed(format!(
+                "{:?} was sent by {:?}, not {:?}",
+                stuck.inner.tx_hash(),
+                stuck.inner.signer(),
+                sender
+            )));
+        }
+
+        let fees = self
+            .provider
+            .estimate_eip1559_fees()
+            .await
+            .map_err(|e| ContractError::Rpc(e.to_string()))?;
+        let (max_fee, priority_fee) = self.gas.bump(
+            stuck.max_fee_per_gas(),
+            stuck.max_priority_fee_per_gas().unwrap_or(0),
+            fees.max_fee_per_gas,
+            fees.max_priority_fee_per_gas,
+        )?;
+
+        let request = request
+            .with_from(sender)
+            .with_nonce(stuck.nonce())
+            .with_max_fee_per_gas(max_fee)
+            .with_max_priority_fee_per_gas(priority_fee);
+
+        let pending = self
+            .provider
+            .send_transaction(request)
             .await
             .map_err(|e| ContractError::TransactionFailed(e.to_string()))?;
 
-        if !receipt.status() {
+        Ok(*pending.tx_hash())
+    }
+
+    /// Withdraw funds from Veilocity
+    pub async fn withdraw(
+        &self,
+        nullifier: B256,
+        recipient: Address,
+        amount: U256,
+        root: B256,
+        proof: Vec<u8>,
+    ) -> Result<B256, ContractError> {
+        let plan = self
+            .plan_withdraw(nullifier, recipient, amount, root, &proof)
+            .await?;
+        let tx_hash = self
+            .submit_withdraw(nullifier, recipient, amount, root, proof, &plan)
+            .await?;
+
+        debug!("Transaction sent, waiting for confirmation...");
+
+        let outcome = self.wait_for_receipt(tx_hash).await?;
+        if !outcome.success {
             return Err(ContractError::TransactionReverted(
-                "Deposit transaction reverted".to_string(),
+                "Withdrawal transaction reverted".to_string(),
             ));
         }
 
-        info!("Deposit confirmed in tx {:?}", receipt.transaction_hash);
-        Ok(receipt.transaction_hash)
+        info!("Withdrawal confirmed in tx {:?}", tx_hash);
+        Ok(tx_hash)
     }
 
-    /// Withdraw funds from Veilocity
-    pub async fn withdraw(
+    /// Simulate a withdrawal and plan its gas and fees
+    pub async fn plan_withdraw(
+        &self,
+        nullifier: B256,
+        recipient: Address,
+        amount: U256,
+        root: B256,
+        proof: &[u8],
+    ) -> Result<TxPlan, ContractError> {
+        self.check_withdrawal(nullifier, root).await?;
+
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        self.plan_call(&contract.withdraw(
+            nullifier,
+            recipient,
+            amount,
+            root,
+            Bytes::copy_from_slice(proof),
+        ))
+        .await
+    }
+
+    /// Broadcast a planned withdrawal and return its hash without waiting for
+    /// it to be mined
+    pub async fn submit_withdraw(
         &self,
         nullifier: B256,
         recipient: Address,
         amount: U256,
         root: B256,
         proof: Vec<u8>,
+        plan: &TxPlan,
     ) -> Result<B256, ContractError> {
         info!(
             "Withdrawing {} wei to {:?} with nullifier {:?}",
             amount, recipient, nullifier
         );
 
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        let call = contract.withdraw(nullifier, recipient, amount, root, Bytes::from(proof));
+        self.send_planned(call, plan).await
+    }
+
+    /// Check a withdrawal's nullifier is unused and its root is known
+    async fn check_withdrawal(&self, nullifier: B256, root: B256) -> Result<(), ContractError> {
         // Check nullifier hasn't been used
         if self.is_nullifier_used(nullifier).await? {
             return Err(ContractError::NullifierUsed(format!("{:?}", nullifier)));
@@ -158,12 +777,103 @@ impl<P: Provider + Clone> VaultClient<P> {
             return Err(ContractError::InvalidRoot);
         }
 
+        Ok(())
+    }
+
+    /// Simulate a token withdrawal and plan its gas and fees
+    ///
+    /// `proof` must come from the `withdraw_token` circuit, bound to `token`.
+    pub async fn plan_withdraw_token(
+        &self,
+        token: Address,
+        nullifier: B256,
+        recipient: Address,
+        amount: U256,
+        root: B256,
+        proof: &[u8],
+    ) -> Result<TxPlan, ContractError> {
+        self.check_withdrawal(nullifier, root).await?;
+
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        self.plan_call(&contract.withdrawToken(
+            token,
+            nullifier,
+            recipient,
+            amount,
+            root,
+            Bytes::copy_from_slice(proof),
+        ))
+        .await
+    }
+
+    /// Broadcast a planned token withdrawal and return its hash without
+    /// waiting for it to be mined
+    #[allow(clippy::too_many_arguments)]
+    pub async fn submit_withdraw_token(
+        &self,
+        token: Address,
+        nullifier: B256,
+        recipient: Address,
+        amount: U256,
+        root: B256,
+        proof: Vec<u8>,
+        plan: &TxPlan,
+    ) -> Result<B256, ContractError> {
+        info!(
+            "Withdrawing {} of token {:?} to {:?} with nullifier {:?}",
+            amount, token, recipient, nullifier
+        );
+
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        let call = contract.withdrawToken(
+            token,
+            nullifier,
+            recipient,
+            amount,
+            root,
+            Bytes::from(proof),
+        );
+        self.send_planned(call, plan).await
+    }
+
+    /// Settle several withdrawals with one batched proof
+    ///
+    /// `withdrawals` holds `(nullifier, recipient, amount)` per entry, in the
+    /// same order as the slots of the batch witness the proof was built from.
+    pub async fn withdraw_batch(
+        &self,
+        withdrawals: &[(B256, Address, U256)],
+        root: B256,
+        proof: Vec<u8>,
+    ) -> Result<B256, ContractError> {
+        info!(
+            "Withdrawing batch of {} against root {:?}",
+            withdrawals.len(),
+            root
+        );
+
+        // Check no nullifier has been used
+        for (nullifier, _, _) in withdrawals {
+            if self.is_nullifier_used(*nullifier).await? {
+                return Err(ContractError::NullifierUsed(format!("{:?}", nullifier)));
+            }
+        }
+
+        // Check root is valid
+        if !self.is_valid_root(root).await? {
+            return Err(ContractError::InvalidRoot);
+        }
+
+        let nullifiers = withdrawals.iter().map(|(n, _, _)| *n).collect::<Vec<_>>();
+        let recipients = withdrawals.iter().map(|(_, r, _)| *r).collect::<Vec<_>>();
+        let amounts = withdrawals.iter().map(|(_, _, a)| *a).collect::<Vec<_>>();
+
         let contract = IVeilocityVault::new(self.address, &*self.provider);
         let tx = contract
-            .withdraw(nullifier, recipient, amount, root, Bytes::from(proof))
+            .withdrawBatch(nullifiers, recipients, amounts, root, Bytes::from(proof))
             .send()
             .await
-            .map_err(|e| ContractError::TransactionFailed(e.to_string()))?;
+            .map_err(ContractError::from_send)?;
 
         debug!("Transaction sent, waiting for confirmation...");
 
@@ -174,42 +884,106 @@ impl<P: Provider + Clone> VaultClient<P> {
 
         if !receipt.status() {
             return Err(ContractError::TransactionReverted(
-                "Withdrawal transaction reverted".to_string(),
+                "Batch withdrawal transaction reverted".to_string(),
             ));
         }
 
-        info!("Withdrawal confirmed in tx {:?}", receipt.transaction_hash);
+        info!("Batch withdrawal confirmed in tx {:?}", receipt.transaction_hash);
         Ok(receipt.transaction_hash)
     }
 
-    /// Update state root (admin only)
+    /// Update state root with a state-transition proof (admin only)
+    ///
+    /// The proof inserts the next `deposits` unanchored deposits and then
+    /// applies the private transfers whose nullifiers are
+    /// `transfer_nullifiers`, in circuit slot order. The vault needs a
+    /// transition verifier for this; the proof is checked against the public
+    /// inputs the vault will build from its own deposit commitments (see
+    /// `state_transition_public_inputs`) before anything is sent.
     pub async fn update_state_root(
         &self,
         new_root: B256,
+        transfer_nullifiers: &[B256],
+        deposits: u64,
         proof: Vec<u8>,
     ) -> Result<B256, ContractError> {
-        info!("Updating state root to {:?}", new_root);
+        info!(
+            "Updating state root to {:?} with {} deposits and {} transfers",
+            new_root,
+            deposits,
+            transfer_nullifiers.len()
+        );
 
-        let contract = IVeilocityVault::new(self.address, &*self.provider);
-        let tx = contract
-            .updateStateRoot(new_root, Bytes::from(proof))
-            .send()
-            .await
-            .map_err(|e| ContractError::TransactionFailed(e.to_string()))?;
+        if transfer_nullifiers.len() > TRANSFER_BATCH_SIZE
+            || deposits > DEPOSIT_BATCH_SIZE as u64
+            || (transfer_nullifiers.is_empty() && deposits == 0)
+        {
+            return Err(ContractError::InvalidBatch);
+        }
 
-        let receipt = tx
-            .get_receipt()
+        let transition_verifier = self.transition_verifier().await?;
+        if transition_verifier == Address::ZERO {
+            return Err(ContractError::TransitionVerifierNotSet);
+        }
+
+        // Check no nullifier has been used
+        for nullifier in transfer_nullifiers {
+            if self.is_nullifier_used(*nullifier).await? {
+                return Err(ContractError::NullifierUsed(format!("{:?}", nullifier)));
+            }
+        }
+
+        // The proof must insert exactly the deposits the vault recorded next
+        let first_deposit = self.anchored_deposit_count().await?;
+        if U256::from(deposits) > self.deposit_count().await? - first_deposit {
+            return Err(ContractError::InvalidBatch);
+        }
+        let mut deposit_commitments = Vec::with_capacity(deposits as usize);
+        for i in 0..deposits {
+            deposit_commitments.push(
+                self.deposit_commitment(first_deposit + U256::from(i))
+                    .await?,
+            );
+        }
+
+        let old_root = self.current_root().await?;
+        let public_inputs = state_transition_public_inputs(
+            old_root,
+            new_root,
+            transfer_nullifiers,
+            first_deposit,
+            &deposit_commitments,
+        )?;
+
+        let verifier = IVerifier::new(transition_verifier, &*self.provider);
+        let valid = verifier
+            .verify(Bytes::from(proof.clone()), public_inputs)
+            .call()
             .await
-            .map_err(|e| ContractError::TransactionFailed(e.to_string()))?;
+            .map_err(|e| ContractError::ContractCall(e.to_string()))?;
+        if !valid {
+            return Err(ContractError::InvalidProof);
+        }
 
-        if !receipt.status() {
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        let call = contract.updateStateRoot(
+            new_root,
+            transfer_nullifiers.to_vec(),
+            U256::from(deposits),
+            Bytes::from(proof),
+        );
+        let plan = self.plan_call(&call).await?;
+        let tx_hash = self.send_planned(call, &plan).await?;
+
+        let outcome = self.wait_for_receipt(tx_hash).await?;
+        if !outcome.success {
             return Err(ContractError::TransactionReverted(
                 "State root update reverted".to_string(),
             ));
         }
 
-        info!("State root updated in tx {:?}", receipt.transaction_hash);
-        Ok(receipt.transaction_hash)
+        info!("State root updated in tx {:?}", tx_hash);
+        Ok(tx_hash)
     }
 
     /// Get the current block number
@@ -235,19 +1009,101 @@ impl<P: Provider + Clone> VaultClient<P> {
     }
 }
 
-/// Create a vault client with HTTP provider and wallet
-pub async fn create_vault_client(
-    rpc_url: &str,
+/// Create a vault client over the configured RPC endpoints with a wallet
+///
+/// The wallet can be anything that signs Ethereum transactions: an
+/// `EthereumWallet` around a local key or hardware signer, a
+/// [`RemoteSigner`](crate::signer::RemoteSigner), or a
+/// [`SigningWallet`](crate::signer::SigningWallet). With a quorum configured,
+/// root and nullifier reads are checked against every endpoint.
+pub async fn create_vault_client<W>(
+    rpc: &RpcConfig,
     contract_address: Address,
-    signer: PrivateKeySigner,
-) -> Result<VaultClient<impl Provider + Clone>, ContractError> {
-    let wallet = EthereumWallet::from(signer);
+    wallet: W,
+) -> Result<VaultClient<impl Provider + Clone>, ContractError>
+where
+    W: NetworkWallet<Ethereum> + Clone + 'static,
+{
+    let sender = wallet.default_signer_address();
 
     let provider = ProviderBuilder::new()
         .wallet(wallet)
-        .connect_http(rpc_url.parse().map_err(|e| ContractError::Rpc(format!("{}", e)))?);
+        .connect_client(rpc.client()?);
+
+    let client = VaultClient::with_provider(provider, contract_address).with_sender(sender);
+    Ok(match rpc.quorum()? {
+        Some(quorum) => client.with_quorum(quorum),
+        None => client,
+    })
+}
+
+/// `currentRoot` as agreed by the quorum
+async fn quorum_current_root(quorum: &Quorum, address: Address) -> Result<B256, ContractError> {
+    quorum
+        .read(move |provider| async move {
+            IVeilocityVault::new(address, provider)
+                .currentRoot()
+                .call()
+                .await
+                .map_err(ContractError::from_call)
+        })
+        .await
+}
 
-    Ok(VaultClient::with_provider(provider, contract_address))
+/// `isValidRoot` as agreed by the quorum
+async fn quorum_is_valid_root(
+    quorum: &Quorum,
+    address: Address,
+    root: B256,
+) -> Result<bool, ContractError> {
+    quorum
+        .read(move |provider| async move {
+            IVeilocityVault::new(address, provider)
+                .isValidRoot(root)
+                .call()
+                .await
+                .map_err(ContractError::from_call)
+        })
+        .await
+}
+
+/// `isNullifierUsed` as agreed by the quorum
+async fn quorum_is_nullifier_used(
+    quorum: &Quorum,
+    address: Address,
+    nullifier: B256,
+) -> Result<bool, ContractError> {
+    quorum
+        .read(move |provider| async move {
+            IVeilocityVault::new(address, provider)
+                .isNullifierUsed(nullifier)
+                .call()
+                .await
+                .map_err(ContractError::from_call)
+        })
+        .await
+}
+
+/// Check whether a verifier's runtime code embeds the given vk hash
+///
+/// The Barretenberg Solidity verifier stores `VK_HASH` as an immutable, so the
+/// value is written verbatim into the deployed bytecode and can be found there
+/// without a dedicated getter.
+pub async fn verifier_embeds_vk_hash<P: Provider>(
+    provider: &P,
+    verifier: Address,
+    vk_hash: B256,
+) -> Result<bool, ContractError> {
+    let code = provider
+        .get_code_at(verifier)
+        .await
+        .map_err(|e| ContractError::Rpc(e.to_string()))?;
+
+    if code.is_empty() {
+        return Err(ContractError::ContractNotDeployed(format!("{:?}", verifier)));
+    }
+
+    Ok(code.windows(32).any(|w| w == vk_hash.as_slice()))
 }
 
 /// Read-only vault client (no signer required)
@@ -256,6 +1112,8 @@ pub struct VaultReader<P> {
     provider: Arc<P>,
     /// Contract address
     address: Address,
+    /// Endpoints that must agree on root and nullifier reads
+    quorum: Option<Quorum>,
 }
 
 impl<P: Provider + Clone> VaultReader<P> {
@@ -264,17 +1122,28 @@ impl<P: Provider + Clone> VaultReader<P> {
         Self {
             provider: Arc::new(provider),
             address: contract_address,
+            quorum: None,
         }
     }
 
+    /// Answer root and nullifier reads only when the quorum agrees
+    pub fn with_quorum(mut self, quorum: Quorum) -> Self {
+        self.quorum = Some(quorum);
+        self
+    }
+
     /// Get the current state root
     pub async fn current_root(&self) -> Result<B256, ContractError> {
+        if let Some(quorum) = &self.quorum {
+            return quorum_current_root(quorum, self.address).await;
+        }
+
         let contract = IVeilocityVault::new(self.address, &*self.provider);
         let result = contract
             .currentRoot()
             .call()
             .await
-            .map_err(|e| ContractError::ContractCall(e.to_string()))?;
+            .map_err(ContractError::from_call)?;
 
         Ok(result)
     }
@@ -286,7 +1155,7 @@ impl<P: Provider + Clone> VaultReader<P> {
             .depositCount()
             .call()
             .await
-            .map_err(|e| ContractError::ContractCall(e.to_string()))?;
+            .map_err(ContractError::from_call)?;
 
         Ok(result)
     }
@@ -298,35 +1167,78 @@ impl<P: Provider + Clone> VaultReader<P> {
             .totalValueLocked()
             .call()
             .await
-            .map_err(|e| ContractError::ContractCall(e.to_string()))?;
+            .map_err(ContractError::from_call)?;
+
+        Ok(result)
+    }
+
+    /// Get the value locked in a token pool
+    pub async fn token_value_locked(&self, token: Address) -> Result<U256, ContractError> {
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        let result = contract
+            .tokenValueLocked(token)
+            .call()
+            .await
+            .map_err(ContractError::from_call)?;
 
         Ok(result)
     }
 
+    /// Get a token's symbol and decimals
+    pub async fn token_info(&self, token: Address) -> Result<TokenInfo, ContractError> {
+        token::token_info(&*self.provider, token).await
+    }
+
     /// Check if a root is valid
     pub async fn is_valid_root(&self, root: B256) -> Result<bool, ContractError> {
+        if let Some(quorum) = &self.quorum {
+            return quorum_is_valid_root(quorum, self.address, root).await;
+        }
+
         let contract = IVeilocityVault::new(self.address, &*self.provider);
         let result = contract
             .isValidRoot(root)
             .call()
             .await
-            .map_err(|e| ContractError::ContractCall(e.to_string()))?;
+            .map_err(ContractError::from_call)?;
 
         Ok(result)
     }
 
     /// Check if a nullifier has been used
     pub async fn is_nullifier_used(&self, nullifier: B256) -> Result<bool, ContractError> {
+        if let Some(quorum) = &self.quorum {
+            return quorum_is_nullifier_used(quorum, self.address, nullifier).await;
+        }
+
         let contract = IVeilocityVault::new(self.address, &*self.provider);
         let result = contract
             .isNullifierUsed(nullifier)
             .call()
             .await
-            .map_err(|e| ContractError::ContractCall(e.to_string()))?;
+            .map_err(ContractError::from_call)?;
+
+        Ok(result)
+    }
+
+    /// Get the address of the ZK verifier behind the vault
+    pub async fn verifier(&self) -> Result<Address, ContractError> {
+        let contract = IVeilocityVault::new(self.address, &*self.provider);
+        let result = contract
+            .verifier()
+            .call()
+            .await
+            .map_err(ContractError::from_call)?;
 
         Ok(result)
     }
 
+    /// Check whether the deployed verifier was generated from a vk with this hash
+    pub async fn verifier_matches_vk(&self, vk_hash: B256) -> Result<bool, ContractError> {
+        let verifier = self.verifier().await?;
+        verifier_embeds_vk_hash(&*self.provider, verifier, vk_hash).await
+    }
+
     /// Get the current block number
     pub async fn get_block_number(&self) -> Result<u64, ContractError> {
         let block = self
@@ -337,39 +1249,65 @@ impl<P: Provider + Clone> VaultReader<P> {
 
         Ok(block)
     }
+
+    /// Outcome of a transaction, or `None` while it has not been mined
+    pub async fn transaction_outcome(
+        &self,
+        tx_hash: B256,
+    ) -> Result<Option<TxOutcome>, ContractError> {
+        let receipt = self
+            .provider
+            .get_transaction_receipt(tx_hash)
+            .await
+            .map_err(|e| ContractError::Rpc(e.to_string()))?;
+
+        Ok(receipt.as_ref().map(TxOutcome::from))
+    }
 }
 
-/// Create a read-only vault client with HTTP provider
+/// Create a read-only vault client over the configured RPC endpoints
+///
+/// With a quorum configured, root and nullifier reads are checked against
+/// every endpoint.
 pub fn create_vault_reader(
-    rpc_url: &str,
+    rpc: &RpcConfig,
     contract_address: Address,
 ) -> Result<VaultReader<impl Provider + Clone>, ContractError> {
-    let provider = ProviderBuilder::new()
-        .connect_http(rpc_url.parse().map_err(|e| ContractError::Rpc(format!("{}", e)))?);
+    let provider = ProviderBuilder::new().connect_client(rpc.client()?);
 
-    Ok(VaultReader::with_provider(provider, contract_address))
+    let reader = VaultReader::with_provider(provider, contract_address);
+    Ok(match rpc.quorum()? {
+        Some(quorum) => reader.with_quorum(quorum),
+        None => reader,
+    })
 }
 
 // ============================================================================
 // EVENT FETCHING - Real-time chain synchronization
 // ============================================================================
 
-use crate::events::{DepositEvent, EventFilter, StateRootUpdatedEvent, VeilocityEvent, WithdrawalEvent};
+use crate::events::{
+    DepositEvent, EventFilter, LogMeta, StateRootUpdatedEvent, VeilocityEvent, WithdrawalEvent,
+};
+use crate::stream::EventStream;
 use alloy::primitives::keccak256;
 use alloy::rpc::types::Filter;
 use alloy::sol_types::SolEvent;
 
 impl<P: Provider + Clone> VaultReader<P> {
-    /// Fetch deposit events from the chain
+    /// Fetch native and token deposit events from the chain
     pub async fn get_deposit_events(
         &self,
         filter: &EventFilter,
     ) -> Result<Vec<DepositEvent>, ContractError> {
         // Compute event signature hash: keccak256("Deposit(bytes32,uint256,uint256,uint256)")
-        let event_sig = keccak256(IVeilocityVault::Deposit::SIGNATURE.as_bytes());
+        let event_sigs = vec![
+            keccak256(IVeilocityVault::Deposit::SIGNATURE.as_bytes()),
+            IVeilocityVault::TokenDeposit::SIGNATURE_HASH,
+        ];
         let mut log_filter = Filter::new()
             .address(self.address)
-            .event_signature(event_sig);
+            .event_signature(event_sigs);
 
         if let Some(from) = filter.from_block {
             log_filter = log_filter.from_block(from);
@@ -386,25 +1324,26 @@ impl<P: Provider + Clone> VaultReader<P> {
 
         let mut events = Vec::new();
         for log in logs {
-            if let Ok(decoded) = log.log_decode::<IVeilocityVault::Deposit>() {
-                let block_number = log.block_number.unwrap_or(0);
-                let tx_hash = log.transaction_hash.unwrap_or_default();
-                events.push(DepositEvent::from_log(&decoded.inner.data, block_number, tx_hash));
+            if let Some(VeilocityEvent::Deposit(event)) = VeilocityEvent::decode_log(&log) {
+                events.push(event);
             }
         }
 
         Ok(events)
     }
 
-    /// Fetch withdrawal events from the chain
+    /// Fetch native and token withdrawal events from the chain
     pub async fn get_withdrawal_events(
         &self,
         filter: &EventFilter,
     ) -> Result<Vec<WithdrawalEvent>, ContractError> {
-        let event_sig = keccak256(IVeilocityVault::Withdrawal::SIGNATURE.as_bytes());
+        let event_sigs = vec![
+            keccak256(IVeilocityVault::Withdrawal::SIGNATURE.as_bytes()),
+            IVeilocityVault::TokenWithdrawal::SIGNATURE_HASH,
+        ];
         let mut log_filter = Filter::new()
             .address(self.address)
-            .event_signature(event_sig);
+            .event_signature(event_sigs);
 
         if let Some(from) = filter.from_block {
             log_filter = log_filter.from_block(from);
@@ -421,10 +1360,8 @@ impl<P: Provider + Clone> VaultReader<P> {
 
         let mut events = Vec::new();
         for log in logs {
-            if let Ok(decoded) = log.log_decode::<IVeilocityVault::Withdrawal>() {
-                let block_number = log.block_number.unwrap_or(0);
-                let tx_hash = log.transaction_hash.unwrap_or_default();
-                events.push(WithdrawalEvent::from_log(&decoded.inner.data, block_number, tx_hash));
+            if let Some(VeilocityEvent::Withdrawal(event)) = VeilocityEvent::decode_log(&log) {
+                events.push(event);
             }
         }
 
@@ -457,32 +1394,102 @@ impl<P: Provider + Clone> VaultReader<P> {
         let mut events = Vec::new();
         for log in logs {
             if let Ok(decoded) = log.log_decode::<IVeilocityVault::StateRootUpdated>() {
-                let block_number = log.block_number.unwrap_or(0);
-                let tx_hash = log.transaction_hash.unwrap_or_default();
-                events.push(StateRootUpdatedEvent::from_log(&decoded.inner.data, block_number, tx_hash));
+                events.push(StateRootUpdatedEvent::from_log(
+                    &decoded.inner.data,
+                    &LogMeta::of(&log),
+                ));
             }
         }
 
         Ok(events)
     }
 
-    /// Fetch all events in a block range
+    /// Fetch all events in a block range, in chain order
+    ///
+    /// Uses a single log filter for every event type, splitting the range if
+    /// the node rejects it as too large.
     pub async fn get_all_events(
         &self,
         filter: &EventFilter,
     ) -> Result<Vec<VeilocityEvent>, ContractError> {
-        let deposits = self.get_deposit_events(filter).await?;
-        let withdrawals = self.get_withdrawal_events(filter).await?;
-        let state_updates = self.get_state_root_events(filter).await?;
-
-        let mut events: Vec<VeilocityEvent> = Vec::new();
-        events.extend(deposits.into_iter().map(VeilocityEvent::Deposit));
-        events.extend(withdrawals.into_iter().map(VeilocityEvent::Withdrawal));
-        events.extend(state_updates.into_iter().map(VeilocityEvent::StateRootUpdated));
+        let from_block = filter.from_block.unwrap_or(0);
+        let to_block = match filter.to_block {
+            Some(to) => to,
+            None => self.get_block_number().await?,
+        };
+
+        self.event_stream(from_block)
+            .fetch_range(from_block, to_block)
+            .await
+    }
 
-        // Sort by block number
-        events.sort_by_key(|e| e.block_number());
+    /// Stream all vault events starting at `from_block`
+    pub fn event_stream(&self, from_block: u64) -> EventStream<P> {
+        EventStream::new((*self.provider).clone(), self.address, from_block)
+    }
+}
 
-        Ok(events)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_transition_public_inputs() {
+        let old_root = B256::repeat_byte(1);
+        let new_root = B256::repeat_byte(2);
+        let nullifiers = [B256::repeat_byte(3), B256::repeat_byte(4)];
+
+        let first_deposit = U256::from(7);
+        let commitments = [B256::repeat_byte(6)];
+
+        let inputs = state_transition_public_inputs(
+            old_root,
+            new_root,
+            &nullifiers,
+            first_deposit,
+            &commitments,
+        )
+        .unwrap();
+        assert_eq!(inputs.len(), 3 + TRANSFER_BATCH_SIZE + DEPOSIT_BATCH_SIZE);
+        assert_eq!(
+            &inputs[..4],
+            &[old_root, new_root, nullifiers[0], nullifiers[1]]
+        );
+        assert!(inputs[4..2 + TRANSFER_BATCH_SIZE]
+            .iter()
+            .all(|n| *n == B256::ZERO));
+        let deposits = &inputs[2 + TRANSFER_BATCH_SIZE..];
+        assert_eq!(deposits[0], B256::with_last_byte(7));
+        assert_eq!(deposits[1], commitments[0]);
+        assert!(deposits[2..].iter().all(|c| *c == B256::ZERO));
+
+        // A different claimed root or deposit is a different statement for the verifier
+        let forged = state_transition_public_inputs(
+            old_root,
+            B256::repeat_byte(9),
+            &nullifiers,
+            first_deposit,
+            &commitments,
+        );
+        assert_ne!(forged.unwrap(), inputs);
+        let forged = state_transition_public_inputs(
+            old_root,
+            new_root,
+            &nullifiers,
+            first_deposit,
+            &[B256::repeat_byte(9)],
+        );
+        assert_ne!(forged.unwrap(), inputs);
+
+        let too_many = vec![B256::repeat_byte(5); TRANSFER_BATCH_SIZE + 1];
+        assert!(matches!(
+            state_transition_public_inputs(old_root, new_root, &too_many, first_deposit, &[]),
+            Err(ContractError::InvalidBatch)
+        ));
+        let too_many = vec![B256::repeat_byte(5); DEPOSIT_BATCH_SIZE + 1];
+        assert!(matches!(
+            state_transition_public_inputs(old_root, new_root, &[], first_deposit, &too_many),
+            Err(ContractError::InvalidBatch)
+        ));
     }
 }
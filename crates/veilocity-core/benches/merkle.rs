@@ -0,0 +1,52 @@
+//! Sequential vs batched leaf insertion
+//!
+//! Inserting N leaves one by one costs `depth * N` Poseidon hashes; a batch
+//! hashes each touched internal node once, roughly `N + depth`.
+//!
+//! Run with `cargo bench -p veilocity-core --bench merkle`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use veilocity_core::poseidon::FieldElement;
+use veilocity_core::MerkleTree;
+
+fn bench_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merkle_insert");
+    group.sample_size(10);
+
+    for count in [10_000u64, 100_000] {
+        let leaves: Vec<FieldElement> = (0..count).map(FieldElement::from).collect();
+
+        group.bench_with_input(
+            BenchmarkId::new("sequential", count),
+            &leaves,
+            |b, leaves| {
+                b.iter_batched(
+                    MerkleTree::new,
+                    |mut tree| {
+                        for leaf in leaves {
+                            tree.insert(*leaf).unwrap();
+                        }
+                        tree
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+
+        group.bench_with_input(BenchmarkId::new("batch", count), &leaves, |b, leaves| {
+            b.iter_batched(
+                MerkleTree::new,
+                |mut tree| {
+                    tree.insert_batch(leaves).unwrap();
+                    tree
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_insert);
+criterion_main!(benches);
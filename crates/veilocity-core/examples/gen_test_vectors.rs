@@ -0,0 +1,23 @@
+//! Regenerate the Poseidon test vectors shared with the Noir circuits
+//!
+//! Writes `test-vectors/poseidon.json` and `circuits/src/test_vectors.nr`.
+//!
+//! ```bash
+//! cargo run -p veilocity-core --example gen_test_vectors
+//! ```
+
+use std::path::Path;
+use veilocity_core::test_vectors::{generate, to_noir};
+
+fn main() -> std::io::Result<()> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let vectors = generate();
+
+    let json = serde_json::to_string_pretty(&vectors)? + "\n";
+    std::fs::create_dir_all(root.join("test-vectors"))?;
+    std::fs::write(root.join("test-vectors/poseidon.json"), json)?;
+    std::fs::write(root.join("circuits/src/test_vectors.nr"), to_noir(&vectors))?;
+
+    println!("Wrote test-vectors/poseidon.json and circuits/src/test_vectors.nr");
+    Ok(())
+}
@@ -1,5 +1,7 @@
 //! Private account management
 
+use crate::asset::AssetId;
+use crate::merkle::LeafId;
 use crate::poseidon::{
     bytes_to_field, field_to_bytes, u128_to_field, u64_to_field, FieldElement, PoseidonHasher,
 };
@@ -20,11 +22,15 @@ pub struct PrivateAccount {
 
     /// Leaf index in Merkle tree
     pub index: u64,
+
+    /// Pool the balance is held in (native MNT unless set)
+    #[serde(default)]
+    pub asset: AssetId,
 }
 
 impl PrivateAccount {
     /// Create a new account from a secret
-    pub fn new(hasher: &mut PoseidonHasher, secret: &FieldElement, index: u64) -> Self {
+    pub fn new(hasher: &PoseidonHasher, secret: &FieldElement, index: u64) -> Self {
         let pubkey_field = hasher.derive_pubkey(secret);
 
         Self {
@@ -32,12 +38,13 @@ impl PrivateAccount {
             balance: 0,
             nonce: 0,
             index,
+            asset: AssetId::NATIVE,
         }
     }
 
     /// Create an account with initial balance (for deposits)
     pub fn with_balance(
-        hasher: &mut PoseidonHasher,
+        hasher: &PoseidonHasher,
         secret: &FieldElement,
         index: u64,
         balance: u128,
@@ -49,9 +56,16 @@ impl PrivateAccount {
             balance,
             nonce: 0,
             index,
+            asset: AssetId::NATIVE,
         }
     }
 
+    /// Hold the balance in `asset` instead of native MNT
+    pub fn with_asset(mut self, asset: AssetId) -> Self {
+        self.asset = asset;
+        self
+    }
+
     /// Get pubkey as field element
     pub fn pubkey_field(&self) -> FieldElement {
         bytes_to_field(&self.pubkey)
@@ -73,8 +87,29 @@ impl PrivateAccount {
     }
 
     /// Compute the leaf commitment for this account
-    pub fn compute_leaf(&self, hasher: &mut PoseidonHasher) -> FieldElement {
-        hasher.compute_leaf(&self.pubkey_field(), &self.balance_field(), &self.nonce_field())
+    ///
+    /// Token accounts bind their asset into the leaf; native accounts keep
+    /// the 3-input leaf.
+    pub fn compute_leaf(&self, hasher: &PoseidonHasher) -> FieldElement {
+        if self.asset.is_native() {
+            hasher.compute_leaf(&self.pubkey_field(), &self.balance_field(), &self.nonce_field())
+        } else {
+            hasher.compute_token_leaf(
+                &self.pubkey_field(),
+                &self.balance_field(),
+                &self.nonce_field(),
+                &self.asset.to_field(),
+            )
+        }
+    }
+
+    /// Compute the leaf this account has in its tree
+    ///
+    /// `index` is global, so the tree is derived from it; leaves in trees
+    /// after the first are bound to their tree id.
+    pub fn compute_tree_leaf(&self, hasher: &PoseidonHasher, tree_depth: usize) -> FieldElement {
+        let leaf = self.compute_leaf(hasher);
+        hasher.bind_leaf_to_tree(&leaf, LeafId::from_global(self.index, tree_depth).tree_id)
     }
 
     /// Credit balance (for deposits)
@@ -135,14 +170,18 @@ impl AccountSecret {
     }
 
     /// Derive public key
-    pub fn derive_pubkey(&self, hasher: &mut PoseidonHasher) -> FieldElement {
+    pub fn derive_pubkey(&self, hasher: &PoseidonHasher) -> FieldElement {
         hasher.derive_pubkey(&self.secret)
     }
 
     /// Compute nullifier for spending
+    ///
+    /// `index` is the account's `LeafId::nullifier_index`, the global index
+    /// the circuits compute, so accounts at the same position of different
+    /// trees get different nullifiers.
     pub fn compute_nullifier(
         &self,
-        hasher: &mut PoseidonHasher,
+        hasher: &PoseidonHasher,
         index: u64,
         nonce: u64,
     ) -> FieldElement {
@@ -152,11 +191,31 @@ impl AccountSecret {
     /// Compute deposit commitment
     pub fn compute_deposit_commitment(
         &self,
-        hasher: &mut PoseidonHasher,
+        hasher: &PoseidonHasher,
         amount: u128,
     ) -> FieldElement {
         hasher.compute_deposit_commitment(&self.secret, &u128_to_field(amount))
     }
+
+    /// Compute the deposit commitment for a deposit into `asset`'s pool
+    ///
+    /// Same as `compute_deposit_commitment` for native MNT.
+    pub fn compute_asset_deposit_commitment(
+        &self,
+        hasher: &PoseidonHasher,
+        amount: u128,
+        asset: &AssetId,
+    ) -> FieldElement {
+        if asset.is_native() {
+            self.compute_deposit_commitment(hasher, amount)
+        } else {
+            hasher.compute_token_deposit_commitment(
+                &self.secret,
+                &u128_to_field(amount),
+                &asset.to_field(),
+            )
+        }
+    }
 }
 
 #[cfg(test)]
@@ -165,9 +224,9 @@ mod tests {
 
     #[test]
     fn test_account_creation() {
-        let mut hasher = PoseidonHasher::new();
+        let hasher = PoseidonHasher::new();
         let secret = AccountSecret::generate();
-        let account = PrivateAccount::new(&mut hasher, secret.secret(), 0);
+        let account = PrivateAccount::new(&hasher, secret.secret(), 0);
 
         assert_eq!(account.balance, 0);
         assert_eq!(account.nonce, 0);
@@ -177,9 +236,9 @@ mod tests {
 
     #[test]
     fn test_credit_debit() {
-        let mut hasher = PoseidonHasher::new();
+        let hasher = PoseidonHasher::new();
         let secret = AccountSecret::generate();
-        let mut account = PrivateAccount::new(&mut hasher, secret.secret(), 0);
+        let mut account = PrivateAccount::new(&hasher, secret.secret(), 0);
 
         // Credit
         account.credit(1_000_000_000_000_000_000); // 1 MNT
@@ -199,26 +258,82 @@ mod tests {
 
     #[test]
     fn test_leaf_computation() {
-        let mut hasher = PoseidonHasher::new();
+        let hasher = PoseidonHasher::new();
         let secret = AccountSecret::generate();
-        let mut account = PrivateAccount::new(&mut hasher, secret.secret(), 0);
+        let mut account = PrivateAccount::new(&hasher, secret.secret(), 0);
 
-        let leaf1 = account.compute_leaf(&mut hasher);
+        let leaf1 = account.compute_leaf(&hasher);
 
         account.credit(1_000_000_000_000_000_000);
-        let leaf2 = account.compute_leaf(&mut hasher);
+        let leaf2 = account.compute_leaf(&hasher);
 
         assert_ne!(leaf1, leaf2); // Leaf changes when balance changes
     }
 
+    #[test]
+    fn test_asset_binding() {
+        let hasher = PoseidonHasher::new();
+        let secret = AccountSecret::generate();
+        let usdc = AssetId([0x09; 20]);
+        let usdt = AssetId([0x20; 20]);
+
+        let native = PrivateAccount::with_balance(&hasher, secret.secret(), 0, 100);
+        let token = native.clone().with_asset(usdc);
+
+        // Same balance in different pools gives different leaves
+        assert_ne!(native.compute_leaf(&hasher), token.compute_leaf(&hasher));
+        assert_ne!(
+            token.compute_leaf(&hasher),
+            native.clone().with_asset(usdt).compute_leaf(&hasher)
+        );
+
+        // Native commitments are unchanged; token commitments bind the asset
+        assert_eq!(
+            secret.compute_asset_deposit_commitment(&hasher, 100, &AssetId::NATIVE),
+            secret.compute_deposit_commitment(&hasher, 100)
+        );
+        assert_ne!(
+            secret.compute_asset_deposit_commitment(&hasher, 100, &usdc),
+            secret.compute_asset_deposit_commitment(&hasher, 100, &usdt)
+        );
+    }
+
+    #[test]
+    fn test_tree_binding() {
+        let hasher = PoseidonHasher::new();
+        let secret = AccountSecret::generate();
+
+        // Depth 2: global index 4 is leaf 0 of tree 1
+        let first = PrivateAccount::with_balance(&hasher, secret.secret(), 0, 100);
+        let second = PrivateAccount::with_balance(&hasher, secret.secret(), 4, 100);
+
+        // Tree 0 keeps the bare leaf; later trees bind their id
+        assert_eq!(
+            first.compute_tree_leaf(&hasher, 2),
+            first.compute_leaf(&hasher)
+        );
+        assert_ne!(
+            second.compute_tree_leaf(&hasher, 2),
+            second.compute_leaf(&hasher)
+        );
+
+        // Same in-tree index and nonce, different trees, different nullifiers
+        let first_id = LeafId::from_global(first.index, 2);
+        let second_id = LeafId::from_global(second.index, 2);
+        assert_ne!(
+            secret.compute_nullifier(&hasher, first_id.nullifier_index(), 0),
+            secret.compute_nullifier(&hasher, second_id.nullifier_index(), 0)
+        );
+    }
+
     #[test]
     fn test_nullifier_uniqueness() {
-        let mut hasher = PoseidonHasher::new();
+        let hasher = PoseidonHasher::new();
         let secret = AccountSecret::generate();
 
-        let null1 = secret.compute_nullifier(&mut hasher, 0, 0);
-        let null2 = secret.compute_nullifier(&mut hasher, 0, 1);
-        let null3 = secret.compute_nullifier(&mut hasher, 1, 0);
+        let null1 = secret.compute_nullifier(&hasher, 0, 0);
+        let null2 = secret.compute_nullifier(&hasher, 0, 1);
+        let null3 = secret.compute_nullifier(&hasher, 1, 0);
 
         assert_ne!(null1, null2);
         assert_ne!(null1, null3);
@@ -227,15 +342,15 @@ mod tests {
 
     #[test]
     fn test_secret_pubkey_consistency() {
-        let mut hasher = PoseidonHasher::new();
+        let hasher = PoseidonHasher::new();
         let secret = AccountSecret::generate();
 
-        let pubkey1 = secret.derive_pubkey(&mut hasher);
-        let pubkey2 = secret.derive_pubkey(&mut hasher);
+        let pubkey1 = secret.derive_pubkey(&hasher);
+        let pubkey2 = secret.derive_pubkey(&hasher);
 
         assert_eq!(pubkey1, pubkey2);
 
-        let account = PrivateAccount::new(&mut hasher, secret.secret(), 0);
+        let account = PrivateAccount::new(&hasher, secret.secret(), 0);
         assert_eq!(pubkey1, account.pubkey_field());
     }
 }
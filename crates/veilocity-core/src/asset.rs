@@ -0,0 +1,105 @@
+//! Asset identifiers for multi-asset pools
+//!
+//! Every pool in the vault is keyed by its ERC-20 token address; native MNT
+//! uses the zero address. Token accounts and token deposit commitments hash
+//! the asset in as a field element, so a balance in one token can never be
+//! spent as another. Native accounts keep the original asset-free hashes.
+
+use crate::error::CoreError;
+use crate::poseidon::{bytes_to_field, FieldElement};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Token address identifying a pool (the zero address for native MNT)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AssetId(pub [u8; 20]);
+
+impl AssetId {
+    /// Native MNT, deposited with `msg.value`
+    pub const NATIVE: AssetId = AssetId([0u8; 20]);
+
+    /// Whether this is native MNT rather than a token
+    pub fn is_native(&self) -> bool {
+        *self == Self::NATIVE
+    }
+
+    /// Address as a field element, as the circuits and the vault encode it
+    pub fn to_field(&self) -> FieldElement {
+        let mut bytes = [0u8; 32];
+        bytes[12..].copy_from_slice(&self.0);
+        bytes_to_field(&bytes)
+    }
+
+    /// Raw address bytes
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+}
+
+impl From<[u8; 20]> for AssetId {
+    fn from(bytes: [u8; 20]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl fmt::Display for AssetId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl FromStr for AssetId {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s.strip_prefix("0x").unwrap_or(s))
+            .map_err(|_| CoreError::InvalidAsset(s.to_string()))?;
+        let bytes: [u8; 20] = bytes
+            .try_into()
+            .map_err(|_| CoreError::InvalidAsset(s.to_string()))?;
+        Ok(Self(bytes))
+    }
+}
+
+impl Serialize for AssetId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AssetId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poseidon::field_to_bytes;
+
+    #[test]
+    fn test_asset_roundtrip() {
+        let usdc: AssetId = "0x09Bc4E0D864854c6aFB6eB9A9cdF58aC190D0dF9".parse().unwrap();
+        assert!(!usdc.is_native());
+        assert_eq!(usdc.to_string().parse::<AssetId>().unwrap(), usdc);
+
+        let json = serde_json::to_string(&usdc).unwrap();
+        assert_eq!(serde_json::from_str::<AssetId>(&json).unwrap(), usdc);
+
+        assert!("0x1234".parse::<AssetId>().is_err());
+        assert!(AssetId::NATIVE.is_native());
+    }
+
+    #[test]
+    fn test_asset_field_matches_vault_encoding() {
+        // bytes32(uint256(uint160(token))): the address right-aligned in 32 bytes
+        let asset = AssetId([0xab; 20]);
+        let bytes = field_to_bytes(&asset.to_field());
+        assert_eq!(&bytes[..12], &[0u8; 12]);
+        assert_eq!(&bytes[12..], &[0xab; 20]);
+        assert_eq!(AssetId::NATIVE.to_field(), FieldElement::from(0u64));
+    }
+}
@@ -14,6 +14,9 @@ pub enum CoreError {
     #[error("Nullifier already used: {0}")]
     NullifierUsed(String),
 
+    #[error("Nullifier not found: {0}")]
+    NullifierNotFound(String),
+
     #[error("Database error: {0}")]
     Database(#[from] rusqlite::Error),
 
@@ -26,9 +29,46 @@ pub enum CoreError {
     #[error("Tree full")]
     TreeFull,
 
+    #[error("Invalid tree depth {0} (must be 1-32)")]
+    InvalidTreeDepth(usize),
+
+    #[error("Tree depth mismatch: expected {expected}, found {found}")]
+    TreeDepthMismatch { expected: usize, found: usize },
+
+    #[error("Leaf index out of range: {0}")]
+    LeafIndexOutOfRange(u64),
+
+    #[error("Unknown tree: {0}")]
+    UnknownTree(u32),
+
+    #[error("Root not in recent history: {0}")]
+    UnknownRoot(String),
+
+    /// Boxed: `redb::Error` would make every `Result<_, CoreError>` far larger
+    #[error("Tree store error: {0}")]
+    TreeStore(Box<redb::Error>),
+
+    #[error("Corrupted leaf store: {0}")]
+    CorruptedLeaves(String),
+
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+
+    #[error("Transaction not found: {0}")]
+    TransactionNotFound(u64),
+
     #[error("Invalid secret key")]
     InvalidSecretKey,
 
+    #[error("Invalid asset address: {0}")]
+    InvalidAsset(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
+
+impl From<redb::Error> for CoreError {
+    fn from(err: redb::Error) -> Self {
+        CoreError::TreeStore(Box::new(err))
+    }
+}
@@ -4,13 +4,20 @@
 
 pub mod poseidon;
 pub mod merkle;
+pub mod tree_store;
+pub mod nullifier_tree;
 pub mod account;
+pub mod asset;
 pub mod state;
 pub mod transaction;
 pub mod error;
+pub mod test_vectors;
 
 pub use error::CoreError;
 pub use poseidon::PoseidonHasher;
 pub use merkle::MerkleTree;
+pub use tree_store::{MemoryStore, RedbStore, TreeStore};
+pub use nullifier_tree::NullifierTree;
 pub use account::PrivateAccount;
-pub use state::StateManager;
+pub use asset::AssetId;
+pub use state::{StateDelta, StateManager};
@@ -1,63 +1,217 @@
 //! Incremental Merkle Tree implementation
 //!
-//! Depth 20 supports ~1M accounts. Uses Poseidon hash.
+//! Depth is chosen at runtime (20 by default, ~1M accounts). Uses Poseidon
+//! hash. Nodes are kept in a pluggable `TreeStore` (see `tree_store`).
 
 use crate::error::CoreError;
-use crate::poseidon::{FieldElement, PoseidonHasher};
-use std::collections::HashMap;
-
-/// Tree depth (supports 2^20 = ~1M leaves)
+use crate::poseidon::{self, field_to_hex, FieldElement, PoseidonHasher};
+use crate::tree_store::{MemoryStore, NodeUpdate, TreeStore};
+use ark_ff::MontFp;
+use rayon::prelude::*;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// Default tree depth (supports 2^20 = ~1M leaves, matches the circuits)
 pub const TREE_DEPTH: usize = 20;
 
-/// Maximum number of leaves
+/// Maximum number of leaves in a default-depth tree
 pub const MAX_LEAVES: u64 = 1 << TREE_DEPTH;
 
+/// Largest supported tree depth
+pub const MAX_TREE_DEPTH: usize = 32;
+
+/// Recent roots kept by default (matches the vault's `ROOT_HISTORY_SIZE`)
+pub const ROOT_HISTORY_SIZE: usize = 100;
+
+/// Minimum number of parents hashed per rayon job in `insert_batch`
+const PARALLEL_MIN_NODES: usize = 256;
+
+/// Empty subtree hashes for every level up to `MAX_TREE_DEPTH`
+///
+/// `EMPTY_HASHES[0] = hash2(0, 0)` and
+/// `EMPTY_HASHES[i] = hash2(EMPTY_HASHES[i - 1], EMPTY_HASHES[i - 1])`.
+/// They do not depend on the tree depth, so they are shared by every tree.
+pub static EMPTY_HASHES: [FieldElement; MAX_TREE_DEPTH + 1] = [
+    MontFp!("14744269619966411208579211824598458697587494354926760081771325075741142829156"),
+    MontFp!("7423237065226347324353380772367382631490014989348495481811164164159255474657"),
+    MontFp!("11286972368698509976183087595462810875513684078608517520839298933882497716792"),
+    MontFp!("3607627140608796879659380071776844901612302623152076817094415224584923813162"),
+    MontFp!("19712377064642672829441595136074946683621277828620209496774504837737984048981"),
+    MontFp!("20775607673010627194014556968476266066927294572720319469184847051418138353016"),
+    MontFp!("3396914609616007258851405644437304192397291162432396347162513310381425243293"),
+    MontFp!("21551820661461729022865262380882070649935529853313286572328683688269863701601"),
+    MontFp!("6573136701248752079028194407151022595060682063033565181951145966236778420039"),
+    MontFp!("12413880268183407374852357075976609371175688755676981206018884971008854919922"),
+    MontFp!("14271763308400718165336499097156975241954733520325982997864342600795471836726"),
+    MontFp!("20066985985293572387227381049700832219069292839614107140851619262827735677018"),
+    MontFp!("9394776414966240069580838672673694685292165040808226440647796406499139370960"),
+    MontFp!("11331146992410411304059858900317123658895005918277453009197229807340014528524"),
+    MontFp!("15819538789928229930262697811477882737253464456578333862691129291651619515538"),
+    MontFp!("19217088683336594659449020493828377907203207941212636669271704950158751593251"),
+    MontFp!("21035245323335827719745544373081896983162834604456827698288649288827293579666"),
+    MontFp!("6939770416153240137322503476966641397417391950902474480970945462551409848591"),
+    MontFp!("10941962436777715901943463195175331263348098796018438960955633645115732864202"),
+    MontFp!("15019797232609675441998260052101280400536945603062888308240081994073687793470"),
+    MontFp!("11702828337982203149177882813338547876343922920234831094975924378932809409969"),
+    MontFp!("11217067736778784455593535811108456786943573747466706329920902520905755780395"),
+    MontFp!("16072238744996205792852194127671441602062027943016727953216607508365787157389"),
+    MontFp!("17681057402012993898104192736393849603097507831571622013521167331642182653248"),
+    MontFp!("21694045479371014653083846597424257852691458318143380497809004364947786214945"),
+    MontFp!("8163447297445169709687354538480474434591144168767135863541048304198280615192"),
+    MontFp!("14081762237856300239452543304351251708585712948734528663957353575674639038357"),
+    MontFp!("16619959921569409661790279042024627172199214148318086837362003702249041851090"),
+    MontFp!("7022159125197495734384997711896547675021391130223237843255817587255104160365"),
+    MontFp!("4114686047564160449611603615418567457008101555090703535405891656262658644463"),
+    MontFp!("12549363297364877722388257367377629555213421373705596078299904496781819142130"),
+    MontFp!("21443572485391568159800782191812935835534334817699172242223315142338162256601"),
+    MontFp!("7694308195910501081009121293114024464085863242234210875116972222894508088593"),
+];
+
+/// Check that `depth` is a supported tree depth
+pub fn validate_depth(depth: usize) -> Result<(), CoreError> {
+    if depth == 0 || depth > MAX_TREE_DEPTH {
+        return Err(CoreError::InvalidTreeDepth(depth));
+    }
+    Ok(())
+}
+
+/// Position of a leaf across rolled-over trees
+///
+/// When a tree fills up it is sealed and a new one starts. Leaves are numbered
+/// globally, so global index `i` lives in tree `i >> depth` at index
+/// `i % 2^depth`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LeafId {
+    /// Tree the leaf belongs to (0 for the first tree)
+    pub tree_id: u32,
+    /// Index within that tree
+    pub index: u64,
+}
+
+impl LeafId {
+    /// Split a global leaf index for trees of the given depth
+    pub fn from_global(global: u64, depth: usize) -> Self {
+        Self {
+            tree_id: (global >> depth) as u32,
+            index: global & ((1u64 << depth) - 1),
+        }
+    }
+
+    /// Global leaf index for trees of the given depth
+    pub fn to_global(self, depth: usize) -> u64 {
+        ((self.tree_id as u64) << depth) | self.index
+    }
+
+    /// Index nullifiers are derived from: `tree_id * 2^TREE_DEPTH + index`
+    ///
+    /// The circuits count tree ids in units of their own depth (their
+    /// `TREE_SIZE`), whatever depth the local trees have, so this can differ
+    /// from the global index once trees roll over.
+    pub fn nullifier_index(self) -> u64 {
+        ((self.tree_id as u64) << TREE_DEPTH) + self.index
+    }
+}
+
+/// Membership proof for a leaf in one specific tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// Which tree and where in it
+    pub leaf: LeafId,
+    /// Root of that tree the path leads to
+    pub root: FieldElement,
+    /// Sibling hashes from the leaf up
+    pub path: Vec<FieldElement>,
+}
+
+/// A past root together with the number of leaves the tree had then
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoricalRoot {
+    pub root: FieldElement,
+    pub leaf_count: u64,
+    /// Nodes the leaf update that produced this root overwrote, with their
+    /// previous values (empty for appends)
+    overwritten: Vec<NodeUpdate>,
+}
+
 /// Merkle tree with incremental updates
-pub struct MerkleTree {
+///
+/// Nodes live in a `TreeStore`; only the root, leaf count and a ring of
+/// recent roots are kept in memory.
+pub struct MerkleTree<S: TreeStore = MemoryStore> {
+    /// Number of levels below the root
+    depth: usize,
+
     /// Current number of leaves
     leaf_count: u64,
 
-    /// Precomputed empty subtree hashes at each level
-    empty_hashes: [FieldElement; TREE_DEPTH + 1],
-
-    /// Sparse storage: level -> index -> hash
-    /// Only stores non-empty nodes
-    nodes: Vec<HashMap<u64, FieldElement>>,
+    /// Node storage; only stores non-empty nodes
+    store: S,
 
     /// Current root
     root: FieldElement,
 
-    /// Hasher instance
-    hasher: PoseidonHasher,
+    /// Recent roots, oldest first; the last entry is the current root
+    root_history: VecDeque<HistoricalRoot>,
+
+    /// Maximum number of entries in `root_history`
+    root_history_size: usize,
 }
 
-impl MerkleTree {
-    /// Create a new empty Merkle tree
+impl MerkleTree<MemoryStore> {
+    /// Create a new empty in-memory Merkle tree of the default depth
     pub fn new() -> Self {
-        let mut hasher = PoseidonHasher::new();
+        Self::with_depth(TREE_DEPTH).expect("default depth is valid")
+    }
 
-        // Precomputed empty subtree hashes
-        // empty_hashes[0] = hash of empty leaf (hash2(0, 0))
-        // empty_hashes[i] = hash(empty_hashes[i-1], empty_hashes[i-1])
-        let mut empty_hashes = [FieldElement::from(0u64); TREE_DEPTH + 1];
-        empty_hashes[0] = hasher.hash2(&FieldElement::from(0u64), &FieldElement::from(0u64));
+    /// Create a new empty in-memory Merkle tree of the given depth
+    pub fn with_depth(depth: usize) -> Result<Self, CoreError> {
+        Self::with_store(MemoryStore::new(), depth)
+    }
+}
 
-        for i in 1..=TREE_DEPTH {
-            empty_hashes[i] = hasher.hash2(&empty_hashes[i - 1], &empty_hashes[i - 1]);
+impl<S: TreeStore> MerkleTree<S> {
+    /// Open a tree of the given depth over an existing store.
+    ///
+    /// Only the root and leaf count are read; nothing is re-hashed. A store
+    /// written with a different depth is rejected.
+    pub fn with_store(mut store: S, depth: usize) -> Result<Self, CoreError> {
+        validate_depth(depth)?;
+
+        match store.depth()? {
+            Some(stored) if stored != depth => {
+                return Err(CoreError::TreeDepthMismatch {
+                    expected: depth,
+                    found: stored,
+                })
+            }
+            Some(_) => {}
+            None => store.set_depth(depth)?,
         }
 
-        let root = empty_hashes[TREE_DEPTH];
-
-        // Initialize node storage for each level
-        let nodes = (0..=TREE_DEPTH).map(|_| HashMap::new()).collect();
+        let leaf_count = store.leaf_count()?;
+        let root = store.get_node(depth, 0)?.unwrap_or(EMPTY_HASHES[depth]);
 
-        Self {
-            leaf_count: 0,
-            empty_hashes,
-            nodes,
+        Ok(Self {
+            depth,
+            leaf_count,
+            store,
             root,
-            hasher,
+            root_history: VecDeque::from([HistoricalRoot {
+                root,
+                leaf_count,
+                overwritten: Vec::new(),
+            }]),
+            root_history_size: ROOT_HISTORY_SIZE,
+        })
+    }
+
+    /// Keep up to `size` recent roots (at least the current one)
+    pub fn with_root_history(mut self, size: usize) -> Self {
+        self.root_history_size = size.max(1);
+        while self.root_history.len() > self.root_history_size {
+            self.root_history.pop_front();
         }
+        self
     }
 
     /// Get the current root
@@ -70,104 +224,322 @@ impl MerkleTree {
         self.leaf_count
     }
 
+    /// Get the tree depth
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Maximum number of leaves this tree can hold
+    pub fn capacity(&self) -> u64 {
+        1 << self.depth
+    }
+
+    /// Whether every leaf slot is taken
+    pub fn is_full(&self) -> bool {
+        self.leaf_count >= self.capacity()
+    }
+
     /// Get the empty hash at a given level
     pub fn empty_hash(&self, level: usize) -> FieldElement {
-        self.empty_hashes[level]
+        EMPTY_HASHES[level]
+    }
+
+    /// Get a reference to the underlying store
+    pub fn store(&self) -> &S {
+        &self.store
     }
 
     /// Insert a new leaf and return its index
     pub fn insert(&mut self, leaf: FieldElement) -> Result<u64, CoreError> {
-        if self.leaf_count >= MAX_LEAVES {
+        if self.is_full() {
             return Err(CoreError::TreeFull);
         }
 
         let index = self.leaf_count;
-        self.update_leaf(index, leaf)?;
-        self.leaf_count += 1;
+        self.write_leaf(index, leaf, index + 1)?;
+        self.record_root(Vec::new());
 
         Ok(index)
     }
 
+    /// Append several leaves at once and return their index range
+    ///
+    /// Each affected internal node is hashed once, instead of once per leaf
+    /// below it as with repeated `insert` calls, and everything is written to
+    /// the store in a single update. Wide levels are hashed in parallel.
+    pub fn insert_batch(&mut self, leaves: &[FieldElement]) -> Result<Range<u64>, CoreError> {
+        let start = self.leaf_count;
+        let end = start + leaves.len() as u64;
+
+        if end > self.capacity() {
+            return Err(CoreError::TreeFull);
+        }
+        if leaves.is_empty() {
+            return Ok(start..end);
+        }
+
+        let mut updates: Vec<NodeUpdate> = Vec::with_capacity(leaves.len() * 2 + self.depth);
+        updates.extend(
+            leaves
+                .iter()
+                .enumerate()
+                .map(|(i, leaf)| (0, start + i as u64, *leaf)),
+        );
+
+        // Nodes changed at the current level cover indices lo..lo + level_nodes.len()
+        let mut lo = start;
+        let mut level_nodes = leaves.to_vec();
+
+        for level in 0..self.depth {
+            let hi = lo + level_nodes.len() as u64;
+            let parent_lo = lo / 2;
+            let parent_hi = (hi - 1) / 2 + 1;
+
+            // Only the two edge children can fall outside the changed range,
+            // so store reads stay on this thread and hashing fans out
+            let child = |index: u64| -> Result<FieldElement, CoreError> {
+                if (lo..hi).contains(&index) {
+                    Ok(level_nodes[(index - lo) as usize])
+                } else {
+                    self.node(level, index)
+                }
+            };
+            let pairs = (parent_lo..parent_hi)
+                .map(|parent| Ok((child(parent * 2)?, child(parent * 2 + 1)?)))
+                .collect::<Result<Vec<_>, CoreError>>()?;
+
+            let parents: Vec<FieldElement> = pairs
+                .par_iter()
+                .with_min_len(PARALLEL_MIN_NODES)
+                .map(|(left, right)| poseidon::hash2(left, right))
+                .collect();
+
+            updates.extend(
+                parents
+                    .iter()
+                    .enumerate()
+                    .map(|(i, hash)| (level + 1, parent_lo + i as u64, *hash)),
+            );
+
+            lo = parent_lo;
+            level_nodes = parents;
+        }
+
+        self.store.write_nodes(&updates, end)?;
+        self.leaf_count = end;
+        self.root = level_nodes[0];
+        self.record_root(Vec::new());
+
+        Ok(start..end)
+    }
+
     /// Update a leaf at the given index
+    ///
+    /// Only inserted leaves can be updated; new leaves go through `insert`.
+    /// The nodes on the leaf's path are recorded with the new root, so proofs
+    /// against earlier roots still in the history can be rebuilt.
     pub fn update_leaf(&mut self, index: u64, leaf: FieldElement) -> Result<(), CoreError> {
+        if index >= self.leaf_count {
+            return Err(CoreError::LeafIndexOutOfRange(index));
+        }
+
+        let overwritten = (0..=self.depth)
+            .map(|level| {
+                let node = index >> level;
+                Ok((level, node, self.node(level, node)?))
+            })
+            .collect::<Result<Vec<_>, CoreError>>()?;
+
+        self.write_leaf(index, leaf, self.leaf_count)?;
+        self.record_root(overwritten);
+        Ok(())
+    }
+
+    /// Push the current root onto the history ring
+    fn record_root(&mut self, overwritten: Vec<NodeUpdate>) {
+        if self.root_history.len() == self.root_history_size {
+            self.root_history.pop_front();
+        }
+        self.root_history.push_back(HistoricalRoot {
+            root: self.root,
+            leaf_count: self.leaf_count,
+            overwritten,
+        });
+    }
+
+    /// Recent roots, oldest first (the last one is the current root)
+    pub fn root_history(&self) -> impl Iterator<Item = &HistoricalRoot> {
+        self.root_history.iter()
+    }
+
+    /// Whether `root` is the current root or one still in the history ring
+    pub fn is_known_root(&self, root: &FieldElement) -> bool {
+        self.leaf_count_at(root).is_some()
+    }
+
+    /// Number of leaves the tree had when its root was `root`
+    pub fn leaf_count_at(&self, root: &FieldElement) -> Option<u64> {
+        self.history_position(root)
+            .map(|position| self.root_history[position].leaf_count)
+    }
+
+    /// Position of the latest history entry with `root`
+    fn history_position(&self, root: &FieldElement) -> Option<usize> {
+        self.root_history
+            .iter()
+            .rposition(|entry| entry.root == *root)
+    }
+
+    /// Get the Merkle proof for a leaf as of a historical root
+    ///
+    /// Siblings covering only leaves inserted before that root are read from
+    /// the store, or from the nodes later leaf updates overwrote; empty ones
+    /// use the empty hashes; the one subtree per level that straddles the old
+    /// leaf count is re-hashed from its leaves.
+    pub fn get_proof_at(
+        &self,
+        index: u64,
+        root: FieldElement,
+    ) -> Result<Vec<FieldElement>, CoreError> {
+        let position = self
+            .history_position(&root)
+            .ok_or_else(|| CoreError::UnknownRoot(field_to_hex(&root)))?;
+
+        if index >= self.root_history[position].leaf_count {
+            return Err(CoreError::LeafIndexOutOfRange(index));
+        }
+
+        let mut proof = Vec::with_capacity(self.depth);
+        let mut current_index = index;
+
+        for level in 0..self.depth {
+            proof.push(self.node_at(level, current_index ^ 1, position)?);
+            current_index /= 2;
+        }
+
+        // Guard against a store that no longer matches the recorded root
+        let leaf = self.node_at(0, index, position)?;
+        if !self.verify_proof(leaf, index, &proof, root) {
+            return Err(CoreError::InvalidMerkleProof);
+        }
+
+        Ok(proof)
+    }
+
+    /// Node hash as it was at the history entry at `position`
+    fn node_at(
+        &self,
+        level: usize,
+        index: u64,
+        position: usize,
+    ) -> Result<FieldElement, CoreError> {
+        let leaf_count = self.root_history[position].leaf_count;
+        let first_leaf = index << level;
+        let end_leaf = (index + 1) << level;
+
+        if first_leaf >= leaf_count {
+            // Entirely after the old leaf count: still empty back then
+            Ok(EMPTY_HASHES[level])
+        } else if end_leaf <= leaf_count {
+            // Entirely before it: appends never touch it, so it is either
+            // unchanged or saved by the first leaf update that overwrote it
+            let overwritten = self
+                .root_history
+                .iter()
+                .skip(position + 1)
+                .flat_map(|entry| entry.overwritten.iter())
+                .find(|(l, i, _)| *l == level && *i == index);
+            match overwritten {
+                Some((_, _, old)) => Ok(*old),
+                None => self.node(level, index),
+            }
+        } else {
+            let left = self.node_at(level - 1, index * 2, position)?;
+            let right = self.node_at(level - 1, index * 2 + 1, position)?;
+            Ok(poseidon::hash2(&left, &right))
+        }
+    }
+
+    /// Recompute the path from a leaf to the root and write it to the store
+    fn write_leaf(
+        &mut self,
+        index: u64,
+        leaf: FieldElement,
+        leaf_count: u64,
+    ) -> Result<(), CoreError> {
+        if index >= self.capacity() {
+            return Err(CoreError::LeafIndexOutOfRange(index));
+        }
+
+        let mut updates: Vec<NodeUpdate> = Vec::with_capacity(self.depth + 1);
+
         // Store the leaf at level 0
-        self.nodes[0].insert(index, leaf);
+        updates.push((0, index, leaf));
 
         // Update path from leaf to root
         let mut current_index = index;
         let mut current_hash = leaf;
 
-        for level in 0..TREE_DEPTH {
-            // Determine sibling index
-            let sibling_index = if current_index % 2 == 0 {
-                current_index + 1
-            } else {
-                current_index - 1
-            };
-
+        for level in 0..self.depth {
             // Get sibling hash (or empty hash if not present)
-            let sibling_hash = self.nodes[level]
-                .get(&sibling_index)
-                .copied()
-                .unwrap_or(self.empty_hashes[level]);
+            let sibling_hash = self.node(level, current_index ^ 1)?;
 
             // Compute parent hash
-            let parent_hash = if current_index % 2 == 0 {
-                self.hasher.hash2(&current_hash, &sibling_hash)
+            let parent_hash = if current_index.is_multiple_of(2) {
+                poseidon::hash2(&current_hash, &sibling_hash)
             } else {
-                self.hasher.hash2(&sibling_hash, &current_hash)
+                poseidon::hash2(&sibling_hash, &current_hash)
             };
 
             // Move to parent level
             let parent_index = current_index / 2;
-            self.nodes[level + 1].insert(parent_index, parent_hash);
+            updates.push((level + 1, parent_index, parent_hash));
 
             current_index = parent_index;
             current_hash = parent_hash;
         }
 
+        self.store.write_nodes(&updates, leaf_count)?;
+        self.leaf_count = leaf_count;
         self.root = current_hash;
         Ok(())
     }
 
+    /// Node hash at a level, falling back to the empty subtree hash
+    fn node(&self, level: usize, index: u64) -> Result<FieldElement, CoreError> {
+        Ok(self
+            .store
+            .get_node(level, index)?
+            .unwrap_or(EMPTY_HASHES[level]))
+    }
+
     /// Get the Merkle proof (path) for a leaf at the given index
-    pub fn get_proof(&self, index: u64) -> Vec<FieldElement> {
-        let mut proof = Vec::with_capacity(TREE_DEPTH);
+    pub fn get_proof(&self, index: u64) -> Result<Vec<FieldElement>, CoreError> {
+        let mut proof = Vec::with_capacity(self.depth);
         let mut current_index = index;
 
-        for level in 0..TREE_DEPTH {
-            let sibling_index = if current_index % 2 == 0 {
-                current_index + 1
-            } else {
-                current_index - 1
-            };
-
-            let sibling_hash = self.nodes[level]
-                .get(&sibling_index)
-                .copied()
-                .unwrap_or(self.empty_hashes[level]);
-
-            proof.push(sibling_hash);
+        for level in 0..self.depth {
+            proof.push(self.node(level, current_index ^ 1)?);
             current_index /= 2;
         }
 
-        proof
+        Ok(proof)
     }
 
     /// Get the leaf value at the given index
-    pub fn get_leaf(&self, index: u64) -> Option<FieldElement> {
-        self.nodes[0].get(&index).copied()
+    pub fn get_leaf(&self, index: u64) -> Result<Option<FieldElement>, CoreError> {
+        self.store.get_node(0, index)
     }
 
     /// Verify a Merkle proof
     pub fn verify_proof(
-        &mut self,
+        &self,
         leaf: FieldElement,
         index: u64,
         proof: &[FieldElement],
         root: FieldElement,
     ) -> bool {
-        if proof.len() != TREE_DEPTH {
+        if proof.len() != self.depth {
             return false;
         }
 
@@ -175,10 +547,10 @@ impl MerkleTree {
         let mut current_index = index;
 
         for sibling in proof.iter() {
-            current_hash = if current_index % 2 == 0 {
-                self.hasher.hash2(&current_hash, sibling)
+            current_hash = if current_index.is_multiple_of(2) {
+                poseidon::hash2(&current_hash, sibling)
             } else {
-                self.hasher.hash2(sibling, &current_hash)
+                poseidon::hash2(sibling, &current_hash)
             };
             current_index /= 2;
         }
@@ -186,9 +558,9 @@ impl MerkleTree {
         current_hash == root
     }
 
-    /// Static verification (without mutating hasher)
+    /// Static verification against a default-depth tree
     pub fn verify_proof_static(
-        hasher: &mut PoseidonHasher,
+        hasher: &PoseidonHasher,
         leaf: FieldElement,
         index: u64,
         proof: &[FieldElement],
@@ -202,7 +574,7 @@ impl MerkleTree {
         let mut current_index = index;
 
         for sibling in proof.iter() {
-            current_hash = if current_index % 2 == 0 {
+            current_hash = if current_index.is_multiple_of(2) {
                 hasher.hash2(&current_hash, sibling)
             } else {
                 hasher.hash2(sibling, &current_hash)
@@ -214,7 +586,7 @@ impl MerkleTree {
     }
 }
 
-impl Default for MerkleTree {
+impl Default for MerkleTree<MemoryStore> {
     fn default() -> Self {
         Self::new()
     }
@@ -241,7 +613,7 @@ mod tests {
         assert_eq!(index, 0);
         assert_eq!(tree.leaf_count(), 1);
 
-        let proof = tree.get_proof(index);
+        let proof = tree.get_proof(index).unwrap();
         assert_eq!(proof.len(), TREE_DEPTH);
 
         assert!(tree.verify_proof(leaf, index, &proof, tree.root()));
@@ -252,15 +624,15 @@ mod tests {
         let mut tree = MerkleTree::new();
 
         for i in 0..10 {
-            let leaf = FieldElement::from(i as u64);
+            let leaf = FieldElement::from(i);
             let index = tree.insert(leaf).unwrap();
             assert_eq!(index, i);
         }
 
         // Verify all proofs
         for i in 0..10 {
-            let leaf = FieldElement::from(i as u64);
-            let proof = tree.get_proof(i);
+            let leaf = FieldElement::from(i);
+            let proof = tree.get_proof(i).unwrap();
             assert!(tree.verify_proof(leaf, i, &proof, tree.root()));
         }
     }
@@ -279,11 +651,29 @@ mod tests {
 
         assert_ne!(root1, root2);
 
-        let proof = tree.get_proof(index);
+        let proof = tree.get_proof(index).unwrap();
         assert!(tree.verify_proof(leaf2, index, &proof, root2));
         assert!(!tree.verify_proof(leaf1, index, &proof, root2));
     }
 
+    #[test]
+    fn test_update_leaf_past_end_rejected() {
+        let mut tree = MerkleTree::new();
+        tree.insert(FieldElement::from(100u64)).unwrap();
+        let root = tree.root();
+
+        assert!(matches!(
+            tree.update_leaf(1, FieldElement::from(200u64)),
+            Err(CoreError::LeafIndexOutOfRange(1))
+        ));
+        assert!(tree.update_leaf(tree.capacity(), FieldElement::from(200u64)).is_err());
+
+        // Nothing was written
+        assert_eq!(tree.leaf_count(), 1);
+        assert_eq!(tree.root(), root);
+        assert_eq!(tree.root_history().count(), 2);
+    }
+
     #[test]
     fn test_invalid_proof() {
         let mut tree = MerkleTree::new();
@@ -291,7 +681,7 @@ mod tests {
         let leaf = FieldElement::from(12345u64);
         tree.insert(leaf).unwrap();
 
-        let proof = tree.get_proof(0);
+        let proof = tree.get_proof(0).unwrap();
 
         // Wrong leaf
         let wrong_leaf = FieldElement::from(99999u64);
@@ -301,6 +691,234 @@ mod tests {
         assert!(!tree.verify_proof(leaf, 1, &proof, tree.root()));
     }
 
+    #[test]
+    fn test_redb_tree_reopens_without_rehashing() {
+        use crate::tree_store::RedbStore;
+
+        let path = std::env::temp_dir().join(format!(
+            "veilocity-merkle-{}.redb",
+            hex::encode(rand::random::<[u8; 8]>())
+        ));
+
+        let mut memory = MerkleTree::new();
+        {
+            let mut tree =
+                MerkleTree::with_store(RedbStore::open(&path).unwrap(), TREE_DEPTH).unwrap();
+            for i in 0..5u64 {
+                tree.insert(FieldElement::from(i)).unwrap();
+                memory.insert(FieldElement::from(i)).unwrap();
+            }
+            assert_eq!(tree.root(), memory.root());
+        }
+
+        let mut tree =
+            MerkleTree::with_store(RedbStore::open(&path).unwrap(), TREE_DEPTH).unwrap();
+        assert_eq!(tree.leaf_count(), 5);
+        assert_eq!(tree.root(), memory.root());
+        assert_eq!(tree.get_proof(3).unwrap(), memory.get_proof(3).unwrap());
+
+        // Incremental updates continue from the stored state
+        tree.insert(FieldElement::from(5u64)).unwrap();
+        memory.insert(FieldElement::from(5u64)).unwrap();
+        assert_eq!(tree.root(), memory.root());
+
+        drop(tree);
+
+        // Reopening with another depth would misread every node
+        assert!(matches!(
+            MerkleTree::with_store(RedbStore::open(&path).unwrap(), 8),
+            Err(CoreError::TreeDepthMismatch { expected: 8, found: TREE_DEPTH })
+        ));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_insert_batch_matches_sequential() {
+        let leaves: Vec<FieldElement> = (0..37u64).map(FieldElement::from).collect();
+
+        let mut sequential = MerkleTree::new();
+        for leaf in &leaves {
+            sequential.insert(*leaf).unwrap();
+        }
+
+        // Start from a non-aligned offset so batches share nodes with earlier leaves
+        let mut batched = MerkleTree::new();
+        batched.insert(leaves[0]).unwrap();
+        assert_eq!(batched.insert_batch(&leaves[1..20]).unwrap(), 1..20);
+        assert_eq!(batched.insert_batch(&[]).unwrap(), 20..20);
+        assert_eq!(batched.insert_batch(&leaves[20..]).unwrap(), 20..37);
+
+        assert_eq!(batched.root(), sequential.root());
+        assert_eq!(batched.leaf_count(), 37);
+        for i in [0, 1, 19, 20, 36] {
+            assert_eq!(batched.get_proof(i).unwrap(), sequential.get_proof(i).unwrap());
+        }
+
+        let mut small = MerkleTree::with_depth(2).unwrap();
+        assert!(matches!(small.insert_batch(&leaves[..5]), Err(CoreError::TreeFull)));
+        assert_eq!(small.leaf_count(), 0);
+    }
+
+    #[test]
+    fn test_empty_hashes_table() {
+        let zero = FieldElement::from(0u64);
+        let mut expected = poseidon::hash2(&zero, &zero);
+        for empty in EMPTY_HASHES.iter() {
+            assert_eq!(*empty, expected);
+            expected = poseidon::hash2(&expected, &expected);
+        }
+    }
+
+    #[test]
+    fn test_parallel_batch_matches_sequential() {
+        // Wide enough that the lowest levels are hashed across threads
+        let leaves: Vec<FieldElement> = (0..1500u64).map(FieldElement::from).collect();
+
+        let mut sequential = MerkleTree::with_depth(12).unwrap();
+        for leaf in &leaves {
+            sequential.insert(*leaf).unwrap();
+        }
+
+        let mut batched = MerkleTree::with_depth(12).unwrap();
+        batched.insert_batch(&leaves[..3]).unwrap();
+        batched.insert_batch(&leaves[3..]).unwrap();
+
+        assert_eq!(batched.root(), sequential.root());
+        for i in [0, 2, 3, 777, 1499] {
+            assert_eq!(batched.get_proof(i).unwrap(), sequential.get_proof(i).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_get_proof_at_historical_root() {
+        let mut tree = MerkleTree::new().with_root_history(4);
+        let mut roots = Vec::new();
+
+        for i in 0..7u64 {
+            tree.insert(FieldElement::from(i + 1)).unwrap();
+            roots.push(tree.root());
+        }
+        assert_eq!(tree.root_history().count(), 4);
+
+        // Root after 5 leaves is still in the ring; after 3 it has been evicted
+        let old_root = roots[4];
+        assert_eq!(tree.leaf_count_at(&old_root), Some(5));
+        assert!(!tree.is_known_root(&roots[2]));
+
+        // Matches a tree that stopped at 5 leaves
+        let mut reference = MerkleTree::new();
+        for i in 0..5u64 {
+            reference.insert(FieldElement::from(i + 1)).unwrap();
+        }
+        for index in 0..5 {
+            let proof = tree.get_proof_at(index, old_root).unwrap();
+            assert_eq!(proof, reference.get_proof(index).unwrap());
+        }
+
+        assert!(matches!(
+            tree.get_proof_at(5, old_root),
+            Err(CoreError::LeafIndexOutOfRange(5))
+        ));
+        assert!(matches!(
+            tree.get_proof_at(0, roots[2]),
+            Err(CoreError::UnknownRoot(_))
+        ));
+
+        // Rewriting leaves keeps older roots and their paths, also when the
+        // same nodes are overwritten twice
+        let mut tree = tree.with_root_history(8);
+        tree.update_leaf(0, FieldElement::from(99u64)).unwrap();
+        tree.update_leaf(1, FieldElement::from(97u64)).unwrap();
+        tree.update_leaf(3, FieldElement::from(98u64)).unwrap();
+        assert!(tree.is_known_root(&old_root));
+        assert!(tree.is_known_root(&tree.root()));
+        for index in 0..5 {
+            let proof = tree.get_proof_at(index, old_root).unwrap();
+            assert_eq!(proof, reference.get_proof(index).unwrap());
+        }
+        let current = tree.root();
+        assert_eq!(tree.get_proof_at(3, current).unwrap(), tree.get_proof(3).unwrap());
+    }
+
+    #[test]
+    fn test_custom_depth() {
+        let mut tree = MerkleTree::with_depth(2).unwrap();
+        assert_eq!(tree.capacity(), 4);
+
+        for i in 0..4u64 {
+            tree.insert(FieldElement::from(i)).unwrap();
+        }
+        assert!(tree.is_full());
+        assert!(matches!(tree.insert(FieldElement::from(4u64)), Err(CoreError::TreeFull)));
+
+        let proof = tree.get_proof(3).unwrap();
+        assert_eq!(proof.len(), 2);
+        assert!(tree.verify_proof(FieldElement::from(3u64), 3, &proof, tree.root()));
+
+        assert!(MerkleTree::with_depth(0).is_err());
+        assert!(MerkleTree::with_depth(MAX_TREE_DEPTH + 1).is_err());
+    }
+
+    #[test]
+    fn test_leaf_id_roundtrip() {
+        let id = LeafId::from_global(9, 2);
+        assert_eq!(id, LeafId { tree_id: 2, index: 1 });
+        assert_eq!(id.to_global(2), 9);
+        assert_eq!(LeafId::from_global(5, TREE_DEPTH).tree_id, 0);
+    }
+
+    #[test]
+    fn test_nullifier_index_matches_circuits() {
+        // Every circuit sizes its trees and global indices from its own copy
+        // of these constants
+        let circuits = [
+            (
+                "src/merkle.nr",
+                include_str!("../../../circuits/src/merkle.nr"),
+            ),
+            (
+                "withdraw",
+                include_str!("../../../circuits/withdraw/src/main.nr"),
+            ),
+            (
+                "withdraw_batch",
+                include_str!("../../../circuits/withdraw_batch/src/main.nr"),
+            ),
+            (
+                "withdraw_token",
+                include_str!("../../../circuits/withdraw_token/src/main.nr"),
+            ),
+            (
+                "state_transition",
+                include_str!("../../../circuits/state_transition/src/main.nr"),
+            ),
+        ];
+        for (name, source) in circuits {
+            assert!(
+                source.contains(&format!("global TREE_DEPTH: u32 = {};", TREE_DEPTH)),
+                "{} does not use TREE_DEPTH {}",
+                name,
+                TREE_DEPTH
+            );
+            assert!(
+                source.contains(&format!("global TREE_SIZE: Field = {};", MAX_LEAVES)),
+                "{} does not use TREE_SIZE {}",
+                name,
+                MAX_LEAVES
+            );
+            assert!(source.contains("tree_id * TREE_SIZE + index"), "{}", name);
+        }
+
+        // The circuits' global index, whatever depth the local trees have
+        let id = LeafId::from_global(9, 2);
+        assert_eq!(id.nullifier_index(), 2 * MAX_LEAVES + 1);
+        assert_eq!(
+            LeafId::from_global(9, TREE_DEPTH).nullifier_index(),
+            LeafId::from_global(9, TREE_DEPTH).to_global(TREE_DEPTH)
+        );
+    }
+
     #[test]
     fn test_root_changes_on_insert() {
         let mut tree = MerkleTree::new();
@@ -0,0 +1,388 @@
+//! Indexed Merkle tree over spent nullifiers
+//!
+//! Each leaf commits to a nullifier and a pointer to the next larger one,
+//! forming a sorted linked list inside the tree. Membership is an ordinary
+//! Merkle path; non-membership of `v` is a path to the "low" leaf whose value
+//! is below `v` and whose successor is above it (or absent). Leaf 0 is the
+//! zero leaf, so every nullifier has a low leaf.
+
+use crate::error::CoreError;
+use crate::merkle::{MerkleTree, MAX_TREE_DEPTH};
+use crate::poseidon::{self, field_to_hex, u64_to_field, FieldElement};
+use std::collections::BTreeMap;
+
+/// Depth of the nullifier tree (up to 2^32 spent nullifiers)
+pub const NULLIFIER_TREE_DEPTH: usize = MAX_TREE_DEPTH;
+
+/// A leaf of the nullifier tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexedLeaf {
+    /// The nullifier (0 for the initial leaf)
+    pub value: FieldElement,
+    /// Tree index of the next larger nullifier (0 if none)
+    pub next_index: u64,
+    /// Value of the next larger nullifier (0 if none)
+    pub next_value: FieldElement,
+}
+
+impl IndexedLeaf {
+    /// The initial leaf at index 0
+    pub fn zero() -> Self {
+        Self {
+            value: FieldElement::from(0u64),
+            next_index: 0,
+            next_value: FieldElement::from(0u64),
+        }
+    }
+
+    /// Leaf commitment: hash3(value, next_index, next_value)
+    pub fn hash(&self) -> FieldElement {
+        poseidon::hash3(
+            &self.value,
+            &u64_to_field(self.next_index),
+            &self.next_value,
+        )
+    }
+
+    /// Whether this is the largest nullifier in the tree
+    pub fn is_last(&self) -> bool {
+        self.next_index == 0 && self.next_value == FieldElement::from(0u64)
+    }
+
+    /// Whether `value` falls strictly between this leaf and its successor
+    pub fn brackets(&self, value: &FieldElement) -> bool {
+        self.value < *value && (self.is_last() || *value < self.next_value)
+    }
+}
+
+/// Proof that a nullifier is (or is not) in the tree with a given root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NullifierProof {
+    /// The leaf holding the nullifier, or the low leaf for non-membership
+    pub leaf: IndexedLeaf,
+    /// Index of that leaf
+    pub index: u64,
+    /// Sibling hashes from the leaf up
+    pub path: Vec<FieldElement>,
+    /// Root the path leads to
+    pub root: FieldElement,
+}
+
+impl NullifierProof {
+    /// Check that the path leads from `leaf` to `root`
+    fn verify_path(&self) -> bool {
+        let mut current_hash = self.leaf.hash();
+        let mut current_index = self.index;
+
+        for sibling in &self.path {
+            current_hash = if current_index.is_multiple_of(2) {
+                poseidon::hash2(&current_hash, sibling)
+            } else {
+                poseidon::hash2(sibling, &current_hash)
+            };
+            current_index /= 2;
+        }
+
+        self.path.len() == NULLIFIER_TREE_DEPTH && current_hash == self.root
+    }
+
+    /// Verify that `value` is in the tree
+    pub fn verify_membership(&self, value: &FieldElement) -> bool {
+        self.leaf.value == *value && self.verify_path()
+    }
+
+    /// Verify that `value` is not in the tree
+    pub fn verify_non_membership(&self, value: &FieldElement) -> bool {
+        self.leaf.brackets(value) && self.verify_path()
+    }
+}
+
+/// Indexed Merkle tree of spent nullifiers
+pub struct NullifierTree {
+    /// Commitments to `leaves`
+    tree: MerkleTree,
+    /// Leaves by tree index
+    leaves: Vec<IndexedLeaf>,
+    /// Tree index by value, for finding low leaves
+    by_value: BTreeMap<FieldElement, u64>,
+}
+
+impl NullifierTree {
+    /// Create a tree holding only the zero leaf
+    pub fn new() -> Self {
+        Self::from_leaves(vec![IndexedLeaf::zero()]).expect("zero leaf is a valid tree")
+    }
+
+    /// Rebuild a tree from its leaves in index order
+    ///
+    /// Leaf 0 must hold the value 0 (its pointers change with every insert),
+    /// and following the pointers from it must visit every leaf once, in
+    /// increasing order.
+    pub fn from_leaves(leaves: Vec<IndexedLeaf>) -> Result<Self, CoreError> {
+        if leaves.first().map(|leaf| leaf.value) != Some(FieldElement::from(0u64)) {
+            return Err(CoreError::CorruptedLeaves(
+                "nullifier tree must start with the zero leaf".to_string(),
+            ));
+        }
+
+        let mut by_value = BTreeMap::new();
+        for (index, leaf) in leaves.iter().enumerate() {
+            if by_value.insert(leaf.value, index as u64).is_some() {
+                return Err(CoreError::CorruptedLeaves(format!(
+                    "duplicate nullifier {}",
+                    field_to_hex(&leaf.value)
+                )));
+            }
+        }
+
+        Self::check_linked_list(&leaves)?;
+
+        let mut tree = MerkleTree::with_depth(NULLIFIER_TREE_DEPTH)?;
+        let hashes: Vec<FieldElement> = leaves.iter().map(IndexedLeaf::hash).collect();
+        tree.insert_batch(&hashes)?;
+
+        Ok(Self {
+            tree,
+            leaves,
+            by_value,
+        })
+    }
+
+    /// Check that the leaves form one sorted list starting at leaf 0
+    ///
+    /// Values strictly increase along the list, so the walk visits each leaf
+    /// at most once and always ends.
+    fn check_linked_list(leaves: &[IndexedLeaf]) -> Result<(), CoreError> {
+        let mut index = 0;
+        let mut visited = 1;
+
+        while !leaves[index].is_last() {
+            let leaf = &leaves[index];
+            let linked = leaves
+                .get(leaf.next_index as usize)
+                .is_some_and(|next| next.value == leaf.next_value && next.value > leaf.value);
+            if !linked {
+                return Err(CoreError::CorruptedLeaves(format!(
+                    "leaf {} does not point to its successor",
+                    index
+                )));
+            }
+            index = leaf.next_index as usize;
+            visited += 1;
+        }
+
+        if visited != leaves.len() {
+            return Err(CoreError::CorruptedLeaves(format!(
+                "{} of {} leaves are not linked from the zero leaf",
+                leaves.len() - visited,
+                leaves.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Current root
+    pub fn root(&self) -> FieldElement {
+        self.tree.root()
+    }
+
+    /// Number of leaves, including the zero leaf
+    pub fn leaf_count(&self) -> u64 {
+        self.leaves.len() as u64
+    }
+
+    /// Leaf at a tree index
+    pub fn leaf(&self, index: u64) -> Option<&IndexedLeaf> {
+        self.leaves.get(index as usize)
+    }
+
+    /// Whether `value` has been inserted
+    pub fn contains(&self, value: &FieldElement) -> bool {
+        *value != FieldElement::from(0u64) && self.by_value.contains_key(value)
+    }
+
+    /// Index of the largest leaf whose value is below `value`
+    fn low_index(&self, value: &FieldElement) -> u64 {
+        // The zero leaf is below every non-zero value
+        self.by_value
+            .range(..*value)
+            .next_back()
+            .map(|(_, index)| *index)
+            .unwrap_or(0)
+    }
+
+    /// Insert a nullifier
+    ///
+    /// Returns the leaves written, as (index, leaf): the low leaf now pointing
+    /// at the new nullifier, then the new leaf itself.
+    pub fn insert(&mut self, value: FieldElement) -> Result<Vec<(u64, IndexedLeaf)>, CoreError> {
+        if value == FieldElement::from(0u64) {
+            return Err(CoreError::InvalidFieldElement);
+        }
+        if self.by_value.contains_key(&value) {
+            return Err(CoreError::NullifierUsed(field_to_hex(&value)));
+        }
+
+        let index = self.leaf_count();
+        if index >= self.tree.capacity() {
+            return Err(CoreError::TreeFull);
+        }
+        let low_index = self.low_index(&value);
+        let low = self.leaves[low_index as usize];
+
+        let new_leaf = IndexedLeaf {
+            value,
+            next_index: low.next_index,
+            next_value: low.next_value,
+        };
+        let low = IndexedLeaf {
+            next_index: index,
+            next_value: value,
+            ..low
+        };
+
+        self.tree.update_leaf(low_index, low.hash())?;
+        self.tree.insert(new_leaf.hash())?;
+
+        self.leaves[low_index as usize] = low;
+        self.leaves.push(new_leaf);
+        self.by_value.insert(value, index);
+
+        Ok(vec![(low_index, low), (index, new_leaf)])
+    }
+
+    /// Prove that `value` is in the tree
+    pub fn prove_membership(&self, value: &FieldElement) -> Result<NullifierProof, CoreError> {
+        let index = match self.by_value.get(value) {
+            Some(index) if *value != FieldElement::from(0u64) => *index,
+            _ => return Err(CoreError::NullifierNotFound(field_to_hex(value))),
+        };
+        self.proof(index)
+    }
+
+    /// Prove that `value` is not in the tree
+    pub fn prove_non_membership(&self, value: &FieldElement) -> Result<NullifierProof, CoreError> {
+        if *value == FieldElement::from(0u64) {
+            return Err(CoreError::InvalidFieldElement);
+        }
+        if self.by_value.contains_key(value) {
+            return Err(CoreError::NullifierUsed(field_to_hex(value)));
+        }
+        self.proof(self.low_index(value))
+    }
+
+    fn proof(&self, index: u64) -> Result<NullifierProof, CoreError> {
+        Ok(NullifierProof {
+            leaf: self.leaves[index as usize],
+            index,
+            path: self.tree.get_proof(index)?,
+            root: self.root(),
+        })
+    }
+}
+
+impl Default for NullifierTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sorted_links() {
+        let mut tree = NullifierTree::new();
+        for value in [30u64, 10, 20] {
+            tree.insert(FieldElement::from(value)).unwrap();
+        }
+
+        // 0 -> 10 -> 20 -> 30, by index 0 -> 2 -> 3 -> 1
+        let next: Vec<(u64, FieldElement)> = (0..4)
+            .map(|i| {
+                let leaf = tree.leaf(i).unwrap();
+                (leaf.next_index, leaf.next_value)
+            })
+            .collect();
+        assert_eq!(next[0], (2, FieldElement::from(10u64)));
+        assert_eq!(next[2], (3, FieldElement::from(20u64)));
+        assert_eq!(next[3], (1, FieldElement::from(30u64)));
+        assert!(tree.leaf(1).unwrap().is_last());
+
+        assert!(matches!(
+            tree.insert(FieldElement::from(20u64)),
+            Err(CoreError::NullifierUsed(_))
+        ));
+    }
+
+    #[test]
+    fn test_membership_and_non_membership() {
+        let mut tree = NullifierTree::new();
+        let empty_proof = tree
+            .prove_non_membership(&FieldElement::from(15u64))
+            .unwrap();
+        assert!(empty_proof.verify_non_membership(&FieldElement::from(15u64)));
+
+        tree.insert(FieldElement::from(10u64)).unwrap();
+        tree.insert(FieldElement::from(20u64)).unwrap();
+
+        let member = tree.prove_membership(&FieldElement::from(20u64)).unwrap();
+        assert!(member.verify_membership(&FieldElement::from(20u64)));
+        assert!(!member.verify_non_membership(&FieldElement::from(20u64)));
+
+        // Between two nullifiers, and above the largest one
+        for value in [15u64, 25] {
+            let value = FieldElement::from(value);
+            let proof = tree.prove_non_membership(&value).unwrap();
+            assert!(proof.verify_non_membership(&value));
+            assert!(!proof.verify_membership(&value));
+        }
+
+        // Every insert moves the root
+        assert_ne!(empty_proof.root, tree.root());
+        assert!(matches!(
+            tree.prove_non_membership(&FieldElement::from(10u64)),
+            Err(CoreError::NullifierUsed(_))
+        ));
+        assert!(matches!(
+            tree.prove_membership(&FieldElement::from(15u64)),
+            Err(CoreError::NullifierNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_leaves_matches_inserts() {
+        let mut tree = NullifierTree::new();
+        for value in [7u64, 3, 9, 5] {
+            tree.insert(FieldElement::from(value)).unwrap();
+        }
+
+        let leaves: Vec<IndexedLeaf> = (0..tree.leaf_count())
+            .map(|i| *tree.leaf(i).unwrap())
+            .collect();
+        let rebuilt = NullifierTree::from_leaves(leaves).unwrap();
+        assert_eq!(rebuilt.root(), tree.root());
+        assert!(rebuilt.contains(&FieldElement::from(9u64)));
+
+        assert!(NullifierTree::from_leaves(Vec::new()).is_err());
+
+        // A broken or partial list is rejected
+        let leaves: Vec<IndexedLeaf> = (0..tree.leaf_count())
+            .map(|i| *tree.leaf(i).unwrap())
+            .collect();
+        let mut unsorted = leaves.clone();
+        unsorted[0].next_value = FieldElement::from(4u64);
+        assert!(NullifierTree::from_leaves(unsorted).is_err());
+        let mut unlinked = leaves.clone();
+        unlinked.push(IndexedLeaf {
+            value: FieldElement::from(11u64),
+            next_index: 0,
+            next_value: FieldElement::from(0u64),
+        });
+        assert!(NullifierTree::from_leaves(unlinked).is_err());
+        let mut nonzero = leaves;
+        nonzero[0].value = FieldElement::from(1u64);
+        assert!(NullifierTree::from_leaves(nonzero).is_err());
+    }
+}
@@ -6,67 +6,100 @@
 use ark_bn254::Fr;
 use ark_ff::{BigInteger, PrimeField};
 use light_poseidon::{Poseidon, PoseidonHasher as LightPoseidonHasher};
+use std::cell::RefCell;
 
 /// Field element type (BN254 scalar field)
 pub type FieldElement = Fr;
 
-/// Poseidon hasher wrapper for consistent API
-pub struct PoseidonHasher {
-    hasher_1: Poseidon<Fr>,
-    hasher_2: Poseidon<Fr>,
-    hasher_3: Poseidon<Fr>,
-    hasher_4: Poseidon<Fr>,
+thread_local! {
+    /// Per-thread Circom Poseidon instances, one per arity
+    ///
+    /// `light_poseidon::Poseidon` needs `&mut self` to hash, so each thread
+    /// keeps its own set and the public API stays stateless.
+    static HASHERS: RefCell<[Poseidon<Fr>; 4]> = RefCell::new(
+        [1, 2, 3, 4].map(|width| Poseidon::<Fr>::new_circom(width).expect("Failed to create hasher")),
+    );
 }
 
+fn hash(inputs: &[FieldElement]) -> FieldElement {
+    HASHERS.with(|hashers| {
+        hashers.borrow_mut()[inputs.len() - 1]
+            .hash(inputs)
+            .expect("Hash failed")
+    })
+}
+
+/// Hash 1 field element
+pub fn hash1(a: &FieldElement) -> FieldElement {
+    hash(&[*a])
+}
+
+/// Hash 2 field elements (for Merkle tree nodes)
+pub fn hash2(left: &FieldElement, right: &FieldElement) -> FieldElement {
+    hash(&[*left, *right])
+}
+
+/// Hash 3 field elements (for account leaves)
+pub fn hash3(a: &FieldElement, b: &FieldElement, c: &FieldElement) -> FieldElement {
+    hash(&[*a, *b, *c])
+}
+
+/// Hash 4 field elements
+pub fn hash4(
+    a: &FieldElement,
+    b: &FieldElement,
+    c: &FieldElement,
+    d: &FieldElement,
+) -> FieldElement {
+    hash(&[*a, *b, *c, *d])
+}
+
+/// Poseidon hasher handle for a consistent API
+///
+/// Stateless and `Copy`; hashing goes through the free functions above, so the
+/// handle can be shared freely across threads.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PoseidonHasher;
+
 impl PoseidonHasher {
     pub fn new() -> Self {
-        Self {
-            hasher_1: Poseidon::<Fr>::new_circom(1).expect("Failed to create hasher"),
-            hasher_2: Poseidon::<Fr>::new_circom(2).expect("Failed to create hasher"),
-            hasher_3: Poseidon::<Fr>::new_circom(3).expect("Failed to create hasher"),
-            hasher_4: Poseidon::<Fr>::new_circom(4).expect("Failed to create hasher"),
-        }
+        Self
     }
 
     /// Hash 1 field element
-    pub fn hash1(&mut self, a: &FieldElement) -> FieldElement {
-        self.hasher_1.hash(&[*a]).expect("Hash failed")
+    pub fn hash1(&self, a: &FieldElement) -> FieldElement {
+        hash1(a)
     }
 
     /// Hash 2 field elements (for Merkle tree nodes)
-    pub fn hash2(&mut self, left: &FieldElement, right: &FieldElement) -> FieldElement {
-        self.hasher_2.hash(&[*left, *right]).expect("Hash failed")
+    pub fn hash2(&self, left: &FieldElement, right: &FieldElement) -> FieldElement {
+        hash2(left, right)
     }
 
     /// Hash 3 field elements (for account leaves)
-    pub fn hash3(
-        &mut self,
-        a: &FieldElement,
-        b: &FieldElement,
-        c: &FieldElement,
-    ) -> FieldElement {
-        self.hasher_3.hash(&[*a, *b, *c]).expect("Hash failed")
+    pub fn hash3(&self, a: &FieldElement, b: &FieldElement, c: &FieldElement) -> FieldElement {
+        hash3(a, b, c)
     }
 
     /// Hash 4 field elements
     pub fn hash4(
-        &mut self,
+        &self,
         a: &FieldElement,
         b: &FieldElement,
         c: &FieldElement,
         d: &FieldElement,
     ) -> FieldElement {
-        self.hasher_4.hash(&[*a, *b, *c, *d]).expect("Hash failed")
+        hash4(a, b, c, d)
     }
 
     /// Derive public key from secret
-    pub fn derive_pubkey(&mut self, secret: &FieldElement) -> FieldElement {
+    pub fn derive_pubkey(&self, secret: &FieldElement) -> FieldElement {
         self.hash1(secret)
     }
 
     /// Compute nullifier for spending
     pub fn compute_nullifier(
-        &mut self,
+        &self,
         secret: &FieldElement,
         index: &FieldElement,
         nonce: &FieldElement,
@@ -76,7 +109,7 @@ impl PoseidonHasher {
 
     /// Compute account leaf commitment
     pub fn compute_leaf(
-        &mut self,
+        &self,
         pubkey: &FieldElement,
         balance: &FieldElement,
         nonce: &FieldElement,
@@ -84,19 +117,46 @@ impl PoseidonHasher {
         self.hash3(pubkey, balance, nonce)
     }
 
+    /// Bind an account leaf to the tree holding it
+    ///
+    /// Tree 0 keeps the bare leaf; later trees use hash(leaf, tree_id), so a
+    /// leaf only verifies against the tree it was inserted into.
+    pub fn bind_leaf_to_tree(&self, leaf: &FieldElement, tree_id: u32) -> FieldElement {
+        if tree_id == 0 {
+            *leaf
+        } else {
+            self.hash2(leaf, &FieldElement::from(tree_id as u64))
+        }
+    }
+
     /// Compute deposit commitment
     pub fn compute_deposit_commitment(
-        &mut self,
+        &self,
         secret: &FieldElement,
         amount: &FieldElement,
     ) -> FieldElement {
         self.hash2(secret, amount)
     }
-}
 
-impl Default for PoseidonHasher {
-    fn default() -> Self {
-        Self::new()
+    /// Compute token account leaf commitment: hash(pubkey, balance, nonce, asset)
+    pub fn compute_token_leaf(
+        &self,
+        pubkey: &FieldElement,
+        balance: &FieldElement,
+        nonce: &FieldElement,
+        asset: &FieldElement,
+    ) -> FieldElement {
+        self.hash4(pubkey, balance, nonce, asset)
+    }
+
+    /// Compute token deposit commitment: hash(secret, amount, asset)
+    pub fn compute_token_deposit_commitment(
+        &self,
+        secret: &FieldElement,
+        amount: &FieldElement,
+        asset: &FieldElement,
+    ) -> FieldElement {
+        self.hash3(secret, amount, asset)
     }
 }
 
@@ -146,7 +206,7 @@ mod tests {
 
     #[test]
     fn test_hash2_deterministic() {
-        let mut hasher = PoseidonHasher::new();
+        let hasher = PoseidonHasher::new();
         let a = FieldElement::from(1u64);
         let b = FieldElement::from(2u64);
 
@@ -158,7 +218,7 @@ mod tests {
 
     #[test]
     fn test_hash2_different_inputs() {
-        let mut hasher = PoseidonHasher::new();
+        let hasher = PoseidonHasher::new();
         let a = FieldElement::from(1u64);
         let b = FieldElement::from(2u64);
         let c = FieldElement::from(3u64);
@@ -169,9 +229,25 @@ mod tests {
         assert_ne!(hash1, hash2);
     }
 
+    #[test]
+    fn test_hash_across_threads() {
+        let hasher = PoseidonHasher::new();
+        let a = FieldElement::from(1u64);
+        let b = FieldElement::from(2u64);
+        let expected = hasher.hash2(&a, &b);
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| std::thread::spawn(move || hasher.hash2(&a, &b)))
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+        assert_eq!(hash2(&a, &b), expected);
+    }
+
     #[test]
     fn test_derive_pubkey() {
-        let mut hasher = PoseidonHasher::new();
+        let hasher = PoseidonHasher::new();
         let secret = FieldElement::from(12345u64);
 
         let pubkey = hasher.derive_pubkey(&secret);
@@ -182,7 +258,7 @@ mod tests {
 
     #[test]
     fn test_compute_leaf() {
-        let mut hasher = PoseidonHasher::new();
+        let hasher = PoseidonHasher::new();
         let pubkey = FieldElement::from(100u64);
         let balance = u128_to_field(1_000_000_000_000_000_000u128); // 1 MNT
         let nonce = FieldElement::from(0u64);
@@ -210,7 +286,7 @@ mod tests {
 
     #[test]
     fn test_nullifier_uniqueness() {
-        let mut hasher = PoseidonHasher::new();
+        let hasher = PoseidonHasher::new();
         let secret = FieldElement::from(12345u64);
 
         let null1 = hasher.compute_nullifier(
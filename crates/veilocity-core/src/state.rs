@@ -1,36 +1,131 @@
 //! State management for the private execution layer
 
 use crate::account::{AccountSecret, PrivateAccount};
+use crate::asset::AssetId;
 use crate::error::CoreError;
-use crate::merkle::MerkleTree;
-use crate::poseidon::{bytes_to_field, u128_to_field, FieldElement, PoseidonHasher};
-use rusqlite::{params, Connection};
+use crate::merkle::{validate_depth, LeafId, MerkleProof, MerkleTree, TREE_DEPTH};
+use crate::nullifier_tree::{IndexedLeaf, NullifierProof, NullifierTree};
+use crate::tree_store::{MemoryStore, RedbStore, TreeStore};
+use crate::poseidon::{
+    bytes_to_field, field_to_bytes, FieldElement, PoseidonHasher,
+};
+use crate::transaction::{Transaction, TransactionFilter, TransactionStatus, TransactionType};
+use rusqlite::types::{Type, Value};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
 use std::collections::HashSet;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Merkle tree over whichever store the state manager was opened with
+pub type StateTree = MerkleTree<Box<dyn TreeStore>>;
+
+/// Changes that must land together, applied by `StateManager::apply`
+#[derive(Debug, Clone, Default)]
+pub struct StateDelta {
+    /// Accounts to write back (balance, nonce and their tree leaf)
+    pub accounts: Vec<PrivateAccount>,
+    /// Nullifiers to mark as used
+    pub nullifiers: Vec<[u8; 32]>,
+    /// Nullifiers of failed spends to release for reuse
+    pub released_nullifiers: Vec<[u8; 32]>,
+    /// Transactions to record (id 0) or update (existing id)
+    pub transactions: Vec<Transaction>,
+}
+
+impl StateDelta {
+    /// An empty delta
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_account(mut self, account: PrivateAccount) -> Self {
+        self.accounts.push(account);
+        self
+    }
+
+    pub fn with_nullifier(mut self, nullifier: [u8; 32]) -> Self {
+        self.nullifiers.push(nullifier);
+        self
+    }
+
+    pub fn with_released_nullifier(mut self, nullifier: [u8; 32]) -> Self {
+        self.released_nullifiers.push(nullifier);
+        self
+    }
+
+    pub fn with_transaction(mut self, tx: Transaction) -> Self {
+        self.transactions.push(tx);
+        self
+    }
+}
 
 /// State manager for Veilocity
+///
+/// Leaves are numbered globally across trees: when the active tree fills up
+/// it is sealed and a new one of the same depth starts (see `LeafId`).
 pub struct StateManager {
     /// SQLite connection for persistent storage
     db: Connection,
-    /// In-memory Merkle tree
-    tree: MerkleTree,
+    /// Merkle trees by tree id; the last one takes new leaves
+    trees: Vec<StateTree>,
+    /// Depth of every tree
+    tree_depth: usize,
+    /// Path of tree 0's store; `None` keeps the trees in memory
+    tree_path: Option<PathBuf>,
     /// Poseidon hasher
     hasher: PoseidonHasher,
     /// Set of used nullifiers (in-memory cache)
     used_nullifiers: HashSet<[u8; 32]>,
+    /// Commitment to the used nullifiers, for (non-)membership proofs
+    nullifier_tree: NullifierTree,
 }
 
 impl StateManager {
-    /// Create a new state manager with a database path
+    /// Create a new state manager with a database path and the default depth
+    ///
+    /// The Merkle tree nodes are kept in redb files next to the database
+    /// (`<db>.tree`, then `<db>.tree.1`, ... after rollover), so opening does
+    /// not re-hash the leaves.
     pub fn new(db_path: &Path) -> Result<Self, CoreError> {
+        Self::open(db_path, TREE_DEPTH)
+    }
+
+    /// Create a state manager whose trees have the given depth
+    ///
+    /// Fails if the database was created with a different depth.
+    pub fn open(db_path: &Path, tree_depth: usize) -> Result<Self, CoreError> {
         let db = Connection::open(db_path)?;
         Self::init_db(&db)?;
+        Self::with_db(db, Some(db_path.with_extension("tree")), tree_depth)
+    }
+
+    /// Create an in-memory state manager (for testing)
+    pub fn in_memory() -> Result<Self, CoreError> {
+        Self::in_memory_with_depth(TREE_DEPTH)
+    }
+
+    /// Create an in-memory state manager with the given tree depth
+    pub fn in_memory_with_depth(tree_depth: usize) -> Result<Self, CoreError> {
+        let db = Connection::open_in_memory()?;
+        Self::init_db(&db)?;
+        Self::with_db(db, None, tree_depth)
+    }
+
+    fn with_db(
+        db: Connection,
+        tree_path: Option<PathBuf>,
+        tree_depth: usize,
+    ) -> Result<Self, CoreError> {
+        validate_depth(tree_depth)?;
+        Self::check_tree_depth(&db, tree_depth)?;
 
         let mut manager = Self {
             db,
-            tree: MerkleTree::new(),
+            trees: Vec::new(),
+            tree_depth,
+            tree_path,
             hasher: PoseidonHasher::new(),
             used_nullifiers: HashSet::new(),
+            nullifier_tree: NullifierTree::new(),
         };
 
         // Load existing state from database
@@ -39,43 +134,21 @@ impl StateManager {
         Ok(manager)
     }
 
-    /// Create an in-memory state manager (for testing)
-    pub fn in_memory() -> Result<Self, CoreError> {
-        let db = Connection::open_in_memory()?;
-        Self::init_db(&db)?;
-
-        Ok(Self {
-            db,
-            tree: MerkleTree::new(),
-            hasher: PoseidonHasher::new(),
-            used_nullifiers: HashSet::new(),
-        })
-    }
-
     /// Initialize database schema
     fn init_db(db: &Connection) -> Result<(), CoreError> {
         db.execute_batch(
             "
-            -- Accounts table
+            -- Accounts table (one per pubkey and asset)
             CREATE TABLE IF NOT EXISTS accounts (
                 id INTEGER PRIMARY KEY,
-                pubkey BLOB NOT NULL UNIQUE,
+                pubkey BLOB NOT NULL,
                 balance_encrypted BLOB NOT NULL,
                 nonce INTEGER NOT NULL DEFAULT 0,
                 leaf_index INTEGER NOT NULL UNIQUE,
+                asset BLOB NOT NULL DEFAULT (zeroblob(20)),
                 created_at INTEGER NOT NULL,
-                updated_at INTEGER NOT NULL
-            );
-
-            -- Transactions table
-            CREATE TABLE IF NOT EXISTS transactions (
-                id INTEGER PRIMARY KEY,
-                tx_type TEXT NOT NULL,
-                nullifier BLOB,
-                data BLOB NOT NULL,
-                status TEXT NOT NULL,
-                created_at INTEGER NOT NULL,
-                updated_at INTEGER NOT NULL
+                updated_at INTEGER NOT NULL,
+                UNIQUE (pubkey, asset)
             );
 
             -- Nullifiers table (for double-spend prevention)
@@ -84,6 +157,27 @@ impl StateManager {
                 created_at INTEGER NOT NULL
             );
 
+            -- Nullifier tree leaves (a sorted linked list over the nullifiers)
+            CREATE TABLE IF NOT EXISTS nullifier_leaves (
+                leaf_index INTEGER PRIMARY KEY,
+                value BLOB NOT NULL,
+                next_index INTEGER NOT NULL,
+                next_value BLOB NOT NULL
+            );
+
+            -- Merkle leaves (every leaf in the tree, not just our accounts)
+            CREATE TABLE IF NOT EXISTS leaves (
+                leaf_index INTEGER PRIMARY KEY,
+                leaf BLOB NOT NULL
+            );
+
+            -- Merkle tree roots, written with the leaves so a tree store
+            -- left out of step by an interrupted write is caught on open
+            CREATE TABLE IF NOT EXISTS tree_roots (
+                tree_id INTEGER PRIMARY KEY,
+                root BLOB NOT NULL
+            );
+
             -- Sync state
             CREATE TABLE IF NOT EXISTS sync_state (
                 key TEXT PRIMARY KEY,
@@ -92,90 +186,502 @@ impl StateManager {
 
             -- Create indexes
             CREATE INDEX IF NOT EXISTS idx_accounts_leaf_index ON accounts(leaf_index);
-            CREATE INDEX IF NOT EXISTS idx_transactions_status ON transactions(status);
             ",
         )?;
 
+        db.execute_batch(TRANSACTIONS_SCHEMA)?;
+        Self::migrate_transactions(db)?;
+        Self::migrate_assets(db)?;
+        Self::migrate_spend_nonce(db)?;
+
         Ok(())
     }
 
-    /// Load state from database
-    fn load_state(&mut self) -> Result<(), CoreError> {
-        // Load accounts and rebuild Merkle tree
-        let mut stmt = self
-            .db
-            .prepare("SELECT pubkey, balance_encrypted, nonce, leaf_index FROM accounts ORDER BY leaf_index")?;
-
-        let accounts = stmt.query_map([], |row| {
-            let pubkey: Vec<u8> = row.get(0)?;
-            let balance_bytes: Vec<u8> = row.get(1)?;
-            let nonce: u64 = row.get(2)?;
-            let leaf_index: u64 = row.get(3)?;
-            Ok((pubkey, balance_bytes, nonce, leaf_index))
+    /// Add the asset columns to databases written before multi-asset support
+    ///
+    /// Existing accounts and transactions are native MNT. The accounts table
+    /// is rebuilt, since its pubkey uniqueness now includes the asset.
+    fn migrate_assets(db: &Connection) -> Result<(), CoreError> {
+        let has_column = |table: &str| -> Result<bool, CoreError> {
+            Ok(db.query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = 'asset'",
+                params![table],
+                |row| row.get(0),
+            )?)
+        };
+
+        if !has_column("transactions")? {
+            db.execute_batch("ALTER TABLE transactions ADD COLUMN asset BLOB")?;
+        }
+
+        if !has_column("accounts")? {
+            db.execute_batch(
+                "
+                BEGIN;
+                DROP INDEX IF EXISTS idx_accounts_leaf_index;
+                ALTER TABLE accounts RENAME TO accounts_legacy;
+                CREATE TABLE accounts (
+                    id INTEGER PRIMARY KEY,
+                    pubkey BLOB NOT NULL,
+                    balance_encrypted BLOB NOT NULL,
+                    nonce INTEGER NOT NULL DEFAULT 0,
+                    leaf_index INTEGER NOT NULL UNIQUE,
+                    asset BLOB NOT NULL DEFAULT (zeroblob(20)),
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL,
+                    UNIQUE (pubkey, asset)
+                );
+                INSERT INTO accounts (id, pubkey, balance_encrypted, nonce, leaf_index,
+                    created_at, updated_at)
+                SELECT id, pubkey, balance_encrypted, nonce, leaf_index, created_at, updated_at
+                FROM accounts_legacy;
+                DROP TABLE accounts_legacy;
+                CREATE INDEX idx_accounts_leaf_index ON accounts(leaf_index);
+                COMMIT;
+                ",
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Move history written with a JSON `data` column into typed columns
+    fn migrate_transactions(db: &Connection) -> Result<(), CoreError> {
+        let has_data: bool = db.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('transactions') WHERE name = 'data'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_data {
+            return Ok(());
+        }
+
+        db.execute_batch(
+            "
+            DROP INDEX IF EXISTS idx_transactions_status;
+            DROP INDEX IF EXISTS idx_transactions_type;
+            DROP INDEX IF EXISTS idx_transactions_created_at;
+            ALTER TABLE transactions RENAME TO transactions_legacy;
+            ",
+        )?;
+        db.execute_batch(TRANSACTIONS_SCHEMA)?;
+
+        let mut stmt = db.prepare(
+            "SELECT id, tx_type, nullifier, data, status, created_at, updated_at
+             FROM transactions_legacy",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<Vec<u8>>>(2)?,
+                row.get::<_, Vec<u8>>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, i64>(5)?,
+                row.get::<_, i64>(6)?,
+            ))
         })?;
 
-        for account in accounts {
-            let (pubkey, balance_bytes, nonce, leaf_index) = account?;
+        for row in rows {
+            let (id, tx_type, nullifier, data, status, created_at, updated_at) = row?;
+            let data: serde_json::Value = serde_json::from_slice(&data).unwrap_or_default();
+            let field = |key: &str| data.get(key).and_then(|v| v.as_str()).map(str::to_string);
+
+            let tx_hash = field("tx_hash").and_then(|h| hex::decode(h).ok());
+            db.execute(
+                "INSERT INTO transactions (id, tx_type, status, amount, nullifier, tx_hash,
+                 counterparty, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    id,
+                    tx_type,
+                    status,
+                    field("amount").unwrap_or_else(|| "0".to_string()),
+                    nullifier,
+                    tx_hash,
+                    field("recipient"),
+                    created_at,
+                    updated_at
+                ],
+            )?;
+        }
+        drop(stmt);
 
-            // Decode balance (stored as little-endian u128)
-            let balance = if balance_bytes.len() >= 16 {
-                let mut bytes = [0u8; 16];
-                bytes.copy_from_slice(&balance_bytes[..16]);
-                u128::from_le_bytes(bytes)
-            } else {
-                0
-            };
+        db.execute("DROP TABLE transactions_legacy", [])?;
+        Ok(())
+    }
+
+    /// Add the spend nonce column to history written before it was recorded
+    fn migrate_spend_nonce(db: &Connection) -> Result<(), CoreError> {
+        let has_nonce: bool = db.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('transactions') WHERE name = 'nonce'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_nonce {
+            db.execute_batch("ALTER TABLE transactions ADD COLUMN nonce INTEGER")?;
+        }
 
-            // Reconstruct account and compute leaf
-            let mut pubkey_arr = [0u8; 32];
-            if pubkey.len() == 32 {
-                pubkey_arr.copy_from_slice(&pubkey);
+        Ok(())
+    }
+
+    /// Record the tree depth on first open and reject a different one later
+    fn check_tree_depth(db: &Connection, tree_depth: usize) -> Result<(), CoreError> {
+        let stored: Option<Vec<u8>> = db
+            .query_row(
+                "SELECT value FROM sync_state WHERE key = 'tree_depth'",
+                [],
+                |row| row.get(0),
+            )
+            .ok();
+
+        let found = match stored {
+            Some(bytes) if bytes.len() >= 8 => {
+                let mut arr = [0u8; 8];
+                arr.copy_from_slice(&bytes[..8]);
+                Some(u64::from_le_bytes(arr) as usize)
             }
+            // Databases from before depth was configurable used the default
+            _ if Self::stored_leaf_count(db)? > 0 => Some(TREE_DEPTH),
+            _ => None,
+        };
 
-            let pubkey_field = bytes_to_field(&pubkey_arr);
-            let balance_field = u128_to_field(balance);
-            let nonce_field = FieldElement::from(nonce);
+        match found {
+            Some(found) if found != tree_depth => Err(CoreError::TreeDepthMismatch {
+                expected: tree_depth,
+                found,
+            }),
+            _ => {
+                let bytes = (tree_depth as u64).to_le_bytes();
+                db.execute(
+                    "INSERT OR REPLACE INTO sync_state (key, value) VALUES ('tree_depth', ?1)",
+                    params![bytes.as_slice()],
+                )?;
+                Ok(())
+            }
+        }
+    }
 
-            let leaf = self
-                .hasher
-                .compute_leaf(&pubkey_field, &balance_field, &nonce_field);
+    /// Location of a tree's on-disk store
+    fn tree_file(&self, tree_id: u32) -> Option<PathBuf> {
+        let base = self.tree_path.as_ref()?;
+        Some(if tree_id == 0 {
+            base.clone()
+        } else {
+            PathBuf::from(format!("{}.{}", base.display(), tree_id))
+        })
+    }
 
-            // Insert into tree at correct index
-            while self.tree.leaf_count() < leaf_index {
-                // Insert empty leaves to reach the correct index
-                let empty = self.hasher.hash2(&FieldElement::from(0u64), &FieldElement::from(0u64));
-                self.tree.insert(empty)?;
+    /// Open a tree's store (creating it if needed)
+    fn open_tree(&self, tree_id: u32) -> Result<StateTree, CoreError> {
+        let store: Box<dyn TreeStore> = match self.tree_file(tree_id) {
+            Some(path) => Box::new(RedbStore::open(&path)?),
+            None => Box::new(MemoryStore::new()),
+        };
+        MerkleTree::with_store(store, self.tree_depth)
+    }
+
+    /// Open an empty tree, discarding anything a stale store still holds
+    fn fresh_tree(&self, tree_id: u32) -> Result<StateTree, CoreError> {
+        let tree = self.open_tree(tree_id)?;
+        match self.tree_file(tree_id) {
+            Some(path) if tree.leaf_count() > 0 => {
+                drop(tree);
+                std::fs::remove_file(&path)?;
+                self.open_tree(tree_id)
             }
-            self.tree.insert(leaf)?;
+            _ => Ok(tree),
         }
+    }
+
+    /// Number of leaves in the `leaves` table
+    fn stored_leaf_count(db: &Connection) -> Result<u64, CoreError> {
+        let count: i64 = db.query_row("SELECT COUNT(*) FROM leaves", [], |row| row.get(0))?;
+        Ok(count as u64)
+    }
 
-        // Load nullifiers
-        let mut stmt = self.db.prepare("SELECT nullifier FROM nullifiers")?;
+    /// Load state from database
+    fn load_state(&mut self) -> Result<(), CoreError> {
+        let stored = Self::stored_leaf_count(&self.db)?;
+        let capacity = 1u64 << self.tree_depth;
+        let tree_count = stored.div_ceil(capacity).max(1) as u32;
+
+        for tree_id in 0..tree_count {
+            let expected = stored
+                .saturating_sub(tree_id as u64 * capacity)
+                .min(capacity);
+
+            // The leaves table is the source of truth; a tree store that is
+            // missing or out of step with it (a different leaf count, or a
+            // root other than the one committed with the leaves) is started
+            // over and rebuilt
+            let tree = self.open_tree(tree_id)?;
+            if tree.leaf_count() == expected && self.stored_tree_root(tree_id)? == Some(tree.root())
+            {
+                self.trees.push(tree);
+            } else {
+                drop(tree);
+                let tree = self.fresh_tree(tree_id)?;
+                self.trees.push(tree);
+                self.load_leaves(tree_id)?;
+                self.persist_tree_root(tree_id)?;
+            }
+        }
+
+        if stored == 0 {
+            self.rebuild_from_accounts()?;
+        }
+
+        // Load nullifiers, in the order they were marked
+        let mut stmt = self
+            .db
+            .prepare("SELECT nullifier FROM nullifiers ORDER BY rowid")?;
         let nullifiers = stmt.query_map([], |row| {
             let nullifier: Vec<u8> = row.get(0)?;
             Ok(nullifier)
         })?;
 
+        let mut ordered = Vec::new();
         for nullifier in nullifiers {
             let nullifier = nullifier?;
             if nullifier.len() == 32 {
                 let mut arr = [0u8; 32];
                 arr.copy_from_slice(&nullifier);
-                self.used_nullifiers.insert(arr);
+                if self.used_nullifiers.insert(arr) {
+                    ordered.push(arr);
+                }
+            }
+        }
+        drop(stmt);
+
+        self.load_nullifier_tree(&ordered)
+    }
+
+    /// Load the nullifier tree from `nullifier_leaves`
+    ///
+    /// The `nullifiers` table is the source of truth; if the stored tree does
+    /// not hold exactly those nullifiers (databases from before the tree
+    /// existed, or an interrupted write) it is rebuilt from them.
+    fn load_nullifier_tree(&mut self, nullifiers: &[[u8; 32]]) -> Result<(), CoreError> {
+        let mut stmt = self.db.prepare(
+            "SELECT leaf_index, value, next_index, next_value FROM nullifier_leaves
+             ORDER BY leaf_index",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let leaf_index: i64 = row.get(0)?;
+            let value: Vec<u8> = row.get(1)?;
+            let next_index: i64 = row.get(2)?;
+            let next_value: Vec<u8> = row.get(3)?;
+            Ok((leaf_index as u64, value, next_index as u64, next_value))
+        })?;
+
+        let mut leaves = Vec::new();
+        for row in rows {
+            let (leaf_index, value, next_index, next_value) = row?;
+            let (Ok(value), Ok(next_value)) = (
+                <[u8; 32]>::try_from(value),
+                <[u8; 32]>::try_from(next_value),
+            ) else {
+                return Err(CoreError::CorruptedLeaves(format!(
+                    "invalid nullifier leaf at index {}",
+                    leaf_index
+                )));
+            };
+            if leaf_index != leaves.len() as u64 {
+                return Err(CoreError::CorruptedLeaves(format!(
+                    "missing nullifier leaf at index {}",
+                    leaves.len()
+                )));
+            }
+            leaves.push(IndexedLeaf {
+                value: bytes_to_field(&value),
+                next_index,
+                next_value: bytes_to_field(&next_value),
+            });
+        }
+        drop(stmt);
+
+        if !leaves.is_empty() {
+            let tree = NullifierTree::from_leaves(leaves)?;
+            let in_sync = tree.leaf_count() == nullifiers.len() as u64 + 1
+                && nullifiers
+                    .iter()
+                    .all(|nullifier| tree.contains(&bytes_to_field(nullifier)));
+            if in_sync {
+                self.nullifier_tree = tree;
+                return Ok(());
+            }
+        }
+
+        self.rebuild_nullifier_tree(nullifiers)
+    }
+
+    /// Rebuild the nullifier tree from `nullifiers` and persist its leaves
+    fn rebuild_nullifier_tree(&mut self, nullifiers: &[[u8; 32]]) -> Result<(), CoreError> {
+        let mut tree = NullifierTree::new();
+        for nullifier in nullifiers {
+            tree.insert(bytes_to_field(nullifier))?;
+        }
+
+        self.db.execute("DELETE FROM nullifier_leaves", [])?;
+        for index in 0..tree.leaf_count() {
+            let leaf = *tree.leaf(index).expect("index below leaf count");
+            self.persist_nullifier_leaf(index, &leaf)?;
+        }
+        self.nullifier_tree = tree;
+
+        Ok(())
+    }
+
+    /// Rebuild one tree from its range of the `leaves` table
+    fn load_leaves(&mut self, tree_id: u32) -> Result<(), CoreError> {
+        let start = LeafId { tree_id, index: 0 }.to_global(self.tree_depth);
+        let end = start + (1u64 << self.tree_depth);
+        let tree = &mut self.trees[tree_id as usize];
+
+        let mut stmt = self.db.prepare(
+            "SELECT leaf_index, leaf FROM leaves
+             WHERE leaf_index >= ?1 AND leaf_index < ?2 ORDER BY leaf_index",
+        )?;
+
+        let rows = stmt.query_map(params![start as i64, end as i64], |row| {
+            let leaf_index: i64 = row.get(0)?;
+            let leaf: Vec<u8> = row.get(1)?;
+            Ok((leaf_index as u64, leaf))
+        })?;
+
+        let mut leaves = Vec::new();
+        for row in rows {
+            let (leaf_index, leaf) = row?;
+            let leaf: [u8; 32] = leaf.try_into().map_err(|_| {
+                CoreError::CorruptedLeaves(format!("invalid leaf at index {}", leaf_index))
+            })?;
+
+            // Indices are written in order, so a gap means a corrupted table
+            let expected = start + leaves.len() as u64;
+            if leaf_index != expected {
+                return Err(CoreError::CorruptedLeaves(format!(
+                    "missing leaf at index {}",
+                    expected
+                )));
+            }
+
+            leaves.push(bytes_to_field(&leaf));
+        }
+
+        tree.insert_batch(&leaves)?;
+        Ok(())
+    }
+
+    /// Rebuild the Merkle tree from the local accounts (databases written
+    /// before leaves were persisted), then persist the rebuilt leaves
+    fn rebuild_from_accounts(&mut self) -> Result<(), CoreError> {
+        let mut stmt = self.db.prepare(&format!(
+            "SELECT {} FROM accounts ORDER BY leaf_index",
+            ACCOUNT_COLUMNS
+        ))?;
+        let accounts = stmt
+            .query_map([], read_account)?
+            .collect::<Result<Vec<_>, _>>()?;
+        drop(stmt);
+
+        for account in accounts {
+            let leaf_index = account.index;
+            let leaf = account.compute_tree_leaf(&self.hasher, self.tree_depth);
+
+            // Insert into tree at correct index
+            while self.leaf_count() < leaf_index {
+                // Insert empty leaves to reach the correct index
+                let empty = self.hasher.hash2(&FieldElement::from(0u64), &FieldElement::from(0u64));
+                self.insert_leaf(empty)?;
             }
+            self.insert_leaf(leaf)?;
         }
 
         Ok(())
     }
 
-    /// Get the current state root
+    /// Write a leaf through to the `leaves` table
+    fn persist_leaf(&self, index: u64, leaf: &FieldElement) -> Result<(), CoreError> {
+        let bytes = field_to_bytes(leaf);
+        self.db.execute(
+            "INSERT OR REPLACE INTO leaves (leaf_index, leaf) VALUES (?1, ?2)",
+            params![index as i64, bytes.as_slice()],
+        )?;
+        Ok(())
+    }
+
+    /// Write a tree's current root through to the `tree_roots` table
+    fn persist_tree_root(&self, tree_id: u32) -> Result<(), CoreError> {
+        let tree = self
+            .tree_by_id(tree_id)
+            .ok_or(CoreError::UnknownTree(tree_id))?;
+        self.db.execute(
+            "INSERT OR REPLACE INTO tree_roots (tree_id, root) VALUES (?1, ?2)",
+            params![tree_id as i64, field_to_bytes(&tree.root()).as_slice()],
+        )?;
+        Ok(())
+    }
+
+    /// Root last committed for a tree, if any
+    fn stored_tree_root(&self, tree_id: u32) -> Result<Option<FieldElement>, CoreError> {
+        let root: Option<Vec<u8>> = self
+            .db
+            .query_row(
+                "SELECT root FROM tree_roots WHERE tree_id = ?1",
+                params![tree_id as i64],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        root.map(|root| {
+            let root: [u8; 32] = root.try_into().map_err(|_| {
+                CoreError::CorruptedLeaves(format!("invalid root of tree {}", tree_id))
+            })?;
+            Ok(bytes_to_field(&root))
+        })
+        .transpose()
+    }
+
+    /// Write a nullifier tree leaf through to the `nullifier_leaves` table
+    fn persist_nullifier_leaf(&self, index: u64, leaf: &IndexedLeaf) -> Result<(), CoreError> {
+        self.db.execute(
+            "INSERT OR REPLACE INTO nullifier_leaves (leaf_index, value, next_index, next_value)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                index as i64,
+                field_to_bytes(&leaf.value).as_slice(),
+                leaf.next_index as i64,
+                field_to_bytes(&leaf.next_value).as_slice()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get the current state root (the root of the active tree)
     pub fn state_root(&self) -> FieldElement {
-        self.tree.root()
+        self.tree().root()
     }
 
-    /// Get the current leaf count
+    /// Get the current leaf count across all trees
     pub fn leaf_count(&self) -> u64 {
-        self.tree.leaf_count()
+        let sealed = (self.trees.len() as u64 - 1) << self.tree_depth;
+        sealed + self.tree().leaf_count()
+    }
+
+    /// Depth of every tree
+    pub fn tree_depth(&self) -> usize {
+        self.tree_depth
+    }
+
+    /// Number of trees, including the active one
+    pub fn tree_count(&self) -> u32 {
+        self.trees.len() as u32
+    }
+
+    /// Root of a specific tree
+    pub fn tree_root(&self, tree_id: u32) -> Option<FieldElement> {
+        self.tree_by_id(tree_id).map(|tree| tree.root())
     }
 
     /// Check if a nullifier has been used
@@ -189,6 +695,9 @@ impl StateManager {
             return Err(CoreError::NullifierUsed(hex::encode(nullifier)));
         }
 
+        for (index, leaf) in self.nullifier_tree.insert(bytes_to_field(nullifier))? {
+            self.persist_nullifier_leaf(index, &leaf)?;
+        }
         self.used_nullifiers.insert(*nullifier);
 
         let now = std::time::SystemTime::now()
@@ -204,18 +713,86 @@ impl StateManager {
         Ok(())
     }
 
+    /// Forget a nullifier marked by a transaction that then failed
+    ///
+    /// The indexed tree cannot delete leaves, so it is rebuilt from the
+    /// remaining nullifiers.
+    pub fn unmark_nullifier(&mut self, nullifier: &[u8; 32]) -> Result<(), CoreError> {
+        if !self.used_nullifiers.remove(nullifier) {
+            return Ok(());
+        }
+
+        self.db.execute(
+            "DELETE FROM nullifiers WHERE nullifier = ?1",
+            params![nullifier.as_slice()],
+        )?;
+
+        self.reload_nullifier_tree()
+    }
+
+    /// Rebuild the nullifier tree from the `nullifiers` table
+    fn reload_nullifier_tree(&mut self) -> Result<(), CoreError> {
+        let mut stmt = self
+            .db
+            .prepare("SELECT nullifier FROM nullifiers ORDER BY rowid")?;
+        let stored = stmt
+            .query_map([], |row| row.get::<_, Vec<u8>>(0))?
+            .filter_map(|row| row.map(|bytes| bytes.try_into().ok()).transpose())
+            .collect::<Result<Vec<[u8; 32]>, _>>()?;
+        drop(stmt);
+
+        self.rebuild_nullifier_tree(&stored)
+    }
+
+    /// Root of the nullifier tree
+    pub fn nullifier_root(&self) -> FieldElement {
+        self.nullifier_tree.root()
+    }
+
+    /// Get a reference to the nullifier tree
+    pub fn nullifier_tree(&self) -> &NullifierTree {
+        &self.nullifier_tree
+    }
+
+    /// Prove that a nullifier is in the nullifier tree
+    pub fn prove_nullifier_spent(&self, nullifier: &[u8; 32]) -> Result<NullifierProof, CoreError> {
+        self.nullifier_tree
+            .prove_membership(&bytes_to_field(nullifier))
+    }
+
+    /// Prove that a nullifier is not in the nullifier tree
+    pub fn prove_nullifier_unspent(
+        &self,
+        nullifier: &[u8; 32],
+    ) -> Result<NullifierProof, CoreError> {
+        self.nullifier_tree
+            .prove_non_membership(&bytes_to_field(nullifier))
+    }
+
     /// Create a new account and insert into the tree
     pub fn create_account(
         &mut self,
         secret: &AccountSecret,
         initial_balance: u128,
     ) -> Result<PrivateAccount, CoreError> {
-        let index = self.tree.leaf_count();
-        let account = PrivateAccount::with_balance(&mut self.hasher, secret.secret(), index, initial_balance);
+        self.create_asset_account(secret, AssetId::NATIVE, initial_balance)
+    }
+
+    /// Create a new account holding `asset` and insert into the tree
+    pub fn create_asset_account(
+        &mut self,
+        secret: &AccountSecret,
+        asset: AssetId,
+        initial_balance: u128,
+    ) -> Result<PrivateAccount, CoreError> {
+        let index = self.leaf_count();
+        let account =
+            PrivateAccount::with_balance(&self.hasher, secret.secret(), index, initial_balance)
+                .with_asset(asset);
 
         // Compute leaf and insert into tree
-        let leaf = account.compute_leaf(&mut self.hasher);
-        self.tree.insert(leaf)?;
+        let leaf = account.compute_tree_leaf(&self.hasher, self.tree_depth);
+        self.insert_leaf(leaf)?;
 
         // Store in database
         let now = std::time::SystemTime::now()
@@ -226,13 +803,15 @@ impl StateManager {
         let balance_bytes = initial_balance.to_le_bytes();
 
         self.db.execute(
-            "INSERT INTO accounts (pubkey, balance_encrypted, nonce, leaf_index, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO accounts (pubkey, balance_encrypted, nonce, leaf_index, asset,
+             created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![
                 account.pubkey.as_slice(),
                 balance_bytes.as_slice(),
                 account.nonce as i64,
                 account.index as i64,
+                asset.as_bytes().as_slice(),
                 now as i64,
                 now as i64,
             ],
@@ -241,80 +820,48 @@ impl StateManager {
         Ok(account)
     }
 
-    /// Get an account by public key
+    /// Get the native MNT account of a public key
     pub fn get_account(&self, pubkey: &[u8; 32]) -> Result<Option<PrivateAccount>, CoreError> {
-        let mut stmt = self.db.prepare(
-            "SELECT pubkey, balance_encrypted, nonce, leaf_index FROM accounts WHERE pubkey = ?1",
-        )?;
-
-        let mut rows = stmt.query(params![pubkey.as_slice()])?;
-
-        if let Some(row) = rows.next()? {
-            let pubkey_bytes: Vec<u8> = row.get(0)?;
-            let balance_bytes: Vec<u8> = row.get(1)?;
-            let nonce: i64 = row.get(2)?;
-            let leaf_index: i64 = row.get(3)?;
-
-            let mut pubkey_arr = [0u8; 32];
-            if pubkey_bytes.len() == 32 {
-                pubkey_arr.copy_from_slice(&pubkey_bytes);
-            }
+        self.get_asset_account(pubkey, &AssetId::NATIVE)
+    }
 
-            let balance = if balance_bytes.len() >= 16 {
-                let mut bytes = [0u8; 16];
-                bytes.copy_from_slice(&balance_bytes[..16]);
-                u128::from_le_bytes(bytes)
-            } else {
-                0
-            };
+    /// Get the account a public key holds `asset` in
+    pub fn get_asset_account(
+        &self,
+        pubkey: &[u8; 32],
+        asset: &AssetId,
+    ) -> Result<Option<PrivateAccount>, CoreError> {
+        let mut stmt = self.db.prepare(&format!(
+            "SELECT {} FROM accounts WHERE pubkey = ?1 AND asset = ?2",
+            ACCOUNT_COLUMNS
+        ))?;
+        let mut rows = stmt.query_map(
+            params![pubkey.as_slice(), asset.as_bytes().as_slice()],
+            read_account,
+        )?;
+        Ok(rows.next().transpose()?)
+    }
 
-            Ok(Some(PrivateAccount {
-                pubkey: pubkey_arr,
-                balance,
-                nonce: nonce as u64,
-                index: leaf_index as u64,
-            }))
-        } else {
-            Ok(None)
-        }
+    /// Get every account of a public key, native MNT first
+    pub fn get_accounts(&self, pubkey: &[u8; 32]) -> Result<Vec<PrivateAccount>, CoreError> {
+        let mut stmt = self.db.prepare(&format!(
+            "SELECT {} FROM accounts WHERE pubkey = ?1 ORDER BY asset, leaf_index",
+            ACCOUNT_COLUMNS
+        ))?;
+        let accounts = stmt
+            .query_map(params![pubkey.as_slice()], read_account)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(accounts)
     }
 
     /// Get an account by leaf index
     pub fn get_account_by_index(&self, index: u64) -> Result<Option<PrivateAccount>, CoreError> {
-        let mut stmt = self.db.prepare(
-            "SELECT pubkey, balance_encrypted, nonce, leaf_index FROM accounts WHERE leaf_index = ?1",
-        )?;
-
-        let mut rows = stmt.query(params![index as i64])?;
-
-        if let Some(row) = rows.next()? {
-            let pubkey_bytes: Vec<u8> = row.get(0)?;
-            let balance_bytes: Vec<u8> = row.get(1)?;
-            let nonce: i64 = row.get(2)?;
-            let leaf_index: i64 = row.get(3)?;
-
-            let mut pubkey_arr = [0u8; 32];
-            if pubkey_bytes.len() == 32 {
-                pubkey_arr.copy_from_slice(&pubkey_bytes);
-            }
-
-            let balance = if balance_bytes.len() >= 16 {
-                let mut bytes = [0u8; 16];
-                bytes.copy_from_slice(&balance_bytes[..16]);
-                u128::from_le_bytes(bytes)
-            } else {
-                0
-            };
-
-            Ok(Some(PrivateAccount {
-                pubkey: pubkey_arr,
-                balance,
-                nonce: nonce as u64,
-                index: leaf_index as u64,
-            }))
-        } else {
-            Ok(None)
-        }
+        let mut stmt = self.db.prepare(&format!(
+            "SELECT {} FROM accounts WHERE leaf_index = ?1",
+            ACCOUNT_COLUMNS
+        ))?;
+        let mut rows = stmt.query_map(params![index as i64], read_account)?;
+        Ok(rows.next().transpose()?)
     }
 
     /// Update an account balance and nonce
@@ -338,25 +885,62 @@ impl StateManager {
         )?;
 
         // Update Merkle tree
-        let leaf = account.compute_leaf(&mut self.hasher);
-        self.tree.update_leaf(account.index, leaf)?;
+        let leaf = account.compute_tree_leaf(&self.hasher, self.tree_depth);
+        self.update_leaf(account.index, leaf)?;
 
         Ok(())
     }
 
-    /// Get Merkle proof for an account
-    pub fn get_merkle_proof(&self, index: u64) -> Vec<FieldElement> {
-        self.tree.get_proof(index)
+    /// Get Merkle proof for the leaf at a global index
+    ///
+    /// The proof is against the root of the tree holding the leaf, which is
+    /// not the current state root once that tree has been sealed.
+    pub fn get_merkle_proof(&self, index: u64) -> Result<MerkleProof, CoreError> {
+        let leaf = LeafId::from_global(index, self.tree_depth);
+        let tree = self
+            .tree_by_id(leaf.tree_id)
+            .ok_or(CoreError::UnknownTree(leaf.tree_id))?;
+
+        Ok(MerkleProof {
+            leaf,
+            root: tree.root(),
+            path: tree.get_proof(leaf.index)?,
+        })
+    }
+
+    /// Get Merkle proof for the leaf at a global index as of a recent root
+    /// of its tree, e.g. one the vault still accepts after we synced past it
+    pub fn get_merkle_proof_at(
+        &self,
+        index: u64,
+        root: FieldElement,
+    ) -> Result<MerkleProof, CoreError> {
+        let leaf = LeafId::from_global(index, self.tree_depth);
+        let tree = self
+            .trees
+            .get(leaf.tree_id as usize)
+            .ok_or(CoreError::UnknownTree(leaf.tree_id))?;
+
+        Ok(MerkleProof {
+            leaf,
+            root,
+            path: tree.get_proof_at(leaf.index, root)?,
+        })
+    }
+
+    /// Get a reference to the hasher
+    pub fn hasher(&self) -> &PoseidonHasher {
+        &self.hasher
     }
 
-    /// Get a mutable reference to the hasher
-    pub fn hasher(&mut self) -> &mut PoseidonHasher {
-        &mut self.hasher
+    /// Get a reference to the active Merkle tree
+    pub fn tree(&self) -> &StateTree {
+        self.trees.last().expect("state manager always has a tree")
     }
 
-    /// Get a reference to the Merkle tree
-    pub fn tree(&self) -> &MerkleTree {
-        &self.tree
+    /// Get a reference to a tree by id
+    pub fn tree_by_id(&self, tree_id: u32) -> Option<&StateTree> {
+        self.trees.get(tree_id as usize)
     }
 
     // ========================================================================
@@ -370,20 +954,17 @@ impl StateManager {
             .prepare("SELECT value FROM sync_state WHERE key = 'last_synced_block'")
             .ok()?;
 
-        let result = stmt
-            .query_row([], |row| {
-                let bytes: Vec<u8> = row.get(0)?;
-                if bytes.len() >= 8 {
-                    let mut arr = [0u8; 8];
-                    arr.copy_from_slice(&bytes[..8]);
-                    Ok(u64::from_le_bytes(arr))
-                } else {
-                    Ok(0)
-                }
-            })
-            .ok();
-
-        result
+        stmt.query_row([], |row| {
+            let bytes: Vec<u8> = row.get(0)?;
+            if bytes.len() >= 8 {
+                let mut arr = [0u8; 8];
+                arr.copy_from_slice(&bytes[..8]);
+                Ok(u64::from_le_bytes(arr))
+            } else {
+                Ok(0)
+            }
+        })
+        .ok()
     }
 
     /// Set the last synced block checkpoint
@@ -397,12 +978,151 @@ impl StateManager {
     }
 
     /// Insert a leaf directly into the Merkle tree (for syncing deposits)
+    ///
+    /// The leaf is persisted so the full tree survives a restart. When the
+    /// active tree is full it is sealed and the leaf starts a new tree; trees
+    /// deeper than `TREE_DEPTH` do not roll over. Returns the global leaf
+    /// index.
     pub fn insert_leaf(&mut self, leaf: FieldElement) -> Result<u64, CoreError> {
-        let index = self.tree.leaf_count();
-        self.tree.insert(leaf)?;
+        if self.tree().is_full() {
+            // Trees deeper than the circuits' would share nullifier indices
+            // with the next tree (see `LeafId::nullifier_index`)
+            if self.tree_depth > TREE_DEPTH {
+                return Err(CoreError::TreeFull);
+            }
+            let tree = self.fresh_tree(self.trees.len() as u32)?;
+            self.trees.push(tree);
+        }
+
+        let tree_id = self.trees.len() as u32 - 1;
+        let tree = self.trees.last_mut().expect("state manager always has a tree");
+        let index = LeafId {
+            tree_id,
+            index: tree.insert(leaf)?,
+        }
+        .to_global(self.tree_depth);
+
+        self.persist_leaf(index, &leaf)?;
+        self.persist_tree_root(tree_id)?;
         Ok(index)
     }
 
+    /// Replace an existing leaf (by global index) and persist it
+    pub fn update_leaf(&mut self, index: u64, leaf: FieldElement) -> Result<(), CoreError> {
+        let id = LeafId::from_global(index, self.tree_depth);
+        self.trees
+            .get_mut(id.tree_id as usize)
+            .ok_or(CoreError::UnknownTree(id.tree_id))?
+            .update_leaf(id.index, leaf)?;
+        self.persist_leaf(index, &leaf)?;
+        self.persist_tree_root(id.tree_id)?;
+        Ok(())
+    }
+
+    /// Apply a delta atomically
+    ///
+    /// Account updates, nullifiers and history are written in one SQLite
+    /// transaction. If any step or the commit fails, the transaction is rolled
+    /// back and the in-memory trees are restored, so nothing of the delta
+    /// remains. Tree stores are written outside that transaction; the tree
+    /// roots committed with it let `load_state` rebuild a store that a crash
+    /// or failed restore left ahead of the database. Returns the ids of
+    /// `delta.transactions`, in order.
+    pub fn apply(&mut self, delta: &StateDelta) -> Result<Vec<u64>, CoreError> {
+        let mut seen = HashSet::new();
+        for nullifier in &delta.nullifiers {
+            if self.used_nullifiers.contains(nullifier) || !seen.insert(*nullifier) {
+                return Err(CoreError::NullifierUsed(hex::encode(nullifier)));
+            }
+        }
+
+        // Released nullifiers that are actually in use, to re-mark on failure
+        let released: Vec<[u8; 32]> = delta
+            .released_nullifiers
+            .iter()
+            .filter(|nullifier| self.used_nullifiers.contains(*nullifier))
+            .copied()
+            .collect();
+
+        // Leaves the accounts currently have, to put back on failure
+        let mut old_leaves = Vec::with_capacity(delta.accounts.len());
+        for account in &delta.accounts {
+            let id = LeafId::from_global(account.index, self.tree_depth);
+            let leaf = self
+                .tree_by_id(id.tree_id)
+                .ok_or(CoreError::UnknownTree(id.tree_id))?
+                .get_leaf(id.index)?
+                .ok_or_else(|| CoreError::AccountNotFound(hex::encode(account.pubkey)))?;
+            old_leaves.push((id, leaf));
+        }
+
+        self.db.execute_batch("BEGIN IMMEDIATE")?;
+        let result = self.apply_uncommitted(delta).and_then(|ids| {
+            self.db.execute_batch("COMMIT")?;
+            Ok(ids)
+        });
+
+        if let Err(err) = &result {
+            // Best effort: the error being returned is the one that matters,
+            // so restore failures are only logged
+            if let Err(e) = self.db.execute_batch("ROLLBACK") {
+                tracing::warn!("Rolling back after '{}' failed: {}", err, e);
+            }
+
+            for (id, leaf) in old_leaves.into_iter().rev() {
+                if let Some(tree) = self.trees.get_mut(id.tree_id as usize) {
+                    if let Err(e) = tree.update_leaf(id.index, leaf) {
+                        tracing::warn!(
+                            "Restoring leaf {} of tree {} after '{}' failed: {}",
+                            id.index,
+                            id.tree_id,
+                            err,
+                            e
+                        );
+                    }
+                }
+            }
+            let mut changed = false;
+            for nullifier in &delta.nullifiers {
+                changed |= self.used_nullifiers.remove(nullifier);
+            }
+            for nullifier in &released {
+                changed |= self.used_nullifiers.insert(*nullifier);
+            }
+            if changed {
+                if let Err(e) = self.reload_nullifier_tree() {
+                    tracing::warn!("Reloading nullifier tree after '{}' failed: {}", err, e);
+                }
+            }
+        }
+
+        result
+    }
+
+    fn apply_uncommitted(&mut self, delta: &StateDelta) -> Result<Vec<u64>, CoreError> {
+        for account in &delta.accounts {
+            self.update_account(account)?;
+        }
+        for nullifier in &delta.released_nullifiers {
+            self.unmark_nullifier(nullifier)?;
+        }
+        for nullifier in &delta.nullifiers {
+            self.mark_nullifier_used(nullifier)?;
+        }
+
+        let mut ids = Vec::with_capacity(delta.transactions.len());
+        for tx in &delta.transactions {
+            if tx.id == 0 {
+                ids.push(self.record_transaction(tx)?);
+            } else {
+                self.update_transaction(tx)?;
+                ids.push(tx.id);
+            }
+        }
+
+        Ok(ids)
+    }
+
     /// Get the number of pending transactions
     pub fn pending_transaction_count(&self) -> Result<u64, CoreError> {
         let mut stmt = self
@@ -417,59 +1137,92 @@ impl StateManager {
     // TRANSACTION HISTORY
     // ========================================================================
 
-    /// Record a transaction in history
-    pub fn record_transaction(
-        &mut self,
-        tx_type: &str,
-        amount: u128,
-        tx_hash: Option<&[u8]>,
-        recipient: Option<&str>,
-        status: &str,
-    ) -> Result<i64, CoreError> {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        // Store transaction data as JSON
-        let data = serde_json::json!({
-            "amount": amount.to_string(),
-            "tx_hash": tx_hash.map(hex::encode),
-            "recipient": recipient,
-        });
-        let data_bytes = serde_json::to_vec(&data).unwrap_or_default();
-
+    /// Record a transaction in history and return its id
+    pub fn record_transaction(&mut self, tx: &Transaction) -> Result<u64, CoreError> {
         self.db.execute(
-            "INSERT INTO transactions (tx_type, data, status, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![tx_type, data_bytes.as_slice(), status, now as i64, now as i64],
+            "INSERT INTO transactions (tx_type, status, amount, nullifier, tx_hash,
+             block_number, leaf_index, counterparty, asset, created_at, updated_at, nonce)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                tx.tx_type.as_str(),
+                tx.status.as_str(),
+                tx.amount.to_string(),
+                tx.nullifier.as_ref().map(|n| n.as_slice()),
+                tx.tx_hash.as_ref().map(|h| h.as_slice()),
+                tx.block_number.map(|b| b as i64),
+                tx.leaf_index.map(|i| i as i64),
+                tx.counterparty,
+                (!tx.asset.is_native()).then(|| tx.asset.as_bytes().to_vec()),
+                tx.created_at as i64,
+                tx.updated_at as i64,
+                tx.nonce.map(|n| n as i64)
+            ],
         )?;
 
-        Ok(self.db.last_insert_rowid())
+        Ok(self.db.last_insert_rowid() as u64)
     }
 
-    /// Get transaction history
-    pub fn get_transactions(&self, limit: u32) -> Result<Vec<TransactionRecord>, CoreError> {
-        let mut stmt = self.db.prepare(
-            "SELECT id, tx_type, data, status, created_at FROM transactions
-             ORDER BY created_at DESC LIMIT ?1",
-        )?;
+    /// Get a transaction by id
+    pub fn get_transaction(&self, id: u64) -> Result<Option<Transaction>, CoreError> {
+        let mut stmt = self.db.prepare(&format!(
+            "SELECT {} FROM transactions WHERE id = ?1",
+            TRANSACTION_COLUMNS
+        ))?;
+        let mut rows = stmt.query_map(params![id as i64], read_transaction)?;
+        Ok(rows.next().transpose()?)
+    }
 
-        let rows = stmt.query_map(params![limit as i64], |row| {
-            let id: i64 = row.get(0)?;
-            let tx_type: String = row.get(1)?;
-            let data_bytes: Vec<u8> = row.get(2)?;
-            let status: String = row.get(3)?;
-            let created_at: i64 = row.get(4)?;
-
-            Ok(TransactionRecord {
-                id: id as u64,
-                tx_type,
-                data: data_bytes,
-                status,
-                created_at: created_at as u64,
-            })
-        })?;
+    /// Get the transaction that was broadcast as `tx_hash`
+    pub fn get_transaction_by_hash(
+        &self,
+        tx_hash: &[u8; 32],
+    ) -> Result<Option<Transaction>, CoreError> {
+        let mut stmt = self.db.prepare(&format!(
+            "SELECT {} FROM transactions WHERE tx_hash = ?1",
+            TRANSACTION_COLUMNS
+        ))?;
+        let mut rows = stmt.query_map(params![tx_hash.as_slice()], read_transaction)?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Get transaction history matching `filter`, newest first
+    pub fn get_transactions(
+        &self,
+        filter: &TransactionFilter,
+    ) -> Result<Vec<Transaction>, CoreError> {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+
+        if let Some(tx_type) = filter.tx_type {
+            conditions.push("tx_type = ?");
+            values.push(Value::Text(tx_type.as_str().to_string()));
+        }
+        if let Some(status) = filter.status {
+            conditions.push("status = ?");
+            values.push(Value::Text(status.as_str().to_string()));
+        }
+        if let Some(since) = filter.since {
+            conditions.push("created_at >= ?");
+            values.push(Value::Integer(since as i64));
+        }
+        if let Some(until) = filter.until {
+            conditions.push("created_at < ?");
+            values.push(Value::Integer(until as i64));
+        }
+
+        let mut sql = format!("SELECT {} FROM transactions", TRANSACTION_COLUMNS);
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY created_at DESC, id DESC");
+        if let Some(limit) = filter.limit {
+            sql.push_str(" LIMIT ?");
+            values.push(Value::Integer(limit as i64));
+        }
+
+        let mut stmt = self.db.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values), read_transaction)?;
 
         let mut transactions = Vec::new();
         for row in rows {
@@ -479,8 +1232,99 @@ impl StateManager {
         Ok(transactions)
     }
 
+    /// Write a transaction's status, tx hash, block and leaf index back to
+    /// its row
+    pub fn update_transaction(&mut self, tx: &Transaction) -> Result<(), CoreError> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let updated = self.db.execute(
+            "UPDATE transactions SET status = ?1, tx_hash = ?2, block_number = ?3,
+             leaf_index = ?4, updated_at = ?5 WHERE id = ?6",
+            params![
+                tx.status.as_str(),
+                tx.tx_hash.as_ref().map(|h| h.as_slice()),
+                tx.block_number.map(|b| b as i64),
+                tx.leaf_index.map(|i| i as i64),
+                now as i64,
+                tx.id as i64
+            ],
+        )?;
+        if updated == 0 {
+            return Err(CoreError::TransactionNotFound(tx.id));
+        }
+
+        Ok(())
+    }
+
+    /// Mark a transaction failed and undo its optimistic local effects
+    ///
+    /// For transfers and withdrawals the spending account (at the
+    /// transaction's leaf index) is credited back and its nonce rewound, and
+    /// the nullifier is released so the funds can be spent again. All of it
+    /// is written as one `StateDelta`.
+    ///
+    /// The rollback is refused unless the account is still at the nonce
+    /// right after this spend: a later spend was proven against the debited
+    /// leaf, so rewinding underneath it would corrupt the account.
+    pub fn fail_transaction(&mut self, id: u64) -> Result<(), CoreError> {
+        let mut tx = self
+            .get_transaction(id)?
+            .ok_or(CoreError::TransactionNotFound(id))?;
+        if tx.status == TransactionStatus::Failed {
+            return Ok(());
+        }
+
+        let mut delta = StateDelta::new();
+
+        let spent = matches!(
+            tx.tx_type,
+            TransactionType::Transfer | TransactionType::Withdraw
+        );
+        let applied = matches!(
+            tx.status,
+            TransactionStatus::Submitted | TransactionStatus::Confirmed
+        );
+
+        if spent && applied {
+            if let Some(mut account) = match tx.leaf_index {
+                Some(index) => self.get_account_by_index(index)?,
+                None => None,
+            } {
+                let nonce = tx.nonce.ok_or_else(|| {
+                    CoreError::InvalidTransaction(format!(
+                        "transaction {} did not record its spend nonce",
+                        id
+                    ))
+                })?;
+                if account.nonce != nonce + 1 {
+                    return Err(CoreError::InvalidTransaction(format!(
+                        "transaction {} spent nonce {} but the account is at nonce {}",
+                        id, nonce, account.nonce
+                    )));
+                }
+
+                account.credit(tx.amount);
+                account.nonce = nonce;
+                delta = delta.with_account(account);
+            }
+            if let Some(nullifier) = tx.nullifier {
+                delta = delta.with_released_nullifier(nullifier);
+            }
+        }
+
+        tx.status = TransactionStatus::Failed;
+        self.apply(&delta.with_transaction(tx)).map(|_| ())
+    }
+
     /// Update transaction status
-    pub fn update_transaction_status(&mut self, id: i64, status: &str) -> Result<(), CoreError> {
+    pub fn update_transaction_status(
+        &mut self,
+        id: u64,
+        status: TransactionStatus,
+    ) -> Result<(), CoreError> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
@@ -488,44 +1332,126 @@ impl StateManager {
 
         self.db.execute(
             "UPDATE transactions SET status = ?1, updated_at = ?2 WHERE id = ?3",
-            params![status, now as i64, id],
+            params![status.as_str(), now as i64, id as i64],
         )?;
 
         Ok(())
     }
 }
 
-/// Represents a transaction record from the database
-#[derive(Debug, Clone)]
-pub struct TransactionRecord {
-    pub id: u64,
-    pub tx_type: String,
-    pub data: Vec<u8>,
-    pub status: String,
-    pub created_at: u64,
-}
+/// Transaction history table and its indexes
+const TRANSACTIONS_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS transactions (
+        id INTEGER PRIMARY KEY,
+        tx_type TEXT NOT NULL,
+        status TEXT NOT NULL,
+        amount TEXT NOT NULL,
+        nullifier BLOB,
+        tx_hash BLOB,
+        block_number INTEGER,
+        leaf_index INTEGER,
+        counterparty TEXT,
+        asset BLOB,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL,
+        nonce INTEGER
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_transactions_status ON transactions(status);
+    CREATE INDEX IF NOT EXISTS idx_transactions_type ON transactions(tx_type);
+    CREATE INDEX IF NOT EXISTS idx_transactions_created_at ON transactions(created_at);
+";
+
+/// Columns read by `read_transaction`, in order
+const TRANSACTION_COLUMNS: &str = "id, tx_type, status, amount, nullifier, tx_hash, \
+     block_number, leaf_index, counterparty, created_at, updated_at, asset, nonce";
+
+/// Decode a `transactions` row selected with `TRANSACTION_COLUMNS`
+fn read_transaction(row: &Row) -> rusqlite::Result<Transaction> {
+    fn invalid(index: usize, err: CoreError) -> rusqlite::Error {
+        rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(err))
+    }
 
-impl TransactionRecord {
-    /// Parse the data field as JSON and extract amount
-    pub fn amount(&self) -> Option<u128> {
-        serde_json::from_slice::<serde_json::Value>(&self.data)
-            .ok()
-            .and_then(|v| v.get("amount")?.as_str()?.parse().ok())
+    fn hash(row: &Row, index: usize) -> rusqlite::Result<Option<[u8; 32]>> {
+        row.get::<_, Option<Vec<u8>>>(index)?
+            .map(|bytes| {
+                bytes.try_into().map_err(|_| {
+                    invalid(index, CoreError::InvalidTransaction("expected 32 bytes".into()))
+                })
+            })
+            .transpose()
     }
 
-    /// Parse the data field as JSON and extract tx_hash
-    pub fn tx_hash(&self) -> Option<String> {
-        serde_json::from_slice::<serde_json::Value>(&self.data)
-            .ok()
-            .and_then(|v| v.get("tx_hash")?.as_str().map(|s| s.to_string()))
+    let amount: String = row.get(3)?;
+
+    Ok(Transaction {
+        id: row.get::<_, i64>(0)? as u64,
+        tx_type: row
+            .get::<_, String>(1)?
+            .parse::<TransactionType>()
+            .map_err(|e| invalid(1, e))?,
+        status: row
+            .get::<_, String>(2)?
+            .parse::<TransactionStatus>()
+            .map_err(|e| invalid(2, e))?,
+        amount: amount.parse().map_err(|_| {
+            invalid(3, CoreError::InvalidTransaction(format!("invalid amount '{}'", amount)))
+        })?,
+        nullifier: hash(row, 4)?,
+        tx_hash: hash(row, 5)?,
+        block_number: row.get::<_, Option<i64>>(6)?.map(|b| b as u64),
+        leaf_index: row.get::<_, Option<i64>>(7)?.map(|i| i as u64),
+        counterparty: row.get(8)?,
+        created_at: row.get::<_, i64>(9)? as u64,
+        updated_at: row.get::<_, i64>(10)? as u64,
+        asset: row
+            .get::<_, Option<Vec<u8>>>(11)?
+            .map(|bytes| {
+                <[u8; 20]>::try_from(bytes).map(AssetId).map_err(|_| {
+                    invalid(11, CoreError::InvalidTransaction("expected 20-byte asset".into()))
+                })
+            })
+            .transpose()?
+            .unwrap_or_default(),
+        nonce: row.get::<_, Option<i64>>(12)?.map(|n| n as u64),
+    })
+}
+
+/// Columns read by `read_account`, in order
+const ACCOUNT_COLUMNS: &str = "pubkey, balance_encrypted, nonce, leaf_index, asset";
+
+/// Decode an `accounts` row selected with `ACCOUNT_COLUMNS`
+fn read_account(row: &Row) -> rusqlite::Result<PrivateAccount> {
+    let pubkey_bytes: Vec<u8> = row.get(0)?;
+    let balance_bytes: Vec<u8> = row.get(1)?;
+    let asset_bytes: Vec<u8> = row.get(4)?;
+
+    let mut pubkey = [0u8; 32];
+    if pubkey_bytes.len() == 32 {
+        pubkey.copy_from_slice(&pubkey_bytes);
     }
 
-    /// Parse the data field as JSON and extract recipient
-    pub fn recipient(&self) -> Option<String> {
-        serde_json::from_slice::<serde_json::Value>(&self.data)
-            .ok()
-            .and_then(|v| v.get("recipient")?.as_str().map(|s| s.to_string()))
+    // Balance is stored as a little-endian u128
+    let balance = if balance_bytes.len() >= 16 {
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&balance_bytes[..16]);
+        u128::from_le_bytes(bytes)
+    } else {
+        0
+    };
+
+    let mut asset = [0u8; 20];
+    if asset_bytes.len() == 20 {
+        asset.copy_from_slice(&asset_bytes);
     }
+
+    Ok(PrivateAccount {
+        pubkey,
+        balance,
+        nonce: row.get::<_, i64>(2)? as u64,
+        index: row.get::<_, i64>(3)? as u64,
+        asset: AssetId(asset),
+    })
 }
 
 #[cfg(test)]
@@ -577,6 +1503,162 @@ mod tests {
         assert!(manager.mark_nullifier_used(&nullifier).is_err());
     }
 
+    #[test]
+    fn test_nullifier_tree_survives_reopen() {
+        let path = std::env::temp_dir().join(format!(
+            "veilocity-state-{}.db",
+            hex::encode(rand::random::<[u8; 8]>())
+        ));
+        let spent = [[3u8; 32], [1u8; 32], [2u8; 32]];
+        let unspent = [4u8; 32];
+
+        let root = {
+            let mut manager = StateManager::new(&path).unwrap();
+            for nullifier in &spent {
+                manager.mark_nullifier_used(nullifier).unwrap();
+            }
+            manager.nullifier_root()
+        };
+
+        let reopened = StateManager::new(&path).unwrap();
+        assert_eq!(reopened.nullifier_root(), root);
+
+        let proof = reopened.prove_nullifier_spent(&spent[1]).unwrap();
+        assert!(proof.verify_membership(&bytes_to_field(&spent[1])));
+        let proof = reopened.prove_nullifier_unspent(&unspent).unwrap();
+        assert!(proof.verify_non_membership(&bytes_to_field(&unspent)));
+        assert_eq!(proof.root, root);
+        assert!(reopened.prove_nullifier_unspent(&spent[0]).is_err());
+
+        // Databases without stored tree leaves rebuild them from the nullifiers
+        reopened
+            .db
+            .execute("DELETE FROM nullifier_leaves", [])
+            .unwrap();
+        drop(reopened);
+        let rebuilt = StateManager::new(&path).unwrap();
+        assert_eq!(rebuilt.nullifier_root(), root);
+        drop(rebuilt);
+
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(path.with_extension("tree")).ok();
+    }
+
+    #[test]
+    fn test_leaves_survive_reopen() {
+        let path = std::env::temp_dir().join(format!(
+            "veilocity-state-{}.db",
+            hex::encode(rand::random::<[u8; 8]>())
+        ));
+
+        let (root, leaf_count) = {
+            let mut manager = StateManager::new(&path).unwrap();
+            // Synced leaves that don't belong to any local account
+            manager.insert_leaf(FieldElement::from(11u64)).unwrap();
+            manager.insert_leaf(FieldElement::from(22u64)).unwrap();
+            let account = manager
+                .create_account(&AccountSecret::generate(), 5)
+                .unwrap();
+            manager.update_leaf(0, FieldElement::from(33u64)).unwrap();
+            assert_eq!(account.index, 2);
+            (manager.state_root(), manager.leaf_count())
+        };
+
+        let reopened = StateManager::new(&path).unwrap();
+        assert_eq!(reopened.leaf_count(), leaf_count);
+        assert_eq!(reopened.state_root(), root);
+        assert_eq!(
+            reopened.tree().get_leaf(0).unwrap(),
+            Some(FieldElement::from(33u64))
+        );
+        drop(reopened);
+
+        // Losing the tree file only costs a rebuild from the leaves table
+        std::fs::remove_file(path.with_extension("tree")).unwrap();
+        let rebuilt = StateManager::new(&path).unwrap();
+        assert_eq!(rebuilt.state_root(), root);
+        drop(rebuilt);
+
+        // The tree depth is fixed once the database exists
+        assert!(matches!(
+            StateManager::open(&path, 8),
+            Err(CoreError::TreeDepthMismatch { expected: 8, found: 20 })
+        ));
+
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(path.with_extension("tree")).ok();
+    }
+
+    #[test]
+    fn test_tree_rollover() {
+        let path = std::env::temp_dir().join(format!(
+            "veilocity-rollover-{}.db",
+            hex::encode(rand::random::<[u8; 8]>())
+        ));
+
+        let (roots, proof) = {
+            let mut manager = StateManager::open(&path, 2).unwrap();
+            for i in 0..6u64 {
+                assert_eq!(manager.insert_leaf(FieldElement::from(i)).unwrap(), i);
+            }
+            assert_eq!(manager.tree_count(), 2);
+            assert_eq!(manager.leaf_count(), 6);
+
+            // Global index 5 is the second leaf of tree 1
+            let proof = manager.get_merkle_proof(5).unwrap();
+            assert_eq!(proof.leaf, LeafId { tree_id: 1, index: 1 });
+            assert_eq!(proof.root, manager.state_root());
+            assert_eq!(manager.get_merkle_proof(1).unwrap().root, manager.tree_root(0).unwrap());
+            assert_eq!(proof.path.len(), 2);
+
+            let roots = (manager.tree_root(0).unwrap(), manager.tree_root(1).unwrap());
+            (roots, proof)
+        };
+
+        let reopened = StateManager::open(&path, 2).unwrap();
+        assert_eq!(reopened.tree_count(), 2);
+        assert_eq!(reopened.tree_root(0), Some(roots.0));
+        assert_eq!(reopened.tree_root(1), Some(roots.1));
+        assert_eq!(reopened.get_merkle_proof(5).unwrap(), proof);
+        drop(reopened);
+
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(path.with_extension("tree")).ok();
+        std::fs::remove_file(format!("{}.1", path.with_extension("tree").display())).ok();
+    }
+
+    #[test]
+    fn test_accounts_bound_to_their_tree() {
+        let mut manager = StateManager::in_memory_with_depth(2).unwrap();
+        let secret = AccountSecret::generate();
+
+        // Fill tree 0 so the account lands at leaf 0 of tree 1 (global index 4)
+        for i in 0..4u64 {
+            manager.insert_leaf(FieldElement::from(i)).unwrap();
+        }
+        let account = manager.create_account(&secret, 1_000).unwrap();
+        assert_eq!(account.index, 4);
+
+        let proof = manager.get_merkle_proof(account.index).unwrap();
+        let leaf = manager.tree_by_id(1).unwrap().get_leaf(0).unwrap().unwrap();
+        assert_eq!(leaf, account.compute_tree_leaf(manager.hasher(), 2));
+        assert_ne!(leaf, account.compute_leaf(manager.hasher()));
+        assert!(manager
+            .tree()
+            .verify_proof(leaf, proof.leaf.index, &proof.path, proof.root));
+
+        // Tree 0 holds the same secret at the same in-tree index; the
+        // nullifier index keeps their nullifiers apart, and counts trees in
+        // units of the circuits' depth rather than this state's
+        let in_tree_0 = LeafId::from_global(proof.leaf.index, 2);
+        assert_eq!(proof.leaf.nullifier_index(), 1 << TREE_DEPTH);
+        assert_ne!(proof.leaf.nullifier_index(), account.index);
+        assert_ne!(
+            secret.compute_nullifier(manager.hasher(), proof.leaf.nullifier_index(), 0),
+            secret.compute_nullifier(manager.hasher(), in_tree_0.nullifier_index(), 0)
+        );
+    }
+
     #[test]
     fn test_account_update() {
         let mut manager = StateManager::in_memory().unwrap();
@@ -591,7 +1673,351 @@ mod tests {
 
         assert_ne!(root1, root2);
 
+        // The vault may still hold the earlier root; its paths stay available
+        let proof = manager.get_merkle_proof_at(account.index, root1).unwrap();
+        assert_eq!(proof.root, root1);
+
         let retrieved = manager.get_account(&account.pubkey).unwrap().unwrap();
         assert_eq!(retrieved.balance, 2_000_000_000);
     }
+
+    #[test]
+    fn test_failed_withdrawal_rolled_back() {
+        let mut manager = StateManager::in_memory().unwrap();
+        let secret = AccountSecret::generate();
+        let nullifier = [8u8; 32];
+
+        let mut account = manager.create_account(&secret, 1_000).unwrap();
+        let root = manager.state_root();
+        let nullifier_root = manager.nullifier_root();
+
+        // Optimistically applied once the withdrawal was broadcast
+        let tx = Transaction::new(TransactionType::Withdraw, 400)
+            .with_status(TransactionStatus::Submitted)
+            .with_nullifier(nullifier)
+            .with_leaf_index(account.index)
+            .with_nonce(account.nonce)
+            .with_tx_hash([1u8; 32]);
+        let id = manager.record_transaction(&tx).unwrap();
+        account.debit(400);
+        manager.update_account(&account).unwrap();
+        manager.mark_nullifier_used(&nullifier).unwrap();
+
+        manager.fail_transaction(id).unwrap();
+
+        let restored = manager.get_account(&account.pubkey).unwrap().unwrap();
+        assert_eq!((restored.balance, restored.nonce), (1_000, 0));
+        assert_eq!(manager.state_root(), root);
+        assert!(!manager.is_nullifier_used(&nullifier));
+        assert_eq!(manager.nullifier_root(), nullifier_root);
+        assert_eq!(
+            manager.get_transaction(id).unwrap().unwrap().status,
+            TransactionStatus::Failed
+        );
+
+        // Failing twice changes nothing
+        manager.fail_transaction(id).unwrap();
+        assert_eq!(manager.get_account(&account.pubkey).unwrap().unwrap().balance, 1_000);
+        assert!(matches!(
+            manager.fail_transaction(999),
+            Err(CoreError::TransactionNotFound(999))
+        ));
+    }
+
+    #[test]
+    fn test_failed_spend_not_rolled_back_under_later_spend() {
+        let mut manager = StateManager::in_memory().unwrap();
+        let mut account = manager
+            .create_account(&AccountSecret::generate(), 1_000)
+            .unwrap();
+
+        let first = Transaction::new(TransactionType::Transfer, 300)
+            .with_status(TransactionStatus::Submitted)
+            .with_nullifier([1u8; 32])
+            .with_leaf_index(account.index)
+            .with_nonce(account.nonce);
+        account.debit(300);
+        let first_id = manager
+            .apply(
+                &StateDelta::new()
+                    .with_account(account.clone())
+                    .with_nullifier([1u8; 32])
+                    .with_transaction(first),
+            )
+            .unwrap()[0];
+
+        // A second spend proven against the debited leaf
+        let second = Transaction::new(TransactionType::Withdraw, 200)
+            .with_status(TransactionStatus::Submitted)
+            .with_nullifier([2u8; 32])
+            .with_leaf_index(account.index)
+            .with_nonce(account.nonce);
+        account.debit(200);
+        manager
+            .apply(
+                &StateDelta::new()
+                    .with_account(account.clone())
+                    .with_nullifier([2u8; 32])
+                    .with_transaction(second),
+            )
+            .unwrap();
+        let root = manager.state_root();
+
+        assert!(matches!(
+            manager.fail_transaction(first_id),
+            Err(CoreError::InvalidTransaction(_))
+        ));
+        let stored = manager.get_account(&account.pubkey).unwrap().unwrap();
+        assert_eq!((stored.balance, stored.nonce), (500, 2));
+        assert_eq!(manager.state_root(), root);
+        assert!(manager.is_nullifier_used(&[1u8; 32]));
+        assert_eq!(
+            manager.get_transaction(first_id).unwrap().unwrap().status,
+            TransactionStatus::Submitted
+        );
+    }
+
+    #[test]
+    fn test_apply_is_atomic() {
+        let mut manager = StateManager::in_memory().unwrap();
+        let mut account = manager
+            .create_account(&AccountSecret::generate(), 1_000)
+            .unwrap();
+        let root = manager.state_root();
+        let nullifier_root = manager.nullifier_root();
+
+        account.debit(300);
+        account.nonce += 1;
+        let tx = Transaction::new(TransactionType::Transfer, 300)
+            .with_status(TransactionStatus::Proven)
+            .with_nullifier([4u8; 32]);
+
+        // Updating a transaction that doesn't exist fails the whole delta
+        let failing = StateDelta::new()
+            .with_account(account.clone())
+            .with_nullifier([4u8; 32])
+            .with_transaction(tx.clone())
+            .with_transaction(Transaction { id: 999, ..tx.clone() });
+        assert!(matches!(
+            manager.apply(&failing),
+            Err(CoreError::TransactionNotFound(999))
+        ));
+        assert_eq!(manager.get_account(&account.pubkey).unwrap().unwrap().balance, 1_000);
+        assert_eq!(manager.state_root(), root);
+        assert!(!manager.is_nullifier_used(&[4u8; 32]));
+        assert_eq!(manager.nullifier_root(), nullifier_root);
+        assert!(manager.get_transactions(&TransactionFilter::new()).unwrap().is_empty());
+
+        let delta = StateDelta::new()
+            .with_account(account.clone())
+            .with_nullifier([4u8; 32])
+            .with_transaction(tx);
+        let ids = manager.apply(&delta).unwrap();
+        assert_eq!(manager.get_account(&account.pubkey).unwrap().unwrap().balance, 700);
+        assert_ne!(manager.state_root(), root);
+        assert!(manager.is_nullifier_used(&[4u8; 32]));
+        assert_eq!(manager.get_transaction(ids[0]).unwrap().unwrap().amount, 300);
+
+        // A nullifier spent twice is rejected before anything is written
+        assert!(matches!(
+            manager.apply(&delta),
+            Err(CoreError::NullifierUsed(_))
+        ));
+    }
+
+    #[test]
+    fn test_interrupted_apply_rebuilds_tree_on_reopen() {
+        let path = std::env::temp_dir().join(format!(
+            "veilocity-state-{}.db",
+            hex::encode(rand::random::<[u8; 8]>())
+        ));
+
+        let (root, account) = {
+            let mut manager = StateManager::new(&path).unwrap();
+            let account = manager
+                .create_account(&AccountSecret::generate(), 1_000)
+                .unwrap();
+            let root = manager.state_root();
+
+            let mut spent = account.clone();
+            spent.debit(300);
+            spent.nonce += 1;
+            let delta = StateDelta::new()
+                .with_account(spent)
+                .with_nullifier([5u8; 32]);
+
+            // Stop between the writes and COMMIT, as a crash would: the tree
+            // store already holds the new leaf, and closing the connection
+            // rolls the database back
+            manager.db.execute_batch("BEGIN IMMEDIATE").unwrap();
+            manager.apply_uncommitted(&delta).unwrap();
+            assert_ne!(manager.state_root(), root);
+            (root, account)
+        };
+
+        // Same leaf count, different root: the tree is rebuilt from the leaves
+        let reopened = StateManager::new(&path).unwrap();
+        let restored = reopened.get_account(&account.pubkey).unwrap().unwrap();
+        assert_eq!(reopened.state_root(), root);
+        assert_eq!(restored.balance, 1_000);
+        assert!(!reopened.is_nullifier_used(&[5u8; 32]));
+
+        let leaf = account.compute_tree_leaf(reopened.hasher(), reopened.tree_depth());
+        let proof = reopened.get_merkle_proof(account.index).unwrap();
+        assert!(reopened
+            .tree()
+            .verify_proof(leaf, proof.leaf.index, &proof.path, root));
+        drop(reopened);
+
+        // The rebuilt tree is trusted on the next open
+        assert_eq!(StateManager::new(&path).unwrap().state_root(), root);
+
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(path.with_extension("tree")).ok();
+    }
+
+    #[test]
+    fn test_transaction_history_filters() {
+        let mut manager = StateManager::in_memory().unwrap();
+
+        let deposit = Transaction {
+            created_at: 100,
+            updated_at: 100,
+            ..Transaction::new(TransactionType::Deposit, 5)
+                .with_status(TransactionStatus::Confirmed)
+                .with_tx_hash([7u8; 32])
+                .with_block_number(42)
+                .with_leaf_index(3)
+        };
+        let withdraw = Transaction {
+            created_at: 200,
+            updated_at: 200,
+            ..Transaction::new(TransactionType::Withdraw, u128::MAX)
+                .with_nullifier([9u8; 32])
+                .with_counterparty("0xabc")
+        };
+
+        let deposit_id = manager.record_transaction(&deposit).unwrap();
+        let withdraw_id = manager.record_transaction(&withdraw).unwrap();
+
+        let all = manager.get_transactions(&TransactionFilter::new()).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0], Transaction { id: withdraw_id, ..withdraw });
+        assert_eq!(all[1], Transaction { id: deposit_id, ..deposit });
+
+        let filter = TransactionFilter::new().with_type(TransactionType::Deposit);
+        assert_eq!(manager.get_transactions(&filter).unwrap()[0].id, deposit_id);
+
+        let filter = TransactionFilter::new().with_status(TransactionStatus::Pending);
+        assert_eq!(manager.get_transactions(&filter).unwrap()[0].id, withdraw_id);
+
+        let filter = TransactionFilter::new().since(150).until(250);
+        assert_eq!(manager.get_transactions(&filter).unwrap().len(), 1);
+        assert_eq!(manager.get_transactions(&TransactionFilter::new().with_limit(1)).unwrap().len(), 1);
+
+        manager
+            .update_transaction_status(withdraw_id, TransactionStatus::Failed)
+            .unwrap();
+        let updated = manager.get_transaction(withdraw_id).unwrap().unwrap();
+        assert_eq!(updated.status, TransactionStatus::Failed);
+        assert!(manager.get_transaction(999).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_legacy_transactions_migrated() {
+        let db = Connection::open_in_memory().unwrap();
+        db.execute_batch(
+            "CREATE TABLE transactions (
+                id INTEGER PRIMARY KEY,
+                tx_type TEXT NOT NULL,
+                nullifier BLOB,
+                data BLOB NOT NULL,
+                status TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE INDEX idx_transactions_status ON transactions(status);",
+        )
+        .unwrap();
+        let data = format!(
+            r#"{{"amount":"1000","tx_hash":"{}","recipient":"0xdead"}}"#,
+            hex::encode([5u8; 32])
+        );
+        db.execute(
+            "INSERT INTO transactions (tx_type, data, status, created_at, updated_at)
+             VALUES ('withdraw', ?1, 'confirmed', 10, 10)",
+            params![data.as_bytes()],
+        )
+        .unwrap();
+
+        StateManager::init_db(&db).unwrap();
+        let manager = StateManager::with_db(db, None, TREE_DEPTH).unwrap();
+
+        let tx = manager.get_transaction(1).unwrap().unwrap();
+        assert_eq!(tx.tx_type, TransactionType::Withdraw);
+        assert_eq!(tx.status, TransactionStatus::Confirmed);
+        assert_eq!(tx.amount, 1000);
+        assert_eq!(tx.tx_hash, Some([5u8; 32]));
+        assert_eq!(tx.counterparty.as_deref(), Some("0xdead"));
+    }
+
+    #[test]
+    fn test_asset_accounts() {
+        let mut manager = StateManager::in_memory().unwrap();
+        let secret = AccountSecret::generate();
+        let usdc = AssetId([0x09; 20]);
+
+        let native = manager.create_account(&secret, 1_000).unwrap();
+        let token = manager.create_asset_account(&secret, usdc, 250).unwrap();
+        assert_ne!(native.index, token.index);
+
+        // The same pubkey holds one account per asset
+        assert_eq!(manager.get_account(&native.pubkey).unwrap().unwrap().balance, 1_000);
+        let stored = manager.get_asset_account(&native.pubkey, &usdc).unwrap().unwrap();
+        assert_eq!(stored.balance, 250);
+        assert_eq!(stored.asset, usdc);
+        assert_eq!(manager.get_accounts(&native.pubkey).unwrap().len(), 2);
+        assert!(manager.create_asset_account(&secret, usdc, 1).is_err());
+
+        // The token leaf binds the asset
+        let hasher = PoseidonHasher::new();
+        let leaf = manager.tree().get_leaf(token.index).unwrap().unwrap();
+        assert_eq!(leaf, token.compute_leaf(&hasher));
+
+        let tx = Transaction::new(TransactionType::Deposit, 250).with_asset(usdc);
+        let id = manager.record_transaction(&tx).unwrap();
+        assert_eq!(manager.get_transaction(id).unwrap().unwrap().asset, usdc);
+    }
+
+    #[test]
+    fn test_accounts_migrated_to_assets() {
+        let db = Connection::open_in_memory().unwrap();
+        db.execute_batch(
+            "CREATE TABLE accounts (
+                id INTEGER PRIMARY KEY,
+                pubkey BLOB NOT NULL UNIQUE,
+                balance_encrypted BLOB NOT NULL,
+                nonce INTEGER NOT NULL DEFAULT 0,
+                leaf_index INTEGER NOT NULL UNIQUE,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE INDEX idx_accounts_leaf_index ON accounts(leaf_index);",
+        )
+        .unwrap();
+        db.execute(
+            "INSERT INTO accounts (pubkey, balance_encrypted, nonce, leaf_index, created_at,
+             updated_at) VALUES (?1, ?2, 2, 0, 10, 10)",
+            params![[3u8; 32].as_slice(), 500u128.to_le_bytes().as_slice()],
+        )
+        .unwrap();
+
+        StateManager::init_db(&db).unwrap();
+        let manager = StateManager::with_db(db, None, TREE_DEPTH).unwrap();
+
+        let account = manager.get_account(&[3u8; 32]).unwrap().unwrap();
+        assert_eq!(account.balance, 500);
+        assert_eq!(account.nonce, 2);
+        assert!(account.asset.is_native());
+    }
 }
@@ -0,0 +1,394 @@
+//! Deterministic Poseidon test vectors shared with the Noir circuits
+//!
+//! `test-vectors/poseidon.json` and `circuits/src/test_vectors.nr` are both
+//! generated from [`generate`], so the Rust and Noir hashes are checked
+//! against the same values. Regenerate them with:
+//!
+//! ```bash
+//! cargo run -p veilocity-core --example gen_test_vectors
+//! ```
+
+use crate::merkle::MerkleTree;
+use crate::poseidon::{field_to_hex, u128_to_field, u64_to_field, FieldElement, PoseidonHasher};
+use serde::{Deserialize, Serialize};
+
+/// A raw Poseidon hash vector
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashVector {
+    pub inputs: Vec<String>,
+    pub output: String,
+}
+
+/// An account leaf: hash(pubkey, balance, nonce)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeafVector {
+    pub pubkey: String,
+    pub balance: String,
+    pub nonce: String,
+    pub leaf: String,
+}
+
+/// A nullifier: hash(secret, index, nonce)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NullifierVector {
+    pub secret: String,
+    pub index: String,
+    pub nonce: String,
+    pub nullifier: String,
+}
+
+/// A deposit commitment: hash(secret, amount)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitmentVector {
+    pub secret: String,
+    pub amount: String,
+    pub commitment: String,
+}
+
+/// Merkle path of one leaf in a sample tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProofVector {
+    pub index: u64,
+    pub path: Vec<String>,
+}
+
+/// A sample tree: its leaves, root, and one proof per leaf
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleVector {
+    pub leaves: Vec<String>,
+    pub root: String,
+    pub proofs: Vec<MerkleProofVector>,
+}
+
+/// All shared test vectors
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVectors {
+    pub hash1: Vec<HashVector>,
+    pub hash2: Vec<HashVector>,
+    pub hash3: Vec<HashVector>,
+    pub hash4: Vec<HashVector>,
+    pub leaves: Vec<LeafVector>,
+    pub nullifiers: Vec<NullifierVector>,
+    pub deposit_commitments: Vec<CommitmentVector>,
+    pub merkle_roots: Vec<MerkleVector>,
+}
+
+/// Header of the generated Noir test module
+const NOIR_HEADER: &str = "\
+// Poseidon test vectors shared with veilocity-core (test-vectors/poseidon.json)
+// Generated by `cargo run -p veilocity-core --example gen_test_vectors` - do not edit.
+
+use crate::merkle::compute_root_from_path;
+use crate::poseidon_utils::{
+    compute_deposit_commitment, compute_leaf, compute_nullifier, hash1, hash2, hash3,
+};
+use poseidon::poseidon::bn254::hash_4;
+";
+
+/// Compute the test vectors with the Rust implementation
+pub fn generate() -> TestVectors {
+    let hasher = PoseidonHasher::new();
+
+    let zero = u64_to_field(0);
+    let one_mnt = u128_to_field(1_000_000_000_000_000_000);
+    let max = zero - u64_to_field(1);
+    let secret = u64_to_field(123456789);
+
+    let hex = |values: &[FieldElement]| values.iter().map(field_to_hex).collect::<Vec<_>>();
+
+    let hash1 = [zero, u64_to_field(1), u64_to_field(12345), max]
+        .iter()
+        .map(|a| HashVector {
+            inputs: hex(&[*a]),
+            output: field_to_hex(&hasher.hash1(a)),
+        })
+        .collect();
+
+    let hash2 = [
+        [zero, zero],
+        [u64_to_field(1), u64_to_field(2)],
+        [u64_to_field(12345), one_mnt],
+        [max, u64_to_field(1)],
+    ]
+    .iter()
+    .map(|[a, b]| HashVector {
+        inputs: hex(&[*a, *b]),
+        output: field_to_hex(&hasher.hash2(a, b)),
+    })
+    .collect();
+
+    let hash3 = [
+        [zero, zero, zero],
+        [u64_to_field(1), u64_to_field(2), u64_to_field(3)],
+        [secret, zero, zero],
+    ]
+    .iter()
+    .map(|[a, b, c]| HashVector {
+        inputs: hex(&[*a, *b, *c]),
+        output: field_to_hex(&hasher.hash3(a, b, c)),
+    })
+    .collect();
+
+    let hash4 = [
+        [zero, zero, zero, zero],
+        [u64_to_field(1), u64_to_field(2), u64_to_field(3), u64_to_field(4)],
+    ]
+    .iter()
+    .map(|[a, b, c, d]| HashVector {
+        inputs: hex(&[*a, *b, *c, *d]),
+        output: field_to_hex(&hasher.hash4(a, b, c, d)),
+    })
+    .collect();
+
+    let leaves = [
+        (hasher.derive_pubkey(&secret), u128_to_field(2_000_000_000_000_000_000), zero),
+        (hasher.derive_pubkey(&u64_to_field(12345)), one_mnt, u64_to_field(3)),
+    ]
+    .iter()
+    .map(|(pubkey, balance, nonce)| LeafVector {
+        pubkey: field_to_hex(pubkey),
+        balance: field_to_hex(balance),
+        nonce: field_to_hex(nonce),
+        leaf: field_to_hex(&hasher.compute_leaf(pubkey, balance, nonce)),
+    })
+    .collect();
+
+    let nullifiers = [
+        (secret, zero, zero),
+        (secret, zero, u64_to_field(1)),
+        (u64_to_field(12345), u64_to_field(7), u64_to_field(2)),
+    ]
+    .iter()
+    .map(|(secret, index, nonce)| NullifierVector {
+        secret: field_to_hex(secret),
+        index: field_to_hex(index),
+        nonce: field_to_hex(nonce),
+        nullifier: field_to_hex(&hasher.compute_nullifier(secret, index, nonce)),
+    })
+    .collect();
+
+    let deposit_commitments = [
+        (secret, one_mnt),
+        (u64_to_field(54321), u128_to_field(1_000_000_000_000_000)),
+    ]
+    .iter()
+    .map(|(secret, amount)| CommitmentVector {
+        secret: field_to_hex(secret),
+        amount: field_to_hex(amount),
+        commitment: field_to_hex(&hasher.compute_deposit_commitment(secret, amount)),
+    })
+    .collect();
+
+    let pubkey = hasher.derive_pubkey(&secret);
+    let merkle_roots = vec![
+        merkle_vector(&[hasher.compute_leaf(&pubkey, &one_mnt, &zero)]),
+        merkle_vector(&[u64_to_field(1), u64_to_field(2), u64_to_field(3)]),
+    ];
+
+    TestVectors {
+        hash1,
+        hash2,
+        hash3,
+        hash4,
+        leaves,
+        nullifiers,
+        deposit_commitments,
+        merkle_roots,
+    }
+}
+
+/// Build a sample tree from `leaves` and record its root and proofs
+fn merkle_vector(leaves: &[FieldElement]) -> MerkleVector {
+    let mut tree = MerkleTree::new();
+    for leaf in leaves {
+        tree.insert(*leaf).expect("sample tree fits");
+    }
+
+    MerkleVector {
+        leaves: leaves.iter().map(field_to_hex).collect(),
+        root: field_to_hex(&tree.root()),
+        proofs: (0..leaves.len() as u64)
+            .map(|index| MerkleProofVector {
+                index,
+                path: tree
+                    .get_proof(index)
+                    .expect("in-memory tree")
+                    .iter()
+                    .map(field_to_hex)
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+/// Render the vectors as a Noir test module
+pub fn to_noir(vectors: &TestVectors) -> String {
+    let mut out = String::from(NOIR_HEADER);
+
+    let hash_lines = |vectors: &[HashVector], call: &dyn Fn(&[String]) -> String| {
+        vectors
+            .iter()
+            .map(|v| format!("assert({} == {});", call(&v.inputs), v.output))
+            .collect::<Vec<_>>()
+    };
+
+    noir_test(
+        &mut out,
+        "test_vectors_hash1",
+        hash_lines(&vectors.hash1, &|i| format!("hash1({})", i[0])),
+    );
+    noir_test(
+        &mut out,
+        "test_vectors_hash2",
+        hash_lines(&vectors.hash2, &|i| format!("hash2({}, {})", i[0], i[1])),
+    );
+    noir_test(
+        &mut out,
+        "test_vectors_hash3",
+        hash_lines(&vectors.hash3, &|i| format!("hash3({}, {}, {})", i[0], i[1], i[2])),
+    );
+    noir_test(
+        &mut out,
+        "test_vectors_hash4",
+        hash_lines(&vectors.hash4, &|i| format!("hash_4([{}])", i.join(", "))),
+    );
+
+    noir_test(
+        &mut out,
+        "test_vectors_leaves",
+        vectors
+            .leaves
+            .iter()
+            .map(|v| {
+                format!(
+                    "assert(compute_leaf({}, {}, {}) == {});",
+                    v.pubkey, v.balance, v.nonce, v.leaf
+                )
+            })
+            .collect(),
+    );
+    noir_test(
+        &mut out,
+        "test_vectors_nullifiers",
+        vectors
+            .nullifiers
+            .iter()
+            .map(|v| {
+                format!(
+                    "assert(compute_nullifier({}, {}, {}) == {});",
+                    v.secret, v.index, v.nonce, v.nullifier
+                )
+            })
+            .collect(),
+    );
+    noir_test(
+        &mut out,
+        "test_vectors_deposit_commitments",
+        vectors
+            .deposit_commitments
+            .iter()
+            .map(|v| {
+                format!(
+                    "assert(compute_deposit_commitment({}, {}) == {});",
+                    v.secret, v.amount, v.commitment
+                )
+            })
+            .collect(),
+    );
+    noir_test(
+        &mut out,
+        "test_vectors_merkle_roots",
+        vectors
+            .merkle_roots
+            .iter()
+            .flat_map(|tree| {
+                tree.proofs.iter().map(move |proof| {
+                    format!(
+                        "assert(compute_root_from_path({}, {}, [{}]) == {});",
+                        tree.leaves[proof.index as usize],
+                        proof.index,
+                        proof.path.join(", "),
+                        tree.root
+                    )
+                })
+            })
+            .collect(),
+    );
+
+    out
+}
+
+/// Append one `#[test]` function with the given body lines
+fn noir_test(out: &mut String, name: &str, lines: Vec<String>) {
+    out.push_str(&format!("\n#[test]\nfn {}() {{\n", name));
+    for line in lines {
+        out.push_str(&format!("    {}\n", line));
+    }
+    out.push_str("}\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::process::Command;
+
+    const VECTORS_JSON: &str = include_str!("../../../test-vectors/poseidon.json");
+    const VECTORS_NOIR: &str = include_str!("../../../circuits/src/test_vectors.nr");
+
+    #[test]
+    fn test_vectors_match_rust() {
+        let expected: TestVectors = serde_json::from_str(VECTORS_JSON).unwrap();
+        assert_eq!(generate(), expected);
+    }
+
+    #[test]
+    fn test_vectors_noir_in_sync() {
+        let expected: TestVectors = serde_json::from_str(VECTORS_JSON).unwrap();
+        assert_eq!(
+            to_noir(&expected),
+            VECTORS_NOIR,
+            "circuits/src/test_vectors.nr is stale; run the gen_test_vectors example"
+        );
+    }
+
+    #[test]
+    fn test_vectors_circomlib_reference() {
+        // poseidon([1, 2]) from circomlib's own test suite
+        let vectors: TestVectors = serde_json::from_str(VECTORS_JSON).unwrap();
+        assert_eq!(
+            vectors.hash2[1].output,
+            "0x115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a"
+        );
+    }
+
+    /// Runs the Noir side of the vectors; skipped when nargo is not installed
+    #[test]
+    fn test_vectors_nargo() {
+        let nargo_available = Command::new("nargo")
+            .arg("--version")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+
+        if !nargo_available {
+            eprintln!("nargo not found, skipping Noir test vectors");
+            return;
+        }
+
+        let circuits_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../circuits");
+        let output = Command::new("nargo")
+            .current_dir(&circuits_dir)
+            .arg("test")
+            .arg("test_vectors")
+            .output()
+            .expect("failed to run nargo test");
+
+        assert!(
+            output.status.success(),
+            "nargo test failed:\n{}\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
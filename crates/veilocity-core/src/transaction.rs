@@ -1,18 +1,56 @@
 //! Transaction types for the private execution layer
 
-use crate::poseidon::{field_to_bytes, FieldElement};
+use crate::asset::AssetId;
+use crate::error::CoreError;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 /// Transaction type enum
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TransactionType {
     Deposit,
     Transfer,
     Withdraw,
 }
 
+impl TransactionType {
+    /// Name stored in the `transactions.tx_type` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionType::Deposit => "deposit",
+            TransactionType::Transfer => "transfer",
+            TransactionType::Withdraw => "withdraw",
+        }
+    }
+}
+
+impl fmt::Display for TransactionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TransactionType {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "deposit" => Ok(TransactionType::Deposit),
+            "transfer" => Ok(TransactionType::Transfer),
+            "withdraw" => Ok(TransactionType::Withdraw),
+            other => Err(CoreError::InvalidTransaction(format!(
+                "unknown transaction type '{}'",
+                other
+            ))),
+        }
+    }
+}
+
 /// Transaction status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TransactionStatus {
     /// Transaction created but not yet proven
     Pending,
@@ -26,188 +64,226 @@ pub enum TransactionStatus {
     Failed,
 }
 
-/// A deposit transaction
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DepositTransaction {
-    /// Deposit commitment (hash of secret and amount)
-    pub commitment: [u8; 32],
+impl TransactionStatus {
+    /// Name stored in the `transactions.status` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionStatus::Pending => "pending",
+            TransactionStatus::Proven => "proven",
+            TransactionStatus::Submitted => "submitted",
+            TransactionStatus::Confirmed => "confirmed",
+            TransactionStatus::Failed => "failed",
+        }
+    }
+}
+
+impl fmt::Display for TransactionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TransactionStatus {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(TransactionStatus::Pending),
+            "proven" => Ok(TransactionStatus::Proven),
+            "submitted" => Ok(TransactionStatus::Submitted),
+            "confirmed" => Ok(TransactionStatus::Confirmed),
+            "failed" => Ok(TransactionStatus::Failed),
+            other => Err(CoreError::InvalidTransaction(format!(
+                "unknown transaction status '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// A transaction in the local history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
+    /// Row id (0 until recorded)
+    pub id: u64,
+    /// Deposit, transfer or withdrawal
+    pub tx_type: TransactionType,
+    /// Lifecycle status
+    pub status: TransactionStatus,
     /// Amount in wei
     pub amount: u128,
-    /// Status
-    pub status: TransactionStatus,
+    /// Nullifier spent by this transaction (transfers and withdrawals)
+    pub nullifier: Option<[u8; 32]>,
     /// On-chain transaction hash (if submitted)
-    pub tx_hash: Option<String>,
+    pub tx_hash: Option<[u8; 32]>,
     /// Block number (if confirmed)
     pub block_number: Option<u64>,
-    /// Leaf index assigned (if confirmed)
+    /// Leaf index assigned (deposits, once confirmed) or of the spending
+    /// account (transfers and withdrawals)
     pub leaf_index: Option<u64>,
-    /// Timestamp
-    pub timestamp: u64,
+    /// Transfer recipient pubkey or withdrawal recipient address
+    pub counterparty: Option<String>,
+    /// Creation time (unix seconds)
+    pub created_at: u64,
+    /// Last update time (unix seconds)
+    pub updated_at: u64,
+    /// Pool the amount is in (native MNT unless set)
+    #[serde(default)]
+    pub asset: AssetId,
+    /// Account nonce the spend was proven with (transfers and withdrawals)
+    #[serde(default)]
+    pub nonce: Option<u64>,
 }
 
-impl DepositTransaction {
-    pub fn new(commitment: FieldElement, amount: u128) -> Self {
+impl Transaction {
+    /// A new pending transaction, timestamped now
+    pub fn new(tx_type: TransactionType, amount: u128) -> Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
         Self {
-            commitment: field_to_bytes(&commitment),
-            amount,
+            id: 0,
+            tx_type,
             status: TransactionStatus::Pending,
+            amount,
+            nullifier: None,
             tx_hash: None,
             block_number: None,
             leaf_index: None,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            counterparty: None,
+            created_at: now,
+            updated_at: now,
+            asset: AssetId::NATIVE,
+            nonce: None,
         }
     }
 
-    pub fn commitment_hex(&self) -> String {
-        format!("0x{}", hex::encode(self.commitment))
+    pub fn with_status(mut self, status: TransactionStatus) -> Self {
+        self.status = status;
+        self
     }
-}
 
-/// A private transfer transaction
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TransferTransaction {
-    /// Nullifier for sender's spend
-    pub nullifier: [u8; 32],
-    /// Recipient's public key
-    pub recipient_pubkey: [u8; 32],
-    /// Amount transferred
-    pub amount: u128,
-    /// Old state root
-    pub old_state_root: [u8; 32],
-    /// New state root
-    pub new_state_root: [u8; 32],
-    /// ZK proof (if generated)
-    pub proof: Option<Vec<u8>>,
-    /// Status
-    pub status: TransactionStatus,
-    /// Timestamp
-    pub timestamp: u64,
-}
+    pub fn with_nullifier(mut self, nullifier: [u8; 32]) -> Self {
+        self.nullifier = Some(nullifier);
+        self
+    }
 
-impl TransferTransaction {
-    pub fn new(
-        nullifier: FieldElement,
-        recipient_pubkey: FieldElement,
-        amount: u128,
-        old_state_root: FieldElement,
-        new_state_root: FieldElement,
-    ) -> Self {
-        Self {
-            nullifier: field_to_bytes(&nullifier),
-            recipient_pubkey: field_to_bytes(&recipient_pubkey),
-            amount,
-            old_state_root: field_to_bytes(&old_state_root),
-            new_state_root: field_to_bytes(&new_state_root),
-            proof: None,
-            status: TransactionStatus::Pending,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        }
+    pub fn with_tx_hash(mut self, tx_hash: [u8; 32]) -> Self {
+        self.tx_hash = Some(tx_hash);
+        self
     }
 
-    pub fn nullifier_hex(&self) -> String {
-        format!("0x{}", hex::encode(self.nullifier))
+    pub fn with_block_number(mut self, block_number: u64) -> Self {
+        self.block_number = Some(block_number);
+        self
     }
-}
 
-/// A withdrawal transaction
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WithdrawTransaction {
-    /// Nullifier for this withdrawal
-    pub nullifier: [u8; 32],
-    /// Recipient address on Mantle
-    pub recipient: String,
-    /// Amount to withdraw
-    pub amount: u128,
-    /// State root used in proof
-    pub state_root: [u8; 32],
-    /// ZK proof
-    pub proof: Option<Vec<u8>>,
-    /// Status
-    pub status: TransactionStatus,
-    /// On-chain transaction hash
-    pub tx_hash: Option<String>,
-    /// Block number
-    pub block_number: Option<u64>,
-    /// Timestamp
-    pub timestamp: u64,
-}
+    pub fn with_leaf_index(mut self, leaf_index: u64) -> Self {
+        self.leaf_index = Some(leaf_index);
+        self
+    }
 
-impl WithdrawTransaction {
-    pub fn new(
-        nullifier: FieldElement,
-        recipient: String,
-        amount: u128,
-        state_root: FieldElement,
-    ) -> Self {
-        Self {
-            nullifier: field_to_bytes(&nullifier),
-            recipient,
-            amount,
-            state_root: field_to_bytes(&state_root),
-            proof: None,
-            status: TransactionStatus::Pending,
-            tx_hash: None,
-            block_number: None,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        }
+    pub fn with_counterparty(mut self, counterparty: impl Into<String>) -> Self {
+        self.counterparty = Some(counterparty.into());
+        self
+    }
+
+    pub fn with_asset(mut self, asset: AssetId) -> Self {
+        self.asset = asset;
+        self
     }
 
-    pub fn nullifier_hex(&self) -> String {
-        format!("0x{}", hex::encode(self.nullifier))
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
     }
 
-    pub fn state_root_hex(&self) -> String {
-        format!("0x{}", hex::encode(self.state_root))
+    pub fn nullifier_hex(&self) -> Option<String> {
+        self.nullifier.map(|n| format!("0x{}", hex::encode(n)))
+    }
+
+    pub fn tx_hash_hex(&self) -> Option<String> {
+        self.tx_hash.map(|h| format!("0x{}", hex::encode(h)))
     }
 }
 
-/// Union type for any transaction
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum Transaction {
-    Deposit(DepositTransaction),
-    Transfer(TransferTransaction),
-    Withdraw(WithdrawTransaction),
+/// Which transactions to return from the history
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionFilter {
+    /// Only this type
+    pub tx_type: Option<TransactionType>,
+    /// Only this status
+    pub status: Option<TransactionStatus>,
+    /// Created at or after this time (unix seconds)
+    pub since: Option<u64>,
+    /// Created before this time (unix seconds)
+    pub until: Option<u64>,
+    /// At most this many, newest first
+    pub limit: Option<u32>,
 }
 
-impl Transaction {
-    pub fn tx_type(&self) -> TransactionType {
-        match self {
-            Transaction::Deposit(_) => TransactionType::Deposit,
-            Transaction::Transfer(_) => TransactionType::Transfer,
-            Transaction::Withdraw(_) => TransactionType::Withdraw,
-        }
+impl TransactionFilter {
+    /// Match every transaction
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    pub fn status(&self) -> TransactionStatus {
-        match self {
-            Transaction::Deposit(tx) => tx.status,
-            Transaction::Transfer(tx) => tx.status,
-            Transaction::Withdraw(tx) => tx.status,
-        }
+    pub fn with_type(mut self, tx_type: TransactionType) -> Self {
+        self.tx_type = Some(tx_type);
+        self
     }
 
-    pub fn amount(&self) -> u128 {
-        match self {
-            Transaction::Deposit(tx) => tx.amount,
-            Transaction::Transfer(tx) => tx.amount,
-            Transaction::Withdraw(tx) => tx.amount,
-        }
+    pub fn with_status(mut self, status: TransactionStatus) -> Self {
+        self.status = Some(status);
+        self
     }
 
-    pub fn timestamp(&self) -> u64 {
-        match self {
-            Transaction::Deposit(tx) => tx.timestamp,
-            Transaction::Transfer(tx) => tx.timestamp,
-            Transaction::Withdraw(tx) => tx.timestamp,
+    pub fn since(mut self, timestamp: u64) -> Self {
+        self.since = Some(timestamp);
+        self
+    }
+
+    pub fn until(mut self, timestamp: u64) -> Self {
+        self.until = Some(timestamp);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_and_status_roundtrip() {
+        for tx_type in [
+            TransactionType::Deposit,
+            TransactionType::Transfer,
+            TransactionType::Withdraw,
+        ] {
+            assert_eq!(
+                tx_type.as_str().parse::<TransactionType>().unwrap(),
+                tx_type
+            );
+        }
+        for status in [
+            TransactionStatus::Pending,
+            TransactionStatus::Proven,
+            TransactionStatus::Submitted,
+            TransactionStatus::Confirmed,
+            TransactionStatus::Failed,
+        ] {
+            assert_eq!(
+                status.as_str().parse::<TransactionStatus>().unwrap(),
+                status
+            );
         }
+        assert!("bridge".parse::<TransactionType>().is_err());
     }
 }
@@ -0,0 +1,254 @@
+//! Storage backends for the Merkle tree
+//!
+//! A `TreeStore` holds the non-empty nodes of a `MerkleTree` plus its leaf
+//! count. `MemoryStore` keeps them in RAM; `RedbStore` keeps them on disk so a
+//! tree can be reopened without re-hashing its leaves and without holding all
+//! 2^20 leaves in memory.
+
+use crate::error::CoreError;
+use crate::poseidon::{bytes_to_field, field_to_bytes, FieldElement};
+use redb::{Database, TableDefinition};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A node write: (level, index, hash). Level 0 holds the leaves.
+pub type NodeUpdate = (usize, u64, FieldElement);
+
+/// Backing storage for a Merkle tree
+pub trait TreeStore: Send {
+    /// Number of leaves inserted so far
+    fn leaf_count(&self) -> Result<u64, CoreError>;
+
+    /// Hash of the node at `level` and `index`, if it has been written
+    fn get_node(&self, level: usize, index: u64) -> Result<Option<FieldElement>, CoreError>;
+
+    /// Write `nodes` and the new leaf count as one atomic update
+    fn write_nodes(&mut self, nodes: &[NodeUpdate], leaf_count: u64) -> Result<(), CoreError>;
+
+    /// Depth of the tree this store was created for, if recorded
+    fn depth(&self) -> Result<Option<usize>, CoreError>;
+
+    /// Record the depth of the tree using this store
+    fn set_depth(&mut self, depth: usize) -> Result<(), CoreError>;
+}
+
+impl<S: TreeStore + ?Sized> TreeStore for Box<S> {
+    fn leaf_count(&self) -> Result<u64, CoreError> {
+        (**self).leaf_count()
+    }
+
+    fn get_node(&self, level: usize, index: u64) -> Result<Option<FieldElement>, CoreError> {
+        (**self).get_node(level, index)
+    }
+
+    fn write_nodes(&mut self, nodes: &[NodeUpdate], leaf_count: u64) -> Result<(), CoreError> {
+        (**self).write_nodes(nodes, leaf_count)
+    }
+
+    fn depth(&self) -> Result<Option<usize>, CoreError> {
+        (**self).depth()
+    }
+
+    fn set_depth(&mut self, depth: usize) -> Result<(), CoreError> {
+        (**self).set_depth(depth)
+    }
+}
+
+/// In-memory sparse node storage
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    /// Sparse storage: level -> index -> hash
+    nodes: Vec<HashMap<u64, FieldElement>>,
+    leaf_count: u64,
+    depth: Option<usize>,
+}
+
+impl MemoryStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TreeStore for MemoryStore {
+    fn leaf_count(&self) -> Result<u64, CoreError> {
+        Ok(self.leaf_count)
+    }
+
+    fn get_node(&self, level: usize, index: u64) -> Result<Option<FieldElement>, CoreError> {
+        Ok(self
+            .nodes
+            .get(level)
+            .and_then(|nodes| nodes.get(&index))
+            .copied())
+    }
+
+    fn write_nodes(&mut self, nodes: &[NodeUpdate], leaf_count: u64) -> Result<(), CoreError> {
+        for &(level, index, hash) in nodes {
+            if self.nodes.len() <= level {
+                self.nodes.resize_with(level + 1, HashMap::new);
+            }
+            self.nodes[level].insert(index, hash);
+        }
+        self.leaf_count = leaf_count;
+        Ok(())
+    }
+
+    fn depth(&self) -> Result<Option<usize>, CoreError> {
+        Ok(self.depth)
+    }
+
+    fn set_depth(&mut self, depth: usize) -> Result<(), CoreError> {
+        self.depth = Some(depth);
+        Ok(())
+    }
+}
+
+/// Nodes keyed by (level, index)
+const NODES: TableDefinition<(u8, u64), &[u8]> = TableDefinition::new("nodes");
+
+/// Tree metadata
+const META: TableDefinition<&str, u64> = TableDefinition::new("meta");
+
+const LEAF_COUNT_KEY: &str = "leaf_count";
+const DEPTH_KEY: &str = "depth";
+
+/// On-disk node storage backed by redb
+pub struct RedbStore {
+    db: Database,
+}
+
+/// Map any redb error into `CoreError::TreeStore`
+fn store_error(err: impl Into<redb::Error>) -> CoreError {
+    CoreError::from(err.into())
+}
+
+impl RedbStore {
+    /// Open the store at `path`, creating it if it doesn't exist
+    pub fn open(path: &Path) -> Result<Self, CoreError> {
+        let db = Database::create(path).map_err(store_error)?;
+
+        // Create the tables up front so reads never hit a missing table
+        let txn = db.begin_write().map_err(store_error)?;
+        txn.open_table(NODES).map_err(store_error)?;
+        txn.open_table(META).map_err(store_error)?;
+        txn.commit().map_err(store_error)?;
+
+        Ok(Self { db })
+    }
+
+    fn read_meta(&self, key: &str) -> Result<Option<u64>, CoreError> {
+        let txn = self.db.begin_read().map_err(store_error)?;
+        let meta = txn.open_table(META).map_err(store_error)?;
+        Ok(meta.get(key).map_err(store_error)?.map(|v| v.value()))
+    }
+
+    fn write_meta(&self, key: &str, value: u64) -> Result<(), CoreError> {
+        let txn = self.db.begin_write().map_err(store_error)?;
+        {
+            let mut meta = txn.open_table(META).map_err(store_error)?;
+            meta.insert(key, value).map_err(store_error)?;
+        }
+        txn.commit().map_err(store_error)?;
+        Ok(())
+    }
+
+    fn read_node(&self, level: usize, index: u64) -> Result<Option<[u8; 32]>, CoreError> {
+        let txn = self.db.begin_read().map_err(store_error)?;
+        let nodes = txn.open_table(NODES).map_err(store_error)?;
+
+        let Some(value) = nodes.get((level as u8, index)).map_err(store_error)? else {
+            return Ok(None);
+        };
+
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(value.value());
+        Ok(Some(bytes))
+    }
+
+    fn write(&self, updates: &[NodeUpdate], leaf_count: u64) -> Result<(), CoreError> {
+        let txn = self.db.begin_write().map_err(store_error)?;
+        {
+            let mut nodes = txn.open_table(NODES).map_err(store_error)?;
+            for (level, index, hash) in updates {
+                nodes
+                    .insert((*level as u8, *index), field_to_bytes(hash).as_slice())
+                    .map_err(store_error)?;
+            }
+
+            let mut meta = txn.open_table(META).map_err(store_error)?;
+            meta.insert(LEAF_COUNT_KEY, leaf_count)
+                .map_err(store_error)?;
+        }
+        txn.commit().map_err(store_error)?;
+        Ok(())
+    }
+}
+
+impl TreeStore for RedbStore {
+    fn leaf_count(&self) -> Result<u64, CoreError> {
+        Ok(self.read_meta(LEAF_COUNT_KEY)?.unwrap_or(0))
+    }
+
+    fn get_node(&self, level: usize, index: u64) -> Result<Option<FieldElement>, CoreError> {
+        Ok(self
+            .read_node(level, index)?
+            .map(|bytes| bytes_to_field(&bytes)))
+    }
+
+    fn write_nodes(&mut self, nodes: &[NodeUpdate], leaf_count: u64) -> Result<(), CoreError> {
+        self.write(nodes, leaf_count)
+    }
+
+    fn depth(&self) -> Result<Option<usize>, CoreError> {
+        Ok(self.read_meta(DEPTH_KEY)?.map(|depth| depth as usize))
+    }
+
+    fn set_depth(&mut self, depth: usize) -> Result<(), CoreError> {
+        self.write_meta(DEPTH_KEY, depth as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redb_store_roundtrip() {
+        let path = std::env::temp_dir().join(format!(
+            "veilocity-tree-{}.redb",
+            hex::encode(rand::random::<[u8; 8]>())
+        ));
+
+        {
+            let mut store = RedbStore::open(&path).unwrap();
+            assert_eq!(store.leaf_count().unwrap(), 0);
+            assert_eq!(store.get_node(0, 0).unwrap(), None);
+
+            store
+                .write_nodes(
+                    &[
+                        (0, 0, FieldElement::from(7u64)),
+                        (1, 0, FieldElement::from(9u64)),
+                    ],
+                    1,
+                )
+                .unwrap();
+        }
+
+        let store = RedbStore::open(&path).unwrap();
+        assert_eq!(store.leaf_count().unwrap(), 1);
+        assert_eq!(
+            store.get_node(0, 0).unwrap(),
+            Some(FieldElement::from(7u64))
+        );
+        assert_eq!(
+            store.get_node(1, 0).unwrap(),
+            Some(FieldElement::from(9u64))
+        );
+        assert_eq!(store.get_node(1, 1).unwrap(), None);
+
+        drop(store);
+        std::fs::remove_file(&path).ok();
+    }
+}
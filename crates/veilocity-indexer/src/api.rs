@@ -54,11 +54,21 @@ pub struct DepositsResponse {
 
 #[derive(Serialize)]
 pub struct DepositInfo {
+    /// Stable event identifier, `<block>-<log index>`
+    pub id: String,
     pub commitment: String,
+    /// Token address, the zero address for native MNT
+    pub token: String,
+    /// Amount in the token's base units (wei for MNT)
     pub amount_wei: String,
-    pub amount_mnt: f64,
+    /// Amount in MNT, for native deposits only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_mnt: Option<f64>,
     pub leaf_index: u64,
     pub block_number: u64,
+    pub block_hash: String,
+    pub transaction_index: u64,
+    pub log_index: u64,
     pub tx_hash: String,
 }
 
@@ -71,11 +81,21 @@ pub struct WithdrawalsResponse {
 
 #[derive(Serialize)]
 pub struct WithdrawalInfo {
+    /// Stable event identifier, `<block>-<log index>`
+    pub id: String,
     pub nullifier: String,
     pub recipient: String,
+    /// Token address, the zero address for native MNT
+    pub token: String,
+    /// Amount in the token's base units (wei for MNT)
     pub amount_wei: String,
-    pub amount_mnt: f64,
+    /// Amount in MNT, for native withdrawals only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_mnt: Option<f64>,
     pub block_number: u64,
+    pub block_hash: String,
+    pub transaction_index: u64,
+    pub log_index: u64,
     pub tx_hash: String,
 }
 
@@ -116,11 +136,16 @@ async fn get_deposits(State(state): State<SharedState>) -> Json<DepositsResponse
         .map(|d| {
             let amount: u128 = d.amount.try_into().unwrap_or(0);
             DepositInfo {
+                id: d.id().to_string(),
                 commitment: format!("0x{}", hex::encode(d.commitment)),
+                token: format!("{:?}", d.token),
                 amount_wei: d.amount.to_string(),
-                amount_mnt: amount as f64 / 1e18,
+                amount_mnt: d.token.is_zero().then(|| amount as f64 / 1e18),
                 leaf_index: d.leaf_index,
                 block_number: d.block_number,
+                block_hash: format!("0x{}", hex::encode(d.block_hash)),
+                transaction_index: d.transaction_index,
+                log_index: d.log_index,
                 tx_hash: format!("0x{}", hex::encode(d.tx_hash)),
             }
         })
@@ -140,11 +165,16 @@ async fn get_withdrawals(State(state): State<SharedState>) -> Json<WithdrawalsRe
         .map(|w| {
             let amount: u128 = w.amount.try_into().unwrap_or(0);
             WithdrawalInfo {
+                id: w.id().to_string(),
                 nullifier: format!("0x{}", hex::encode(w.nullifier)),
                 recipient: format!("{:?}", w.recipient),
+                token: format!("{:?}", w.token),
                 amount_wei: w.amount.to_string(),
-                amount_mnt: amount as f64 / 1e18,
+                amount_mnt: w.token.is_zero().then(|| amount as f64 / 1e18),
                 block_number: w.block_number,
+                block_hash: format!("0x{}", hex::encode(w.block_hash)),
+                transaction_index: w.transaction_index,
+                log_index: w.log_index,
                 tx_hash: format!("0x{}", hex::encode(w.tx_hash)),
             }
         })
@@ -5,9 +5,9 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
-use veilocity_contracts::{create_vault_reader, EventFilter, VeilocityEvent};
+use veilocity_contracts::{create_vault_reader, EventId, RpcConfig, VeilocityEvent};
 use veilocity_core::poseidon::{bytes_to_field, field_to_bytes};
-use veilocity_core::MerkleTree;
+use veilocity_core::{MerkleTree, TreeStore};
 
 /// Maximum blocks to scan per batch (Mantle RPC limits to 10k)
 const BLOCKS_PER_BATCH: u64 = 9000;
@@ -17,8 +17,14 @@ const BLOCKS_PER_BATCH: u64 = 9000;
 pub struct IndexedDeposit {
     pub commitment: B256,
     pub amount: U256,
+    /// Token deposited (the zero address for native MNT)
+    #[serde(default)]
+    pub token: Address,
     pub leaf_index: u64,
     pub block_number: u64,
+    pub block_hash: B256,
+    pub transaction_index: u64,
+    pub log_index: u64,
     pub tx_hash: B256,
 }
 
@@ -28,10 +34,36 @@ pub struct IndexedWithdrawal {
     pub nullifier: B256,
     pub recipient: Address,
     pub amount: U256,
+    /// Token withdrawn (the zero address for native MNT)
+    #[serde(default)]
+    pub token: Address,
     pub block_number: u64,
+    pub block_hash: B256,
+    pub transaction_index: u64,
+    pub log_index: u64,
     pub tx_hash: B256,
 }
 
+impl IndexedDeposit {
+    /// Stable identifier of the deposit event
+    pub fn id(&self) -> EventId {
+        EventId {
+            block_number: self.block_number,
+            log_index: self.log_index,
+        }
+    }
+}
+
+impl IndexedWithdrawal {
+    /// Stable identifier of the withdrawal event
+    pub fn id(&self) -> EventId {
+        EventId {
+            block_number: self.block_number,
+            log_index: self.log_index,
+        }
+    }
+}
+
 /// Current indexer state - served via API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexerState {
@@ -84,17 +116,21 @@ impl Default for IndexerState {
     }
 }
 
+/// Merkle tree used by the indexer (in memory, or on disk with `--tree-path`)
+pub type IndexerTree = MerkleTree<Box<dyn TreeStore>>;
+
 /// Run the background sync loop
 pub async fn run_sync_loop(
     state: Arc<RwLock<IndexerState>>,
-    rpc_url: &str,
+    mut tree: IndexerTree,
+    rpc: &RpcConfig,
     vault_address: Address,
     deployment_block: u64,
     poll_interval: u64,
 ) {
     info!("Starting background sync from block {}", deployment_block);
 
-    let vault = match create_vault_reader(rpc_url, vault_address) {
+    let vault = match create_vault_reader(rpc, vault_address) {
         Ok(v) => v,
         Err(e) => {
             error!("Failed to create vault reader: {}", e);
@@ -102,13 +138,20 @@ pub async fn run_sync_loop(
         }
     };
 
-    // Initial sync - fetch all historical events
-    let mut from_block = deployment_block;
-    let mut tree = MerkleTree::new();
+    // Initial sync - fetch all historical events. A persisted tree already
+    // holds the leaves it has seen, so those are not re-hashed.
+    let mut stream = vault
+        .event_stream(deployment_block)
+        .with_max_range(BLOCKS_PER_BATCH);
+    if tree.leaf_count() > 0 {
+        info!("Opened persisted tree with {} leaves", tree.leaf_count());
+    }
     let mut deposits = Vec::new();
     let mut withdrawals = Vec::new();
     let mut nullifiers = Vec::new();
     let mut leaves = Vec::new();
+    // Last event indexed; anything at or before it was already seen
+    let mut last_event: Option<EventId> = None;
 
     loop {
         // Get current block
@@ -123,7 +166,7 @@ pub async fn run_sync_loop(
 
         // Calculate sync progress
         let total_blocks = current_block.saturating_sub(deployment_block);
-        let synced_blocks = from_block.saturating_sub(deployment_block);
+        let synced_blocks = stream.next_block().saturating_sub(deployment_block);
         let progress = if total_blocks > 0 {
             ((synced_blocks as f64 / total_blocks as f64) * 100.0) as u8
         } else {
@@ -134,10 +177,10 @@ pub async fn run_sync_loop(
         {
             let mut s = state.write().await;
             s.sync_progress = progress;
-            s.is_syncing = from_block < current_block;
+            s.is_syncing = stream.next_block() < current_block;
         }
 
-        if from_block > current_block {
+        if stream.next_block() > current_block {
             // Fully synced, wait for new blocks
             {
                 let mut s = state.write().await;
@@ -148,56 +191,63 @@ pub async fn run_sync_loop(
             continue;
         }
 
-        // Fetch events in batches
-        let to_block = std::cmp::min(from_block + BLOCKS_PER_BATCH - 1, current_block);
-
-        debug!("Fetching events from block {} to {}", from_block, to_block);
-
-        let filter = EventFilter::block_range(from_block, to_block);
-
-        // Fetch all events concurrently
-        let events = match vault.get_all_events(&filter).await {
-            Ok(e) => e,
+        // Fetch the next batch of events; a failed batch is retried from the same block
+        let batch = match stream.next_batch(current_block).await {
+            Ok(Some(batch)) => batch,
+            Ok(None) => continue,
             Err(e) => {
                 warn!("Failed to fetch events: {}", e);
                 tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
                 continue;
             }
         };
+        let to_block = batch.to_block;
+
+        debug!(
+            "Fetched {} events from block {} to {}",
+            batch.events.len(),
+            batch.from_block,
+            to_block
+        );
 
-        let events_count = events.len();
+        let events_count = batch.events.len();
 
         // Process events
-        for event in events {
+        for event in batch.events {
+            if last_event.is_some_and(|last| event.id() <= last) {
+                debug!("Skipping already indexed event {}", event.id());
+                continue;
+            }
+            last_event = Some(event.id());
+
             match event {
                 VeilocityEvent::Deposit(deposit) => {
                     let leaf_index: u64 = deposit.leaf_index.try_into().unwrap_or(0);
 
-                    // Insert into Merkle tree
-                    let commitment_field = bytes_to_field(&deposit.commitment.0);
-
                     // Fill gaps if needed (shouldn't happen normally)
                     while (leaves.len() as u64) < leaf_index {
-                        let empty = [0u8; 32];
-                        leaves.push(empty);
-                        let _ = tree.insert(bytes_to_field(&empty));
+                        leaves.push([0u8; 32]);
                     }
 
                     leaves.push(deposit.commitment.0);
-                    let _ = tree.insert(commitment_field);
 
                     deposits.push(IndexedDeposit {
                         commitment: deposit.commitment,
                         amount: deposit.amount,
+                        token: deposit.token,
                         leaf_index,
                         block_number: deposit.block_number,
+                        block_hash: deposit.block_hash,
+                        transaction_index: deposit.transaction_index,
+                        log_index: deposit.log_index,
                         tx_hash: deposit.tx_hash,
                     });
 
                     debug!(
-                        "Indexed deposit #{}: {} wei",
+                        "Indexed deposit #{}: {} of {:?}",
                         leaf_index,
-                        deposit.amount
+                        deposit.amount,
+                        deposit.token
                     );
                 }
                 VeilocityEvent::Withdrawal(withdrawal) => {
@@ -207,13 +257,18 @@ pub async fn run_sync_loop(
                         nullifier: withdrawal.nullifier,
                         recipient: withdrawal.recipient,
                         amount: withdrawal.amount,
+                        token: withdrawal.token,
                         block_number: withdrawal.block_number,
+                        block_hash: withdrawal.block_hash,
+                        transaction_index: withdrawal.transaction_index,
+                        log_index: withdrawal.log_index,
                         tx_hash: withdrawal.tx_hash,
                     });
 
                     debug!(
-                        "Indexed withdrawal: {} wei to {:?}",
+                        "Indexed withdrawal: {} of {:?} to {:?}",
                         withdrawal.amount,
+                        withdrawal.token,
                         withdrawal.recipient
                     );
                 }
@@ -223,6 +278,13 @@ pub async fn run_sync_loop(
             }
         }
 
+        // Insert this batch's new leaves into the Merkle tree in one go
+        let tree_len = (tree.leaf_count() as usize).min(leaves.len());
+        let new_leaves: Vec<_> = leaves[tree_len..].iter().map(bytes_to_field).collect();
+        if let Err(e) = tree.insert_batch(&new_leaves) {
+            error!("Failed to insert {} leaves: {}", new_leaves.len(), e);
+        }
+
         // Update state
         {
             let deposit_count = vault.deposit_count().await.unwrap_or_default();
@@ -239,8 +301,6 @@ pub async fn run_sync_loop(
             s.tvl_wei = tvl.to_string();
         }
 
-        from_block = to_block + 1;
-
         if events_count > 0 {
             info!(
                 "Synced to block {}: {} deposits, {} withdrawals",
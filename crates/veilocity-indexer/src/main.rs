@@ -8,20 +8,34 @@ mod api;
 
 use anyhow::Result;
 use clap::Parser;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{info, Level};
 use tracing_subscriber::FmtSubscriber;
 
-use crate::indexer::IndexerState;
+use veilocity_contracts::RpcConfig;
+use veilocity_core::merkle::TREE_DEPTH;
+use veilocity_core::{MemoryStore, MerkleTree, RedbStore, TreeStore};
+
+use crate::indexer::{IndexerState, IndexerTree};
 
 #[derive(Parser, Debug)]
 #[command(name = "veilocity-indexer")]
 #[command(about = "Fast indexer service for Veilocity")]
 struct Args {
-    /// RPC URL for the network
-    #[arg(long, env = "RPC_URL", default_value = "https://rpc.sepolia.mantle.xyz")]
-    rpc_url: String,
+    /// RPC URLs for the network, comma-separated; later ones are fallbacks
+    #[arg(
+        long,
+        env = "RPC_URL",
+        value_delimiter = ',',
+        default_value = "https://rpc.sepolia.mantle.xyz"
+    )]
+    rpc_url: Vec<String>,
+
+    /// Number of RPC URLs that must agree on state root reads
+    #[arg(long, env = "RPC_QUORUM")]
+    rpc_quorum: Option<usize>,
 
     /// Vault contract address
     #[arg(long, env = "VAULT_ADDRESS")]
@@ -38,6 +52,10 @@ struct Args {
     /// Sync poll interval in seconds
     #[arg(long, default_value = "2")]
     poll_interval: u64,
+
+    /// Keep the Merkle tree in this redb file instead of in memory
+    #[arg(long, env = "TREE_PATH")]
+    tree_path: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -51,7 +69,7 @@ async fn main() -> Result<()> {
     let args = Args::parse();
 
     info!("Starting Veilocity Indexer");
-    info!("  RPC URL: {}", args.rpc_url);
+    info!("  RPC URL: {}", args.rpc_url.join(", "));
     info!("  Vault: {}", args.vault_address);
     info!("  Port: {}", args.port);
 
@@ -61,19 +79,35 @@ async fn main() -> Result<()> {
         .parse()
         .expect("Invalid vault address");
 
+    // Open the Merkle tree
+    let store: Box<dyn TreeStore> = match &args.tree_path {
+        Some(path) => {
+            info!("  Tree: {}", path.display());
+            Box::new(RedbStore::open(path)?)
+        }
+        None => Box::new(MemoryStore::new()),
+    };
+    let tree: IndexerTree = MerkleTree::with_store(store, TREE_DEPTH)?;
+
     // Create shared indexer state
-    let state = Arc::new(RwLock::new(IndexerState::new()));
+    let mut initial_state = IndexerState::new();
+    initial_state.state_root = veilocity_core::poseidon::field_to_bytes(&tree.root());
+    let state = Arc::new(RwLock::new(initial_state));
 
     // Start background sync task
     let sync_state = state.clone();
-    let rpc_url = args.rpc_url.clone();
+    let mut rpc = RpcConfig::new(args.rpc_url.clone());
+    if let Some(quorum) = args.rpc_quorum {
+        rpc = rpc.with_quorum(quorum);
+    }
     let deployment_block = args.deployment_block;
     let poll_interval = args.poll_interval;
 
     tokio::spawn(async move {
         indexer::run_sync_loop(
             sync_state,
-            &rpc_url,
+            tree,
+            &rpc,
             vault_address,
             deployment_block,
             poll_interval,
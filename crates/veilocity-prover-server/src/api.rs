@@ -0,0 +1,137 @@
+//! HTTP API for submitting witnesses and fetching proofs
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::info;
+use veilocity_prover::remote::{
+    JobResponse, JobStatus, ProofResponse, SubmitJobRequest, SubmitJobResponse, VkHashResponse,
+};
+use veilocity_prover::{CircuitType, NoirProver};
+
+use crate::jobs::{JobQueue, SubmitError};
+
+/// State shared by all handlers
+#[derive(Clone)]
+pub struct AppState {
+    pub queue: Arc<JobQueue>,
+    pub prover: Arc<NoirProver>,
+}
+
+type ApiError = (StatusCode, String);
+
+/// Health check response
+#[derive(Serialize)]
+struct HealthResponse {
+    status: &'static str,
+    queued: usize,
+    running: usize,
+}
+
+fn parse_circuit(name: &str) -> Result<CircuitType, ApiError> {
+    CircuitType::from_name(name)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("Unknown circuit: {}", name)))
+}
+
+fn job_not_found(id: &str) -> ApiError {
+    (StatusCode::NOT_FOUND, format!("Job not found: {}", id))
+}
+
+/// Health check endpoint
+async fn health(State(state): State<AppState>) -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok",
+        queued: state.queue.count(JobStatus::Queued).await,
+        running: state.queue.count(JobStatus::Running).await,
+    })
+}
+
+/// Queue a witness for proving
+async fn submit_job(
+    State(state): State<AppState>,
+    Json(request): Json<SubmitJobRequest>,
+) -> Result<(StatusCode, Json<SubmitJobResponse>), ApiError> {
+    let circuit = parse_circuit(&request.circuit)?;
+
+    if !state.prover.is_circuit_compiled(circuit) {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("Circuit {} is not compiled on this server", circuit.name()),
+        ));
+    }
+
+    match state.queue.submit(circuit, request.prover_toml).await {
+        Ok(id) => Ok((StatusCode::ACCEPTED, Json(SubmitJobResponse { id }))),
+        Err(SubmitError::QueueFull) => Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Job queue is full, retry later".to_string(),
+        )),
+    }
+}
+
+/// Poll a job
+async fn get_job(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<JobResponse>, ApiError> {
+    let job = state.queue.get(&id).await.ok_or_else(|| job_not_found(&id))?;
+    Ok(Json(job.to_response(&id)))
+}
+
+/// Fetch the proof of a finished job
+async fn get_proof(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ProofResponse>, ApiError> {
+    let job = state.queue.get(&id).await.ok_or_else(|| job_not_found(&id))?;
+
+    match job.proof {
+        Some(proof) => Ok(Json(ProofResponse {
+            proof: format!("0x{}", hex::encode(proof)),
+        })),
+        None => Err((
+            StatusCode::CONFLICT,
+            format!("Job {} has no proof (status: {:?})", id, job.status),
+        )),
+    }
+}
+
+/// Hash of the verification key this server proves against
+async fn get_vk_hash(
+    State(state): State<AppState>,
+    Path(circuit): Path<String>,
+) -> Result<Json<VkHashResponse>, ApiError> {
+    let circuit = parse_circuit(&circuit)?;
+    let vk_hash = state
+        .prover
+        .vk_hash(circuit)
+        .await
+        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))?;
+
+    Ok(Json(VkHashResponse {
+        vk_hash: format!("0x{}", hex::encode(vk_hash)),
+    }))
+}
+
+/// Run the HTTP server
+pub async fn run_server(addr: &str, state: AppState) -> anyhow::Result<()> {
+    let app = Router::new()
+        .route("/health", get(health))
+        .route("/jobs", post(submit_job))
+        .route("/jobs/:id", get(get_job))
+        .route("/jobs/:id/proof", get(get_proof))
+        .route("/circuits/:circuit/vk_hash", get(get_vk_hash))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Prover API listening on {}", addr);
+
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
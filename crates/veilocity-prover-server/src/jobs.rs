@@ -0,0 +1,246 @@
+//! Proving job queue and worker pool
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tracing::{info, warn};
+use veilocity_prover::remote::{JobResponse, JobStatus};
+use veilocity_prover::{CircuitType, NoirProver};
+
+/// A job as tracked by the server
+#[derive(Debug, Clone)]
+pub struct Job {
+    /// Circuit being proven
+    pub circuit: CircuitType,
+    /// Current status
+    pub status: JobStatus,
+    /// Proof bytes once done
+    pub proof: Option<Vec<u8>>,
+    /// Failure reason once failed
+    pub error: Option<String>,
+    /// When the job finished (for retention)
+    pub finished_at: Option<Instant>,
+}
+
+impl Job {
+    /// Wire representation of this job
+    pub fn to_response(&self, id: &str) -> JobResponse {
+        JobResponse {
+            id: id.to_string(),
+            circuit: self.circuit.name().to_string(),
+            status: self.status,
+            error: self.error.clone(),
+        }
+    }
+}
+
+/// A job waiting for a worker; owns the witness until proving finishes
+pub struct QueuedJob {
+    id: String,
+    circuit: CircuitType,
+    prover_toml: String,
+}
+
+/// Errors returned when submitting a job
+#[derive(Debug, PartialEq, Eq)]
+pub enum SubmitError {
+    /// Every queue slot is taken
+    QueueFull,
+}
+
+/// Bounded queue of proving jobs
+pub struct JobQueue {
+    /// All known jobs by id
+    jobs: RwLock<HashMap<String, Job>>,
+    /// Hands jobs to the worker pool
+    sender: mpsc::Sender<QueuedJob>,
+    /// How long finished jobs stay fetchable
+    retention: Duration,
+}
+
+impl JobQueue {
+    /// Create a queue holding at most `capacity` waiting jobs
+    pub fn new(capacity: usize, retention: Duration) -> (Arc<Self>, mpsc::Receiver<QueuedJob>) {
+        let (sender, receiver) = mpsc::channel(capacity);
+        let queue = Arc::new(Self {
+            jobs: RwLock::new(HashMap::new()),
+            sender,
+            retention,
+        });
+        (queue, receiver)
+    }
+
+    /// Queue a witness for proving and return the job id
+    pub async fn submit(
+        &self,
+        circuit: CircuitType,
+        prover_toml: String,
+    ) -> Result<String, SubmitError> {
+        self.prune().await;
+
+        let id = hex::encode(rand::random::<[u8; 16]>());
+        self.jobs.write().await.insert(
+            id.clone(),
+            Job {
+                circuit,
+                status: JobStatus::Queued,
+                proof: None,
+                error: None,
+                finished_at: None,
+            },
+        );
+
+        let queued = QueuedJob {
+            id: id.clone(),
+            circuit,
+            prover_toml,
+        };
+        if self.sender.try_send(queued).is_err() {
+            self.jobs.write().await.remove(&id);
+            return Err(SubmitError::QueueFull);
+        }
+
+        Ok(id)
+    }
+
+    /// Look up a job
+    pub async fn get(&self, id: &str) -> Option<Job> {
+        self.jobs.read().await.get(id).cloned()
+    }
+
+    /// Count jobs by status
+    pub async fn count(&self, status: JobStatus) -> usize {
+        self.jobs
+            .read()
+            .await
+            .values()
+            .filter(|job| job.status == status)
+            .count()
+    }
+
+    /// Drop finished jobs older than the retention period
+    async fn prune(&self) {
+        let retention = self.retention;
+        self.jobs.write().await.retain(|_, job| {
+            job.finished_at
+                .is_none_or(|finished| finished.elapsed() < retention)
+        });
+    }
+
+    async fn set_running(&self, id: &str) {
+        if let Some(job) = self.jobs.write().await.get_mut(id) {
+            job.status = JobStatus::Running;
+        }
+    }
+
+    async fn finish(&self, id: &str, result: Result<Vec<u8>, String>) {
+        if let Some(job) = self.jobs.write().await.get_mut(id) {
+            match result {
+                Ok(proof) => {
+                    job.status = JobStatus::Done;
+                    job.proof = Some(proof);
+                }
+                Err(e) => {
+                    job.status = JobStatus::Failed;
+                    job.error = Some(e);
+                }
+            }
+            job.finished_at = Some(Instant::now());
+        }
+    }
+}
+
+/// Start `workers` tasks that prove queued jobs one at a time each
+pub fn spawn_workers(
+    queue: Arc<JobQueue>,
+    receiver: mpsc::Receiver<QueuedJob>,
+    prover: Arc<NoirProver>,
+    workers: usize,
+) {
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    for worker in 0..workers {
+        let queue = queue.clone();
+        let receiver = receiver.clone();
+        let prover = prover.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let Some(job) = receiver.lock().await.recv().await else {
+                    break;
+                };
+
+                info!("Worker {} proving {} job {}", worker, job.circuit.name(), job.id);
+                queue.set_running(&job.id).await;
+
+                // nargo and bb block; keep them off the async runtime threads
+                let QueuedJob {
+                    id,
+                    circuit,
+                    prover_toml,
+                } = job;
+                let prover = prover.clone();
+                let job_id = id.clone();
+                let handle = tokio::runtime::Handle::current();
+                let result = tokio::task::spawn_blocking(move || {
+                    // The witness is dropped with this closure once proving ends
+                    handle.block_on(prover.prove_toml(circuit, &prover_toml, &job_id))
+                })
+                .await;
+
+                let result = match result {
+                    Ok(Ok(proof)) => Ok(proof),
+                    Ok(Err(e)) => Err(e.to_string()),
+                    Err(e) => Err(format!("Worker panicked: {}", e)),
+                };
+
+                if let Err(e) = &result {
+                    warn!("Job {} failed: {}", id, e);
+                } else {
+                    info!("Job {} done", id);
+                }
+
+                queue.finish(&id, result).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_submit_rejects_when_full() {
+        let (queue, _receiver) = JobQueue::new(1, Duration::from_secs(60));
+
+        let id = queue
+            .submit(CircuitType::Withdraw, String::new())
+            .await
+            .unwrap();
+        assert_eq!(queue.get(&id).await.unwrap().status, JobStatus::Queued);
+
+        assert_eq!(
+            queue.submit(CircuitType::Withdraw, String::new()).await,
+            Err(SubmitError::QueueFull)
+        );
+        assert_eq!(queue.count(JobStatus::Queued).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_finished_jobs_are_pruned() {
+        let (queue, mut receiver) = JobQueue::new(4, Duration::ZERO);
+
+        let id = queue
+            .submit(CircuitType::Withdraw, String::new())
+            .await
+            .unwrap();
+        receiver.recv().await.unwrap();
+        queue.finish(&id, Ok(vec![1, 2, 3])).await;
+        assert_eq!(queue.get(&id).await.unwrap().proof, Some(vec![1, 2, 3]));
+
+        queue.prune().await;
+        assert!(queue.get(&id).await.is_none());
+    }
+}
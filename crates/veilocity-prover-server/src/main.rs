@@ -0,0 +1,89 @@
+//! Veilocity Prover Server - Remote proving service
+//!
+//! Accepts witnesses over HTTP, proves them with a bounded pool of workers
+//! running nargo and bb, and serves the resulting proofs. Clients use
+//! `veilocity_prover::RemoteProver`.
+
+mod api;
+mod jobs;
+
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn, Level};
+use tracing_subscriber::FmtSubscriber;
+use veilocity_prover::{CircuitType, NoirProver};
+
+use crate::api::AppState;
+use crate::jobs::JobQueue;
+
+#[derive(Parser, Debug)]
+#[command(name = "veilocity-prover-server")]
+#[command(about = "Remote proving service for Veilocity")]
+struct Args {
+    /// Path to the compiled circuits directory
+    #[arg(long, env = "CIRCUITS_PATH", default_value = "circuits")]
+    circuits_path: PathBuf,
+
+    /// HTTP server port
+    #[arg(long, env = "PORT", default_value = "3002")]
+    port: u16,
+
+    /// Number of jobs proven concurrently
+    #[arg(long, env = "WORKERS", default_value = "2")]
+    workers: usize,
+
+    /// Maximum number of jobs waiting for a worker
+    #[arg(long, env = "QUEUE_SIZE", default_value = "32")]
+    queue_size: usize,
+
+    /// How long finished jobs stay fetchable, in seconds
+    #[arg(long, default_value = "600")]
+    retention_secs: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize logging
+    FmtSubscriber::builder()
+        .with_max_level(Level::INFO)
+        .compact()
+        .init();
+
+    let args = Args::parse();
+
+    info!("Starting Veilocity Prover Server");
+    info!("  Circuits: {}", args.circuits_path.display());
+    info!("  Workers: {}", args.workers);
+    info!("  Queue size: {}", args.queue_size);
+    info!("  Port: {}", args.port);
+
+    let prover = Arc::new(NoirProver::new(args.circuits_path));
+
+    for circuit in [
+        CircuitType::Deposit,
+        CircuitType::Withdraw,
+        CircuitType::WithdrawBatch,
+        CircuitType::WithdrawToken,
+        CircuitType::Transfer,
+        CircuitType::StateTransition,
+    ] {
+        if !prover.is_circuit_compiled(circuit) {
+            warn!("Circuit {} is not compiled; its jobs will be rejected", circuit.name());
+        }
+    }
+
+    let (queue, receiver) =
+        JobQueue::new(args.queue_size.max(1), Duration::from_secs(args.retention_secs));
+    jobs::spawn_workers(queue.clone(), receiver, prover.clone(), args.workers.max(1));
+
+    // Start HTTP server
+    let addr = format!("0.0.0.0:{}", args.port);
+    info!("Starting HTTP server on {}", addr);
+
+    api::run_server(&addr, AppState { queue, prover }).await?;
+
+    Ok(())
+}
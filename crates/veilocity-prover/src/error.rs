@@ -48,4 +48,16 @@ pub enum ProverError {
     /// Circuit compilation required
     #[error("Circuit needs to be compiled first. Run: cd circuits && nargo compile")]
     CircuitNotCompiled,
+
+    /// Verification key hash not found next to the vk
+    #[error("Verification key hash not found at {0}. Run: bb write_vk")]
+    VkHashNotFound(String),
+
+    /// Local verification key does not match the deployed verifier
+    #[error("Verification key mismatch: local {local}, expected {expected}. Rebuild the circuit or redeploy the verifier")]
+    VkMismatch { local: String, expected: String },
+
+    /// Remote proving service failed or rejected the request
+    #[error("Remote prover error: {0}")]
+    Remote(String),
 }
@@ -3,9 +3,11 @@
 //! Witness generation and proof creation using Noir/Barretenberg.
 //!
 //! This crate provides:
-//! - Witness generation for deposit, withdrawal, and transfer circuits
+//! - Witness generation for deposit, withdrawal, transfer and state-transition circuits
 //! - Proof generation using Barretenberg (`bb` CLI)
 //! - Proof verification
+//! - Progress events for witness solving, proving and verification
+//! - `RemoteProver`, a client for `veilocity-prover-server`
 //! - Solidity verifier generation
 //!
 //! # Usage
@@ -23,9 +25,17 @@
 //! ```
 
 pub mod error;
+pub mod progress;
 pub mod prover;
+pub mod remote;
 pub mod witness;
 
 pub use error::ProverError;
-pub use prover::{CircuitType, NoirProver, Proof};
-pub use witness::{DepositWitness, FullTransferWitness, TransferWitness, WithdrawWitness, TREE_DEPTH};
+pub use progress::{ProgressCallback, ProofEvent};
+pub use prover::{CircuitType, NoirProver, Proof, Prover};
+pub use remote::RemoteProver;
+pub use witness::{
+    BatchWithdrawWitness, DepositInsertionWitness, DepositWitness, FullTransferWitness,
+    StateTransitionWitness, TransferWitness, WithdrawWitness, DEPOSIT_BATCH_SIZE,
+    TRANSFER_BATCH_SIZE, TREE_DEPTH, WITHDRAW_BATCH_SIZE,
+};
@@ -0,0 +1,50 @@
+//! Proving progress reporting
+//!
+//! `NoirProver` reports what it is actually doing through a callback, so
+//! callers can drive a display from real events instead of timers.
+
+use crate::prover::CircuitType;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A step of proof generation or verification, emitted as it happens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofEvent {
+    /// `nargo execute` started solving the witness
+    WitnessStarted { circuit: CircuitType },
+    /// Witness solved and written to disk
+    WitnessFinished { circuit: CircuitType, elapsed: Duration },
+    /// `bb prove` started
+    ProvingStarted { circuit: CircuitType },
+    /// Proof written to disk
+    ProofWritten {
+        circuit: CircuitType,
+        bytes: usize,
+        elapsed: Duration,
+    },
+    /// `bb verify` started
+    VerificationStarted { circuit: CircuitType },
+    /// Local verification finished
+    Verified {
+        circuit: CircuitType,
+        valid: bool,
+        elapsed: Duration,
+    },
+}
+
+impl ProofEvent {
+    /// The circuit this event belongs to
+    pub fn circuit(&self) -> CircuitType {
+        match self {
+            ProofEvent::WitnessStarted { circuit }
+            | ProofEvent::WitnessFinished { circuit, .. }
+            | ProofEvent::ProvingStarted { circuit }
+            | ProofEvent::ProofWritten { circuit, .. }
+            | ProofEvent::VerificationStarted { circuit }
+            | ProofEvent::Verified { circuit, .. } => *circuit,
+        }
+    }
+}
+
+/// Callback invoked for every `ProofEvent`
+pub type ProgressCallback = Arc<dyn Fn(&ProofEvent) + Send + Sync>;
@@ -3,18 +3,28 @@
 //! This module handles proof generation by invoking the `bb` (Barretenberg) CLI tool.
 
 use crate::error::ProverError;
-use crate::witness::{DepositWitness, TransferWitness, WithdrawWitness};
+use crate::progress::{ProgressCallback, ProofEvent};
+use crate::witness::{
+    BatchWithdrawWitness, DepositWitness, StateTransitionWitness, TransferWitness, WithdrawWitness,
+};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
 use tokio::fs;
 use tracing::{debug, info};
 
 /// Circuit types supported by the prover
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CircuitType {
     Deposit,
     Withdraw,
+    WithdrawBatch,
+    WithdrawToken,
     Transfer,
+    StateTransition,
 }
 
 impl CircuitType {
@@ -23,9 +33,56 @@ impl CircuitType {
         match self {
             CircuitType::Deposit => "deposit",
             CircuitType::Withdraw => "withdraw",
+            CircuitType::WithdrawBatch => "withdraw_batch",
+            CircuitType::WithdrawToken => "withdraw_token",
             CircuitType::Transfer => "transfer",
+            CircuitType::StateTransition => "state_transition",
         }
     }
+
+    /// Parse a circuit type from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "deposit" => Some(CircuitType::Deposit),
+            "withdraw" => Some(CircuitType::Withdraw),
+            "withdraw_batch" => Some(CircuitType::WithdrawBatch),
+            "withdraw_token" => Some(CircuitType::WithdrawToken),
+            "transfer" => Some(CircuitType::Transfer),
+            "state_transition" => Some(CircuitType::StateTransition),
+            _ => None,
+        }
+    }
+}
+
+/// Numbers the proof files written by `verify_proof`, so concurrent calls never share one
+static VERIFY_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Proof generation interface shared by the local and remote provers
+#[allow(async_fn_in_trait)]
+pub trait Prover {
+    /// Generate a proof for a deposit
+    async fn prove_deposit(&self, witness: &DepositWitness) -> Result<Vec<u8>, ProverError>;
+
+    /// Generate a proof for a withdrawal (native or token, see `WithdrawWitness::circuit`)
+    async fn prove_withdraw(&self, witness: &WithdrawWitness) -> Result<Vec<u8>, ProverError>;
+
+    /// Generate a single proof for a batch of withdrawals
+    async fn prove_withdraw_batch(
+        &self,
+        witness: &BatchWithdrawWitness,
+    ) -> Result<Vec<u8>, ProverError>;
+
+    /// Generate a proof for a transfer
+    async fn prove_transfer(&self, witness: &TransferWitness) -> Result<Vec<u8>, ProverError>;
+
+    /// Generate a proof that a batch of transfers moves the old state root to the new one
+    async fn prove_state_transition(
+        &self,
+        witness: &StateTransitionWitness,
+    ) -> Result<Vec<u8>, ProverError>;
+
+    /// Hash of the verification key proofs are generated against
+    async fn vk_hash(&self, circuit_type: CircuitType) -> Result<[u8; 32], ProverError>;
 }
 
 /// Noir prover using Barretenberg backend
@@ -34,6 +91,10 @@ pub struct NoirProver {
     circuits_dir: PathBuf,
     /// Path for temporary witness/proof files
     work_dir: PathBuf,
+    /// Expected vk hash per circuit; proving is refused when the local vk differs
+    pinned_vk_hashes: HashMap<CircuitType, [u8; 32]>,
+    /// Receives progress events while proving and verifying
+    progress: Option<ProgressCallback>,
 }
 
 impl NoirProver {
@@ -44,6 +105,42 @@ impl NoirProver {
         Self {
             circuits_dir,
             work_dir,
+            pinned_vk_hashes: HashMap::new(),
+            progress: None,
+        }
+    }
+
+    /// Report proving and verification progress to `callback`
+    pub fn with_progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&ProofEvent) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Emit a progress event, if anyone is listening
+    fn report(&self, event: ProofEvent) {
+        if let Some(progress) = &self.progress {
+            progress(&event);
+        }
+    }
+
+    /// Pin the vk hash of the verifier deployed for one circuit
+    ///
+    /// Once pinned, proving that circuit checks the local vk hash first and
+    /// fails with `ProverError::VkMismatch` instead of producing a proof that
+    /// would revert on-chain. Other circuits are unaffected.
+    pub fn with_pinned_vk_hash(mut self, circuit_type: CircuitType, vk_hash: [u8; 32]) -> Self {
+        self.pinned_vk_hashes.insert(circuit_type, vk_hash);
+        self
+    }
+
+    /// Check the local vk against the hash pinned for this circuit, if any
+    async fn check_pinned_vk(&self, circuit_type: CircuitType) -> Result<(), ProverError> {
+        match self.pinned_vk_hashes.get(&circuit_type) {
+            Some(expected) => self.check_vk(circuit_type, expected).await,
+            None => Ok(()),
         }
     }
 
@@ -64,6 +161,9 @@ impl NoirProver {
     fn get_circuit_dir(&self, circuit_type: CircuitType) -> PathBuf {
         match circuit_type {
             CircuitType::Withdraw => self.circuits_dir.join("withdraw"),
+            CircuitType::WithdrawBatch => self.circuits_dir.join("withdraw_batch"),
+            CircuitType::WithdrawToken => self.circuits_dir.join("withdraw_token"),
+            CircuitType::StateTransition => self.circuits_dir.join("state_transition"),
             CircuitType::Deposit => self.circuits_dir.clone(),
             CircuitType::Transfer => self.circuits_dir.clone(),
         }
@@ -73,6 +173,15 @@ impl NoirProver {
     fn get_circuit_path(&self, circuit_type: CircuitType) -> PathBuf {
         match circuit_type {
             CircuitType::Withdraw => self.circuits_dir.join("withdraw/target/withdraw.json"),
+            CircuitType::WithdrawBatch => {
+                self.circuits_dir.join("withdraw_batch/target/withdraw_batch.json")
+            }
+            CircuitType::WithdrawToken => {
+                self.circuits_dir.join("withdraw_token/target/withdraw_token.json")
+            }
+            CircuitType::StateTransition => {
+                self.circuits_dir.join("state_transition/target/state_transition.json")
+            }
             CircuitType::Deposit => self.circuits_dir.join("target/veilocity_circuits.json"),
             CircuitType::Transfer => self.circuits_dir.join("target/veilocity_circuits.json"),
         }
@@ -82,11 +191,58 @@ impl NoirProver {
     fn get_vk_path(&self, circuit_type: CircuitType) -> PathBuf {
         match circuit_type {
             CircuitType::Withdraw => self.circuits_dir.join("withdraw/target/vk/vk"),
+            CircuitType::WithdrawBatch => self.circuits_dir.join("withdraw_batch/target/vk/vk"),
+            CircuitType::WithdrawToken => self.circuits_dir.join("withdraw_token/target/vk/vk"),
+            CircuitType::StateTransition => self.circuits_dir.join("state_transition/target/vk/vk"),
             CircuitType::Deposit => self.circuits_dir.join("target/vk/vk"),
             CircuitType::Transfer => self.circuits_dir.join("target/vk/vk"),
         }
     }
 
+    /// Get the vk hash path for a given circuit type (written by `bb write_vk`)
+    fn get_vk_hash_path(&self, circuit_type: CircuitType) -> PathBuf {
+        self.get_vk_path(circuit_type).with_file_name("vk_hash")
+    }
+
+    /// Read the hash of the local verification key
+    ///
+    /// This is the same value the generated Solidity verifier embeds as `VK_HASH`.
+    pub async fn vk_hash(&self, circuit_type: CircuitType) -> Result<[u8; 32], ProverError> {
+        let vk_hash_path = self.get_vk_hash_path(circuit_type);
+
+        if !vk_hash_path.exists() {
+            return Err(ProverError::VkHashNotFound(vk_hash_path.display().to_string()));
+        }
+
+        let bytes = fs::read(&vk_hash_path).await?;
+        bytes.as_slice().try_into().map_err(|_| {
+            ProverError::InvalidInput(format!(
+                "vk_hash must be 32 bytes, got {} at {:?}",
+                bytes.len(),
+                vk_hash_path
+            ))
+        })
+    }
+
+    /// Check the local verification key against an expected hash
+    pub async fn check_vk(
+        &self,
+        circuit_type: CircuitType,
+        expected: &[u8; 32],
+    ) -> Result<(), ProverError> {
+        let local = self.vk_hash(circuit_type).await?;
+
+        if &local != expected {
+            return Err(ProverError::VkMismatch {
+                local: format!("0x{}", hex::encode(local)),
+                expected: format!("0x{}", hex::encode(expected)),
+            });
+        }
+
+        debug!("Verification key for {:?} matches 0x{}", circuit_type, hex::encode(local));
+        Ok(())
+    }
+
     /// Check if the circuit has been compiled
     pub fn is_compiled(&self) -> bool {
         // Check if withdraw circuit is compiled (main circuit for now)
@@ -98,6 +254,11 @@ impl NoirProver {
         self.get_circuit_path(circuit_type).exists()
     }
 
+    /// Check if a verification key exists for a specific circuit type
+    pub fn has_vk(&self, circuit_type: CircuitType) -> bool {
+        self.get_vk_path(circuit_type).exists()
+    }
+
     /// Compile the Noir circuits
     pub async fn compile(&self) -> Result<(), ProverError> {
         info!("Compiling Noir circuits...");
@@ -122,66 +283,114 @@ impl NoirProver {
 
     /// Generate a proof for a deposit
     pub async fn prove_deposit(&self, witness: &DepositWitness) -> Result<Vec<u8>, ProverError> {
-        self.ensure_work_dir().await?;
-
-        // Write witness to Prover.toml
-        let prover_toml = self.work_dir.join("Prover.toml");
-        fs::write(&prover_toml, witness.to_toml()).await?;
-
-        // Generate proof
-        self.generate_proof(CircuitType::Deposit).await
+        self.prove_toml(CircuitType::Deposit, &witness.to_toml(), "proof")
+            .await
     }
 
-    /// Generate a proof for a withdrawal
+    /// Generate a proof for a withdrawal, with the token circuit if the witness has an asset
     pub async fn prove_withdraw(&self, witness: &WithdrawWitness) -> Result<Vec<u8>, ProverError> {
-        self.ensure_work_dir().await?;
-
-        // Write witness to Prover.toml
-        let prover_toml = self.work_dir.join("Prover.toml");
-        fs::write(&prover_toml, witness.to_toml()).await?;
+        self.prove_toml(witness.circuit(), &witness.to_toml(), "proof")
+            .await
+    }
 
-        // Generate proof
-        self.generate_proof(CircuitType::Withdraw).await
+    /// Generate a single proof for a batch of withdrawals
+    pub async fn prove_withdraw_batch(
+        &self,
+        witness: &BatchWithdrawWitness,
+    ) -> Result<Vec<u8>, ProverError> {
+        self.prove_toml(CircuitType::WithdrawBatch, &witness.to_toml(), "proof")
+            .await
     }
 
     /// Generate a proof for a transfer
     pub async fn prove_transfer(&self, witness: &TransferWitness) -> Result<Vec<u8>, ProverError> {
+        self.prove_toml(CircuitType::Transfer, &witness.to_toml(), "proof")
+            .await
+    }
+
+    /// Generate a proof that a batch of transfers moves the old state root to the new one
+    pub async fn prove_state_transition(
+        &self,
+        witness: &StateTransitionWitness,
+    ) -> Result<Vec<u8>, ProverError> {
+        self.prove_toml(CircuitType::StateTransition, &witness.to_toml(), "proof")
+            .await
+    }
+
+    /// Generate a proof from Prover.toml contents
+    ///
+    /// `job` names the input, witness and proof files, so concurrent jobs on
+    /// one circuits directory never overwrite each other. The input and the
+    /// solved witness are deleted once the job finishes, whether it succeeded
+    /// or not.
+    pub async fn prove_toml(
+        &self,
+        circuit_type: CircuitType,
+        prover_toml: &str,
+        job: &str,
+    ) -> Result<Vec<u8>, ProverError> {
+        if job.is_empty() || !job.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(ProverError::InvalidInput(format!("Invalid job name: {:?}", job)));
+        }
+
         self.ensure_work_dir().await?;
 
-        // Write witness to Prover.toml
-        let prover_toml = self.work_dir.join("Prover.toml");
-        fs::write(&prover_toml, witness.to_toml()).await?;
+        let circuit_dir = self.get_circuit_dir(circuit_type);
+        let prover_name = format!("Prover_{}", job);
+        let input_path = circuit_dir.join(format!("{}.toml", prover_name));
+        let witness_name = format!("{}_witness", job);
+        let witness_path = circuit_dir.join("target").join(format!("{}.gz", witness_name));
+
+        fs::write(&input_path, prover_toml).await?;
 
-        // Generate proof
-        self.generate_proof(CircuitType::Transfer).await
+        let result = self
+            .generate_proof(circuit_type, &prover_name, &witness_name, &witness_path, job)
+            .await;
+
+        // Private inputs must not outlive the job
+        let _ = fs::remove_file(&input_path).await;
+        let _ = fs::remove_file(&witness_path).await;
+
+        result
     }
 
     /// Generate proof using bb (Barretenberg)
-    async fn generate_proof(&self, circuit_type: CircuitType) -> Result<Vec<u8>, ProverError> {
+    async fn generate_proof(
+        &self,
+        circuit_type: CircuitType,
+        prover_name: &str,
+        witness_name: &str,
+        witness_path: &Path,
+        job: &str,
+    ) -> Result<Vec<u8>, ProverError> {
         if !self.is_circuit_compiled(circuit_type) {
             return Err(ProverError::CircuitNotCompiled);
         }
 
+        self.check_pinned_vk(circuit_type).await?;
+
         let circuit_dir = self.get_circuit_dir(circuit_type);
         let circuit_path = self.get_circuit_path(circuit_type);
-        let witness_path = self.work_dir.join("witness.gz");
-        let proof_path = self.work_dir.join("proof");
-        let prover_toml = self.work_dir.join("Prover.toml");
+        let proof_dir = self.work_dir.join(job);
+        let proof_path = proof_dir.join("proof");
 
-        debug!("Generating witness for {:?} from {:?}...", circuit_type, circuit_dir);
+        fs::create_dir_all(&proof_dir).await?;
 
-        // Copy Prover.toml to circuit directory
-        let circuit_prover_toml = circuit_dir.join("Prover.toml");
-        std::fs::copy(&prover_toml, &circuit_prover_toml)
-            .map_err(|e| ProverError::CommandFailed(format!("Failed to copy Prover.toml: {}", e)))?;
+        debug!("Generating witness for {:?} from {:?}...", circuit_type, circuit_dir);
 
         // Step 1: Generate witness using nargo execute
         // The witness file will be created in target/<witness_name>.gz
-        let witness_name = "proof_witness";
+        let started = Instant::now();
+        self.report(ProofEvent::WitnessStarted {
+            circuit: circuit_type,
+        });
+
         let output = Command::new("nargo")
             .current_dir(&circuit_dir)
             .arg("execute")
             .arg(witness_name)
+            .arg("--prover-name")
+            .arg(prover_name)
             .output()
             .map_err(|e| ProverError::CommandFailed(format!("Failed to run nargo execute: {}", e)))?;
 
@@ -194,18 +403,25 @@ impl NoirProver {
             )));
         }
 
-        // The witness file is created in target/<witness_name>.gz
-        let actual_witness_path = circuit_dir.join("target").join(format!("{}.gz", witness_name));
+        self.report(ProofEvent::WitnessFinished {
+            circuit: circuit_type,
+            elapsed: started.elapsed(),
+        });
 
-        debug!("Generating proof with bb from witness at {:?}...", actual_witness_path);
+        debug!("Generating proof with bb from witness at {:?}...", witness_path);
 
         // Step 2: Generate proof using bb
+        let started = Instant::now();
+        self.report(ProofEvent::ProvingStarted {
+            circuit: circuit_type,
+        });
+
         let output = Command::new("bb")
             .arg("prove")
             .arg("-b")
             .arg(&circuit_path)
             .arg("-w")
-            .arg(&actual_witness_path)
+            .arg(witness_path)
             .arg("-o")
             .arg(&proof_path)
             .output()
@@ -213,11 +429,19 @@ impl NoirProver {
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
+            let _ = fs::remove_dir_all(&proof_dir).await;
             return Err(ProverError::ProofGeneration(stderr.to_string()));
         }
 
         // Read the generated proof
         let proof = fs::read(&proof_path).await?;
+        let _ = fs::remove_dir_all(&proof_dir).await;
+
+        self.report(ProofEvent::ProofWritten {
+            circuit: circuit_type,
+            bytes: proof.len(),
+            elapsed: started.elapsed(),
+        });
 
         info!(
             "Proof generated successfully ({} bytes)",
@@ -241,12 +465,21 @@ impl NoirProver {
 
         let circuit_path = self.get_circuit_path(circuit_type);
         let vk_path = self.get_vk_path(circuit_type);
-        let proof_path = self.work_dir.join("proof_to_verify");
+        let proof_path = self.work_dir.join(format!(
+            "proof_to_verify_{}_{}",
+            std::process::id(),
+            VERIFY_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
 
         // Write proof to file
         fs::write(&proof_path, proof).await?;
 
         // Verify using bb
+        let started = Instant::now();
+        self.report(ProofEvent::VerificationStarted {
+            circuit: circuit_type,
+        });
+
         let output = Command::new("bb")
             .arg("verify")
             .arg("-b")
@@ -255,10 +488,19 @@ impl NoirProver {
             .arg(&vk_path)
             .arg("-p")
             .arg(&proof_path)
-            .output()
+            .output();
+        let _ = fs::remove_file(&proof_path).await;
+        let output = output
             .map_err(|e| ProverError::CommandFailed(format!("Failed to run bb verify: {}", e)))?;
 
-        Ok(output.status.success())
+        let valid = output.status.success();
+        self.report(ProofEvent::Verified {
+            circuit: circuit_type,
+            valid,
+            elapsed: started.elapsed(),
+        });
+
+        Ok(valid)
     }
 
     /// Generate the verification key for a specific circuit
@@ -334,6 +576,38 @@ impl NoirProver {
     }
 }
 
+impl Prover for NoirProver {
+    async fn prove_deposit(&self, witness: &DepositWitness) -> Result<Vec<u8>, ProverError> {
+        NoirProver::prove_deposit(self, witness).await
+    }
+
+    async fn prove_withdraw(&self, witness: &WithdrawWitness) -> Result<Vec<u8>, ProverError> {
+        NoirProver::prove_withdraw(self, witness).await
+    }
+
+    async fn prove_withdraw_batch(
+        &self,
+        witness: &BatchWithdrawWitness,
+    ) -> Result<Vec<u8>, ProverError> {
+        NoirProver::prove_withdraw_batch(self, witness).await
+    }
+
+    async fn prove_transfer(&self, witness: &TransferWitness) -> Result<Vec<u8>, ProverError> {
+        NoirProver::prove_transfer(self, witness).await
+    }
+
+    async fn prove_state_transition(
+        &self,
+        witness: &StateTransitionWitness,
+    ) -> Result<Vec<u8>, ProverError> {
+        NoirProver::prove_state_transition(self, witness).await
+    }
+
+    async fn vk_hash(&self, circuit_type: CircuitType) -> Result<[u8; 32], ProverError> {
+        NoirProver::vk_hash(self, circuit_type).await
+    }
+}
+
 /// Proof data structure for serialization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Proof {
@@ -371,7 +645,34 @@ mod tests {
     fn test_circuit_type_names() {
         assert_eq!(CircuitType::Deposit.name(), "deposit");
         assert_eq!(CircuitType::Withdraw.name(), "withdraw");
+        assert_eq!(CircuitType::WithdrawBatch.name(), "withdraw_batch");
+        assert_eq!(CircuitType::WithdrawToken.name(), "withdraw_token");
         assert_eq!(CircuitType::Transfer.name(), "transfer");
+        assert_eq!(CircuitType::StateTransition.name(), "state_transition");
+    }
+
+    #[test]
+    fn test_circuit_type_from_name() {
+        for circuit in [
+            CircuitType::Deposit,
+            CircuitType::Withdraw,
+            CircuitType::WithdrawBatch,
+            CircuitType::WithdrawToken,
+            CircuitType::Transfer,
+            CircuitType::StateTransition,
+        ] {
+            assert_eq!(CircuitType::from_name(circuit.name()), Some(circuit));
+        }
+        assert_eq!(CircuitType::from_name("unknown"), None);
+    }
+
+    #[tokio::test]
+    async fn test_prove_toml_rejects_bad_job_name() {
+        let prover = NoirProver::default_paths();
+        assert!(matches!(
+            prover.prove_toml(CircuitType::Withdraw, "", "../escape").await,
+            Err(ProverError::InvalidInput(_))
+        ));
     }
 
     #[test]
@@ -385,4 +686,172 @@ mod tests {
         let proof = Proof::new(vec![1, 2, 3, 4], vec![], CircuitType::Deposit);
         assert_eq!(proof.proof_hex(), "0x01020304");
     }
+
+    #[test]
+    fn test_progress_callback() {
+        use std::sync::Mutex;
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let prover =
+            NoirProver::default_paths().with_progress(move |e| sink.lock().unwrap().push(e.clone()));
+
+        prover.report(ProofEvent::ProvingStarted {
+            circuit: CircuitType::Withdraw,
+        });
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].circuit(), CircuitType::Withdraw);
+    }
+
+    #[tokio::test]
+    async fn test_check_vk() {
+        let circuits_dir = std::env::temp_dir().join(format!("veilocity-vk-{}", std::process::id()));
+        let vk_dir = circuits_dir.join("withdraw/target/vk");
+        std::fs::create_dir_all(&vk_dir).unwrap();
+        std::fs::write(vk_dir.join("vk_hash"), [7u8; 32]).unwrap();
+
+        let prover = NoirProver::new(circuits_dir.clone());
+        assert_eq!(prover.vk_hash(CircuitType::Withdraw).await.unwrap(), [7u8; 32]);
+        assert!(prover.check_vk(CircuitType::Withdraw, &[7u8; 32]).await.is_ok());
+        assert!(matches!(
+            prover.check_vk(CircuitType::Withdraw, &[8u8; 32]).await,
+            Err(ProverError::VkMismatch { .. })
+        ));
+        assert!(matches!(
+            prover.vk_hash(CircuitType::Deposit).await,
+            Err(ProverError::VkHashNotFound(_))
+        ));
+
+        // A pin only applies to its own circuit
+        let pinned = NoirProver::new(circuits_dir.clone())
+            .with_pinned_vk_hash(CircuitType::Withdraw, [7u8; 32]);
+        assert!(pinned.check_pinned_vk(CircuitType::Withdraw).await.is_ok());
+        assert!(pinned.check_pinned_vk(CircuitType::Deposit).await.is_ok());
+        let stale = NoirProver::new(circuits_dir.clone())
+            .with_pinned_vk_hash(CircuitType::Withdraw, [8u8; 32]);
+        assert!(matches!(
+            stale.check_pinned_vk(CircuitType::Withdraw).await,
+            Err(ProverError::VkMismatch { .. })
+        ));
+        assert!(stale.check_pinned_vk(CircuitType::Transfer).await.is_ok());
+
+        std::fs::remove_dir_all(&circuits_dir).unwrap();
+    }
+
+    /// Proves a real deposit and transfer transition, then checks `bb verify`
+    /// rejects the proof once it claims a different new root or deposit
+    /// commitment; skipped when nargo or bb is not installed
+    #[tokio::test]
+    async fn test_state_transition_rejects_wrong_new_root() {
+        use crate::witness::{DepositInsertionWitness, FullTransferWitness};
+        use veilocity_core::account::AccountSecret;
+        use veilocity_core::merkle::LeafId;
+        use veilocity_core::poseidon::{field_to_bytes, u128_to_field, u64_to_field};
+        use veilocity_core::state::StateManager;
+
+        let available = |tool: &str| {
+            Command::new(tool)
+                .arg("--version")
+                .output()
+                .map(|output| output.status.success())
+                .unwrap_or(false)
+        };
+        if !available("nargo") || !available("bb") {
+            eprintln!("nargo or bb not found, skipping state transition proof test");
+            return;
+        }
+
+        // Sender at leaf 0 pays the recipient at leaf 1
+        let mut state = StateManager::in_memory().unwrap();
+        let sender_secret = AccountSecret::generate();
+        let mut sender = state.create_account(&sender_secret, 1_000).unwrap();
+        let mut recipient = state
+            .create_account(&AccountSecret::generate(), 0)
+            .unwrap();
+        let (sender_old, recipient_old) = (sender.clone(), recipient.clone());
+
+        // The batch first inserts a new deposit at leaf 2
+        let old_root = state.state_root();
+        let commitment = AccountSecret::generate().compute_deposit_commitment(state.hasher(), 500);
+        let deposit_index = state.insert_leaf(commitment).unwrap();
+        let deposit_root = state.state_root();
+        let deposit = DepositInsertionWitness::new(
+            old_root,
+            deposit_root,
+            commitment,
+            deposit_index,
+            state.get_merkle_proof(deposit_index).unwrap().path,
+        )
+        .unwrap();
+
+        let sender_path_old = state.get_merkle_proof(sender.index).unwrap().path;
+        let recipient_path_old = state.get_merkle_proof(recipient.index).unwrap().path;
+
+        sender.debit(400);
+        state.update_account(&sender).unwrap();
+        let recipient_path_new = state.get_merkle_proof(recipient.index).unwrap().path;
+        recipient.credit(400);
+        state.update_account(&recipient).unwrap();
+        let sender_path_new = state.get_merkle_proof(sender.index).unwrap().path;
+        let new_root = state.state_root();
+
+        let transfer = FullTransferWitness::new(
+            deposit_root,
+            new_root,
+            sender_secret.compute_nullifier(
+                state.hasher(),
+                LeafId::from_global(sender_old.index, state.tree_depth()).nullifier_index(),
+                sender_old.nonce,
+            ),
+            *sender_secret.secret(),
+            u128_to_field(sender_old.balance),
+            u64_to_field(sender_old.nonce),
+            u64_to_field(sender_old.index),
+            sender_path_old,
+            sender_path_new,
+            recipient_old.pubkey_field(),
+            u128_to_field(recipient_old.balance),
+            u64_to_field(recipient_old.nonce),
+            u64_to_field(recipient_old.index),
+            recipient_path_old,
+            recipient_path_new,
+            u128_to_field(400),
+        )
+        .unwrap();
+        let transition =
+            StateTransitionWitness::new(deposit_index, &[deposit], &[transfer]).unwrap();
+
+        let circuits_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../circuits");
+        let prover = NoirProver::new(circuits_dir);
+        let circuit = CircuitType::StateTransition;
+        if !prover.is_circuit_compiled(circuit) {
+            let output = Command::new("nargo")
+                .current_dir(prover.get_circuit_dir(circuit))
+                .arg("compile")
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        }
+        if !prover.has_vk(circuit) {
+            prover.generate_vk(circuit).await.unwrap();
+        }
+
+        let proof = prover.prove_state_transition(&transition).await.unwrap();
+        assert!(prover.verify_proof(&proof, circuit).await.unwrap());
+
+        // bb carries the public inputs in the proof; claim another new root,
+        // then a deposit the vault never recorded
+        for claimed in [new_root, commitment] {
+            let claimed = field_to_bytes(&claimed);
+            let at = proof
+                .windows(32)
+                .position(|window| window == claimed)
+                .expect("value among the proof's public inputs");
+            let mut forged = proof.clone();
+            forged[at..at + 32].copy_from_slice(&field_to_bytes(&u64_to_field(1)));
+            assert!(!prover.verify_proof(&forged, circuit).await.unwrap());
+        }
+    }
 }
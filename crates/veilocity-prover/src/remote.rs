@@ -0,0 +1,282 @@
+//! Remote proving over HTTP
+//!
+//! `RemoteProver` submits witnesses to a `veilocity-prover-server`, polls the
+//! job until it finishes and downloads the proof. The request and response
+//! types below are the server's wire format.
+
+use crate::error::ProverError;
+use crate::progress::{ProgressCallback, ProofEvent};
+use crate::prover::{CircuitType, Prover};
+use crate::witness::{
+    BatchWithdrawWitness, DepositWitness, StateTransitionWitness, TransferWitness, WithdrawWitness,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Request body for `POST /jobs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitJobRequest {
+    /// Circuit name (see `CircuitType::name`)
+    pub circuit: String,
+    /// Prover.toml contents
+    pub prover_toml: String,
+}
+
+/// Response body for `POST /jobs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitJobResponse {
+    /// Job id to poll
+    pub id: String,
+}
+
+/// State of a proving job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Waiting for a free worker
+    Queued,
+    /// Witness solving or proving in progress
+    Running,
+    /// Proof ready to fetch
+    Done,
+    /// Proving failed; see `JobResponse::error`
+    Failed,
+}
+
+/// Response body for `GET /jobs/:id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobResponse {
+    /// Job id
+    pub id: String,
+    /// Circuit name
+    pub circuit: String,
+    /// Current status
+    pub status: JobStatus,
+    /// Failure reason when `status` is `Failed`
+    pub error: Option<String>,
+}
+
+/// Response body for `GET /jobs/:id/proof`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofResponse {
+    /// Proof bytes as 0x-prefixed hex
+    pub proof: String,
+}
+
+/// Response body for `GET /circuits/:circuit/vk_hash`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VkHashResponse {
+    /// Verification key hash as 0x-prefixed hex
+    pub vk_hash: String,
+}
+
+/// Prover client for a remote `veilocity-prover-server`
+pub struct RemoteProver {
+    /// Server base URL
+    base_url: String,
+    /// HTTP client
+    client: reqwest::Client,
+    /// Delay between job status polls
+    poll_interval: Duration,
+    /// Give up on a job after this long
+    timeout: Duration,
+    /// Receives progress events while proving
+    progress: Option<ProgressCallback>,
+}
+
+impl RemoteProver {
+    /// Create a client for the server at `base_url`
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+            poll_interval: Duration::from_millis(500),
+            timeout: Duration::from_secs(600),
+            progress: None,
+        }
+    }
+
+    /// Set the delay between job status polls
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Set how long to wait for a job before giving up
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Report proving progress to `callback`
+    pub fn with_progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&ProofEvent) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Emit a progress event, if anyone is listening
+    fn report(&self, event: ProofEvent) {
+        if let Some(progress) = &self.progress {
+            progress(&event);
+        }
+    }
+
+    /// Submit a Prover.toml for `circuit_type` and wait for the proof
+    pub async fn prove_toml(
+        &self,
+        circuit_type: CircuitType,
+        prover_toml: &str,
+    ) -> Result<Vec<u8>, ProverError> {
+        let started = Instant::now();
+        self.report(ProofEvent::ProvingStarted {
+            circuit: circuit_type,
+        });
+
+        let request = SubmitJobRequest {
+            circuit: circuit_type.name().to_string(),
+            prover_toml: prover_toml.to_string(),
+        };
+        let job: SubmitJobResponse = self
+            .send(self.client.post(format!("{}/jobs", self.base_url)).json(&request))
+            .await?;
+
+        debug!("Submitted {:?} job {} to {}", circuit_type, job.id, self.base_url);
+
+        loop {
+            let status: JobResponse = self
+                .send(self.client.get(format!("{}/jobs/{}", self.base_url, job.id)))
+                .await?;
+
+            match status.status {
+                JobStatus::Done => break,
+                JobStatus::Failed => {
+                    return Err(ProverError::ProofGeneration(
+                        status.error.unwrap_or_else(|| "unknown error".to_string()),
+                    ));
+                }
+                JobStatus::Queued | JobStatus::Running => {}
+            }
+
+            if started.elapsed() > self.timeout {
+                return Err(ProverError::Remote(format!(
+                    "Job {} did not finish within {}s",
+                    job.id,
+                    self.timeout.as_secs()
+                )));
+            }
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+
+        let response: ProofResponse = self
+            .send(self.client.get(format!("{}/jobs/{}/proof", self.base_url, job.id)))
+            .await?;
+        let proof = hex::decode(response.proof.trim_start_matches("0x"))
+            .map_err(|e| ProverError::Remote(format!("Invalid proof encoding: {}", e)))?;
+
+        self.report(ProofEvent::ProofWritten {
+            circuit: circuit_type,
+            bytes: proof.len(),
+            elapsed: started.elapsed(),
+        });
+
+        Ok(proof)
+    }
+
+    /// Send a request and decode the JSON response, surfacing server errors
+    async fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, ProverError> {
+        let response = request
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await
+            .map_err(|e| ProverError::Remote(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ProverError::Remote(format!("{}: {}", status, body)));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| ProverError::Remote(format!("Invalid response: {}", e)))
+    }
+}
+
+impl Prover for RemoteProver {
+    async fn prove_deposit(&self, witness: &DepositWitness) -> Result<Vec<u8>, ProverError> {
+        self.prove_toml(CircuitType::Deposit, &witness.to_toml())
+            .await
+    }
+
+    async fn prove_withdraw(&self, witness: &WithdrawWitness) -> Result<Vec<u8>, ProverError> {
+        self.prove_toml(witness.circuit(), &witness.to_toml())
+            .await
+    }
+
+    async fn prove_withdraw_batch(
+        &self,
+        witness: &BatchWithdrawWitness,
+    ) -> Result<Vec<u8>, ProverError> {
+        self.prove_toml(CircuitType::WithdrawBatch, &witness.to_toml())
+            .await
+    }
+
+    async fn prove_transfer(&self, witness: &TransferWitness) -> Result<Vec<u8>, ProverError> {
+        self.prove_toml(CircuitType::Transfer, &witness.to_toml())
+            .await
+    }
+
+    async fn prove_state_transition(
+        &self,
+        witness: &StateTransitionWitness,
+    ) -> Result<Vec<u8>, ProverError> {
+        self.prove_toml(CircuitType::StateTransition, &witness.to_toml())
+            .await
+    }
+
+    async fn vk_hash(&self, circuit_type: CircuitType) -> Result<[u8; 32], ProverError> {
+        let response: VkHashResponse = self
+            .send(self.client.get(format!(
+                "{}/circuits/{}/vk_hash",
+                self.base_url,
+                circuit_type.name()
+            )))
+            .await?;
+
+        let bytes = hex::decode(response.vk_hash.trim_start_matches("0x"))
+            .map_err(|e| ProverError::Remote(format!("Invalid vk hash encoding: {}", e)))?;
+        bytes.as_slice().try_into().map_err(|_| {
+            ProverError::Remote(format!("vk hash must be 32 bytes, got {}", bytes.len()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_status_wire_format() {
+        assert_eq!(serde_json::to_string(&JobStatus::Queued).unwrap(), "\"queued\"");
+        assert_eq!(
+            serde_json::from_str::<JobStatus>("\"failed\"").unwrap(),
+            JobStatus::Failed
+        );
+    }
+
+    #[test]
+    fn test_base_url_trailing_slash() {
+        let prover = RemoteProver::new("http://localhost:3002/");
+        assert_eq!(prover.base_url, "http://localhost:3002");
+    }
+}
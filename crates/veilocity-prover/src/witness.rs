@@ -3,12 +3,22 @@
 //! This module generates the private and public inputs for each circuit type.
 
 use crate::error::ProverError;
+use crate::prover::CircuitType;
 use serde::{Deserialize, Serialize};
-use veilocity_core::poseidon::{field_to_hex, FieldElement};
+use veilocity_core::poseidon::{field_to_hex, u64_to_field, FieldElement};
 
-/// Tree depth constant (must match Noir circuit)
+/// Tree depth the circuits are compiled for; every Merkle path must match it
 pub const TREE_DEPTH: usize = 20;
 
+/// Withdrawal slots per batched proof (must match the withdraw_batch circuit)
+pub const WITHDRAW_BATCH_SIZE: usize = 4;
+
+/// Transfer slots per state-transition proof (must match the state_transition circuit)
+pub const TRANSFER_BATCH_SIZE: usize = 4;
+
+/// Deposit slots per state-transition proof (must match the state_transition circuit)
+pub const DEPOSIT_BATCH_SIZE: usize = 4;
+
 /// Deposit witness for the deposit circuit
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DepositWitness {
@@ -67,10 +77,16 @@ pub struct WithdrawWitness {
     pub balance: String,
     /// Private: Account nonce
     pub nonce: String,
-    /// Private: Leaf index
+    /// Private: Leaf index within its tree
     pub index: String,
     /// Private: Merkle path (siblings)
     pub path: Vec<String>,
+    /// Private: Tree the leaf and `state_root` belong to
+    #[serde(default)]
+    pub tree_id: u32,
+    /// Public: ERC-20 token address (as field), for the withdraw_token circuit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
 }
 
 impl WithdrawWitness {
@@ -105,9 +121,31 @@ impl WithdrawWitness {
             nonce: field_to_hex(&nonce),
             index: field_to_hex(&index),
             path: path.iter().map(field_to_hex).collect(),
+            tree_id: 0,
+            asset: None,
         })
     }
 
+    /// Set the tree the account lives in (after rollover)
+    pub fn with_tree_id(mut self, tree_id: u32) -> Self {
+        self.tree_id = tree_id;
+        self
+    }
+
+    /// Withdraw from a token account; the proof is for the withdraw_token circuit
+    pub fn with_asset(mut self, asset: FieldElement) -> Self {
+        self.asset = Some(field_to_hex(&asset));
+        self
+    }
+
+    /// Circuit this witness is proven with
+    pub fn circuit(&self) -> CircuitType {
+        match self.asset {
+            Some(_) => CircuitType::WithdrawToken,
+            None => CircuitType::Withdraw,
+        }
+    }
+
     /// Convert to Prover.toml format
     pub fn to_toml(&self) -> String {
         let path_str = self
@@ -117,24 +155,33 @@ impl WithdrawWitness {
             .collect::<Vec<_>>()
             .join(", ");
 
+        let asset_str = self
+            .asset
+            .as_ref()
+            .map(|asset| format!("asset = \"{}\"\n", asset))
+            .unwrap_or_default();
+
         format!(
             r#"state_root = "{}"
 nullifier = "{}"
 amount = "{}"
 recipient = "{}"
-secret = "{}"
+{}secret = "{}"
 balance = "{}"
 nonce = "{}"
 index = "{}"
+tree_id = "{}"
 path = [{}]"#,
             self.state_root,
             self.nullifier,
             self.amount,
             self.recipient,
+            asset_str,
             self.secret,
             self.balance,
             self.nonce,
             self.index,
+            self.tree_id,
             path_str
         )
     }
@@ -145,6 +192,135 @@ path = [{}]"#,
     }
 }
 
+/// Batched withdrawal witness for the withdraw_batch circuit
+///
+/// Holds up to `WITHDRAW_BATCH_SIZE` withdrawals proven against one state root.
+/// Unused slots are zero-filled, which the circuit treats as padding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchWithdrawWitness {
+    /// Public: State root shared by every withdrawal
+    pub state_root: String,
+    /// Public: Nullifier per slot
+    pub nullifiers: Vec<String>,
+    /// Public: Withdrawal amount per slot
+    pub amounts: Vec<String>,
+    /// Public: Recipient address (as field) per slot
+    pub recipients: Vec<String>,
+    /// Private: Account secret per slot
+    pub secrets: Vec<String>,
+    /// Private: Current balance per slot
+    pub balances: Vec<String>,
+    /// Private: Account nonce per slot
+    pub nonces: Vec<String>,
+    /// Private: Leaf index per slot
+    pub indices: Vec<String>,
+    /// Private: Merkle path (siblings) per slot
+    pub paths: Vec<Vec<String>>,
+    /// Number of real (non-padding) withdrawals
+    #[serde(skip)]
+    pub count: usize,
+    /// Private: Tree every withdrawal is proven against
+    #[serde(default)]
+    pub tree_id: u32,
+}
+
+impl BatchWithdrawWitness {
+    /// Build a batch from individual withdrawal witnesses
+    ///
+    /// All withdrawals must be proven against the same state root.
+    pub fn new(withdrawals: &[WithdrawWitness]) -> Result<Self, ProverError> {
+        let first = withdrawals
+            .first()
+            .ok_or_else(|| ProverError::InvalidInput("Batch must not be empty".to_string()))?;
+
+        if withdrawals.len() > WITHDRAW_BATCH_SIZE {
+            return Err(ProverError::InvalidInput(format!(
+                "Batch holds at most {} withdrawals, got {}",
+                WITHDRAW_BATCH_SIZE,
+                withdrawals.len()
+            )));
+        }
+
+        if withdrawals
+            .iter()
+            .any(|w| w.state_root != first.state_root || w.tree_id != first.tree_id)
+        {
+            return Err(ProverError::InvalidInput(
+                "All withdrawals in a batch must share the same tree and state root".to_string(),
+            ));
+        }
+
+        let zero = field_to_hex(&u64_to_field(0));
+        let pad = |values: Vec<String>| {
+            let mut values = values;
+            values.resize(WITHDRAW_BATCH_SIZE, zero.clone());
+            values
+        };
+
+        let mut paths: Vec<Vec<String>> = withdrawals.iter().map(|w| w.path.clone()).collect();
+        paths.resize(WITHDRAW_BATCH_SIZE, vec![zero.clone(); TREE_DEPTH]);
+
+        Ok(Self {
+            state_root: first.state_root.clone(),
+            nullifiers: pad(withdrawals.iter().map(|w| w.nullifier.clone()).collect()),
+            amounts: pad(withdrawals.iter().map(|w| w.amount.clone()).collect()),
+            recipients: pad(withdrawals.iter().map(|w| w.recipient.clone()).collect()),
+            secrets: pad(withdrawals.iter().map(|w| w.secret.clone()).collect()),
+            balances: pad(withdrawals.iter().map(|w| w.balance.clone()).collect()),
+            nonces: pad(withdrawals.iter().map(|w| w.nonce.clone()).collect()),
+            indices: pad(withdrawals.iter().map(|w| w.index.clone()).collect()),
+            paths,
+            count: withdrawals.len(),
+            tree_id: first.tree_id,
+        })
+    }
+
+    /// Convert to Prover.toml format
+    pub fn to_toml(&self) -> String {
+        let list = |values: &[String]| {
+            values
+                .iter()
+                .map(|v| format!("\"{}\"", v))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let paths_str = self
+            .paths
+            .iter()
+            .map(|p| format!("[{}]", list(p)))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!(
+            r#"state_root = "{}"
+nullifiers = [{}]
+amounts = [{}]
+recipients = [{}]
+secrets = [{}]
+balances = [{}]
+nonces = [{}]
+indices = [{}]
+paths = [{}]
+tree_id = "{}""#,
+            self.state_root,
+            list(&self.nullifiers),
+            list(&self.amounts),
+            list(&self.recipients),
+            list(&self.secrets),
+            list(&self.balances),
+            list(&self.nonces),
+            list(&self.indices),
+            paths_str,
+            self.tree_id
+        )
+    }
+
+    /// Convert to JSON for bb prove
+    pub fn to_json(&self) -> Result<String, ProverError> {
+        serde_json::to_string_pretty(self).map_err(ProverError::from)
+    }
+}
+
 /// Transfer witness for the private transfer circuit (simplified version)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransferWitness {
@@ -166,6 +342,9 @@ pub struct TransferWitness {
     pub recipient_pubkey: String,
     /// Private: Transfer amount
     pub amount: String,
+    /// Private: Tree the sender lives in
+    #[serde(default)]
+    pub tree_id: u32,
 }
 
 impl TransferWitness {
@@ -200,9 +379,16 @@ impl TransferWitness {
             sender_path: sender_path.iter().map(field_to_hex).collect(),
             recipient_pubkey: field_to_hex(&recipient_pubkey),
             amount: field_to_hex(&amount),
+            tree_id: 0,
         })
     }
 
+    /// Set the tree the sender lives in (after rollover)
+    pub fn with_tree_id(mut self, tree_id: u32) -> Self {
+        self.tree_id = tree_id;
+        self
+    }
+
     /// Convert to Prover.toml format
     pub fn to_toml(&self) -> String {
         let sender_path_str = self
@@ -221,7 +407,8 @@ sender_nonce = "{}"
 sender_index = "{}"
 sender_path = [{}]
 recipient_pubkey = "{}"
-amount = "{}""#,
+amount = "{}"
+tree_id = "{}""#,
             self.old_state_root,
             self.nullifier,
             self.sender_secret,
@@ -230,7 +417,8 @@ amount = "{}""#,
             self.sender_index,
             sender_path_str,
             self.recipient_pubkey,
-            self.amount
+            self.amount,
+            self.tree_id
         )
     }
 
@@ -271,6 +459,9 @@ pub struct FullTransferWitness {
     pub recipient_path_new: Vec<String>,
     /// Transfer amount
     pub amount: String,
+    /// Tree holding both accounts
+    #[serde(default)]
+    pub tree_id: u32,
 }
 
 impl FullTransferWitness {
@@ -336,9 +527,16 @@ impl FullTransferWitness {
             recipient_path_old: recipient_path_old.iter().map(field_to_hex).collect(),
             recipient_path_new: recipient_path_new.iter().map(field_to_hex).collect(),
             amount: field_to_hex(&amount),
+            tree_id: 0,
         })
     }
 
+    /// Set the tree both accounts live in (after rollover)
+    pub fn with_tree_id(mut self, tree_id: u32) -> Self {
+        self.tree_id = tree_id;
+        self
+    }
+
     /// Convert to Prover.toml format matching the Noir circuit signature
     pub fn to_toml(&self) -> String {
         let sender_path_old_str = self
@@ -382,7 +580,8 @@ recipient_nonce = "{}"
 recipient_index = "{}"
 recipient_path_old = [{}]
 recipient_path_new = [{}]
-amount = "{}""#,
+amount = "{}"
+tree_id = "{}""#,
             self.old_state_root,
             self.new_state_root,
             self.nullifier,
@@ -398,7 +597,337 @@ amount = "{}""#,
             self.recipient_index,
             recipient_path_old_str,
             recipient_path_new_str,
-            self.amount
+            self.amount,
+            self.tree_id
+        )
+    }
+
+    /// Convert to JSON for bb prove
+    pub fn to_json(&self) -> Result<String, ProverError> {
+        serde_json::to_string_pretty(self).map_err(ProverError::from)
+    }
+}
+
+/// One deposit inserted by a state transition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepositInsertionWitness {
+    /// State root before the deposit is inserted
+    pub old_state_root: String,
+    /// State root after the deposit is inserted
+    pub new_state_root: String,
+    /// The deposit commitment, inserted as the leaf
+    pub commitment: String,
+    /// Global leaf index (the deposit's index in the vault)
+    pub index: u64,
+    /// Merkle path of the leaf (inserting the leaf doesn't change it)
+    pub path: Vec<String>,
+}
+
+impl DepositInsertionWitness {
+    /// Create a new deposit insertion witness
+    pub fn new(
+        old_state_root: FieldElement,
+        new_state_root: FieldElement,
+        commitment: FieldElement,
+        index: u64,
+        path: Vec<FieldElement>,
+    ) -> Result<Self, ProverError> {
+        if path.len() != TREE_DEPTH {
+            return Err(ProverError::InvalidInput(format!(
+                "Path must have {} elements, got {}",
+                TREE_DEPTH,
+                path.len()
+            )));
+        }
+
+        Ok(Self {
+            old_state_root: field_to_hex(&old_state_root),
+            new_state_root: field_to_hex(&new_state_root),
+            commitment: field_to_hex(&commitment),
+            index,
+            path: path.iter().map(field_to_hex).collect(),
+        })
+    }
+}
+
+/// State-transition witness for the state_transition circuit
+///
+/// Inserts up to `DEPOSIT_BATCH_SIZE` deposits and then chains up to
+/// `TRANSFER_BATCH_SIZE` full transfers from `old_state_root` to
+/// `new_state_root`; the vault checks this proof against its own deposit
+/// commitments before accepting a new root. Unused slots are zero-filled,
+/// which the circuit treats as padding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateTransitionWitness {
+    /// Public: State root before the batch
+    pub old_state_root: String,
+    /// Public: State root after the batch
+    pub new_state_root: String,
+    /// Public: Sender nullifier per slot
+    pub nullifiers: Vec<String>,
+    /// Public: Global leaf index of the first deposit (the vault's anchored deposit count)
+    #[serde(default)]
+    pub first_deposit: String,
+    /// Public: Deposit commitment per slot
+    #[serde(default)]
+    pub deposit_commitments: Vec<String>,
+    /// Private: Tree and leaf index of the first deposit
+    #[serde(default)]
+    pub deposit_tree_id: u32,
+    #[serde(default)]
+    pub deposit_index: u64,
+    /// Private: Merkle path per deposit slot
+    #[serde(default)]
+    pub deposit_paths: Vec<Vec<String>>,
+    /// Private: Sender data per slot
+    pub sender_secrets: Vec<String>,
+    pub sender_balances: Vec<String>,
+    pub sender_nonces: Vec<String>,
+    pub sender_indices: Vec<String>,
+    pub sender_paths_old: Vec<Vec<String>>,
+    pub sender_paths_new: Vec<Vec<String>>,
+    /// Private: Recipient data per slot
+    pub recipient_pubkeys: Vec<String>,
+    pub recipient_balances: Vec<String>,
+    pub recipient_nonces: Vec<String>,
+    pub recipient_indices: Vec<String>,
+    pub recipient_paths_old: Vec<Vec<String>>,
+    pub recipient_paths_new: Vec<Vec<String>>,
+    /// Private: Transfer amount per slot
+    pub amounts: Vec<String>,
+    /// Private: Tree holding every account in the batch
+    #[serde(default)]
+    pub tree_id: u32,
+    /// Number of real (non-padding) transfers
+    #[serde(skip)]
+    pub count: usize,
+    /// Number of real (non-padding) deposits
+    #[serde(skip)]
+    pub deposit_count: usize,
+}
+
+impl StateTransitionWitness {
+    /// Build a state transition from the next deposits and consecutive transfers
+    ///
+    /// `first_deposit` is the vault's anchored deposit count; the deposits
+    /// must be the ones at that index onwards, in one tree. Each deposit and
+    /// then each transfer must start from the root the previous one ended at,
+    /// all transfers must be in the same tree, and no nullifier may appear
+    /// twice.
+    pub fn new(
+        first_deposit: u64,
+        deposits: &[DepositInsertionWitness],
+        transfers: &[FullTransferWitness],
+    ) -> Result<Self, ProverError> {
+        if deposits.is_empty() && transfers.is_empty() {
+            return Err(ProverError::InvalidInput(
+                "State transition must contain at least one deposit or transfer".to_string(),
+            ));
+        }
+
+        if deposits.len() > DEPOSIT_BATCH_SIZE {
+            return Err(ProverError::InvalidInput(format!(
+                "State transition holds at most {} deposits, got {}",
+                DEPOSIT_BATCH_SIZE,
+                deposits.len()
+            )));
+        }
+
+        if transfers.len() > TRANSFER_BATCH_SIZE {
+            return Err(ProverError::InvalidInput(format!(
+                "State transition holds at most {} transfers, got {}",
+                TRANSFER_BATCH_SIZE,
+                transfers.len()
+            )));
+        }
+
+        for (k, deposit) in deposits.iter().enumerate() {
+            if deposit.index != first_deposit + k as u64 {
+                return Err(ProverError::InvalidInput(format!(
+                    "Deposit {} has leaf index {}, expected {}",
+                    k,
+                    deposit.index,
+                    first_deposit + k as u64
+                )));
+            }
+        }
+
+        if let Some(last) = deposits.last() {
+            if last.index >> TREE_DEPTH != first_deposit >> TREE_DEPTH {
+                return Err(ProverError::InvalidInput(
+                    "All deposits in a state transition must share the same tree".to_string(),
+                ));
+            }
+        }
+
+        // Deposits come first, so the roots chain through them into the transfers
+        let steps: Vec<(&String, &String)> = deposits
+            .iter()
+            .map(|d| (&d.old_state_root, &d.new_state_root))
+            .chain(
+                transfers
+                    .iter()
+                    .map(|t| (&t.old_state_root, &t.new_state_root)),
+            )
+            .collect();
+        for (i, pair) in steps.windows(2).enumerate() {
+            if pair[1].0 != pair[0].1 {
+                return Err(ProverError::InvalidInput(format!(
+                    "Step {} does not start from the root step {} ended at",
+                    i + 1,
+                    i
+                )));
+            }
+        }
+        let old_state_root = steps[0].0.clone();
+        let new_state_root = steps[steps.len() - 1].1.clone();
+
+        let tree_id = transfers.first().map_or(0, |t| t.tree_id);
+        if transfers.iter().any(|t| t.tree_id != tree_id) {
+            return Err(ProverError::InvalidInput(
+                "All transfers in a state transition must share the same tree".to_string(),
+            ));
+        }
+
+        for (i, transfer) in transfers.iter().enumerate() {
+            if transfers[..i]
+                .iter()
+                .any(|t| t.nullifier == transfer.nullifier)
+            {
+                return Err(ProverError::InvalidInput(format!(
+                    "Duplicate nullifier in transfer {}",
+                    i
+                )));
+            }
+        }
+
+        let zero = field_to_hex(&u64_to_field(0));
+        let slots = |value: fn(&FullTransferWitness) -> &String| {
+            let mut values: Vec<String> = transfers.iter().map(|t| value(t).clone()).collect();
+            values.resize(TRANSFER_BATCH_SIZE, zero.clone());
+            values
+        };
+        let path_slots = |path: fn(&FullTransferWitness) -> &Vec<String>| {
+            let mut paths: Vec<Vec<String>> = transfers.iter().map(|t| path(t).clone()).collect();
+            paths.resize(TRANSFER_BATCH_SIZE, vec![zero.clone(); TREE_DEPTH]);
+            paths
+        };
+
+        let mut deposit_commitments: Vec<String> =
+            deposits.iter().map(|d| d.commitment.clone()).collect();
+        deposit_commitments.resize(DEPOSIT_BATCH_SIZE, zero.clone());
+        let mut deposit_paths: Vec<Vec<String>> = deposits.iter().map(|d| d.path.clone()).collect();
+        deposit_paths.resize(DEPOSIT_BATCH_SIZE, vec![zero.clone(); TREE_DEPTH]);
+
+        Ok(Self {
+            old_state_root,
+            new_state_root,
+            nullifiers: slots(|t| &t.nullifier),
+            first_deposit: field_to_hex(&u64_to_field(first_deposit)),
+            deposit_commitments,
+            deposit_tree_id: (first_deposit >> TREE_DEPTH) as u32,
+            deposit_index: first_deposit & ((1 << TREE_DEPTH) - 1),
+            deposit_paths,
+            sender_secrets: slots(|t| &t.sender_secret),
+            sender_balances: slots(|t| &t.sender_balance),
+            sender_nonces: slots(|t| &t.sender_nonce),
+            sender_indices: slots(|t| &t.sender_index),
+            sender_paths_old: path_slots(|t| &t.sender_path_old),
+            sender_paths_new: path_slots(|t| &t.sender_path_new),
+            recipient_pubkeys: slots(|t| &t.recipient_pubkey),
+            recipient_balances: slots(|t| &t.recipient_balance),
+            recipient_nonces: slots(|t| &t.recipient_nonce),
+            recipient_indices: slots(|t| &t.recipient_index),
+            recipient_paths_old: path_slots(|t| &t.recipient_path_old),
+            recipient_paths_new: path_slots(|t| &t.recipient_path_new),
+            amounts: slots(|t| &t.amount),
+            tree_id,
+            count: transfers.len(),
+            deposit_count: deposits.len(),
+        })
+    }
+
+    /// Public inputs in circuit order: old root, new root, every nullifier
+    /// slot, the first deposit index, then every deposit slot
+    ///
+    /// The vault rebuilds the same list from its current root, the new root,
+    /// the submitted nullifiers, its anchored deposit count and its recorded
+    /// deposit commitments, zero-padded.
+    pub fn public_inputs(&self) -> Vec<String> {
+        let mut inputs = vec![self.old_state_root.clone(), self.new_state_root.clone()];
+        inputs.extend(self.nullifiers.iter().cloned());
+        inputs.push(self.first_deposit.clone());
+        inputs.extend(self.deposit_commitments.iter().cloned());
+        inputs
+    }
+
+    /// Nullifiers of the real (non-padding) transfers
+    pub fn transfer_nullifiers(&self) -> &[String] {
+        &self.nullifiers[..self.count]
+    }
+
+    /// Convert to Prover.toml format
+    pub fn to_toml(&self) -> String {
+        let list = |values: &[String]| {
+            values
+                .iter()
+                .map(|v| format!("\"{}\"", v))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let nested = |paths: &[Vec<String>]| {
+            paths
+                .iter()
+                .map(|p| format!("[{}]", list(p)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        format!(
+            r#"old_state_root = "{}"
+new_state_root = "{}"
+nullifiers = [{}]
+sender_secrets = [{}]
+sender_balances = [{}]
+sender_nonces = [{}]
+sender_indices = [{}]
+sender_paths_old = [{}]
+sender_paths_new = [{}]
+recipient_pubkeys = [{}]
+recipient_balances = [{}]
+recipient_nonces = [{}]
+recipient_indices = [{}]
+recipient_paths_old = [{}]
+recipient_paths_new = [{}]
+amounts = [{}]
+tree_id = "{}"
+first_deposit = "{}"
+deposit_commitments = [{}]
+deposit_tree_id = "{}"
+deposit_index = "{}"
+deposit_paths = [{}]"#,
+            self.old_state_root,
+            self.new_state_root,
+            list(&self.nullifiers),
+            list(&self.sender_secrets),
+            list(&self.sender_balances),
+            list(&self.sender_nonces),
+            list(&self.sender_indices),
+            nested(&self.sender_paths_old),
+            nested(&self.sender_paths_new),
+            list(&self.recipient_pubkeys),
+            list(&self.recipient_balances),
+            list(&self.recipient_nonces),
+            list(&self.recipient_indices),
+            nested(&self.recipient_paths_old),
+            nested(&self.recipient_paths_new),
+            list(&self.amounts),
+            self.tree_id,
+            self.first_deposit,
+            list(&self.deposit_commitments),
+            self.deposit_tree_id,
+            self.deposit_index,
+            nested(&self.deposit_paths)
         )
     }
 
@@ -440,6 +969,13 @@ mod tests {
         .unwrap();
 
         assert_eq!(witness.path.len(), TREE_DEPTH);
+        assert_eq!(witness.circuit(), CircuitType::Withdraw);
+        assert!(!witness.to_toml().contains("asset"));
+
+        let token = witness.with_asset(u64_to_field(9));
+        assert_eq!(token.circuit(), CircuitType::WithdrawToken);
+        let asset_line = format!("asset = \"{}\"\n", field_to_hex(&u64_to_field(9)));
+        assert!(token.to_toml().contains(&asset_line));
     }
 
     #[test]
@@ -459,6 +995,44 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_batch_withdraw_witness_padding() {
+        let withdrawal = |root: u64, nullifier: u64| {
+            WithdrawWitness::new(
+                u64_to_field(root),
+                u64_to_field(nullifier),
+                u64_to_field(3),
+                u64_to_field(4),
+                u64_to_field(5),
+                u64_to_field(6),
+                u64_to_field(7),
+                u64_to_field(8),
+                vec![u64_to_field(9); TREE_DEPTH],
+            )
+            .unwrap()
+        };
+
+        let batch = BatchWithdrawWitness::new(&[withdrawal(1, 2), withdrawal(1, 3)]).unwrap();
+        assert_eq!(batch.count, 2);
+        assert_eq!(batch.nullifiers.len(), WITHDRAW_BATCH_SIZE);
+        assert_eq!(batch.paths.len(), WITHDRAW_BATCH_SIZE);
+        assert_eq!(batch.nullifiers[2], field_to_hex(&u64_to_field(0)));
+        assert_eq!(batch.paths[3], vec![field_to_hex(&u64_to_field(0)); TREE_DEPTH]);
+        assert!(batch.to_toml().contains("paths = [["));
+
+        assert!(BatchWithdrawWitness::new(&[]).is_err());
+        assert!(BatchWithdrawWitness::new(&[withdrawal(1, 2), withdrawal(2, 3)]).is_err());
+        assert!(BatchWithdrawWitness::new(&[
+            withdrawal(1, 2),
+            withdrawal(1, 3).with_tree_id(1)
+        ])
+        .is_err());
+        assert!(batch.to_toml().contains("tree_id = \"0\""));
+        let rolled = BatchWithdrawWitness::new(&[withdrawal(1, 2).with_tree_id(1)]).unwrap();
+        assert!(rolled.to_toml().contains("tree_id = \"1\""));
+        assert!(BatchWithdrawWitness::new(&vec![withdrawal(1, 2); WITHDRAW_BATCH_SIZE + 1]).is_err());
+    }
+
     #[test]
     fn test_transfer_witness_creation() {
         let witness = TransferWitness::new(
@@ -477,13 +1051,157 @@ mod tests {
         assert_eq!(witness.sender_path.len(), TREE_DEPTH);
     }
 
+    #[test]
+    fn test_state_transition_witness() {
+        let transfer = |old_root: u64, new_root: u64, nullifier: u64| {
+            let path = vec![u64_to_field(0); TREE_DEPTH];
+            FullTransferWitness::new(
+                u64_to_field(old_root),
+                u64_to_field(new_root),
+                u64_to_field(nullifier),
+                u64_to_field(3),
+                u64_to_field(4),
+                u64_to_field(5),
+                u64_to_field(6),
+                path.clone(),
+                path.clone(),
+                u64_to_field(7),
+                u64_to_field(8),
+                u64_to_field(9),
+                u64_to_field(10),
+                path.clone(),
+                path,
+                u64_to_field(11),
+            )
+            .unwrap()
+        };
+
+        let transition =
+            StateTransitionWitness::new(7, &[], &[transfer(1, 2, 20), transfer(2, 3, 21)]).unwrap();
+        assert_eq!(transition.count, 2);
+        assert_eq!(transition.deposit_count, 0);
+        assert_eq!(transition.old_state_root, field_to_hex(&u64_to_field(1)));
+        assert_eq!(transition.new_state_root, field_to_hex(&u64_to_field(3)));
+        assert_eq!(transition.nullifiers.len(), TRANSFER_BATCH_SIZE);
+        assert_eq!(transition.recipient_paths_new.len(), TRANSFER_BATCH_SIZE);
+        assert_eq!(transition.amounts[2], field_to_hex(&u64_to_field(0)));
+        assert_eq!(transition.transfer_nullifiers().len(), 2);
+        assert!(transition.to_toml().contains("sender_paths_old = [["));
+
+        let inputs = transition.public_inputs();
+        assert_eq!(inputs.len(), 3 + TRANSFER_BATCH_SIZE + DEPOSIT_BATCH_SIZE);
+        assert_eq!(inputs[2], field_to_hex(&u64_to_field(20)));
+        assert_eq!(inputs[5], field_to_hex(&u64_to_field(0)));
+        // The vault's anchored deposit count is public even without deposits
+        assert_eq!(inputs[6], field_to_hex(&u64_to_field(7)));
+        assert_eq!(inputs[7], field_to_hex(&u64_to_field(0)));
+
+        // A transfer that does not continue from the previous root is a bad transition
+        assert!(
+            StateTransitionWitness::new(0, &[], &[transfer(1, 2, 20), transfer(5, 3, 21)]).is_err()
+        );
+        assert!(
+            StateTransitionWitness::new(0, &[], &[transfer(1, 2, 20), transfer(2, 3, 20)]).is_err()
+        );
+        // Transfers of different trees can't share a transition
+        assert!(StateTransitionWitness::new(
+            0,
+            &[],
+            &[transfer(1, 2, 20), transfer(2, 3, 21).with_tree_id(1)]
+        )
+        .is_err());
+        assert!(StateTransitionWitness::new(0, &[], &[]).is_err());
+        let too_many = (0..=TRANSFER_BATCH_SIZE as u64)
+            .map(|i| transfer(i, i + 1, 20 + i))
+            .collect::<Vec<_>>();
+        assert!(StateTransitionWitness::new(0, &[], &too_many).is_err());
+    }
+
+    #[test]
+    fn test_state_transition_with_deposits() {
+        let path = vec![u64_to_field(0); TREE_DEPTH];
+        let deposit = |old_root: u64, new_root: u64, index: u64| {
+            DepositInsertionWitness::new(
+                u64_to_field(old_root),
+                u64_to_field(new_root),
+                u64_to_field(100 + index),
+                index,
+                path.clone(),
+            )
+            .unwrap()
+        };
+        let transfers = [FullTransferWitness::new(
+            u64_to_field(3),
+            u64_to_field(4),
+            u64_to_field(20),
+            u64_to_field(3),
+            u64_to_field(4),
+            u64_to_field(5),
+            u64_to_field(6),
+            path.clone(),
+            path.clone(),
+            u64_to_field(7),
+            u64_to_field(8),
+            u64_to_field(9),
+            u64_to_field(10),
+            path.clone(),
+            path.clone(),
+            u64_to_field(11),
+        )
+        .unwrap()];
+
+        // Deposits are inserted before the transfers
+        let transition =
+            StateTransitionWitness::new(5, &[deposit(1, 2, 5), deposit(2, 3, 6)], &transfers)
+                .unwrap();
+        assert_eq!(transition.deposit_count, 2);
+        assert_eq!(transition.old_state_root, field_to_hex(&u64_to_field(1)));
+        assert_eq!(transition.new_state_root, field_to_hex(&u64_to_field(4)));
+        assert_eq!(transition.deposit_paths.len(), DEPOSIT_BATCH_SIZE);
+        assert_eq!(transition.deposit_index, 5);
+        assert!(transition.to_toml().contains("deposit_paths = [["));
+
+        let inputs = transition.public_inputs();
+        let deposits = 2 + TRANSFER_BATCH_SIZE;
+        assert_eq!(inputs[deposits], field_to_hex(&u64_to_field(5)));
+        assert_eq!(inputs[deposits + 1], field_to_hex(&u64_to_field(105)));
+        assert_eq!(inputs[deposits + 2], field_to_hex(&u64_to_field(106)));
+        assert_eq!(inputs[deposits + 3], field_to_hex(&u64_to_field(0)));
+
+        // A deposit-only batch is a valid transition
+        assert!(StateTransitionWitness::new(5, &[deposit(1, 2, 5)], &[]).is_ok());
+        // Deposits must be the vault's next ones, in order
+        assert!(StateTransitionWitness::new(4, &[deposit(1, 2, 5)], &[]).is_err());
+        assert!(
+            StateTransitionWitness::new(5, &[deposit(1, 2, 5), deposit(2, 3, 7)], &[]).is_err()
+        );
+        // Roots chain from the deposits into the transfers
+        assert!(StateTransitionWitness::new(5, &[deposit(1, 2, 5)], &transfers).is_err());
+        // A batch can't span two trees
+        let last = (1 << TREE_DEPTH) - 1;
+        assert!(StateTransitionWitness::new(
+            last,
+            &[deposit(1, 2, last), deposit(2, 3, last + 1)],
+            &[]
+        )
+        .is_err());
+        // The first deposit of a later tree is placed by tree id and leaf index
+        let opening =
+            StateTransitionWitness::new(last + 1, &[deposit(1, 2, last + 1)], &[]).unwrap();
+        assert_eq!((opening.deposit_tree_id, opening.deposit_index), (1, 0));
+        let too_many = (0..=DEPOSIT_BATCH_SIZE as u64)
+            .map(|i| deposit(i, i + 1, i))
+            .collect::<Vec<_>>();
+        assert!(StateTransitionWitness::new(0, &too_many, &[]).is_err());
+    }
+
     /// Generate a valid test withdraw witness with correct Merkle proof
     /// This test outputs the Prover.toml for debugging
     #[test]
     fn test_generate_valid_withdraw_witness() {
-        use veilocity_core::poseidon::{field_to_hex, PoseidonHasher};
+        use veilocity_core::poseidon::PoseidonHasher;
 
-        let mut hasher = PoseidonHasher::new();
+        let hasher = PoseidonHasher::new();
 
         // Test values matching circuit test
         let secret = u64_to_field(123456789);
@@ -511,8 +1229,7 @@ mod tests {
         // Compute state root from leaf and path
         let mut current = leaf;
         let index_val: u64 = 0;
-        for i in 0..TREE_DEPTH {
-            let sibling = path[i];
+        for (i, &sibling) in path.iter().enumerate() {
             let bit = (index_val >> i) & 1;
             if bit == 0 {
                 current = hasher.hash2(&current, &sibling);